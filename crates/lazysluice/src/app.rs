@@ -14,6 +14,7 @@ pub enum Screen {
     MessageDetail,
     Metrics,
     ConsumerGroupInput,
+    StartTimeInput,
 }
 
 /// Active field in publish screen.
@@ -28,8 +29,8 @@ pub enum PublishInputField {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum LayoutMode {
     #[default]
-    SinglePane,  // Current behavior
-    ThreePane,   // Topics | Messages | Details
+    SinglePane, // Current behavior
+    ThreePane, // Topics | Messages | Details
 }
 
 /// Connection status.
@@ -52,14 +53,15 @@ pub struct AppState {
     // Topic list
     pub topics: Vec<Topic>,
     pub topic_cursor: usize,
-    pub visited_topics: std::collections::HashSet<String>,  // Track visited topics
+    pub visited_topics: std::collections::HashSet<String>, // Track visited topics
 
     // Tail view
     pub messages: Vec<MessageDelivery>,
     pub message_cursor: usize,
     pub paused: bool,
     pub initial_position: InitialPosition,
-    pub current_topic: Option<String>,  // Track which topic we're subscribed to
+    pub start_timestamp: Option<i64>, // Unix ms for InitialPosition::Timestamp
+    pub current_topic: Option<String>, // Track which topic we're subscribed to
 
     // Publish draft
     pub publish_topic: String,
@@ -95,10 +97,14 @@ pub struct AppState {
     pub consumer_group: Option<String>,
     pub consumer_group_input: String,
 
+    // Start time selection
+    pub start_time_input: String,
+    pub start_time_status: Option<String>,
+
     // Search/filter (Phase 4)
     pub search_query: String,
     pub search_active: bool,
-    pub filtered_messages: Vec<usize>,  // Indices into messages vec
+    pub filtered_messages: Vec<usize>, // Indices into messages vec
 }
 
 impl AppState {
@@ -124,14 +130,35 @@ impl AppState {
     }
 
     /// Toggle between Earliest and Latest subscription positions.
+    #[allow(dead_code)]
     pub fn toggle_initial_position(&mut self) {
         self.initial_position = match self.initial_position {
             InitialPosition::Earliest => InitialPosition::Latest,
             InitialPosition::Latest => InitialPosition::Earliest,
-            InitialPosition::Offset => InitialPosition::Earliest,
+            InitialPosition::Offset | InitialPosition::Timestamp => InitialPosition::Earliest,
         };
     }
 
+    /// Parse the start time input and switch to the Timestamp position.
+    pub fn apply_start_time(&mut self) -> bool {
+        match sluice_client::parse_timestamp(&self.start_time_input) {
+            Ok(timestamp) if timestamp > 0 => {
+                self.start_timestamp = Some(timestamp);
+                self.initial_position = InitialPosition::Timestamp;
+                self.start_time_status = None;
+                true
+            }
+            Ok(_) => {
+                self.start_time_status = Some("Error: start time must be after 1970".to_string());
+                false
+            }
+            Err(e) => {
+                self.start_time_status = Some(format!("Error: {e}"));
+                false
+            }
+        }
+    }
+
     /// Cycle between Topic and Payload fields in publish screen.
     pub fn cycle_publish_field(&mut self) {
         self.publish_active_field = match self.publish_active_field {
@@ -293,6 +320,7 @@ impl AppState {
     }
 
     /// Get visible messages (filtered or all).
+    #[allow(dead_code)]
     pub fn visible_messages(&self) -> Vec<&MessageDelivery> {
         if self.search_active && !self.search_query.trim().is_empty() {
             self.filtered_messages
@@ -377,6 +405,55 @@ mod tests {
         assert_eq!(state.initial_position, InitialPosition::Earliest);
     }
 
+    #[test]
+    fn start_time_enables_timestamp_position() {
+        let mut state = AppState::new(128);
+
+        state.start_time_input = "2024-05-01T14:05:00Z".to_string();
+        assert!(state.apply_start_time());
+        assert_eq!(state.start_timestamp, Some(1_714_572_300_000));
+        assert_eq!(state.initial_position, InitialPosition::Timestamp);
+        assert!(state.start_time_status.is_none());
+
+        // Offsets and Unix milliseconds are accepted too
+        state.start_time_input = "2024-05-01T16:05:00.250+02:00".to_string();
+        assert!(state.apply_start_time());
+        assert_eq!(state.start_timestamp, Some(1_714_572_300_250));
+
+        state.start_time_input = " 1714572300000 ".to_string();
+        assert!(state.apply_start_time());
+        assert_eq!(state.start_timestamp, Some(1_714_572_300_000));
+    }
+
+    #[test]
+    fn invalid_start_time_is_rejected() {
+        let mut state = AppState::new(128);
+
+        for input in ["", "yesterday", "2024-13-01T00:00:00Z", "2024-05-01"] {
+            state.start_time_input = input.to_string();
+            assert!(!state.apply_start_time(), "accepted {input:?}");
+            assert!(state.start_time_status.is_some());
+        }
+        assert_eq!(state.start_timestamp, None);
+        assert_eq!(state.initial_position, InitialPosition::Earliest);
+
+        // Times at or before the epoch are rejected
+        state.start_time_input = "0".to_string();
+        assert!(!state.apply_start_time());
+        assert_eq!(
+            state.start_time_status.as_deref(),
+            Some("Error: start time must be after 1970")
+        );
+
+        // A rejected input keeps the start time already applied
+        state.start_time_input = "2024-05-01T14:05:00Z".to_string();
+        assert!(state.apply_start_time());
+        state.start_time_input = "not a time".to_string();
+        assert!(!state.apply_start_time());
+        assert_eq!(state.start_timestamp, Some(1_714_572_300_000));
+        assert_eq!(state.initial_position, InitialPosition::Timestamp);
+    }
+
     #[test]
     fn publish_active_field_defaults_to_topic() {
         let state = AppState::new(128);
@@ -409,6 +486,7 @@ mod tests {
             payload: vec![1, 2, 3],
             attributes: Default::default(),
             timestamp: 12345,
+            ..Default::default()
         };
 
         let msg2 = MessageDelivery {
@@ -417,6 +495,7 @@ mod tests {
             payload: vec![4, 5, 6],
             attributes: Default::default(),
             timestamp: 12346,
+            ..Default::default()
         };
 
         // Initially no messages
@@ -562,6 +641,7 @@ mod tests {
             payload: b"hello world".to_vec(),
             attributes: Default::default(),
             timestamp: 12345,
+            ..Default::default()
         };
 
         let msg2 = MessageDelivery {
@@ -570,6 +650,7 @@ mod tests {
             payload: b"goodbye moon".to_vec(),
             attributes: Default::default(),
             timestamp: 12346,
+            ..Default::default()
         };
 
         state.messages.push(msg1);
@@ -606,6 +687,7 @@ mod tests {
             payload: b"payload".to_vec(),
             attributes: Default::default(),
             timestamp: 12345,
+            ..Default::default()
         };
 
         state.messages.push(msg);
@@ -632,6 +714,7 @@ mod tests {
             payload: b"data".to_vec(),
             attributes: attrs,
             timestamp: 12345,
+            ..Default::default()
         };
 
         state.messages.push(msg);
@@ -662,6 +745,7 @@ mod tests {
             payload: b"Hello World".to_vec(),
             attributes: Default::default(),
             timestamp: 12345,
+            ..Default::default()
         };
 
         state.messages.push(msg);
@@ -4,7 +4,10 @@ use crossterm::event::KeyCode;
 
 use crate::app::{AppState, ConnStatus, Screen};
 use crate::events::Event;
-use sluice_client::{InitialPosition, SluiceClient, Subscription};
+use sluice_client::{
+    ConnectConfig, CreditConfig, InitialPosition, RetryConfig, SluiceClient, SubscribeOptions,
+    Subscription,
+};
 
 /// Controller: owns app state and mutates it in response to events.
 pub struct Controller {
@@ -14,6 +17,7 @@ pub struct Controller {
     endpoint: String,
     tls_ca: Option<std::path::PathBuf>,
     tls_domain: Option<String>,
+    token: Option<String>,
     reconnect_attempt: u32,
     last_reconnect: Option<std::time::Instant>,
 }
@@ -23,6 +27,7 @@ impl Controller {
         endpoint: String,
         tls_ca: Option<std::path::PathBuf>,
         tls_domain: Option<String>,
+        token: Option<String>,
         credits_window: u32,
     ) -> Self {
         Self {
@@ -32,6 +37,7 @@ impl Controller {
             endpoint,
             tls_ca,
             tls_domain,
+            token,
             reconnect_attempt: 0,
             last_reconnect: None,
         }
@@ -42,13 +48,19 @@ impl Controller {
     pub async fn connect(&mut self) {
         tracing::info!("Connecting to server");
         self.state.conn_status = ConnStatus::Connecting;
-        match SluiceClient::connect_with(
-            &self.endpoint,
-            self.tls_ca.as_deref(),
-            self.tls_domain.as_deref(),
-        )
-        .await
-        {
+        let config = ConnectConfig {
+            endpoint: self.endpoint.clone(),
+            tls_ca: self
+                .tls_ca
+                .as_ref()
+                .map(|p| p.to_string_lossy().to_string()),
+            tls_domain: self.tls_domain.clone(),
+            tls_cert: None,
+            tls_key: None,
+            auth_token: self.token.clone(),
+            retry: RetryConfig::default(),
+        };
+        match SluiceClient::connect(config).await {
             Ok(c) => {
                 tracing::info!("Connected successfully");
                 self.client = Some(c);
@@ -91,23 +103,26 @@ impl Controller {
     async fn start_subscription(&mut self, topic: String) {
         tracing::info!("Starting subscription");
         if let Some(ref mut c) = self.client {
-            match c
-                .subscribe(
-                    &topic,
-                    self.state.consumer_group.as_deref(), // Use selected consumer group
-                    None, // consumer_id
-                    self.state.initial_position,
-                    self.state.credits_window,
-                )
-                .await
+            let mut options = SubscribeOptions::default()
+                .initial_position(self.state.initial_position)
+                .credits(CreditConfig::with_window(self.state.credits_window));
+            // Use selected consumer group
+            if let Some(group) = &self.state.consumer_group {
+                options = options.consumer_group(group);
+            }
+            if let (InitialPosition::Timestamp, Some(timestamp)) =
+                (self.state.initial_position, self.state.start_timestamp)
             {
+                options = options.start_timestamp(timestamp);
+            }
+            match c.subscribe_with(&topic, options).await {
                 Ok(sub) => {
                     tracing::info!("Subscription started");
                     self.subscription = Some(sub);
                     self.state.messages.clear();
                     self.state.message_cursor = 0;
-                    self.state.current_topic = Some(topic.clone());  // Track subscribed topic
-                    self.state.visited_topics.insert(topic);  // Mark as visited
+                    self.state.current_topic = Some(topic.clone()); // Track subscribed topic
+                    self.state.visited_topics.insert(topic); // Mark as visited
                 }
                 Err(e) => {
                     tracing::warn!(error = %e, "Failed to start subscription");
@@ -136,12 +151,13 @@ impl Controller {
                         // Track metrics
                         self.state.record_consume();
                         // Auto-scroll to latest if cursor is at end
-                        if self.state.message_cursor + 1 >= self.state.messages.len().saturating_sub(1)
+                        if self.state.message_cursor + 1
+                            >= self.state.messages.len().saturating_sub(1)
                         {
                             self.state.message_cursor = self.state.messages.len().saturating_sub(1);
                         }
                     }
-                    return is_new;  // Return true if new, false if duplicate
+                    return is_new; // Return true if new, false if duplicate
                 }
                 Ok(Ok(None)) => {
                     // Stream ended
@@ -227,6 +243,11 @@ impl Controller {
             return self.handle_consumer_group_key(code).await;
         }
 
+        // Handle start time input screen
+        if self.state.screen == Screen::StartTimeInput {
+            return self.handle_start_time_key(code).await;
+        }
+
         // Handle metrics screen
         if self.state.screen == Screen::Metrics {
             return self.handle_metrics_key(code);
@@ -252,49 +273,40 @@ impl Controller {
                     Screen::MessageDetail => Screen::Tail,
                     Screen::Metrics => Screen::TopicList,
                     Screen::ConsumerGroupInput => Screen::TopicList,
+                    Screen::StartTimeInput => Screen::Tail,
                 };
             }
             KeyCode::Char('p') => {
                 self.state.screen = Screen::Publish;
             }
-            KeyCode::Char('c') => {
-                if self.state.screen == Screen::TopicList {
-                    self.state.screen = Screen::CreateTopic;
-                    self.state.create_topic_name.clear();
-                    self.state.create_topic_status = None;
-                }
+            KeyCode::Char('c') if self.state.screen == Screen::TopicList => {
+                self.state.screen = Screen::CreateTopic;
+                self.state.create_topic_name.clear();
+                self.state.create_topic_status = None;
             }
-            KeyCode::Char('i') => {
-                if self.state.screen == Screen::Tail {
-                    if let Some(msg) = self.state.selected_message().cloned() {
-                        self.state.detail_message = Some(msg);
-                        self.state.screen = Screen::MessageDetail;
-                    }
+            KeyCode::Char('i') if self.state.screen == Screen::Tail => {
+                if let Some(msg) = self.state.selected_message().cloned() {
+                    self.state.detail_message = Some(msg);
+                    self.state.screen = Screen::MessageDetail;
                 }
             }
             KeyCode::Char('t') => {
                 // Toggle layout mode
                 self.state.toggle_layout_mode();
             }
-            KeyCode::Char('m') => {
+            KeyCode::Char('m') if matches!(self.state.screen, Screen::TopicList | Screen::Tail) => {
                 // Open metrics dashboard
-                if matches!(self.state.screen, Screen::TopicList | Screen::Tail) {
-                    self.state.screen = Screen::Metrics;
-                }
+                self.state.screen = Screen::Metrics;
             }
-            KeyCode::Char('g') => {
+            KeyCode::Char('g') if self.state.screen == Screen::TopicList => {
                 // Open consumer group selection
-                if self.state.screen == Screen::TopicList {
-                    self.state.screen = Screen::ConsumerGroupInput;
-                    self.state.consumer_group_input.clear();
-                }
+                self.state.screen = Screen::ConsumerGroupInput;
+                self.state.consumer_group_input.clear();
             }
-            KeyCode::Char('/') => {
+            KeyCode::Char('/') if self.state.screen == Screen::Tail => {
                 // Activate search mode
-                if self.state.screen == Screen::Tail {
-                    self.state.search_active = true;
-                    self.state.search_query.clear();
-                }
+                self.state.search_active = true;
+                self.state.search_query.clear();
             }
             KeyCode::Char('j') | KeyCode::Down => {
                 self.move_cursor_down();
@@ -314,48 +326,44 @@ impl Controller {
             KeyCode::End => {
                 self.jump_to_bottom();
             }
-            KeyCode::Enter => {
-                if self.state.screen == Screen::TopicList {
-                    if let Some(t) = self.state.selected_topic().cloned() {
-                        self.state.publish_topic = t.name.clone();
-                        self.state.screen = Screen::Tail;
-                        self.start_subscription(t.name).await;
-                    }
+            KeyCode::Enter if self.state.screen == Screen::TopicList => {
+                if let Some(t) = self.state.selected_topic().cloned() {
+                    self.state.publish_topic = t.name.clone();
+                    self.state.screen = Screen::Tail;
+                    self.start_subscription(t.name).await;
                 }
             }
-            KeyCode::Char(' ') => {
-                if self.state.screen == Screen::Tail {
-                    self.state.paused = !self.state.paused;
-                }
+            KeyCode::Char(' ') if self.state.screen == Screen::Tail => {
+                self.state.paused = !self.state.paused;
             }
-            KeyCode::Char('a') => {
-                if self.state.screen == Screen::Tail {
-                    if let Some(m) = self.state.selected_message() {
-                        let msg_id = m.message_id.clone();
-                        self.state.acked_ids.insert(msg_id.clone());
-                        // Send Ack RPC
-                        self.send_ack(msg_id).await;
-                    }
+            KeyCode::Char('a') if self.state.screen == Screen::Tail => {
+                if let Some(m) = self.state.selected_message() {
+                    let msg_id = m.message_id.clone();
+                    self.state.acked_ids.insert(msg_id.clone());
+                    // Send Ack RPC
+                    self.send_ack(msg_id).await;
                 }
             }
-            KeyCode::Char('e') => {
-                if self.state.screen == Screen::Tail {
-                    self.state.initial_position = InitialPosition::Earliest;
-                    // Restart subscription with new position if we have a topic
-                    if let Some(topic) = self.state.selected_topic().cloned() {
-                        self.start_subscription(topic.name).await;
-                    }
+            KeyCode::Char('e') if self.state.screen == Screen::Tail => {
+                self.state.initial_position = InitialPosition::Earliest;
+                // Restart subscription with new position if we have a topic
+                if let Some(topic) = self.state.selected_topic().cloned() {
+                    self.start_subscription(topic.name).await;
                 }
             }
-            KeyCode::Char('l') => {
-                if self.state.screen == Screen::Tail {
-                    self.state.initial_position = InitialPosition::Latest;
-                    // Restart subscription with new position if we have a topic
-                    if let Some(topic) = self.state.selected_topic().cloned() {
-                        self.start_subscription(topic.name).await;
-                    }
+            KeyCode::Char('l') if self.state.screen == Screen::Tail => {
+                self.state.initial_position = InitialPosition::Latest;
+                // Restart subscription with new position if we have a topic
+                if let Some(topic) = self.state.selected_topic().cloned() {
+                    self.start_subscription(topic.name).await;
                 }
             }
+            KeyCode::Char('s') if self.state.screen == Screen::Tail => {
+                // Open start time selection
+                self.state.screen = Screen::StartTimeInput;
+                self.state.start_time_input.clear();
+                self.state.start_time_status = None;
+            }
             _ => {}
         }
         true
@@ -526,6 +534,32 @@ impl Controller {
         true
     }
 
+    async fn handle_start_time_key(&mut self, code: KeyCode) -> bool {
+        match code {
+            KeyCode::Esc => {
+                self.state.screen = Screen::Tail;
+                self.state.start_time_input.clear();
+            }
+            KeyCode::Enter if self.state.apply_start_time() => {
+                self.state.screen = Screen::Tail;
+                // Restart subscription from the start time if we have a topic
+                if let Some(topic) = self.state.selected_topic().cloned() {
+                    self.start_subscription(topic.name).await;
+                }
+            }
+            KeyCode::Char(c) => {
+                self.state.start_time_input.push(c);
+                self.state.start_time_status = None;
+            }
+            KeyCode::Backspace => {
+                self.state.start_time_input.pop();
+                self.state.start_time_status = None;
+            }
+            _ => {}
+        }
+        true
+    }
+
     fn handle_metrics_key(&mut self, code: KeyCode) -> bool {
         match code {
             KeyCode::Char('q') => return false,
@@ -581,15 +615,11 @@ impl Controller {
 
     fn move_cursor_down(&mut self) {
         match self.state.screen {
-            Screen::TopicList => {
-                if self.state.topic_cursor + 1 < self.state.topics.len() {
-                    self.state.topic_cursor += 1;
-                }
+            Screen::TopicList if self.state.topic_cursor + 1 < self.state.topics.len() => {
+                self.state.topic_cursor += 1;
             }
-            Screen::Tail => {
-                if self.state.message_cursor + 1 < self.state.messages.len() {
-                    self.state.message_cursor += 1;
-                }
+            Screen::Tail if self.state.message_cursor + 1 < self.state.messages.len() => {
+                self.state.message_cursor += 1;
             }
             _ => {}
         }
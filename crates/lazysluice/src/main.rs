@@ -34,6 +34,10 @@ struct Args {
     #[arg(long, env = "SLUICE_TLS_DOMAIN")]
     tls_domain: Option<String>,
 
+    /// Optional bearer token for servers that require authentication.
+    #[arg(long, env = "SLUICE_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+
     /// Subscription credits window size.
     #[arg(long, env = "SLUICE_CREDITS_WINDOW", default_value_t = 128)]
     credits_window: u32,
@@ -72,6 +76,7 @@ async fn run_tui(args: Args) -> Result<()> {
         args.endpoint,
         args.tls_ca,
         args.tls_domain,
+        args.token,
         args.credits_window,
     );
 
@@ -119,6 +124,7 @@ mod tests {
         assert_eq!(args.credits_window, 128);
         assert!(args.tls_ca.is_none());
         assert!(args.tls_domain.is_none());
+        assert!(args.token.is_none());
     }
 
     #[test]
@@ -46,6 +46,7 @@ pub fn draw(frame: &mut Frame, state: &AppState) {
             Screen::MessageDetail => draw_message_detail(frame, main_area, state),
             Screen::Metrics => draw_metrics(frame, main_area, state),
             Screen::ConsumerGroupInput => draw_consumer_group_input(frame, main_area, state),
+            Screen::StartTimeInput => draw_start_time_input(frame, main_area, state),
         }
     }
 }
@@ -150,13 +151,19 @@ fn draw_topic_list(frame: &mut Frame, area: Rect, state: &AppState) {
                 "  "
             };
 
-            let is_current = state
-                .current_topic
-                .as_ref()
-                .is_some_and(|ct| ct == &t.name);
+            let is_current = state.current_topic.as_ref().is_some_and(|ct| ct == &t.name);
             let current_marker = if is_current { "▶ " } else { "" };
 
-            let display = format!("{}{}{}", visited_marker, current_marker, t.name);
+            let partitions = if t.partition_count > 1 {
+                format!(" ({} partitions)", t.partition_count)
+            } else {
+                String::new()
+            };
+
+            let display = format!(
+                "{}{}{}{}",
+                visited_marker, current_marker, t.name, partitions
+            );
 
             let style = if i == state.topic_cursor {
                 Style::default().add_modifier(Modifier::REVERSED)
@@ -176,10 +183,12 @@ fn draw_topic_list(frame: &mut Frame, area: Rect, state: &AppState) {
 fn draw_tail(frame: &mut Frame, area: Rect, state: &AppState) {
     use sluice_client::InitialPosition;
 
-    let position_indicator = match state.initial_position {
-        InitialPosition::Earliest => "EARLIEST",
-        InitialPosition::Latest => "LATEST",
-        InitialPosition::Offset => "OFFSET",
+    let position_indicator = match (state.initial_position, state.start_timestamp) {
+        (InitialPosition::Earliest, _) => "EARLIEST".to_string(),
+        (InitialPosition::Latest, _) => "LATEST".to_string(),
+        (InitialPosition::Offset, _) => "OFFSET".to_string(),
+        (InitialPosition::Timestamp, Some(timestamp)) => format!("FROM {timestamp}"),
+        (InitialPosition::Timestamp, None) => "TIMESTAMP".to_string(),
     };
 
     let title = if state.search_active {
@@ -263,13 +272,17 @@ fn draw_publish(frame: &mut Frame, area: Rect, state: &AppState) {
 
     // Highlight active field
     let topic_style = if state.publish_active_field == PublishInputField::Topic {
-        Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)
+        Style::default()
+            .fg(Color::Green)
+            .add_modifier(Modifier::BOLD)
     } else {
         Style::default().fg(Color::Cyan)
     };
 
     let payload_style = if state.publish_active_field == PublishInputField::Payload {
-        Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)
+        Style::default()
+            .fg(Color::Green)
+            .add_modifier(Modifier::BOLD)
     } else {
         Style::default().fg(Color::Cyan)
     };
@@ -337,6 +350,7 @@ fn draw_help(frame: &mut Frame, area: Rect) {
   a          Ack selected message
   e          Subscribe from Earliest (history)
   l          Subscribe from Latest (new only)
+  s          Subscribe from a start time
   i          Inspect message details
   /          Search/filter messages
   m          View metrics dashboard
@@ -373,7 +387,9 @@ fn draw_create_topic(frame: &mut Frame, area: Rect, state: &AppState) {
             Span::raw("Topic Name: "),
             Span::styled(
                 &state.create_topic_name,
-                Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
             ),
             Span::styled(" ◄", Style::default().fg(Color::Green)),
         ]),
@@ -389,16 +405,19 @@ fn draw_create_topic(frame: &mut Frame, area: Rect, state: &AppState) {
         )),
     ];
 
-    let para = Paragraph::new(text)
-        .block(Block::default().borders(Borders::ALL).title("Create Topic"));
+    let para =
+        Paragraph::new(text).block(Block::default().borders(Borders::ALL).title("Create Topic"));
     frame.render_widget(para, area);
 }
 
 fn draw_message_detail(frame: &mut Frame, area: Rect, state: &AppState) {
     let Some(ref msg) = state.detail_message else {
         let text = vec![Line::from("No message selected")];
-        let para = Paragraph::new(text)
-            .block(Block::default().borders(Borders::ALL).title("Message Detail"));
+        let para = Paragraph::new(text).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Message Detail"),
+        );
         frame.render_widget(para, area);
         return;
     };
@@ -413,7 +432,10 @@ fn draw_message_detail(frame: &mut Frame, area: Rect, state: &AppState) {
     // Format attributes
     let mut lines = vec![
         Line::from(vec![
-            Span::styled("Message ID: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(
+                "Message ID: ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
             Span::raw(&msg.message_id),
         ]),
         Line::from(vec![
@@ -435,7 +457,9 @@ fn draw_message_detail(frame: &mut Frame, area: Rect, state: &AppState) {
     if !msg.attributes.is_empty() {
         lines.push(Line::from(Span::styled(
             "Attributes:",
-            Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan),
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .fg(Color::Cyan),
         )));
         for (key, value) in &msg.attributes {
             lines.push(Line::from(vec![
@@ -457,7 +481,9 @@ fn draw_message_detail(frame: &mut Frame, area: Rect, state: &AppState) {
     // Add payload
     lines.push(Line::from(Span::styled(
         format!("Payload ({} bytes):", msg.payload.len()),
-        Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan),
+        Style::default()
+            .add_modifier(Modifier::BOLD)
+            .fg(Color::Cyan),
     )));
 
     let payload_str = render_payload(&msg.payload);
@@ -473,7 +499,11 @@ fn draw_message_detail(frame: &mut Frame, area: Rect, state: &AppState) {
     )));
 
     let para = Paragraph::new(lines)
-        .block(Block::default().borders(Borders::ALL).title("Message Detail"))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Message Detail"),
+        )
         .wrap(ratatui::widgets::Wrap { trim: false });
     frame.render_widget(para, area);
 }
@@ -487,11 +517,16 @@ fn draw_metrics(frame: &mut Frame, area: Rect, state: &AppState) {
         Line::from(""),
         Line::from(Span::styled(
             "Metrics Dashboard",
-            Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan),
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .fg(Color::Cyan),
         )),
         Line::from(""),
         Line::from(vec![
-            Span::styled("Connection Uptime: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(
+                "Connection Uptime: ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
             Span::raw(uptime),
         ]),
         Line::from(""),
@@ -544,15 +579,13 @@ fn draw_metrics(frame: &mut Frame, area: Rect, state: &AppState) {
         ]));
         lines.push(Line::from(vec![
             Span::styled("  Consumer Group: ", Style::default().fg(Color::DarkGray)),
-            Span::raw(
-                state
-                    .consumer_group
-                    .as_deref()
-                    .unwrap_or("default"),
-            ),
+            Span::raw(state.consumer_group.as_deref().unwrap_or("default")),
         ]));
         lines.push(Line::from(vec![
-            Span::styled("  Messages Buffered: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(
+                "  Messages Buffered: ",
+                Style::default().fg(Color::DarkGray),
+            ),
             Span::raw(format!("{}", state.messages.len())),
         ]));
         lines.push(Line::from(vec![
@@ -578,7 +611,9 @@ fn draw_consumer_group_input(frame: &mut Frame, area: Rect, state: &AppState) {
             Span::raw("Consumer Group: "),
             Span::styled(
                 &state.consumer_group_input,
-                Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
             ),
             Span::styled(" ◄", Style::default().fg(Color::Green)),
         ]),
@@ -594,8 +629,48 @@ fn draw_consumer_group_input(frame: &mut Frame, area: Rect, state: &AppState) {
         )),
     ];
 
-    let para = Paragraph::new(text)
-        .block(Block::default().borders(Borders::ALL).title("Consumer Group"));
+    let para = Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Consumer Group"),
+    );
+    frame.render_widget(para, area);
+}
+
+fn draw_start_time_input(frame: &mut Frame, area: Rect, state: &AppState) {
+    let mut text = vec![
+        Line::from(""),
+        Line::from(vec![
+            Span::raw("Start time: "),
+            Span::styled(
+                &state.start_time_input,
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(" ◄", Style::default().fg(Color::Green)),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            "RFC 3339 (e.g. 2024-05-01T14:05:00Z) or Unix milliseconds",
+            Style::default().fg(Color::DarkGray),
+        )),
+        Line::from(""),
+    ];
+    if let Some(status) = &state.start_time_status {
+        text.push(Line::from(Span::styled(
+            status.as_str(),
+            Style::default().fg(Color::Red),
+        )));
+        text.push(Line::from(""));
+    }
+    text.push(Line::from(Span::styled(
+        "Enter to subscribe, Esc to cancel",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let para =
+        Paragraph::new(text).block(Block::default().borders(Borders::ALL).title("Start Time"));
     frame.render_widget(para, area);
 }
 
@@ -0,0 +1,75 @@
+//! Example demonstrating high-throughput publishing with a `Publisher`.
+//!
+//! This example shows:
+//! - Opening a streaming publisher with a bounded number of messages in flight
+//! - Sending messages without waiting for each one to be persisted
+//! - Collecting the acknowledgements afterwards
+//!
+//! # Usage
+//!
+//! Start a Sluice server in another terminal:
+//! ```bash
+//! cargo run -p sluice-server
+//! ```
+//!
+//! Then run this example:
+//! ```bash
+//! cargo run --example stream_publish
+//! ```
+
+use std::time::Instant;
+
+use anyhow::Result;
+use sluice_client::{ConnectConfig, PublishOptions, SluiceClient};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize tracing for debug output
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .init();
+
+    // Connect to Sluice server
+    println!("Connecting to Sluice server at http://localhost:50051...");
+    let config = ConnectConfig::plaintext("http://localhost:50051");
+    let mut client = SluiceClient::connect(config).await?;
+    println!("Connected successfully!");
+
+    // Allow up to 500 unacknowledged messages
+    let publisher = client.publisher(500).await?;
+
+    let topic = "example-topic";
+    let count = 10_000;
+
+    println!("\nStreaming {} messages to topic '{}'...", count, topic);
+    let start = Instant::now();
+    let mut pending = Vec::with_capacity(count);
+    for i in 1..=count {
+        let message = format!("Message #{}", i).into_bytes();
+        pending.push(
+            publisher
+                .publish(topic, message, PublishOptions::default())
+                .await?,
+        );
+    }
+
+    // Every acknowledgement arrives once its batch is committed
+    let mut last_sequence = 0;
+    for ack in pending {
+        last_sequence = ack.await?.sequence;
+    }
+    publisher.close().await?;
+
+    let elapsed = start.elapsed();
+    println!("All messages persisted in {:.2?}", elapsed);
+    println!("  Last sequence: {}", last_sequence);
+    println!(
+        "  Throughput: {:.0} msg/s",
+        count as f64 / elapsed.as_secs_f64()
+    );
+
+    Ok(())
+}
@@ -1,17 +1,28 @@
 //! Connection management for Sluice gRPC client.
 
+use std::collections::HashMap;
 use std::path::Path;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::{anyhow, Context, Result};
-use tonic::transport::{Certificate, Channel, ClientTlsConfig, Endpoint};
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::service::interceptor::InterceptedService;
+use tonic::service::Interceptor;
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Endpoint, Identity};
+use tonic::{Code, Request, Status};
 
 use sluice_proto::sluice::v1::sluice_client::SluiceClient as ProtoClient;
 use sluice_proto::sluice::v1::{
-    InitialPosition, ListTopicsRequest, PublishRequest, PublishResponse, Topic,
+    ConsumerGroupInfo, CreateTopicRequest, DeleteConsumerGroupRequest, DeleteTopicRequest,
+    DeleteTopicResponse, DescribeConsumerGroupRequest, DescribeTopicRequest, DescribeTopicResponse,
+    InitialPosition, ListConsumerGroupsRequest, ListTopicsRequest, MessageDelivery, PublishRequest,
+    PublishResponse, PurgeTopicRequest, ResetConsumerGroupRequest, ResetPosition, Topic,
+    TopicConfig, UpdateTopicConfigRequest,
 };
 
-use super::subscription::Subscription;
+use super::publisher::Publisher;
+use super::requester::{Requester, CORRELATION_ID_ATTRIBUTE, REPLY_TO_ATTRIBUTE};
+use super::subscription::{SubscribeOptions, Subscription};
 
 /// Configuration for retry logic with exponential backoff.
 #[derive(Debug, Clone)]
@@ -71,6 +82,12 @@ pub struct ConnectConfig {
     pub tls_ca: Option<String>,
     /// Optional TLS domain name override
     pub tls_domain: Option<String>,
+    /// Optional path to a client certificate for mutual TLS
+    pub tls_cert: Option<String>,
+    /// Optional path to the private key for `tls_cert`
+    pub tls_key: Option<String>,
+    /// Optional bearer token sent with every request
+    pub auth_token: Option<String>,
     /// Retry configuration for connection attempts.
     pub retry: RetryConfig,
 }
@@ -82,6 +99,9 @@ impl ConnectConfig {
             endpoint: endpoint.into(),
             tls_ca: None,
             tls_domain: None,
+            tls_cert: None,
+            tls_key: None,
+            auth_token: None,
             retry: RetryConfig::default(),
         }
     }
@@ -92,6 +112,9 @@ impl ConnectConfig {
             endpoint: endpoint.into(),
             tls_ca: Some(ca_path.into()),
             tls_domain: None,
+            tls_cert: None,
+            tls_key: None,
+            auth_token: None,
             retry: RetryConfig::default(),
         }
     }
@@ -102,6 +125,23 @@ impl ConnectConfig {
         self
     }
 
+    /// Present a client certificate, for servers that require mutual TLS.
+    pub fn with_client_cert(
+        mut self,
+        cert_path: impl Into<String>,
+        key_path: impl Into<String>,
+    ) -> Self {
+        self.tls_cert = Some(cert_path.into());
+        self.tls_key = Some(key_path.into());
+        self
+    }
+
+    /// Authenticate with a bearer token (a static token or a JWT).
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.auth_token = Some(token.into());
+        self
+    }
+
     /// Set the retry configuration.
     pub fn with_retry(mut self, retry: RetryConfig) -> Self {
         self.retry = retry;
@@ -115,9 +155,184 @@ impl ConnectConfig {
     }
 }
 
+/// Where [`SluiceClient::reset_consumer_group`] moves a group's cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetTarget {
+    /// Replay every stored message.
+    Earliest,
+    /// Skip every stored message.
+    Latest,
+    /// Resume with the first message after this sequence.
+    Sequence(u64),
+    /// Resume with the first message published at or after this Unix time (ms).
+    Timestamp(i64),
+}
+
+/// Options for [`SluiceClient::publish_with`].
+#[derive(Debug, Clone, Default)]
+pub struct PublishOptions {
+    /// Routing key (see [`SluiceClient::publish_with_key`]).
+    pub key: Option<String>,
+    /// Application headers stored with the message.
+    pub attributes: HashMap<String, String>,
+    /// Key that makes retries of this publish safe.
+    pub idempotency_key: Option<String>,
+    /// Only publish if the topic's last sequence is still this value.
+    pub expected_last_sequence: Option<u64>,
+    /// Deliver the message at this time (Unix epoch ms) instead of now.
+    pub deliver_at: Option<i64>,
+    /// Drop the message if it is not consumed within this time of delivery.
+    pub ttl: Option<Duration>,
+    /// Delivery priority from 0 (default) to 9.
+    pub priority: u32,
+}
+
+impl PublishOptions {
+    /// Set the routing key.
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// Add an attribute.
+    pub fn attribute(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.attributes.insert(name.into(), value.into());
+        self
+    }
+
+    /// Set the idempotency key.
+    ///
+    /// Publishing again with the same key on the same topic within the
+    /// server's dedup window returns the original message ID and sequence,
+    /// with `duplicate` set, instead of storing a second copy.
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Only publish if the topic's last sequence is still `sequence`
+    /// (0 for an empty topic).
+    ///
+    /// Otherwise the publish fails; [`sequence_conflict`] extracts the
+    /// actual last sequence from the error.
+    pub fn expected_last_sequence(mut self, sequence: u64) -> Self {
+        self.expected_last_sequence = Some(sequence);
+        self
+    }
+
+    /// Make the message visible to subscribers at `timestamp` (Unix epoch ms).
+    ///
+    /// Until then it is stored by the server but not part of the topic; it
+    /// gets its sequence number when it is delivered.
+    pub fn deliver_at(mut self, timestamp: i64) -> Self {
+        self.deliver_at = Some(timestamp);
+        self
+    }
+
+    /// Make the message visible to subscribers after `delay`.
+    ///
+    /// The delivery time is computed from the local clock.
+    pub fn delay(self, delay: Duration) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        self.deliver_at((now + delay).as_millis() as i64)
+    }
+
+    /// Expire the message if it is not consumed within `ttl` of becoming
+    /// visible.
+    ///
+    /// An expired message is never delivered. The server deletes it, or
+    /// moves it to the dead-letter topic if the topic is configured to.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Set the priority, from 0 (default) to 9.
+    ///
+    /// On topics with priority delivery enabled, higher-priority messages
+    /// are delivered first to subscribers using individual acks. Other
+    /// topics ignore it.
+    pub fn priority(mut self, priority: u32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Build the request publishing `payload` to `topic` with these options.
+    pub(crate) fn into_request(self, topic: &str, payload: Vec<u8>) -> PublishRequest {
+        PublishRequest {
+            topic: topic.to_string(),
+            payload,
+            attributes: self.attributes,
+            key: self.key.unwrap_or_default(),
+            idempotency_key: self.idempotency_key.unwrap_or_default(),
+            expected_last_sequence: self.expected_last_sequence,
+            deliver_at: self.deliver_at.unwrap_or_default(),
+            ttl_ms: self
+                .ttl
+                .map_or(0, |ttl| ttl.as_millis().clamp(1, i64::MAX as u128) as i64),
+            priority: self.priority,
+        }
+    }
+}
+
+/// Response metadata carrying the actual last sequence of a topic.
+pub(crate) const LAST_SEQUENCE_METADATA: &str = "sluice-last-sequence";
+
+/// The topic's actual last sequence, if `error` is a publish rejected by
+/// its [`PublishOptions::expected_last_sequence`] check.
+pub fn sequence_conflict(error: &anyhow::Error) -> Option<u64> {
+    let status = error.downcast_ref::<Status>()?;
+    if status.code() != Code::FailedPrecondition {
+        return None;
+    }
+    status
+        .metadata()
+        .get(LAST_SEQUENCE_METADATA)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
+/// Adds the configured bearer token to every request.
+#[derive(Debug, Clone)]
+pub(crate) struct TokenInterceptor {
+    header: Option<MetadataValue<Ascii>>,
+}
+
+impl TokenInterceptor {
+    fn new(token: Option<&str>) -> Result<Self> {
+        let header = token
+            .map(|token| {
+                MetadataValue::try_from(format!("Bearer {token}"))
+                    .context("auth token contains invalid characters")
+            })
+            .transpose()?;
+        Ok(Self { header })
+    }
+}
+
+impl Interceptor for TokenInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> std::result::Result<Request<()>, Status> {
+        if let Some(header) = &self.header {
+            request
+                .metadata_mut()
+                .insert("authorization", header.clone());
+        }
+        Ok(request)
+    }
+}
+
+/// Generated gRPC client with the token interceptor applied.
+pub(crate) type GrpcClient = ProtoClient<InterceptedService<Channel, TokenInterceptor>>;
+
 /// A gRPC client for interacting with Sluice servers.
 pub struct SluiceClient {
-    inner: ProtoClient<Channel>,
+    inner: GrpcClient,
+    /// Request stream reused by [`SluiceClient::request`].
+    requester: Option<Requester>,
 }
 
 impl SluiceClient {
@@ -166,8 +381,14 @@ impl SluiceClient {
             return Err(anyhow!("endpoint must start with http:// or https://"));
         }
 
+        let interceptor = TokenInterceptor::new(config.auth_token.as_deref())?;
+
         if is_http {
-            if config.tls_ca.is_some() || config.tls_domain.is_some() {
+            if config.tls_ca.is_some()
+                || config.tls_domain.is_some()
+                || config.tls_cert.is_some()
+                || config.tls_key.is_some()
+            {
                 return Err(anyhow!(
                     "TLS options are only valid with https:// endpoints"
                 ));
@@ -178,7 +399,8 @@ impl SluiceClient {
                 .await
                 .context("failed to connect to server")?;
             return Ok(Self {
-                inner: ProtoClient::new(channel),
+                inner: ProtoClient::with_interceptor(channel, interceptor),
+                requester: None,
             });
         }
 
@@ -197,6 +419,23 @@ impl SluiceClient {
             tls = tls.domain_name(domain);
         }
 
+        match (&config.tls_cert, &config.tls_key) {
+            (Some(cert_path), Some(key_path)) => {
+                let cert_pem = std::fs::read(Path::new(cert_path)).with_context(|| {
+                    format!("failed to read TLS client certificate: {}", cert_path)
+                })?;
+                let key_pem = std::fs::read(Path::new(key_path))
+                    .with_context(|| format!("failed to read TLS client key: {}", key_path))?;
+                tls = tls.identity(Identity::from_pem(cert_pem, key_pem));
+            }
+            (None, None) => {}
+            _ => {
+                return Err(anyhow!(
+                    "TLS client certificate and key must be given together"
+                ));
+            }
+        }
+
         let channel = Endpoint::from_shared(endpoint.to_string())?
             .tls_config(tls)?
             .connect_timeout(Duration::from_secs(5))
@@ -205,7 +444,8 @@ impl SluiceClient {
             .context("failed to connect to server with TLS")?;
 
         Ok(Self {
-            inner: ProtoClient::new(channel),
+            inner: ProtoClient::with_interceptor(channel, interceptor),
+            requester: None,
         })
     }
 
@@ -219,6 +459,9 @@ impl SluiceClient {
             endpoint: endpoint.to_string(),
             tls_ca: tls_ca.map(|p| p.to_string_lossy().to_string()),
             tls_domain: tls_domain.map(String::from),
+            tls_cert: None,
+            tls_key: None,
+            auth_token: None,
             retry: RetryConfig::default(),
         };
         Self::connect(config).await
@@ -235,24 +478,283 @@ impl SluiceClient {
         Ok(resp.topics)
     }
 
-    /// Publish a message to a topic.
-    pub async fn publish(&mut self, topic: &str, payload: Vec<u8>) -> Result<PublishResponse> {
+    /// Create a topic with `partitions` partitions.
+    ///
+    /// Fails if the topic already exists. Topics created implicitly by
+    /// publishing have a single partition.
+    pub async fn create_topic(&mut self, name: &str, partitions: u32) -> Result<Topic> {
+        self.create_topic_with_config(name, partitions, TopicConfig::default())
+            .await
+    }
+
+    /// Create a topic with `partitions` partitions and its own configuration.
+    ///
+    /// Zero values in `config` use the server defaults.
+    pub async fn create_topic_with_config(
+        &mut self,
+        name: &str,
+        partitions: u32,
+        config: TopicConfig,
+    ) -> Result<Topic> {
+        let resp = self
+            .inner
+            .create_topic(CreateTopicRequest {
+                name: name.to_string(),
+                partitions,
+                config: Some(config),
+            })
+            .await
+            .context("create_topic RPC failed")?
+            .into_inner();
+        resp.topic
+            .ok_or_else(|| anyhow!("create_topic response is missing the topic"))
+    }
+
+    /// Replace a topic's configuration, returning the stored configuration.
+    pub async fn update_topic_config(
+        &mut self,
+        name: &str,
+        config: TopicConfig,
+    ) -> Result<TopicConfig> {
+        let resp = self
+            .inner
+            .update_topic_config(UpdateTopicConfigRequest {
+                name: name.to_string(),
+                config: Some(config),
+            })
+            .await
+            .context("update_topic_config RPC failed")?
+            .into_inner();
+        resp.config
+            .ok_or_else(|| anyhow!("update_topic_config response is missing the config"))
+    }
+
+    /// Delete a topic with its messages and consumer groups.
+    pub async fn delete_topic(&mut self, name: &str) -> Result<DeleteTopicResponse> {
+        let resp = self
+            .inner
+            .delete_topic(DeleteTopicRequest {
+                name: name.to_string(),
+            })
+            .await
+            .context("delete_topic RPC failed")?
+            .into_inner();
+        Ok(resp)
+    }
+
+    /// Delete every message of a topic, keeping the topic itself.
+    ///
+    /// Returns the number of messages deleted.
+    pub async fn purge_topic(&mut self, name: &str) -> Result<u64> {
+        let resp = self
+            .inner
+            .purge_topic(PurgeTopicRequest {
+                name: name.to_string(),
+            })
+            .await
+            .context("purge_topic RPC failed")?
+            .into_inner();
+        Ok(resp.messages_deleted)
+    }
+
+    /// Get a topic's storage statistics and consumer groups.
+    pub async fn describe_topic(&mut self, name: &str) -> Result<DescribeTopicResponse> {
+        let resp = self
+            .inner
+            .describe_topic(DescribeTopicRequest {
+                name: name.to_string(),
+            })
+            .await
+            .context("describe_topic RPC failed")?
+            .into_inner();
+        Ok(resp)
+    }
+
+    /// List consumer groups of a topic, or of every topic if `topic` is `None`.
+    pub async fn list_consumer_groups(
+        &mut self,
+        topic: Option<&str>,
+    ) -> Result<Vec<ConsumerGroupInfo>> {
+        let resp = self
+            .inner
+            .list_consumer_groups(ListConsumerGroupsRequest {
+                topic: topic.unwrap_or_default().to_string(),
+            })
+            .await
+            .context("list_consumer_groups RPC failed")?
+            .into_inner();
+        Ok(resp.consumer_groups)
+    }
+
+    /// Get a consumer group's cursor, lag and connection state.
+    pub async fn describe_consumer_group(
+        &mut self,
+        topic: &str,
+        consumer_group: &str,
+    ) -> Result<ConsumerGroupInfo> {
         let resp = self
             .inner
-            .publish(PublishRequest {
+            .describe_consumer_group(DescribeConsumerGroupRequest {
                 topic: topic.to_string(),
-                payload,
-                attributes: Default::default(),
+                consumer_group: consumer_group.to_string(),
             })
             .await
+            .context("describe_consumer_group RPC failed")?
+            .into_inner();
+        resp.consumer_group
+            .ok_or_else(|| anyhow!("describe_consumer_group response missing group"))
+    }
+
+    /// Move a consumer group's cursor, disconnecting its connected consumers.
+    ///
+    /// Returns the group after the reset.
+    pub async fn reset_consumer_group(
+        &mut self,
+        topic: &str,
+        consumer_group: &str,
+        target: ResetTarget,
+    ) -> Result<ConsumerGroupInfo> {
+        let mut request = ResetConsumerGroupRequest {
+            topic: topic.to_string(),
+            consumer_group: consumer_group.to_string(),
+            ..Default::default()
+        };
+        match target {
+            ResetTarget::Earliest => request.set_position(ResetPosition::ResetEarliest),
+            ResetTarget::Latest => request.set_position(ResetPosition::ResetLatest),
+            ResetTarget::Sequence(sequence) => {
+                request.set_position(ResetPosition::ResetSequence);
+                request.sequence = sequence;
+            }
+            ResetTarget::Timestamp(timestamp) => {
+                request.set_position(ResetPosition::ResetTimestamp);
+                request.timestamp = timestamp;
+            }
+        }
+
+        let resp = self
+            .inner
+            .reset_consumer_group(request)
+            .await
+            .context("reset_consumer_group RPC failed")?
+            .into_inner();
+        resp.consumer_group
+            .ok_or_else(|| anyhow!("reset_consumer_group response missing group"))
+    }
+
+    /// Delete a consumer group, disconnecting its connected consumers.
+    ///
+    /// Returns true if a connected consumer was disconnected.
+    pub async fn delete_consumer_group(
+        &mut self,
+        topic: &str,
+        consumer_group: &str,
+    ) -> Result<bool> {
+        let resp = self
+            .inner
+            .delete_consumer_group(DeleteConsumerGroupRequest {
+                topic: topic.to_string(),
+                consumer_group: consumer_group.to_string(),
+            })
+            .await
+            .context("delete_consumer_group RPC failed")?
+            .into_inner();
+        Ok(resp.disconnected)
+    }
+
+    /// Publish a message to a topic.
+    pub async fn publish(&mut self, topic: &str, payload: Vec<u8>) -> Result<PublishResponse> {
+        self.publish_with_key(topic, "", payload).await
+    }
+
+    /// Publish a message with a routing key.
+    ///
+    /// Within a shared consumer group, messages with the same key are
+    /// delivered to the same consumer in publish order. An empty key
+    /// publishes without one.
+    pub async fn publish_with_key(
+        &mut self,
+        topic: &str,
+        key: &str,
+        payload: Vec<u8>,
+    ) -> Result<PublishResponse> {
+        let options = PublishOptions {
+            key: (!key.is_empty()).then(|| key.to_string()),
+            ..Default::default()
+        };
+        self.publish_with(topic, payload, options).await
+    }
+
+    /// Publish a message with custom options.
+    pub async fn publish_with(
+        &mut self,
+        topic: &str,
+        payload: Vec<u8>,
+        options: PublishOptions,
+    ) -> Result<PublishResponse> {
+        let resp = self
+            .inner
+            .publish(options.into_request(topic, payload))
+            .await
             .context("publish RPC failed")?
             .into_inner();
 
-        Ok(PublishResponse {
-            timestamp: resp.timestamp,
-            message_id: resp.message_id,
-            sequence: resp.sequence,
-        })
+        Ok(resp)
+    }
+
+    /// Open a streaming publisher.
+    ///
+    /// The [`Publisher`] keeps up to `max_in_flight` messages unacknowledged,
+    /// for producers that need more throughput than one publish round-trip
+    /// per message allows.
+    pub async fn publisher(&mut self, max_in_flight: u32) -> Result<Publisher> {
+        Publisher::start(&mut self.inner, max_in_flight).await
+    }
+
+    /// Open a request stream with its own reply inbox.
+    ///
+    /// The inbox is deleted when the [`Requester`] is closed or dropped.
+    pub async fn requester(&mut self) -> Result<Requester> {
+        Requester::start(&mut self.inner).await
+    }
+
+    /// Publish a request to a topic and wait up to `timeout` for the reply.
+    ///
+    /// Requests share one request stream, and so one reply inbox, which is
+    /// opened on first use and deleted when the client is dropped.
+    pub async fn request(
+        &mut self,
+        topic: &str,
+        payload: Vec<u8>,
+        timeout: Duration,
+    ) -> Result<MessageDelivery> {
+        if self.requester.as_ref().map_or(true, Requester::is_closed) {
+            self.requester = Some(Requester::start(&mut self.inner).await?);
+        }
+        let requester = self.requester.as_ref().expect("requester was just opened");
+        requester
+            .request(topic, payload, PublishOptions::default(), timeout)
+            .await
+    }
+
+    /// Answer a request by publishing `payload` to its `reply_to` topic.
+    ///
+    /// Fails if `request` was not sent with [`SluiceClient::request`] or a
+    /// [`Requester`].
+    pub async fn reply(
+        &mut self,
+        request: &MessageDelivery,
+        payload: Vec<u8>,
+    ) -> Result<PublishResponse> {
+        let (Some(reply_to), Some(correlation_id)) = (
+            request.attributes.get(REPLY_TO_ATTRIBUTE),
+            request.attributes.get(CORRELATION_ID_ATTRIBUTE),
+        ) else {
+            return Err(anyhow!("message {} is not a request", request.message_id));
+        };
+        let options =
+            PublishOptions::default().attribute(CORRELATION_ID_ATTRIBUTE, correlation_id.as_str());
+        self.publish_with(reply_to, payload, options).await
     }
 
     /// Publish a message with string payload (convenience method).
@@ -281,4 +783,13 @@ impl SluiceClient {
         )
         .await
     }
+
+    /// Start a subscription with custom options.
+    pub async fn subscribe_with(
+        &mut self,
+        topic: &str,
+        options: SubscribeOptions,
+    ) -> Result<Subscription> {
+        Subscription::start_with_options(&mut self.inner, topic.to_string(), options).await
+    }
 }
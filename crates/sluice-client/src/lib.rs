@@ -34,10 +34,24 @@
 //! ```
 
 mod connection;
+mod publisher;
+mod requester;
 mod subscription;
+mod timestamp;
 
-pub use connection::{ConnectConfig, RetryConfig, SluiceClient};
-pub use subscription::{AutoRefillSubscription, CreditConfig, RefillAmount, Subscription};
+pub use connection::{
+    sequence_conflict, ConnectConfig, PublishOptions, ResetTarget, RetryConfig, SluiceClient,
+};
+pub use publisher::{PendingPublish, Publisher};
+pub use requester::{Requester, CORRELATION_ID_ATTRIBUTE, REPLY_TO_ATTRIBUTE};
+pub use subscription::{
+    AutoRefillSubscription, CreditConfig, RefillAmount, SubscribeOptions, Subscription,
+};
+pub use timestamp::parse_timestamp;
 
 // Re-export proto types that clients commonly use
-pub use sluice_proto::{InitialPosition, MessageDelivery, PublishResponse, Topic};
+pub use sluice_proto::{
+    AckMode, BatchMessage, ConsumerGroupInfo, DeleteTopicResponse, DescribeTopicResponse,
+    InitialPosition, MessageDelivery, PartitionInfo, PublishResponse, SubscriptionMode, Topic,
+    TopicConfig, TopicMessages, TopicPublishResults,
+};
@@ -0,0 +1,211 @@
+//! Streaming publisher for Sluice client.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context as TaskContext, Poll};
+
+use anyhow::{anyhow, Context, Result};
+use tokio::sync::{mpsc, oneshot, OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::metadata::{MetadataMap, MetadataValue};
+use tonic::{Code, Status, Streaming};
+
+use sluice_proto::sluice::v1::{
+    publish_stream_response, PublishError, PublishResponse, PublishStreamRequest,
+    PublishStreamResponse,
+};
+
+use crate::connection::{GrpcClient, PublishOptions, LAST_SEQUENCE_METADATA};
+
+/// A message waiting for its acknowledgement.
+struct Pending {
+    reply: oneshot::Sender<Result<PublishResponse>>,
+    /// Held until the acknowledgement arrives, bounding messages in flight.
+    _permit: OwnedSemaphorePermit,
+}
+
+/// Messages in flight by request ID, or `None` once the stream has ended.
+type PendingMap = Arc<Mutex<Option<HashMap<u64, Pending>>>>;
+
+/// A handle for publishing over a single `PublishStream` RPC.
+///
+/// Messages are sent without waiting for earlier ones to be committed, so
+/// the server can persist many of them per transaction. At most
+/// `max_in_flight` messages are unacknowledged at once; [`Publisher::publish`]
+/// waits for room beyond that. Methods take `&self`, so the handle can be
+/// shared between tasks in an `Arc`.
+pub struct Publisher {
+    /// Sender for outgoing messages.
+    tx: mpsc::Sender<PublishStreamRequest>,
+    /// Messages awaiting their acknowledgement.
+    pending: PendingMap,
+    /// One permit per message that may be in flight.
+    in_flight: Arc<Semaphore>,
+    /// Total number of permits.
+    max_in_flight: u32,
+    /// Request ID for the next message.
+    next_request_id: AtomicU64,
+    /// Task routing acknowledgements to their messages.
+    acks: JoinHandle<()>,
+}
+
+impl Publisher {
+    /// Open a publish stream allowing `max_in_flight` unacknowledged messages.
+    pub(crate) async fn start(client: &mut GrpcClient, max_in_flight: u32) -> Result<Self> {
+        let max_in_flight = max_in_flight.max(1);
+        let (tx, rx) = mpsc::channel::<PublishStreamRequest>(32);
+
+        let response = client
+            .publish_stream(ReceiverStream::new(rx))
+            .await
+            .context("publish_stream RPC failed")?;
+
+        let pending: PendingMap = Arc::new(Mutex::new(Some(HashMap::new())));
+        let acks = tokio::spawn(receive_acks(response.into_inner(), Arc::clone(&pending)));
+
+        Ok(Self {
+            tx,
+            pending,
+            in_flight: Arc::new(Semaphore::new(max_in_flight as usize)),
+            max_in_flight,
+            next_request_id: AtomicU64::new(1),
+            acks,
+        })
+    }
+
+    /// Send a message, waiting only while `max_in_flight` messages are
+    /// unacknowledged.
+    ///
+    /// The returned [`PendingPublish`] resolves to the server's response
+    /// once the message is persisted.
+    pub async fn publish(
+        &self,
+        topic: &str,
+        payload: Vec<u8>,
+        options: PublishOptions,
+    ) -> Result<PendingPublish> {
+        let permit = Arc::clone(&self.in_flight)
+            .acquire_owned()
+            .await
+            .map_err(|_| anyhow!("publisher is closed"))?;
+        let request_id = self.next_request_id.fetch_add(1, Ordering::Relaxed);
+        let (reply, rx) = oneshot::channel();
+
+        match self.pending.lock().expect("pending lock poisoned").as_mut() {
+            Some(pending) => {
+                pending.insert(
+                    request_id,
+                    Pending {
+                        reply,
+                        _permit: permit,
+                    },
+                );
+            }
+            None => return Err(anyhow!("publish stream has ended")),
+        }
+
+        let request = PublishStreamRequest {
+            request_id,
+            message: Some(options.into_request(topic, payload)),
+        };
+        if self.tx.send(request).await.is_err() {
+            if let Some(pending) = self.pending.lock().expect("pending lock poisoned").as_mut() {
+                pending.remove(&request_id);
+            }
+            return Err(anyhow!("publish stream has ended"));
+        }
+
+        Ok(PendingPublish { rx })
+    }
+
+    /// Wait until every message sent so far is acknowledged.
+    pub async fn flush(&self) -> Result<()> {
+        let _all = self
+            .in_flight
+            .acquire_many(self.max_in_flight)
+            .await
+            .map_err(|_| anyhow!("publisher is closed"))?;
+        Ok(())
+    }
+
+    /// Close the stream after every message sent so far is acknowledged.
+    pub async fn close(self) -> Result<()> {
+        drop(self.tx);
+        self.acks
+            .await
+            .map_err(|e| anyhow!("acknowledgement task failed: {e}"))
+    }
+}
+
+/// The acknowledgement of a message sent by [`Publisher::publish`].
+///
+/// Resolves to the server's response, or the error the unary `publish`
+/// would have returned. Dropping it does not cancel the publish.
+pub struct PendingPublish {
+    rx: oneshot::Receiver<Result<PublishResponse>>,
+}
+
+impl Future for PendingPublish {
+    type Output = Result<PublishResponse>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.rx)
+            .poll(cx)
+            .map(|result| result.unwrap_or_else(|_| Err(anyhow!("publish stream has ended"))))
+    }
+}
+
+/// Route acknowledgements to their messages until the stream ends.
+async fn receive_acks(mut rx: Streaming<PublishStreamResponse>, pending: PendingMap) {
+    let end = loop {
+        match rx.message().await {
+            Ok(Some(response)) => {
+                let entry = pending
+                    .lock()
+                    .expect("pending lock poisoned")
+                    .as_mut()
+                    .and_then(|pending| pending.remove(&response.request_id));
+                if let Some(entry) = entry {
+                    let _ = entry.reply.send(ack_result(response.result));
+                }
+            }
+            Ok(None) => break None,
+            Err(status) => break Some(status),
+        }
+    };
+
+    // Fail messages that will never be acknowledged
+    let remaining = pending.lock().expect("pending lock poisoned").take();
+    for entry in remaining.into_iter().flat_map(HashMap::into_values) {
+        let error = match &end {
+            Some(status) => anyhow::Error::new(status.clone()).context("publish stream failed"),
+            None => anyhow!("publish stream ended before the message was acknowledged"),
+        };
+        let _ = entry.reply.send(Err(error));
+    }
+}
+
+/// Convert a stream response to the result of the unary `publish`.
+fn ack_result(result: Option<publish_stream_response::Result>) -> Result<PublishResponse> {
+    match result {
+        Some(publish_stream_response::Result::Ack(ack)) => Ok(ack),
+        Some(publish_stream_response::Result::Error(error)) => {
+            Err(anyhow::Error::new(error_status(error)).context("publish failed"))
+        }
+        None => Err(anyhow!("publish response is missing its result")),
+    }
+}
+
+/// Rebuild the status the unary `publish` would have failed with, so that
+/// [`sequence_conflict`](crate::sequence_conflict) works on it.
+pub(crate) fn error_status(error: PublishError) -> Status {
+    let mut metadata = MetadataMap::new();
+    if let Some(last_sequence) = error.last_sequence {
+        metadata.insert(LAST_SEQUENCE_METADATA, MetadataValue::from(last_sequence));
+    }
+    Status::with_metadata(Code::from(error.code), error.message, metadata)
+}
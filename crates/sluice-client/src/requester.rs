@@ -0,0 +1,162 @@
+//! Request/reply for Sluice client.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::Streaming;
+
+use sluice_proto::sluice::v1::{
+    request_downstream, MessageDelivery, RequestDownstream, RequestUpstream,
+};
+
+use crate::connection::{GrpcClient, PublishOptions};
+use crate::publisher::error_status;
+
+/// Attribute of a request naming the topic to publish its reply to.
+pub const REPLY_TO_ATTRIBUTE: &str = "reply_to";
+
+/// Attribute a reply must copy from its request.
+pub const CORRELATION_ID_ATTRIBUTE: &str = "correlation_id";
+
+/// Requests awaiting their reply by request ID, or `None` once the stream
+/// has ended.
+type PendingMap = Arc<Mutex<Option<HashMap<u64, oneshot::Sender<Result<MessageDelivery>>>>>>;
+
+/// A handle for request/reply over a single `Request` RPC.
+///
+/// The server gives the stream an inbox topic, reused by every request, and
+/// deletes it when the handle is closed or dropped. Methods take `&self`, so
+/// requests can be made from several tasks at once through an `Arc`.
+pub struct Requester {
+    /// Sender for outgoing requests.
+    tx: mpsc::Sender<RequestUpstream>,
+    /// Requests awaiting their reply.
+    pending: PendingMap,
+    /// Request ID for the next request.
+    next_request_id: AtomicU64,
+    /// Task routing replies to their requests.
+    replies: JoinHandle<()>,
+}
+
+impl Requester {
+    /// Open a request stream.
+    pub(crate) async fn start(client: &mut GrpcClient) -> Result<Self> {
+        let (tx, rx) = mpsc::channel::<RequestUpstream>(32);
+
+        let response = client
+            .request(ReceiverStream::new(rx))
+            .await
+            .context("request RPC failed")?;
+
+        let pending: PendingMap = Arc::new(Mutex::new(Some(HashMap::new())));
+        let replies = tokio::spawn(receive_replies(response.into_inner(), Arc::clone(&pending)));
+
+        Ok(Self {
+            tx,
+            pending,
+            next_request_id: AtomicU64::new(1),
+            replies,
+        })
+    }
+
+    /// Publish a request and wait up to `timeout` for its reply.
+    ///
+    /// Fails with `DEADLINE_EXCEEDED` if no reply arrives in time.
+    pub async fn request(
+        &self,
+        topic: &str,
+        payload: Vec<u8>,
+        options: PublishOptions,
+        timeout: Duration,
+    ) -> Result<MessageDelivery> {
+        let request_id = self.next_request_id.fetch_add(1, Ordering::Relaxed);
+        let (reply, rx) = oneshot::channel();
+
+        match self.pending.lock().expect("pending lock poisoned").as_mut() {
+            Some(pending) => {
+                pending.insert(request_id, reply);
+            }
+            None => return Err(anyhow!("request stream has ended")),
+        }
+
+        let request = RequestUpstream {
+            request_id,
+            message: Some(options.into_request(topic, payload)),
+            // 0 would select the server default
+            timeout_ms: timeout.as_millis().clamp(1, u128::from(u32::MAX)) as u32,
+        };
+        if self.tx.send(request).await.is_err() {
+            if let Some(pending) = self.pending.lock().expect("pending lock poisoned").as_mut() {
+                pending.remove(&request_id);
+            }
+            return Err(anyhow!("request stream has ended"));
+        }
+
+        rx.await
+            .unwrap_or_else(|_| Err(anyhow!("request stream has ended")))
+    }
+
+    /// Whether the stream has ended, so that no more requests can be made.
+    pub fn is_closed(&self) -> bool {
+        self.pending
+            .lock()
+            .expect("pending lock poisoned")
+            .is_none()
+    }
+
+    /// Close the stream after every pending request is answered, deleting
+    /// the inbox.
+    pub async fn close(self) -> Result<()> {
+        drop(self.tx);
+        self.replies
+            .await
+            .map_err(|e| anyhow!("reply task failed: {e}"))
+    }
+}
+
+/// Route replies to their requests until the stream ends.
+async fn receive_replies(mut rx: Streaming<RequestDownstream>, pending: PendingMap) {
+    let end = loop {
+        match rx.message().await {
+            Ok(Some(response)) => {
+                let entry = pending
+                    .lock()
+                    .expect("pending lock poisoned")
+                    .as_mut()
+                    .and_then(|pending| pending.remove(&response.request_id));
+                if let Some(entry) = entry {
+                    let _ = entry.send(reply_result(response.result));
+                }
+            }
+            Ok(None) => break None,
+            Err(status) => break Some(status),
+        }
+    };
+
+    // Fail requests that will never be answered
+    let remaining = pending.lock().expect("pending lock poisoned").take();
+    for entry in remaining.into_iter().flat_map(HashMap::into_values) {
+        let error = match &end {
+            Some(status) => anyhow::Error::new(status.clone()).context("request stream failed"),
+            None => anyhow!("request stream ended before the reply arrived"),
+        };
+        let _ = entry.send(Err(error));
+    }
+}
+
+/// Convert a stream response to the reply or the error of its request.
+fn reply_result(result: Option<request_downstream::Result>) -> Result<MessageDelivery> {
+    match result {
+        Some(request_downstream::Result::Reply(reply)) => Ok(reply),
+        Some(request_downstream::Result::Error(error)) => {
+            Err(anyhow::Error::new(error_status(error)).context("request failed"))
+        }
+        None => Err(anyhow!("request response is missing its result")),
+    }
+}
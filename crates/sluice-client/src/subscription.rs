@@ -2,17 +2,21 @@
 
 use anyhow::{anyhow, Context, Result};
 use futures::StreamExt;
+use std::collections::VecDeque;
+use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
-use tonic::transport::Channel;
 use tonic::Streaming;
 
-use sluice_proto::sluice::v1::sluice_client::SluiceClient as ProtoClient;
 use sluice_proto::sluice::v1::{
-    subscribe_downstream, subscribe_upstream, Ack, CreditGrant, InitialPosition, MessageDelivery,
-    SubscribeDownstream, SubscribeUpstream, SubscriptionInit,
+    subscribe_downstream, subscribe_upstream, Ack, AckAndPublish, AckMode, CreditGrant,
+    InitialPosition, MessageDelivery, ModifyAckDeadline, Nack, SubscribeDownstream,
+    SubscribeUpstream, SubscriptionInit, SubscriptionMode, TopicMessages, TopicPublishResults,
 };
 
+use crate::connection::GrpcClient;
+use crate::publisher::error_status;
+
 /// Configures how credits are refilled.
 #[derive(Debug, Clone)]
 pub struct CreditConfig {
@@ -79,6 +83,150 @@ impl RefillAmount {
     }
 }
 
+/// Options for starting a subscription.
+#[derive(Debug, Clone)]
+pub struct SubscribeOptions {
+    /// Consumer group name (defaults to "default").
+    pub consumer_group: Option<String>,
+    /// Consumer ID within the group.
+    pub consumer_id: Option<String>,
+    /// Where to start when the group has no stored cursor.
+    pub initial_position: InitialPosition,
+    /// Start time (Unix epoch ms) for `InitialPosition::Timestamp`.
+    pub start_timestamp: Option<i64>,
+    /// Credit flow control configuration.
+    pub credit_config: CreditConfig,
+    /// How acks advance the consumer group cursor.
+    pub ack_mode: AckMode,
+    /// Failed attempts after which a message is dead-lettered (0 = never).
+    pub max_delivery_attempts: u32,
+    /// Dead-letter topic (defaults to `<topic>.dlq` on the server).
+    pub dead_letter_topic: Option<String>,
+    /// How long a delivered message may stay unacked before redelivery.
+    pub ack_deadline: Option<Duration>,
+    /// Whether to take over the consumer group or share it.
+    pub mode: SubscriptionMode,
+    /// Partitions to consume (empty = all partitions).
+    pub partitions: Vec<u32>,
+    /// Attribute filter evaluated by the server.
+    pub filter: Option<String>,
+}
+
+impl Default for SubscribeOptions {
+    fn default() -> Self {
+        Self {
+            consumer_group: None,
+            consumer_id: None,
+            initial_position: InitialPosition::Latest,
+            start_timestamp: None,
+            credit_config: CreditConfig::default(),
+            ack_mode: AckMode::Cumulative,
+            max_delivery_attempts: 0,
+            dead_letter_topic: None,
+            ack_deadline: None,
+            mode: SubscriptionMode::Exclusive,
+            partitions: Vec::new(),
+            filter: None,
+        }
+    }
+}
+
+impl SubscribeOptions {
+    /// Set the consumer group.
+    pub fn consumer_group(mut self, group: impl Into<String>) -> Self {
+        self.consumer_group = Some(group.into());
+        self
+    }
+
+    /// Set the consumer ID.
+    pub fn consumer_id(mut self, id: impl Into<String>) -> Self {
+        self.consumer_id = Some(id.into());
+        self
+    }
+
+    /// Set the initial position.
+    pub fn initial_position(mut self, position: InitialPosition) -> Self {
+        self.initial_position = position;
+        self
+    }
+
+    /// Start from the first message at or after `timestamp` (Unix epoch ms).
+    ///
+    /// Like `InitialPosition::Offset`, this replaces the group's stored
+    /// cursor. Use [`parse_timestamp`](crate::parse_timestamp) to accept
+    /// RFC 3339 input.
+    pub fn start_timestamp(mut self, timestamp: i64) -> Self {
+        self.initial_position = InitialPosition::Timestamp;
+        self.start_timestamp = Some(timestamp);
+        self
+    }
+
+    /// Set the credit configuration.
+    pub fn credits(mut self, config: CreditConfig) -> Self {
+        self.credit_config = config;
+        self
+    }
+
+    /// Set the ack mode.
+    ///
+    /// With `AckMode::Individual`, acking a message does not ack earlier
+    /// ones; unacked messages are redelivered after reconnecting.
+    pub fn ack_mode(mut self, mode: AckMode) -> Self {
+        self.ack_mode = mode;
+        self
+    }
+
+    /// Dead-letter messages after `max_attempts` failed deliveries.
+    pub fn max_delivery_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_delivery_attempts = max_attempts;
+        self
+    }
+
+    /// Set the dead-letter topic used once `max_delivery_attempts` is reached.
+    pub fn dead_letter_topic(mut self, topic: impl Into<String>) -> Self {
+        self.dead_letter_topic = Some(topic.into());
+        self
+    }
+
+    /// Redeliver messages that are not acked within `deadline`.
+    ///
+    /// Use `Subscription::modify_ack_deadline` to extend the deadline for
+    /// messages that take longer to process.
+    pub fn ack_deadline(mut self, deadline: Duration) -> Self {
+        self.ack_deadline = Some(deadline);
+        self
+    }
+
+    /// Set the subscription mode.
+    ///
+    /// With `SubscriptionMode::Shared`, connections in the same consumer
+    /// group split its messages between them instead of taking over from
+    /// each other. Acks are always individual in shared mode.
+    pub fn mode(mut self, mode: SubscriptionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Consume only the given partitions of a partitioned topic.
+    ///
+    /// Connections of the same consumer group that are assigned disjoint
+    /// partitions consume side by side; one that overlaps takes over.
+    pub fn partitions(mut self, partitions: impl IntoIterator<Item = u32>) -> Self {
+        self.partitions = partitions.into_iter().collect();
+        self
+    }
+
+    /// Receive only messages whose attributes match `expr`.
+    ///
+    /// The server skips other messages without using credits, e.g.
+    /// `region IN ('eu', 'us') AND NOT EXISTS test`. Members of a shared
+    /// group should use the same filter.
+    pub fn filter(mut self, expr: impl Into<String>) -> Self {
+        self.filter = Some(expr.into());
+        self
+    }
+}
+
 /// A handle for controlling an active subscription.
 ///
 /// Manages credit-based flow control and provides methods for receiving
@@ -92,39 +240,52 @@ pub struct Subscription {
     credit_config: CreditConfig,
     /// Remaining credits before refill is needed.
     remaining_credits: u32,
+    /// Deliveries received while waiting for an AckAndPublish result.
+    buffered: VecDeque<MessageDelivery>,
+    /// Transaction ID for the next AckAndPublish.
+    next_transaction_id: u64,
 }
 
 impl Subscription {
     /// Start a new subscription.
     pub(crate) async fn start(
-        client: &mut ProtoClient<Channel>,
+        client: &mut GrpcClient,
         topic: String,
         consumer_group: Option<String>,
         consumer_id: Option<String>,
         initial_position: InitialPosition,
         credits_window: u32,
     ) -> Result<Self> {
-        let credit_config = CreditConfig::with_window(credits_window);
-        Self::start_with_config(
-            client,
-            topic,
+        let options = SubscribeOptions {
             consumer_group,
             consumer_id,
             initial_position,
-            credit_config,
-        )
-        .await
+            credit_config: CreditConfig::with_window(credits_window),
+            ..Default::default()
+        };
+        Self::start_with_options(client, topic, options).await
     }
 
-    /// Start a new subscription with custom credit configuration.
-    pub(crate) async fn start_with_config(
-        client: &mut ProtoClient<Channel>,
+    /// Start a new subscription with custom options.
+    pub(crate) async fn start_with_options(
+        client: &mut GrpcClient,
         topic: String,
-        consumer_group: Option<String>,
-        consumer_id: Option<String>,
-        initial_position: InitialPosition,
-        credit_config: CreditConfig,
+        options: SubscribeOptions,
     ) -> Result<Self> {
+        let SubscribeOptions {
+            consumer_group,
+            consumer_id,
+            initial_position,
+            start_timestamp,
+            credit_config,
+            ack_mode,
+            max_delivery_attempts,
+            dead_letter_topic,
+            ack_deadline,
+            mode,
+            partitions,
+            filter,
+        } = options;
         let (tx, rx) = mpsc::channel::<SubscribeUpstream>(32);
 
         // Send init message
@@ -135,6 +296,14 @@ impl Subscription {
                 consumer_id: consumer_id.unwrap_or_default(),
                 initial_position: initial_position.into(),
                 offset: 0,
+                ack_mode: ack_mode.into(),
+                max_delivery_attempts,
+                dead_letter_topic: dead_letter_topic.unwrap_or_default(),
+                ack_deadline_ms: ack_deadline.map(duration_to_millis).unwrap_or(0),
+                mode: mode.into(),
+                partitions,
+                start_timestamp: start_timestamp.unwrap_or(0),
+                filter: filter.unwrap_or_default(),
             })),
         };
         tx.send(init)
@@ -165,11 +334,17 @@ impl Subscription {
             rx: response.into_inner(),
             credit_config,
             remaining_credits,
+            buffered: VecDeque::new(),
+            next_transaction_id: 1,
         })
     }
 
     /// Get the next message delivery, returning None if stream ends.
     pub async fn next_message(&mut self) -> Result<Option<MessageDelivery>> {
+        if let Some(msg) = self.buffered.pop_front() {
+            return Ok(Some(msg));
+        }
+
         match self.rx.next().await {
             Some(Ok(downstream)) => {
                 if let Some(subscribe_downstream::Response::Delivery(msg)) = downstream.response {
@@ -191,10 +366,10 @@ impl Subscription {
         let threshold =
             (self.credit_config.window_size as f32 * self.credit_config.refill_threshold) as u32;
         if self.remaining_credits < threshold {
-            let grant = self.credit_config.refill_amount.calculate(
-                self.credit_config.window_size,
-                self.remaining_credits,
-            );
+            let grant = self
+                .credit_config
+                .refill_amount
+                .calculate(self.credit_config.window_size, self.remaining_credits);
             if grant > 0 {
                 self.send_credit(grant).await?;
                 self.remaining_credits += grant;
@@ -231,6 +406,109 @@ impl Subscription {
             .map_err(|_| anyhow!("subscription channel closed"))
     }
 
+    /// Ack messages and publish others in one transaction.
+    ///
+    /// Either every ack and publish takes effect or none does, so a pipeline
+    /// that publishes its output together with the acks of its input
+    /// processes each message exactly once. Waits for the server's result;
+    /// deliveries arriving meanwhile are returned by later `next_message`
+    /// calls.
+    pub async fn ack_and_publish(
+        &mut self,
+        ack_message_ids: Vec<String>,
+        publish: Vec<TopicMessages>,
+    ) -> Result<Vec<TopicPublishResults>> {
+        let transaction_id = self.next_transaction_id;
+        self.next_transaction_id += 1;
+
+        self.tx
+            .send(SubscribeUpstream {
+                request: Some(subscribe_upstream::Request::AckAndPublish(AckAndPublish {
+                    transaction_id,
+                    ack_message_ids,
+                    publish,
+                })),
+            })
+            .await
+            .map_err(|_| anyhow!("subscription channel closed"))?;
+
+        loop {
+            let response = match self.rx.next().await {
+                Some(Ok(downstream)) => downstream.response,
+                Some(Err(e)) => return Err(e.into()),
+                None => {
+                    return Err(anyhow!(
+                        "subscription ended before the transaction completed"
+                    ))
+                }
+            };
+            match response {
+                Some(subscribe_downstream::Response::Delivery(msg)) => {
+                    self.consume_credit();
+                    self.buffered.push_back(msg);
+                }
+                Some(subscribe_downstream::Response::AckAndPublishResult(result))
+                    if result.transaction_id == transaction_id =>
+                {
+                    return match result.error {
+                        Some(error) => Err(anyhow::Error::new(error_status(error))
+                            .context("ack_and_publish failed")),
+                        None => Ok(result.topics),
+                    };
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Send a Nack for a specific message ID.
+    ///
+    /// The server redelivers the message after `requeue_delay` with an
+    /// incremented `delivery_attempt`.
+    pub async fn send_nack(&self, message_id: &str, requeue_delay: Duration) -> Result<()> {
+        self.send_nack_with_reason(message_id, requeue_delay, "")
+            .await
+    }
+
+    /// Send a Nack with a failure reason.
+    ///
+    /// The reason is recorded on the dead-letter copy if this was the
+    /// message's last allowed attempt.
+    pub async fn send_nack_with_reason(
+        &self,
+        message_id: &str,
+        requeue_delay: Duration,
+        reason: &str,
+    ) -> Result<()> {
+        self.tx
+            .send(SubscribeUpstream {
+                request: Some(subscribe_upstream::Request::Nack(Nack {
+                    message_id: message_id.to_string(),
+                    requeue_delay_ms: duration_to_millis(requeue_delay),
+                    reason: reason.to_string(),
+                })),
+            })
+            .await
+            .map_err(|_| anyhow!("subscription channel closed"))
+    }
+
+    /// Extend the ack deadline of a message to `deadline` from now.
+    ///
+    /// A zero deadline gives the message up for immediate redelivery.
+    pub async fn modify_ack_deadline(&self, message_id: &str, deadline: Duration) -> Result<()> {
+        self.tx
+            .send(SubscribeUpstream {
+                request: Some(subscribe_upstream::Request::ModifyAckDeadline(
+                    ModifyAckDeadline {
+                        message_id: message_id.to_string(),
+                        ack_deadline_ms: duration_to_millis(deadline),
+                    },
+                )),
+            })
+            .await
+            .map_err(|_| anyhow!("subscription channel closed"))
+    }
+
     /// Get the configured credits window size.
     pub fn credits_window(&self) -> u32 {
         self.credit_config.window_size
@@ -247,6 +525,11 @@ impl Subscription {
     }
 }
 
+/// Convert a duration to whole milliseconds, saturating at `u32::MAX`.
+fn duration_to_millis(duration: Duration) -> u32 {
+    duration.as_millis().min(u32::MAX as u128) as u32
+}
+
 /// A subscription wrapper that automatically refills credits in the background.
 ///
 /// This provides a higher-level API where credit management is handled
@@ -283,6 +566,37 @@ impl AutoRefillSubscription {
         self.inner.send_ack(message_id).await
     }
 
+    /// Reject a message and have it redelivered after a delay.
+    pub async fn send_nack(&self, message_id: &str, requeue_delay: Duration) -> Result<()> {
+        self.inner.send_nack(message_id, requeue_delay).await
+    }
+
+    /// Reject a message with a failure reason.
+    pub async fn send_nack_with_reason(
+        &self,
+        message_id: &str,
+        requeue_delay: Duration,
+        reason: &str,
+    ) -> Result<()> {
+        self.inner
+            .send_nack_with_reason(message_id, requeue_delay, reason)
+            .await
+    }
+
+    /// Extend the ack deadline of a message.
+    pub async fn modify_ack_deadline(&self, message_id: &str, deadline: Duration) -> Result<()> {
+        self.inner.modify_ack_deadline(message_id, deadline).await
+    }
+
+    /// Ack messages and publish others in one transaction.
+    pub async fn ack_and_publish(
+        &mut self,
+        ack_message_ids: Vec<String>,
+        publish: Vec<TopicMessages>,
+    ) -> Result<Vec<TopicPublishResults>> {
+        self.inner.ack_and_publish(ack_message_ids, publish).await
+    }
+
     /// Get the current remaining credits.
     pub fn remaining_credits(&self) -> u32 {
         self.inner.remaining_credits()
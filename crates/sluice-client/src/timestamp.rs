@@ -0,0 +1,97 @@
+//! Parsing of user-supplied timestamps for `InitialPosition::Timestamp`.
+
+use anyhow::{anyhow, Result};
+
+/// Parse a timestamp into Unix epoch milliseconds.
+///
+/// Accepts either a number of milliseconds since the Unix epoch, or an
+/// RFC 3339 date-time such as `2024-05-01T14:05:00Z` or
+/// `2024-05-01T16:05:00.250+02:00`. Seconds may be omitted, and a date-time
+/// without an offset is taken as UTC.
+pub fn parse_timestamp(input: &str) -> Result<i64> {
+    let input = input.trim();
+    if let Ok(millis) = input.parse::<i64>() {
+        return Ok(millis);
+    }
+    parse_rfc3339(input).ok_or_else(|| {
+        anyhow!("invalid timestamp '{input}': expected Unix milliseconds or RFC 3339 (e.g. 2024-05-01T14:05:00Z)")
+    })
+}
+
+fn parse_rfc3339(input: &str) -> Option<i64> {
+    let (date, rest) = input.split_once(['T', 't', ' '])?;
+
+    let mut date_parts = date.splitn(3, '-');
+    let year: i64 = date_parts.next()?.parse().ok()?;
+    let month: u32 = date_parts.next()?.parse().ok()?;
+    let day: u32 = date_parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return None;
+    }
+
+    // Split off the UTC offset
+    let (time, offset_minutes) = if let Some(time) = rest.strip_suffix(['Z', 'z']) {
+        (time, 0)
+    } else if let Some(sign_at) = rest.rfind(['+', '-']) {
+        let (time, offset) = rest.split_at(sign_at);
+        let sign = if offset.starts_with('-') { -1 } else { 1 };
+        let (hours, minutes) = offset[1..].split_once(':')?;
+        let hours: i64 = hours.parse().ok()?;
+        let minutes: i64 = minutes.parse().ok()?;
+        if hours > 23 || minutes > 59 {
+            return None;
+        }
+        (time, sign * (hours * 60 + minutes))
+    } else {
+        (rest, 0)
+    };
+
+    let (time, fraction) = match time.split_once('.') {
+        Some((time, fraction)) => (time, Some(fraction)),
+        None => (time, None),
+    };
+    let mut time_parts = time.splitn(3, ':');
+    let hour: i64 = time_parts.next()?.parse().ok()?;
+    let minute: i64 = time_parts.next()?.parse().ok()?;
+    let second: i64 = match time_parts.next() {
+        Some(second) => second.parse().ok()?,
+        None if fraction.is_none() => 0,
+        None => return None,
+    };
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    // Keep millisecond precision of the fraction
+    let millis = match fraction {
+        Some(fraction) if !fraction.is_empty() && fraction.bytes().all(|b| b.is_ascii_digit()) => {
+            format!("{fraction:0<3}")[..3].parse::<i64>().ok()?
+        }
+        Some(_) => return None,
+        None => 0,
+    };
+
+    let days = days_from_civil(year, month, day);
+    let seconds = days * 86_400 + hour * 3_600 + minute * 60 + second - offset_minutes * 60;
+    Some(seconds * 1_000 + millis)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = (if year >= 0 { year } else { year - 399 }) / 400;
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
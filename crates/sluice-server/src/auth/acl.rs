@@ -0,0 +1,176 @@
+//! Access control lists.
+//!
+//! An ACL is a list of rules, each granting a principal a set of actions on
+//! the topics matching any of its patterns. Anything not granted is denied.
+//! The file is JSON:
+//!
+//! ```json
+//! {
+//!   "rules": [
+//!     { "principal": "orders-service", "topics": ["orders.*"], "actions": ["publish"] },
+//!     { "principal": "*", "topics": ["public.*"], "actions": ["subscribe"] },
+//!     { "principal": "ops", "topics": ["*"], "actions": ["admin"] }
+//!   ]
+//! }
+//! ```
+
+use serde::Deserialize;
+use std::fmt;
+use std::path::Path;
+
+use super::{AuthError, Principal};
+
+/// Something a principal can do to a topic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    /// Publish messages to the topic.
+    Publish,
+    /// Subscribe to the topic and ack or nack its messages.
+    Subscribe,
+    /// Manage the topic. Implies publish and subscribe.
+    Admin,
+}
+
+impl Action {
+    /// Name used in ACL files, logs and metrics.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Action::Publish => "publish",
+            Action::Subscribe => "subscribe",
+            Action::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One grant in an ACL.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AclRule {
+    /// Principal the rule applies to, or `*` for every authenticated principal.
+    pub principal: String,
+    /// Topic name patterns; `*` matches any run of characters.
+    pub topics: Vec<String>,
+    /// Actions granted on the matching topics.
+    pub actions: Vec<Action>,
+}
+
+impl AclRule {
+    fn grants(&self, principal: &Principal, action: Action, topic: &str) -> bool {
+        (self.principal == "*" || self.principal == principal.name())
+            && self
+                .actions
+                .iter()
+                .any(|&granted| granted == action || granted == Action::Admin)
+            && self
+                .topics
+                .iter()
+                .any(|pattern| matches_pattern(pattern, topic))
+    }
+}
+
+/// A set of rules deciding what each principal may do.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Acl {
+    pub rules: Vec<AclRule>,
+}
+
+impl Acl {
+    /// Load an ACL from a JSON file.
+    pub fn load(path: &Path) -> Result<Self, AuthError> {
+        let contents = std::fs::read_to_string(path).map_err(|e| AuthError::Io {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        serde_json::from_str(&contents).map_err(|e| AuthError::InvalidFile {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })
+    }
+
+    /// Returns true if some rule grants `action` on `topic` to `principal`.
+    pub fn allows(&self, principal: &Principal, action: Action, topic: &str) -> bool {
+        self.rules
+            .iter()
+            .any(|rule| rule.grants(principal, action, topic))
+    }
+
+    /// Returns true if `principal` may do anything at all with `topic`.
+    pub fn allows_any(&self, principal: &Principal, topic: &str) -> bool {
+        [Action::Publish, Action::Subscribe]
+            .into_iter()
+            .any(|action| self.allows(principal, action, topic))
+    }
+}
+
+/// Match a topic name against a pattern where `*` matches any run of characters.
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // split always yields at least one item
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No `*` in the pattern
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("orders", "orders"));
+        assert!(!matches_pattern("orders", "orders.eu"));
+        assert!(matches_pattern("orders.*", "orders.eu"));
+        assert!(!matches_pattern("orders.*", "orders"));
+        assert!(matches_pattern("*", "anything"));
+        assert!(matches_pattern("*.dlq", "orders.dlq"));
+        assert!(matches_pattern("team-*.*.events", "team-a.billing.events"));
+        assert!(!matches_pattern("team-*.*.events", "team-a.events"));
+        assert!(!matches_pattern("a*a", "a"));
+    }
+
+    #[test]
+    fn test_acl_rules() {
+        let acl: Acl = serde_json::from_str(
+            r#"{"rules": [
+                {"principal": "orders-service", "topics": ["orders.*"], "actions": ["publish"]},
+                {"principal": "*", "topics": ["public.*"], "actions": ["subscribe"]},
+                {"principal": "ops", "topics": ["*"], "actions": ["admin"]}
+            ]}"#,
+        )
+        .unwrap();
+
+        let orders = Principal::new("orders-service");
+        assert!(acl.allows(&orders, Action::Publish, "orders.eu"));
+        assert!(!acl.allows(&orders, Action::Subscribe, "orders.eu"));
+        assert!(!acl.allows(&orders, Action::Publish, "billing"));
+        assert!(acl.allows(&orders, Action::Subscribe, "public.news"));
+        assert!(!acl.allows_any(&orders, "billing"));
+
+        // Admin implies everything else
+        let ops = Principal::new("ops");
+        assert!(acl.allows(&ops, Action::Admin, "billing"));
+        assert!(acl.allows(&ops, Action::Publish, "billing"));
+        assert!(!acl.allows(&orders, Action::Admin, "orders.eu"));
+    }
+}
@@ -0,0 +1,283 @@
+//! Authentication and per-topic authorization.
+//!
+//! When tokens are configured, every request must carry an
+//! `authorization: Bearer <token>` header. [`AuthInterceptor`] validates the
+//! token and attaches the resulting [`Principal`] to the request; handlers
+//! then ask [`Auth::authorize`] whether that principal may act on a topic.
+//!
+//! Without an ACL every authenticated principal may do everything. With
+//! one, anything the ACL does not grant is denied with `PERMISSION_DENIED`.
+
+pub mod acl;
+pub mod token;
+
+pub use acl::{Acl, AclRule, Action};
+pub use token::{TokenError, TokenValidator};
+
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use thiserror::Error;
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+
+use crate::config::Config;
+use crate::now_millis;
+use crate::observability::metrics::record_permission_denied;
+
+/// Error type for loading auth configuration.
+#[derive(Debug, Error)]
+pub enum AuthError {
+    #[error("failed to read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("invalid auth file {path}: {message}")]
+    InvalidFile { path: PathBuf, message: String },
+}
+
+/// An authenticated client identity.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Principal(String);
+
+impl Principal {
+    /// Create a principal with the given name.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+
+    /// The principal's name, as used in ACL rules.
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+
+    /// The principal attached to a request by [`AuthInterceptor`], if any.
+    pub fn from_request<T>(request: &Request<T>) -> Option<Self> {
+        request.extensions().get::<Principal>().cloned()
+    }
+}
+
+impl fmt::Display for Principal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Token validation and access control for the server.
+#[derive(Debug, Default)]
+pub struct Auth {
+    /// Token validation; `None` disables authentication entirely.
+    validator: Option<TokenValidator>,
+    /// Access rules; `None` allows every authenticated principal everything.
+    acl: Option<Acl>,
+}
+
+impl Auth {
+    /// Auth that accepts every request.
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Require tokens accepted by `validator`, optionally restricted by `acl`.
+    pub fn new(validator: TokenValidator, acl: Option<Acl>) -> Self {
+        Self {
+            validator: Some(validator),
+            acl,
+        }
+    }
+
+    /// Build auth from `--auth-token-file`, `--auth-jwt-secret-file` and
+    /// `--auth-acl-file`.
+    ///
+    /// Authentication is enabled when either token source is configured.
+    /// The command line rejects an ACL without one.
+    pub fn from_config(config: &Config) -> Result<Self, AuthError> {
+        if config.auth_token_file.is_none() && config.auth_jwt_secret_file.is_none() {
+            return Ok(Self::disabled());
+        }
+
+        let mut validator = TokenValidator::new();
+        if let Some(path) = &config.auth_token_file {
+            validator = validator.load_static_tokens(path)?;
+        }
+        if let Some(path) = &config.auth_jwt_secret_file {
+            validator = validator.load_jwt_secret(path)?;
+        }
+        let acl = config.auth_acl_file.as_deref().map(Acl::load).transpose()?;
+
+        Ok(Self::new(validator, acl))
+    }
+
+    /// Returns true if requests must carry a token.
+    pub fn is_enabled(&self) -> bool {
+        self.validator.is_some()
+    }
+
+    /// Resolve the `authorization` header of a request to a principal.
+    ///
+    /// Returns `None` when authentication is disabled.
+    #[allow(clippy::result_large_err)]
+    pub fn authenticate<T>(&self, request: &Request<T>) -> Result<Option<Principal>, Status> {
+        let Some(validator) = &self.validator else {
+            return Ok(None);
+        };
+
+        let header = request
+            .metadata()
+            .get("authorization")
+            .ok_or_else(|| Status::unauthenticated("missing bearer token"))?;
+        let token = header
+            .to_str()
+            .ok()
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("authorization must be a bearer token"))?;
+
+        validator
+            .validate(token.trim(), now_millis() / 1000)
+            .map(Some)
+            .map_err(|e| Status::unauthenticated(e.to_string()))
+    }
+
+    /// Check that `principal` may perform `action` on `topic`.
+    ///
+    /// Fails with `UNAUTHENTICATED` if authentication is enabled and there is
+    /// no principal, and with `PERMISSION_DENIED` if the ACL does not grant
+    /// the action.
+    #[allow(clippy::result_large_err)]
+    pub fn authorize(
+        &self,
+        principal: Option<&Principal>,
+        action: Action,
+        topic: &str,
+    ) -> Result<(), Status> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        let principal = principal.ok_or_else(|| Status::unauthenticated("missing bearer token"))?;
+
+        let Some(acl) = &self.acl else {
+            return Ok(());
+        };
+        if acl.allows(principal, action, topic) {
+            return Ok(());
+        }
+
+        record_permission_denied(topic, action.as_str());
+        tracing::warn!(%principal, %action, topic, "Permission denied");
+        Err(Status::permission_denied(format!(
+            "{principal} may not {action} on topic {topic}"
+        )))
+    }
+
+    /// Returns true if `principal` may see `topic` in topic listings.
+    pub fn can_see(&self, principal: Option<&Principal>, topic: &str) -> bool {
+        if !self.is_enabled() {
+            return true;
+        }
+        match (&self.acl, principal) {
+            (_, None) => false,
+            (None, Some(_)) => true,
+            (Some(acl), Some(principal)) => acl.allows_any(principal, topic),
+        }
+    }
+
+    /// Returns true if `principal` may perform `action` on `topic`.
+    ///
+    /// Unlike [`Auth::authorize`] this neither logs nor counts a denial, for
+    /// filtering listings.
+    pub fn allows(&self, principal: Option<&Principal>, action: Action, topic: &str) -> bool {
+        if !self.is_enabled() {
+            return true;
+        }
+        match (&self.acl, principal) {
+            (_, None) => false,
+            (None, Some(_)) => true,
+            (Some(acl), Some(principal)) => acl.allows(principal, action, topic),
+        }
+    }
+}
+
+/// Interceptor that authenticates every request before it reaches a handler.
+#[derive(Debug, Clone)]
+pub struct AuthInterceptor {
+    auth: Arc<Auth>,
+}
+
+impl AuthInterceptor {
+    /// Create an interceptor that authenticates with `auth`.
+    pub fn new(auth: Arc<Auth>) -> Self {
+        Self { auth }
+    }
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(principal) = self.auth.authenticate(&request)? {
+            request.extensions_mut().insert(principal);
+        }
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bearer(token: &str) -> Request<()> {
+        let mut request = Request::new(());
+        request
+            .metadata_mut()
+            .insert("authorization", format!("Bearer {token}").parse().unwrap());
+        request
+    }
+
+    #[test]
+    fn test_interceptor_attaches_principal() {
+        let validator = TokenValidator::new().with_static_token("s3cret", "alice");
+        let mut interceptor = AuthInterceptor::new(Arc::new(Auth::new(validator, None)));
+
+        let request = interceptor.call(bearer("s3cret")).unwrap();
+        assert_eq!(
+            Principal::from_request(&request),
+            Some(Principal::new("alice"))
+        );
+
+        let status = interceptor.call(bearer("wrong")).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+        let status = interceptor.call(Request::new(())).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+    }
+
+    #[test]
+    fn test_authorize_with_acl() {
+        let acl = Acl {
+            rules: vec![AclRule {
+                principal: "alice".to_string(),
+                topics: vec!["orders.*".to_string()],
+                actions: vec![Action::Publish],
+            }],
+        };
+        let auth = Auth::new(TokenValidator::new(), Some(acl));
+        let alice = Principal::new("alice");
+
+        assert!(auth
+            .authorize(Some(&alice), Action::Publish, "orders.eu")
+            .is_ok());
+        let status = auth
+            .authorize(Some(&alice), Action::Subscribe, "orders.eu")
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        assert!(auth.can_see(Some(&alice), "orders.eu"));
+        assert!(!auth.can_see(Some(&alice), "billing"));
+        assert!(auth.allows(Some(&alice), Action::Publish, "orders.eu"));
+        assert!(!auth.allows(Some(&alice), Action::Admin, "orders.eu"));
+        assert!(!auth.allows(None, Action::Publish, "orders.eu"));
+
+        // Disabled auth allows anonymous requests
+        let disabled = Auth::disabled();
+        assert!(disabled.authorize(None, Action::Admin, "billing").is_ok());
+        assert!(disabled.can_see(None, "billing"));
+    }
+}
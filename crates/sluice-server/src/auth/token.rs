@@ -0,0 +1,271 @@
+//! Bearer token validation.
+//!
+//! Two kinds of token are accepted:
+//! - Static tokens listed in a JSON file, each mapped to a principal:
+//!   `{"tokens": [{"token": "s3cret", "principal": "orders-service"}]}`
+//! - JWTs signed with HMAC (HS256, HS384 or HS512) using a locally
+//!   configured secret. The `sub` claim names the principal, and `exp` and
+//!   `nbf` are honoured when present.
+//!
+//! Static tokens are stored as SHA-256 digests, so looking one up does not
+//! compare secrets byte by byte.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ring::{digest, hmac};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use thiserror::Error;
+
+use super::{AuthError, Principal};
+
+/// Why a token was rejected.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TokenError {
+    #[error("unknown token")]
+    Unknown,
+
+    #[error("malformed token: {0}")]
+    Malformed(String),
+
+    #[error("unsupported token algorithm: {0}")]
+    UnsupportedAlgorithm(String),
+
+    #[error("invalid token signature")]
+    BadSignature,
+
+    #[error("token has expired")]
+    Expired,
+
+    #[error("token is not valid yet")]
+    NotYetValid,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenFile {
+    tokens: Vec<TokenEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenEntry {
+    token: String,
+    principal: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct JwtHeader {
+    alg: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct JwtClaims {
+    sub: String,
+    exp: Option<i64>,
+    nbf: Option<i64>,
+}
+
+/// Checks bearer tokens and resolves them to principals.
+#[derive(Debug, Default)]
+pub struct TokenValidator {
+    /// SHA-256 digest of a static token -> its principal.
+    static_tokens: HashMap<Vec<u8>, Principal>,
+    /// Secret for verifying HMAC-signed JWTs.
+    jwt_secret: Option<Vec<u8>>,
+}
+
+impl TokenValidator {
+    /// Create a validator with no tokens configured.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept a static token for `principal`.
+    pub fn with_static_token(mut self, token: &str, principal: &str) -> Self {
+        self.static_tokens
+            .insert(token_digest(token), Principal::new(principal));
+        self
+    }
+
+    /// Accept JWTs signed with `secret`.
+    pub fn with_jwt_secret(mut self, secret: impl Into<Vec<u8>>) -> Self {
+        self.jwt_secret = Some(secret.into());
+        self
+    }
+
+    /// Add the static tokens listed in a JSON file.
+    pub fn load_static_tokens(mut self, path: &Path) -> Result<Self, AuthError> {
+        let contents = std::fs::read_to_string(path).map_err(|e| AuthError::Io {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        let file: TokenFile =
+            serde_json::from_str(&contents).map_err(|e| AuthError::InvalidFile {
+                path: path.to_path_buf(),
+                message: e.to_string(),
+            })?;
+
+        for entry in file.tokens {
+            if entry.token.is_empty() || entry.principal.is_empty() {
+                return Err(AuthError::InvalidFile {
+                    path: path.to_path_buf(),
+                    message: "tokens and principals cannot be empty".to_string(),
+                });
+            }
+            self = self.with_static_token(&entry.token, &entry.principal);
+        }
+        Ok(self)
+    }
+
+    /// Accept JWTs signed with the secret stored in a file.
+    ///
+    /// Surrounding whitespace, such as a trailing newline, is ignored.
+    pub fn load_jwt_secret(self, path: &Path) -> Result<Self, AuthError> {
+        let secret = std::fs::read(path).map_err(|e| AuthError::Io {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        let start = secret
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .unwrap_or(secret.len());
+        let end = secret
+            .iter()
+            .rposition(|b| !b.is_ascii_whitespace())
+            .map_or(start, |i| i + 1);
+        let secret = &secret[start..end];
+        if secret.is_empty() {
+            return Err(AuthError::InvalidFile {
+                path: path.to_path_buf(),
+                message: "JWT secret is empty".to_string(),
+            });
+        }
+        Ok(self.with_jwt_secret(secret))
+    }
+
+    /// Resolve a bearer token to the principal it authenticates.
+    ///
+    /// `now` is the current Unix time in seconds, used for JWT expiry.
+    pub fn validate(&self, token: &str, now: i64) -> Result<Principal, TokenError> {
+        if let Some(principal) = self.static_tokens.get(&token_digest(token)) {
+            return Ok(principal.clone());
+        }
+
+        match &self.jwt_secret {
+            Some(secret) if token.matches('.').count() == 2 => validate_jwt(token, secret, now),
+            _ => Err(TokenError::Unknown),
+        }
+    }
+}
+
+fn token_digest(token: &str) -> Vec<u8> {
+    digest::digest(&digest::SHA256, token.as_bytes())
+        .as_ref()
+        .to_vec()
+}
+
+fn validate_jwt(token: &str, secret: &[u8], now: i64) -> Result<Principal, TokenError> {
+    let (signing_input, signature) = token
+        .rsplit_once('.')
+        .ok_or_else(|| TokenError::Malformed("expected three segments".to_string()))?;
+    let (header, claims) = signing_input
+        .split_once('.')
+        .ok_or_else(|| TokenError::Malformed("expected three segments".to_string()))?;
+
+    let header: JwtHeader = decode_segment(header)?;
+    let algorithm = match header.alg.as_str() {
+        "HS256" => hmac::HMAC_SHA256,
+        "HS384" => hmac::HMAC_SHA384,
+        "HS512" => hmac::HMAC_SHA512,
+        other => return Err(TokenError::UnsupportedAlgorithm(other.to_string())),
+    };
+
+    let signature = URL_SAFE_NO_PAD
+        .decode(signature)
+        .map_err(|e| TokenError::Malformed(e.to_string()))?;
+    let key = hmac::Key::new(algorithm, secret);
+    hmac::verify(&key, signing_input.as_bytes(), &signature)
+        .map_err(|_| TokenError::BadSignature)?;
+
+    let claims: JwtClaims = decode_segment(claims)?;
+    if claims.exp.is_some_and(|exp| now >= exp) {
+        return Err(TokenError::Expired);
+    }
+    if claims.nbf.is_some_and(|nbf| now < nbf) {
+        return Err(TokenError::NotYetValid);
+    }
+    if claims.sub.is_empty() {
+        return Err(TokenError::Malformed("empty sub claim".to_string()));
+    }
+
+    Ok(Principal::new(claims.sub))
+}
+
+fn decode_segment<T: serde::de::DeserializeOwned>(segment: &str) -> Result<T, TokenError> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(segment)
+        .map_err(|e| TokenError::Malformed(e.to_string()))?;
+    serde_json::from_slice(&bytes).map_err(|e| TokenError::Malformed(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(alg: &str, claims: &str, secret: &[u8]) -> String {
+        let header = URL_SAFE_NO_PAD.encode(format!(r#"{{"alg":"{alg}","typ":"JWT"}}"#));
+        let claims = URL_SAFE_NO_PAD.encode(claims);
+        let signing_input = format!("{header}.{claims}");
+        let key = hmac::Key::new(hmac::HMAC_SHA256, secret);
+        let signature = URL_SAFE_NO_PAD.encode(hmac::sign(&key, signing_input.as_bytes()));
+        format!("{signing_input}.{signature}")
+    }
+
+    #[test]
+    fn test_static_tokens() {
+        let validator = TokenValidator::new().with_static_token("s3cret", "orders-service");
+
+        assert_eq!(
+            validator.validate("s3cret", 0),
+            Ok(Principal::new("orders-service"))
+        );
+        assert_eq!(validator.validate("wrong", 0), Err(TokenError::Unknown));
+    }
+
+    #[test]
+    fn test_jwt_validation() {
+        let secret = b"jwt-secret";
+        let validator = TokenValidator::new().with_jwt_secret(secret.to_vec());
+
+        let token = sign("HS256", r#"{"sub":"alice","exp":2000,"nbf":1000}"#, secret);
+        assert_eq!(
+            validator.validate(&token, 1500),
+            Ok(Principal::new("alice"))
+        );
+        assert_eq!(validator.validate(&token, 2000), Err(TokenError::Expired));
+        assert_eq!(
+            validator.validate(&token, 999),
+            Err(TokenError::NotYetValid)
+        );
+
+        // Signed with another secret
+        let forged = sign("HS256", r#"{"sub":"alice"}"#, b"other-secret");
+        assert_eq!(
+            validator.validate(&forged, 0),
+            Err(TokenError::BadSignature)
+        );
+
+        // Unsigned tokens are never accepted
+        let unsigned = sign("none", r#"{"sub":"alice"}"#, secret);
+        assert_eq!(
+            validator.validate(&unsigned, 0),
+            Err(TokenError::UnsupportedAlgorithm("none".to_string()))
+        );
+
+        // Without a secret, JWTs are just unknown tokens
+        assert_eq!(
+            TokenValidator::new().validate(&token, 1500),
+            Err(TokenError::Unknown)
+        );
+    }
+}
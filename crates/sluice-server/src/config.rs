@@ -5,13 +5,18 @@
 //! - Environment variable overrides
 //! - Sensible defaults for quick start
 
-use clap::Parser;
+use clap::{ArgGroup, Parser};
 use std::path::PathBuf;
 
 /// Sluice: A gRPC-native message broker with credit-based flow control.
 #[derive(Parser, Debug, Clone)]
 #[command(name = "sluice")]
 #[command(author, version, about, long_about = None)]
+#[command(group(
+    ArgGroup::new("auth_tokens")
+        .args(["auth_token_file", "auth_jwt_secret_file"])
+        .multiple(true)
+))]
 pub struct Config {
     /// Host address to bind to
     #[arg(long, env = "SLUICE_HOST", default_value = "0.0.0.0")]
@@ -57,6 +62,10 @@ pub struct Config {
     #[arg(long, env = "SLUICE_WAL_CHECKPOINT_PAGES", default_value_t = 1000)]
     pub wal_checkpoint_pages: i32,
 
+    /// Writer threads for the partitions of partitioned topics (0 = main writer only)
+    #[arg(long, env = "SLUICE_PARTITION_WRITERS", default_value_t = 4)]
+    pub partition_writers: usize,
+
     /// Enable Prometheus metrics endpoint
     #[arg(long, env = "SLUICE_METRICS_ENABLED", default_value_t = true)]
     pub metrics_enabled: bool,
@@ -64,6 +73,59 @@ pub struct Config {
     /// Port for Prometheus metrics HTTP server
     #[arg(long, env = "SLUICE_METRICS_PORT", default_value_t = 9090)]
     pub metrics_port: u16,
+
+    /// Default maximum message age in seconds (unset = keep forever)
+    #[arg(long, env = "SLUICE_RETENTION_MAX_AGE_SECS")]
+    pub retention_max_age_secs: Option<u64>,
+
+    /// Default maximum number of messages kept per topic (unset = unlimited)
+    #[arg(long, env = "SLUICE_RETENTION_MAX_MESSAGES")]
+    pub retention_max_messages: Option<u64>,
+
+    /// Default maximum bytes of payload and attributes kept per topic (unset = unlimited)
+    #[arg(long, env = "SLUICE_RETENTION_MAX_BYTES")]
+    pub retention_max_bytes: Option<u64>,
+
+    /// Interval in seconds between retention passes
+    #[arg(long, env = "SLUICE_RETENTION_INTERVAL_SECS", default_value_t = 60)]
+    pub retention_interval_secs: u64,
+
+    /// Maximum number of messages deleted per retention transaction
+    #[arg(long, env = "SLUICE_RETENTION_CHUNK_SIZE", default_value_t = 1000)]
+    pub retention_chunk_size: usize,
+
+    /// PEM certificate chain to serve TLS with (requires --tls-key)
+    #[arg(long, env = "SLUICE_TLS_CERT", requires = "tls_key")]
+    pub tls_cert: Option<PathBuf>,
+
+    /// PEM private key for --tls-cert
+    #[arg(long, env = "SLUICE_TLS_KEY", requires = "tls_cert")]
+    pub tls_key: Option<PathBuf>,
+
+    /// PEM CA bundle for verifying client certificates (enables mutual TLS)
+    #[arg(long, env = "SLUICE_TLS_CLIENT_CA", requires = "tls_cert")]
+    pub tls_client_ca: Option<PathBuf>,
+
+    /// JSON file of static bearer tokens and their principals (enables auth)
+    #[arg(long, env = "SLUICE_AUTH_TOKEN_FILE")]
+    pub auth_token_file: Option<PathBuf>,
+
+    /// File holding the HMAC secret for signed JWT bearer tokens (enables auth)
+    #[arg(long, env = "SLUICE_AUTH_JWT_SECRET_FILE")]
+    pub auth_jwt_secret_file: Option<PathBuf>,
+
+    /// JSON ACL granting principals publish/subscribe/admin on topic patterns
+    /// (requires --auth-token-file or --auth-jwt-secret-file)
+    #[arg(long, env = "SLUICE_AUTH_ACL_FILE", requires = "auth_tokens")]
+    pub auth_acl_file: Option<PathBuf>,
+
+    /// Reject publishes to topics that were not created with CreateTopic
+    #[arg(long, env = "SLUICE_DISABLE_AUTO_CREATE")]
+    pub disable_auto_create: bool,
+
+    /// How long idempotency keys deduplicate publishes, in seconds (0 = off)
+    #[arg(long, env = "SLUICE_DEDUP_WINDOW_SECS", default_value_t = 300)]
+    pub dedup_window_secs: u64,
 }
 
 impl Config {
@@ -72,6 +134,11 @@ impl Config {
         Self::parse()
     }
 
+    /// The dedup window in milliseconds, as the writer expects it.
+    pub fn dedup_window_ms(&self) -> i64 {
+        i64::try_from(self.dedup_window_secs.saturating_mul(1000)).unwrap_or(i64::MAX)
+    }
+
     /// Create a default configuration for testing.
     #[cfg(test)]
     pub fn test_config(data_dir: PathBuf) -> Self {
@@ -87,8 +154,22 @@ impl Config {
             batch_size: 10,
             batch_delay_ms: 1,
             wal_checkpoint_pages: 100,
+            partition_writers: 2,
             metrics_enabled: false,
             metrics_port: 0,
+            retention_max_age_secs: None,
+            retention_max_messages: None,
+            retention_max_bytes: None,
+            retention_interval_secs: 1,
+            retention_chunk_size: 100,
+            tls_cert: None,
+            tls_key: None,
+            tls_client_ca: None,
+            auth_token_file: None,
+            auth_jwt_secret_file: None,
+            auth_acl_file: None,
+            disable_auto_create: false,
+            dedup_window_secs: 300,
         }
     }
 }
@@ -107,8 +188,22 @@ impl Default for Config {
             batch_size: 100,
             batch_delay_ms: 5,
             wal_checkpoint_pages: 1000,
+            partition_writers: 4,
             metrics_enabled: true,
             metrics_port: 9090,
+            retention_max_age_secs: None,
+            retention_max_messages: None,
+            retention_max_bytes: None,
+            retention_interval_secs: 60,
+            retention_chunk_size: 1000,
+            tls_cert: None,
+            tls_key: None,
+            tls_client_ca: None,
+            auth_token_file: None,
+            auth_jwt_secret_file: None,
+            auth_acl_file: None,
+            disable_auto_create: false,
+            dedup_window_secs: 300,
         }
     }
 }
@@ -123,4 +218,40 @@ mod tests {
         assert_eq!(config.port, 50051);
         assert_eq!(config.host, "0.0.0.0");
     }
+
+    #[test]
+    fn test_tls_flags_require_cert_and_key() {
+        let config = Config::try_parse_from([
+            "sluice",
+            "--tls-cert",
+            "server.pem",
+            "--tls-key",
+            "server.key",
+            "--tls-client-ca",
+            "ca.pem",
+        ])
+        .unwrap();
+        assert_eq!(config.tls_cert, Some(PathBuf::from("server.pem")));
+        assert_eq!(config.tls_client_ca, Some(PathBuf::from("ca.pem")));
+
+        assert!(Config::try_parse_from(["sluice", "--tls-cert", "server.pem"]).is_err());
+        assert!(Config::try_parse_from(["sluice", "--tls-client-ca", "ca.pem"]).is_err());
+    }
+
+    #[test]
+    fn test_acl_flag_requires_token_source() {
+        for source in ["--auth-token-file", "--auth-jwt-secret-file"] {
+            let config = Config::try_parse_from([
+                "sluice",
+                source,
+                "auth.json",
+                "--auth-acl-file",
+                "acl.json",
+            ])
+            .unwrap();
+            assert_eq!(config.auth_acl_file, Some(PathBuf::from("acl.json")));
+        }
+
+        assert!(Config::try_parse_from(["sluice", "--auth-acl-file", "acl.json"]).is_err());
+    }
 }
@@ -0,0 +1,340 @@
+//! Per-message acknowledgement tracking.
+//!
+//! Used by consumer groups in `AckMode::INDIVIDUAL`:
+//! - Tracks delivered-but-unacked (in-flight) sequences
+//! - Remembers acks that arrive out of order
+//! - Computes how far the durable cursor may safely advance
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Result of applying an ACK to the tracker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AckOutcome {
+    /// The message was in flight and is now acked.
+    Acked {
+        /// New durable cursor, if it moved forward.
+        new_cursor: Option<i64>,
+    },
+    /// The message was already acked (duplicate ACK).
+    Duplicate,
+    /// The message was never delivered on this subscription.
+    Unknown,
+}
+
+/// Acknowledgement state for one consumer group.
+///
+/// Global sequences are not contiguous within a topic, so "contiguous"
+/// means "no delivered message below it is still unacked". The cursor
+/// never passes the read position, since messages past it have not been
+/// seen by the consumer yet.
+///
+/// Messages are normally read in sequence order. A tracker with unordered
+/// reads (priority delivery) leaves the read position to the caller, who
+/// must only move it over messages that have all been read.
+#[derive(Debug, Clone, Default)]
+pub struct AckTracker {
+    /// Every message at or below this sequence is acked.
+    cursor: i64,
+    /// Highest sequence read from storage (delivered or skipped).
+    read_through: i64,
+    /// Reads are out of sequence order; `read_through` is set explicitly.
+    unordered: bool,
+    /// Delivered but unacked: sequence -> message ID.
+    in_flight: BTreeMap<i64, String>,
+    /// Acked sequences above the cursor.
+    acked: BTreeSet<i64>,
+}
+
+impl AckTracker {
+    /// Create a tracker resuming from a durable cursor and persisted acks.
+    pub fn new(cursor: i64, acked: impl IntoIterator<Item = i64>) -> Self {
+        Self {
+            cursor,
+            read_through: cursor,
+            unordered: false,
+            in_flight: BTreeMap::new(),
+            acked: acked.into_iter().filter(|&seq| seq > cursor).collect(),
+        }
+    }
+
+    /// Read messages out of sequence order.
+    pub fn with_unordered_reads(mut self) -> Self {
+        self.unordered = true;
+        self
+    }
+
+    /// Every message at or below this sequence has been read.
+    pub fn read_through(&self) -> i64 {
+        self.read_through
+    }
+
+    /// Move the read position of a tracker with unordered reads.
+    ///
+    /// Every message at or below `seq` must have been delivered or skipped.
+    /// Returns the new cursor if it moved.
+    pub fn set_read_through(&mut self, seq: i64) -> Option<i64> {
+        self.read_through = self.read_through.max(seq);
+        self.advance_cursor()
+    }
+
+    /// Returns true if the message was delivered or skipped.
+    pub fn is_read(&self, seq: i64) -> bool {
+        self.is_acked(seq) || self.is_in_flight(seq)
+    }
+
+    /// Current durable cursor.
+    pub fn cursor(&self) -> i64 {
+        self.cursor
+    }
+
+    /// Returns true if the message has been acked.
+    pub fn is_acked(&self, seq: i64) -> bool {
+        seq <= self.cursor || self.acked.contains(&seq)
+    }
+
+    /// Returns true if the message is delivered and awaiting an ACK.
+    pub fn is_in_flight(&self, seq: i64) -> bool {
+        self.in_flight.contains_key(&seq)
+    }
+
+    /// Number of messages awaiting an ACK.
+    pub fn in_flight_count(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Sequences awaiting an ACK, lowest first.
+    pub fn in_flight_seqs(&self) -> impl Iterator<Item = i64> + '_ {
+        self.in_flight.keys().copied()
+    }
+
+    /// Sequence of the in-flight message with `message_id`.
+    pub fn find_in_flight(&self, message_id: &str) -> Option<i64> {
+        self.in_flight
+            .iter()
+            .find(|(_, id)| id.as_str() == message_id)
+            .map(|(&seq, _)| seq)
+    }
+
+    /// Record that a message was delivered to the consumer.
+    pub fn record_delivery(&mut self, seq: i64, message_id: String) {
+        self.in_flight.insert(seq, message_id);
+        self.mark_read(seq);
+    }
+
+    /// Record that a message was read but not delivered (already acked).
+    pub fn record_skip(&mut self, seq: i64) {
+        self.mark_read(seq);
+    }
+
+    /// Record that a message was read but rejected by the subscription's
+    /// filter. It counts as acked. Returns the new cursor if it moved.
+    pub fn record_filtered(&mut self, seq: i64) -> Option<i64> {
+        self.mark_read(seq);
+        self.acked.insert(seq);
+        self.advance_cursor()
+    }
+
+    /// Settle an in-flight message that storage deleted before it was
+    /// acked (expired, or pruned by retention). It counts as acked.
+    /// Returns the new cursor if it moved.
+    pub fn settle(&mut self, seq: i64) -> Option<i64> {
+        self.in_flight.remove(&seq)?;
+        self.acked.insert(seq);
+        self.advance_cursor()
+    }
+
+    /// Apply an ACK for a message.
+    pub fn ack(&mut self, seq: i64) -> AckOutcome {
+        if self.is_acked(seq) {
+            return AckOutcome::Duplicate;
+        }
+        if self.in_flight.remove(&seq).is_none() {
+            return AckOutcome::Unknown;
+        }
+        self.acked.insert(seq);
+
+        AckOutcome::Acked {
+            new_cursor: self.advance_cursor(),
+        }
+    }
+
+    /// Move the read position over `seq`, if reads are in order.
+    fn mark_read(&mut self, seq: i64) {
+        if !self.unordered {
+            self.read_through = self.read_through.max(seq);
+        }
+    }
+
+    /// Move the cursor over every acked message below the lowest
+    /// outstanding one. Returns the new cursor if it moved.
+    fn advance_cursor(&mut self) -> Option<i64> {
+        let limit = match self.in_flight.keys().next() {
+            Some(&lowest_unacked) => (lowest_unacked - 1).min(self.read_through),
+            None => self.read_through,
+        };
+
+        let new_cursor = self.acked.range(..=limit).next_back().copied()?;
+        if new_cursor <= self.cursor {
+            return None;
+        }
+
+        self.cursor = new_cursor;
+        self.acked = self.acked.split_off(&(new_cursor + 1));
+        Some(new_cursor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deliver(tracker: &mut AckTracker, seqs: &[i64]) {
+        for &seq in seqs {
+            tracker.record_delivery(seq, format!("msg-{seq}"));
+        }
+    }
+
+    #[test]
+    fn test_in_order_acks_advance_cursor() {
+        let mut tracker = AckTracker::new(0, []);
+        deliver(&mut tracker, &[1, 2, 3]);
+
+        assert_eq!(
+            tracker.ack(1),
+            AckOutcome::Acked {
+                new_cursor: Some(1)
+            }
+        );
+        assert_eq!(
+            tracker.ack(2),
+            AckOutcome::Acked {
+                new_cursor: Some(2)
+            }
+        );
+        assert_eq!(tracker.in_flight_count(), 1);
+    }
+
+    #[test]
+    fn test_out_of_order_ack_holds_cursor() {
+        let mut tracker = AckTracker::new(0, []);
+        deliver(&mut tracker, &[1, 4, 9]);
+
+        // Acking 9 must not ack 1 and 4
+        assert_eq!(tracker.ack(9), AckOutcome::Acked { new_cursor: None });
+        assert_eq!(tracker.cursor(), 0);
+        assert!(tracker.is_acked(9));
+        assert!(!tracker.is_acked(4));
+
+        assert_eq!(
+            tracker.ack(1),
+            AckOutcome::Acked {
+                new_cursor: Some(1)
+            }
+        );
+        // Filling the gap releases everything acked above it
+        assert_eq!(
+            tracker.ack(4),
+            AckOutcome::Acked {
+                new_cursor: Some(9)
+            }
+        );
+    }
+
+    #[test]
+    fn test_duplicate_and_unknown_acks() {
+        let mut tracker = AckTracker::new(5, []);
+        deliver(&mut tracker, &[7]);
+
+        assert_eq!(tracker.ack(3), AckOutcome::Duplicate);
+        assert_eq!(tracker.ack(8), AckOutcome::Unknown);
+        assert_eq!(
+            tracker.ack(7),
+            AckOutcome::Acked {
+                new_cursor: Some(7)
+            }
+        );
+        assert_eq!(tracker.ack(7), AckOutcome::Duplicate);
+    }
+
+    #[test]
+    fn test_cursor_does_not_pass_read_position() {
+        // Resumed with 8 already acked but 6 and 7 not yet re-read
+        let mut tracker = AckTracker::new(5, [8]);
+        deliver(&mut tracker, &[6]);
+
+        assert_eq!(
+            tracker.ack(6),
+            AckOutcome::Acked {
+                new_cursor: Some(6)
+            }
+        );
+
+        deliver(&mut tracker, &[7]);
+        tracker.record_skip(8);
+        assert_eq!(
+            tracker.ack(7),
+            AckOutcome::Acked {
+                new_cursor: Some(8)
+            }
+        );
+    }
+
+    #[test]
+    fn test_filtered_messages_count_as_acked() {
+        let mut tracker = AckTracker::new(0, []);
+        deliver(&mut tracker, &[1]);
+
+        // Held back by the unacked message below it
+        assert_eq!(tracker.record_filtered(2), None);
+        assert!(tracker.is_acked(2));
+        assert_eq!(
+            tracker.ack(1),
+            AckOutcome::Acked {
+                new_cursor: Some(2)
+            }
+        );
+
+        assert_eq!(tracker.record_filtered(3), Some(3));
+        assert_eq!(tracker.in_flight_count(), 0);
+    }
+
+    #[test]
+    fn test_unordered_reads_hold_cursor_until_read() {
+        let mut tracker = AckTracker::new(0, []).with_unordered_reads();
+        // A high-priority message read before the ones below it
+        deliver(&mut tracker, &[3]);
+        assert_eq!(tracker.read_through(), 0);
+        assert!(tracker.is_read(3));
+        assert!(!tracker.is_read(1));
+
+        assert_eq!(tracker.ack(3), AckOutcome::Acked { new_cursor: None });
+        assert_eq!(tracker.record_filtered(2), None);
+
+        deliver(&mut tracker, &[1]);
+        assert_eq!(tracker.set_read_through(3), None);
+        assert_eq!(
+            tracker.ack(1),
+            AckOutcome::Acked {
+                new_cursor: Some(3)
+            }
+        );
+
+        // Moving the read position alone can advance the cursor
+        assert_eq!(tracker.record_filtered(5), None);
+        assert_eq!(tracker.set_read_through(5), Some(5));
+    }
+
+    #[test]
+    fn test_settled_messages_release_cursor() {
+        let mut tracker = AckTracker::new(0, []);
+        deliver(&mut tracker, &[1, 2]);
+        assert_eq!(tracker.find_in_flight("msg-1"), Some(1));
+        assert_eq!(tracker.ack(2), AckOutcome::Acked { new_cursor: None });
+
+        // Message 1 expired while in flight
+        assert_eq!(tracker.settle(1), Some(2));
+        assert_eq!(tracker.in_flight_count(), 0);
+        assert_eq!(tracker.find_in_flight("msg-1"), None);
+        assert_eq!(tracker.settle(1), None);
+    }
+}
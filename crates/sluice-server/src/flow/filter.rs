@@ -0,0 +1,419 @@
+//! Attribute filters for subscriptions.
+//!
+//! A subscription may carry a filter expression over message attributes.
+//! Messages it rejects are skipped by the server without using a credit.
+//!
+//! Grammar (keywords are case-insensitive):
+//!
+//! ```text
+//! expr  := and ("OR" and)*
+//! and   := unary ("AND" unary)*
+//! unary := "NOT" unary | "(" expr ")" | "EXISTS" name | name test
+//! test  := "=" value | "!=" value | "IN" "(" value ("," value)* ")"
+//!        | "STARTS" "WITH" value
+//! ```
+//!
+//! Names are bare words such as `region` or `sluice.dlq.original_topic`, or
+//! double-quoted. Values are single-quoted, with `''` for a quote:
+//! `region IN ('eu', 'us') AND NOT EXISTS test`.
+
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Maximum nesting depth of an expression.
+const MAX_DEPTH: usize = 32;
+
+/// Why a filter expression could not be parsed.
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("invalid filter at position {position}: {message}")]
+pub struct FilterError {
+    /// Byte offset in the expression.
+    pub position: usize,
+    pub message: String,
+}
+
+/// A parsed filter expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    /// The attribute is present with this value.
+    Eq(String, String),
+    /// The attribute is present with one of these values.
+    In(String, Vec<String>),
+    /// The attribute is present and starts with this prefix.
+    Prefix(String, String),
+    /// The attribute is present.
+    Exists(String),
+    Not(Box<Filter>),
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+}
+
+impl Filter {
+    /// Parse a filter expression.
+    pub fn parse(expr: &str) -> Result<Self, FilterError> {
+        let mut parser = Parser {
+            tokens: tokenize(expr)?,
+            pos: 0,
+            end: expr.len(),
+        };
+        let filter = parser.expr(0)?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(filter),
+            Some((position, _)) => Err(FilterError {
+                position: *position,
+                message: "unexpected input after expression".to_string(),
+            }),
+        }
+    }
+
+    /// Returns true if a message with these attributes passes the filter.
+    ///
+    /// A test on a missing attribute is false, so `region != 'eu'` matches
+    /// messages without a region.
+    pub fn matches(&self, attributes: &HashMap<String, String>) -> bool {
+        match self {
+            Self::Eq(name, value) => attributes.get(name) == Some(value),
+            Self::In(name, values) => attributes.get(name).is_some_and(|v| values.contains(v)),
+            Self::Prefix(name, prefix) => attributes
+                .get(name)
+                .is_some_and(|v| v.starts_with(prefix.as_str())),
+            Self::Exists(name) => attributes.contains_key(name),
+            Self::Not(filter) => !filter.matches(attributes),
+            Self::And(filters) => filters.iter().all(|f| f.matches(attributes)),
+            Self::Or(filters) => filters.iter().any(|f| f.matches(attributes)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// A bare word: a keyword or an attribute name.
+    Word(String),
+    /// A double-quoted attribute name.
+    Name(String),
+    /// A single-quoted value.
+    Value(String),
+    LParen,
+    RParen,
+    Comma,
+    Eq,
+    Ne,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | ':' | '/')
+}
+
+/// Split an expression into tokens paired with their byte offsets.
+fn tokenize(expr: &str) -> Result<Vec<(usize, Token)>, FilterError> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        let token = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' => {
+                chars.next();
+                Token::LParen
+            }
+            ')' => {
+                chars.next();
+                Token::RParen
+            }
+            ',' => {
+                chars.next();
+                Token::Comma
+            }
+            '=' => {
+                chars.next();
+                Token::Eq
+            }
+            '!' => {
+                chars.next();
+                if chars.next_if(|&(_, c)| c == '=').is_none() {
+                    return Err(FilterError {
+                        position: start,
+                        message: "expected '!='".to_string(),
+                    });
+                }
+                Token::Ne
+            }
+            '\'' | '"' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        // A doubled quote stands for itself
+                        Some((_, q)) if q == c => match chars.next_if(|&(_, next)| next == c) {
+                            Some(_) => text.push(c),
+                            None => break,
+                        },
+                        Some((_, other)) => text.push(other),
+                        None => {
+                            return Err(FilterError {
+                                position: start,
+                                message: "unterminated quoted string".to_string(),
+                            })
+                        }
+                    }
+                }
+                if c == '\'' {
+                    Token::Value(text)
+                } else {
+                    Token::Name(text)
+                }
+            }
+            c if is_word_char(c) => {
+                let mut word = String::new();
+                while let Some((_, c)) = chars.next_if(|&(_, c)| is_word_char(c)) {
+                    word.push(c);
+                }
+                Token::Word(word)
+            }
+            other => {
+                return Err(FilterError {
+                    position: start,
+                    message: format!("unexpected character '{other}'"),
+                })
+            }
+        };
+        tokens.push((start, token));
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    /// Length of the expression, reported for errors at its end.
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn error(&self, message: impl Into<String>) -> FilterError {
+        FilterError {
+            position: self.tokens.get(self.pos).map_or(self.end, |(p, _)| *p),
+            message: message.into(),
+        }
+    }
+
+    /// Consume the keyword `keyword` if it is next.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, token: Token, what: &str) -> Result<(), FilterError> {
+        if self.peek() != Some(&token) {
+            return Err(self.error(format!("expected {what}")));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn expr(&mut self, depth: usize) -> Result<Filter, FilterError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("expression is nested too deeply"));
+        }
+
+        let mut terms = vec![self.and(depth)?];
+        while self.keyword("OR") {
+            terms.push(self.and(depth)?);
+        }
+        Ok(combine(terms, Filter::Or))
+    }
+
+    fn and(&mut self, depth: usize) -> Result<Filter, FilterError> {
+        let mut terms = vec![self.unary(depth)?];
+        while self.keyword("AND") {
+            terms.push(self.unary(depth)?);
+        }
+        Ok(combine(terms, Filter::And))
+    }
+
+    fn unary(&mut self, depth: usize) -> Result<Filter, FilterError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("expression is nested too deeply"));
+        }
+
+        if self.keyword("NOT") {
+            return Ok(Filter::Not(Box::new(self.unary(depth + 1)?)));
+        }
+        if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            let filter = self.expr(depth + 1)?;
+            self.expect(Token::RParen, "')'")?;
+            return Ok(filter);
+        }
+        if self.keyword("EXISTS") {
+            return Ok(Filter::Exists(self.name()?));
+        }
+
+        let name = self.name()?;
+        if self.peek() == Some(&Token::Eq) {
+            self.pos += 1;
+            return Ok(Filter::Eq(name, self.value()?));
+        }
+        if self.peek() == Some(&Token::Ne) {
+            self.pos += 1;
+            return Ok(Filter::Not(Box::new(Filter::Eq(name, self.value()?))));
+        }
+        if self.keyword("IN") {
+            self.expect(Token::LParen, "'(' after IN")?;
+            let mut values = vec![self.value()?];
+            while self.peek() == Some(&Token::Comma) {
+                self.pos += 1;
+                values.push(self.value()?);
+            }
+            self.expect(Token::RParen, "')'")?;
+            return Ok(Filter::In(name, values));
+        }
+        if self.keyword("STARTS") {
+            if !self.keyword("WITH") {
+                return Err(self.error("expected WITH after STARTS"));
+            }
+            return Ok(Filter::Prefix(name, self.value()?));
+        }
+        Err(self.error("expected =, !=, IN or STARTS WITH"))
+    }
+
+    fn name(&mut self) -> Result<String, FilterError> {
+        match self.peek() {
+            Some(Token::Word(word)) | Some(Token::Name(word)) => {
+                let name = word.clone();
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => Err(self.error("expected an attribute name")),
+        }
+    }
+
+    fn value(&mut self) -> Result<String, FilterError> {
+        match self.peek() {
+            Some(Token::Value(value)) => {
+                let value = value.clone();
+                self.pos += 1;
+                Ok(value)
+            }
+            _ => Err(self.error("expected a quoted value")),
+        }
+    }
+}
+
+/// Join terms with `op`, without wrapping a single term.
+fn combine(mut terms: Vec<Filter>, op: fn(Vec<Filter>) -> Filter) -> Filter {
+    if terms.len() == 1 {
+        terms.remove(0)
+    } else {
+        op(terms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attrs(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn matches(expr: &str, pairs: &[(&str, &str)]) -> bool {
+        Filter::parse(expr).unwrap().matches(&attrs(pairs))
+    }
+
+    #[test]
+    fn test_equality() {
+        assert!(matches("region = 'eu'", &[("region", "eu")]));
+        assert!(!matches("region = 'eu'", &[("region", "us")]));
+        assert!(!matches("region = 'eu'", &[]));
+
+        assert!(matches("region != 'eu'", &[("region", "us")]));
+        assert!(matches("region != 'eu'", &[]));
+        assert!(!matches("region != 'eu'", &[("region", "eu")]));
+    }
+
+    #[test]
+    fn test_in_prefix_and_exists() {
+        assert!(matches("region IN ('eu', 'us')", &[("region", "us")]));
+        assert!(!matches("region IN ('eu', 'us')", &[("region", "ap")]));
+
+        assert!(matches(
+            "region STARTS WITH 'eu-'",
+            &[("region", "eu-west")]
+        ));
+        assert!(!matches(
+            "region starts with 'eu-'",
+            &[("region", "us-east")]
+        ));
+
+        assert!(matches("EXISTS trace_id", &[("trace_id", "")]));
+        assert!(!matches("exists trace_id", &[("region", "eu")]));
+    }
+
+    #[test]
+    fn test_boolean_operators() {
+        let expr = "type = 'order' AND (region = 'eu' OR region = 'us') AND NOT EXISTS test";
+        assert!(matches(expr, &[("type", "order"), ("region", "us")]));
+        assert!(!matches(expr, &[("type", "order"), ("region", "ap")]));
+        assert!(!matches(
+            expr,
+            &[("type", "order"), ("region", "eu"), ("test", "1")]
+        ));
+
+        // AND binds tighter than OR
+        assert_eq!(
+            Filter::parse("a = '1' OR b = '2' AND c = '3'").unwrap(),
+            Filter::Or(vec![
+                Filter::Eq("a".into(), "1".into()),
+                Filter::And(vec![
+                    Filter::Eq("b".into(), "2".into()),
+                    Filter::Eq("c".into(), "3".into()),
+                ]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_quoting() {
+        assert!(matches("\"and\" = 'it''s'", &[("and", "it's")]));
+        assert!(matches(
+            "sluice.dlq.original_topic = 'orders'",
+            &[("sluice.dlq.original_topic", "orders")]
+        ));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = Filter::parse("region = eu").unwrap_err();
+        assert_eq!(err.position, 9);
+
+        let err = Filter::parse("region = 'eu").unwrap_err();
+        assert_eq!(err.position, 9);
+
+        let err = Filter::parse("region = 'eu' region").unwrap_err();
+        assert_eq!(err.position, 14);
+
+        let err = Filter::parse("(region = 'eu'").unwrap_err();
+        assert_eq!(err.position, 14);
+
+        assert!(Filter::parse("").is_err());
+        assert!(Filter::parse("region").is_err());
+        assert!(Filter::parse("region IN ()").is_err());
+        assert!(Filter::parse(&"NOT ".repeat(100)).is_err());
+        assert!(Filter::parse(&format!("{}a = 'b'{}", "(".repeat(100), ")".repeat(100))).is_err());
+    }
+}
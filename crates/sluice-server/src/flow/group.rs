@@ -0,0 +1,450 @@
+//! Delivery state for a consumer group.
+//!
+//! An exclusive subscription owns its `GroupDelivery` outright. In shared
+//! mode every connection in the group holds the same one, so the read
+//! position, in-flight messages and redelivery schedule are common to all
+//! members and each message is handed to exactly one of them.
+//!
+//! Keyed messages stick to the member that first received their key. When
+//! another member reads such a message it is set aside in the owner's
+//! backlog, so a key's messages reach one consumer in publish order. Once
+//! every message routed by a key is acked the key is free again.
+
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use tokio::sync::{Mutex, Notify};
+use tokio::time::Instant;
+
+use crate::flow::ack::AckTracker;
+use crate::flow::redelivery::RedeliveryQueue;
+
+/// Identifies one connection within a shared consumer group.
+pub type MemberId = u64;
+
+/// Messages set aside for one member before others stop reading for it.
+pub const MAX_BACKLOG: usize = 1000;
+
+/// Delivery progress for a consumer group.
+#[derive(Debug)]
+pub struct DeliveryState {
+    /// Highest sequence read from storage (not persisted until ACK).
+    pub cursor: i64,
+    /// Per-message ack tracking, in individual ack mode only.
+    pub ack_tracker: Option<AckTracker>,
+    /// NACKed messages, ack deadlines and failure counts.
+    pub redelivery: RedeliveryQueue,
+    /// Sequence -> member the message was last delivered to (shared mode).
+    owners: HashMap<i64, MemberId>,
+    /// Routing key -> member that receives its messages (shared mode).
+    key_owners: HashMap<String, MemberId>,
+    /// Sequence -> routing key of keyed messages that are not acked yet.
+    message_keys: BTreeMap<i64, String>,
+    /// Routing key -> number of its messages in `message_keys`.
+    key_refs: HashMap<String, usize>,
+    /// Keyed messages read by one member and waiting for their owner.
+    backlogs: HashMap<MemberId, BTreeSet<i64>>,
+    /// Partition -> durable cursor, for partitions that are ahead of
+    /// `cursor`. Their messages at or below it were already acked.
+    partition_cursors: HashMap<u32, i64>,
+    /// Highest sequence delivered to a consumer.
+    delivered_through: i64,
+    /// Highest sequence acked cumulatively.
+    acked_through: i64,
+    /// Whether skipped messages moved the cursor past what is persisted.
+    filtered: bool,
+}
+
+impl DeliveryState {
+    /// Create delivery state starting after `cursor`.
+    pub fn new(cursor: i64, ack_tracker: Option<AckTracker>, redelivery: RedeliveryQueue) -> Self {
+        Self {
+            cursor,
+            ack_tracker,
+            redelivery,
+            owners: HashMap::new(),
+            key_owners: HashMap::new(),
+            message_keys: BTreeMap::new(),
+            key_refs: HashMap::new(),
+            backlogs: HashMap::new(),
+            partition_cursors: HashMap::new(),
+            delivered_through: cursor,
+            acked_through: cursor,
+            filtered: false,
+        }
+    }
+
+    /// Resume partitions whose durable cursor is ahead of `cursor`.
+    pub fn with_partition_cursors(mut self, cursors: impl IntoIterator<Item = (u32, i64)>) -> Self {
+        let cursor = self.cursor;
+        self.partition_cursors = cursors
+            .into_iter()
+            .filter(|&(_, partition_cursor)| partition_cursor > cursor)
+            .collect();
+        self
+    }
+
+    /// Returns true if `seq` is covered by its partition's durable cursor.
+    pub fn is_partition_acked(&self, partition: u32, seq: i64) -> bool {
+        self.partition_cursors
+            .get(&partition)
+            .is_some_and(|&cursor| seq <= cursor)
+    }
+
+    /// Member that should receive message `seq` with `key`.
+    ///
+    /// An unowned key is claimed by `member` until its messages are acked.
+    /// Messages without a key can go to any member.
+    pub fn route(&mut self, seq: i64, key: Option<&str>, member: MemberId) -> MemberId {
+        let Some(key) = key else {
+            return member;
+        };
+        if let Entry::Vacant(entry) = self.message_keys.entry(seq) {
+            entry.insert(key.to_string());
+            *self.key_refs.entry(key.to_string()).or_default() += 1;
+        }
+        *self.key_owners.entry(key.to_string()).or_insert(member)
+    }
+
+    /// Number of routing keys that have an owner.
+    #[cfg(test)]
+    pub fn owned_key_count(&self) -> usize {
+        self.key_owners.len()
+    }
+
+    /// Returns true if `member` can take more messages into its backlog.
+    pub fn has_backlog_room(&self, member: MemberId) -> bool {
+        self.backlogs.get(&member).map_or(0, BTreeSet::len) < MAX_BACKLOG
+    }
+
+    /// Set a message aside for the member that owns its key.
+    pub fn hand_off(&mut self, seq: i64, owner: MemberId) {
+        self.assign(seq, owner);
+        self.backlogs.entry(owner).or_default().insert(seq);
+    }
+
+    /// The oldest message waiting in a member's backlog.
+    pub fn peek_backlog(&self, member: MemberId) -> Option<i64> {
+        self.backlogs.get(&member)?.first().copied()
+    }
+
+    /// Remove a message from a member's backlog.
+    pub fn take_backlog(&mut self, member: MemberId, seq: i64) {
+        if let Some(backlog) = self.backlogs.get_mut(&member) {
+            backlog.remove(&seq);
+            if backlog.is_empty() {
+                self.backlogs.remove(&member);
+            }
+        }
+    }
+
+    /// Record that `seq` was delivered to `member`.
+    pub fn assign(&mut self, seq: i64, member: MemberId) {
+        self.owners.insert(seq, member);
+    }
+
+    /// Record that `seq` was sent to a consumer.
+    pub fn record_delivered(&mut self, seq: i64) {
+        self.delivered_through = self.delivered_through.max(seq);
+    }
+
+    /// Read past a message rejected by the subscription's filter, or one
+    /// that has expired.
+    ///
+    /// The message counts as acked, so the cursor can move over it.
+    pub fn skip_filtered(&mut self, seq: i64) {
+        if let Some(tracker) = self.ack_tracker.as_mut() {
+            tracker.record_filtered(seq);
+        }
+        self.cursor = seq;
+        self.filtered = true;
+    }
+
+    /// Forget a delivered message that storage deleted before it was acked
+    /// (expired, or pruned by retention).
+    ///
+    /// It counts as acked, so it no longer holds the cursor back.
+    pub fn settle_deleted(&mut self, seq: i64) {
+        if let Some(tracker) = self.ack_tracker.as_mut() {
+            if tracker.settle(seq).is_some() {
+                self.filtered = true;
+            }
+        }
+        self.ack(seq);
+    }
+
+    /// Move the read position of a subscription reading out of sequence
+    /// order. Every message at or below `seq` must have been read.
+    ///
+    /// Skipped messages the read position now covers can move the cursor.
+    pub fn advance_read_position(&mut self, seq: i64) {
+        self.cursor = seq;
+        if let Some(tracker) = self.ack_tracker.as_mut() {
+            if tracker.set_read_through(seq).is_some() {
+                self.filtered = true;
+            }
+        }
+    }
+
+    /// The cursor to persist after skipping filtered-out messages.
+    ///
+    /// Acks persist the cursor as usual, but a run of filtered-out messages
+    /// after the last ack would otherwise show as lag until the next match.
+    /// Returns `None` while a delivered message is still unacked.
+    pub fn take_filtered_cursor(&mut self) -> Option<i64> {
+        if !self.filtered {
+            return None;
+        }
+        let cursor = match self.ack_tracker.as_ref() {
+            Some(tracker) if tracker.in_flight_count() == 0 => tracker.cursor(),
+            None if self.delivered_through <= self.acked_through => self.cursor,
+            _ => return None,
+        };
+        self.filtered = false;
+        Some(cursor)
+    }
+
+    /// Returns true if `seq` was delivered and is still unacked.
+    pub fn is_outstanding(&self, seq: i64) -> bool {
+        match self.ack_tracker.as_ref() {
+            Some(tracker) => tracker.is_in_flight(seq),
+            None => self.acked_through < seq && seq <= self.cursor,
+        }
+    }
+
+    /// Forget a message that has been acked.
+    pub fn ack(&mut self, seq: i64) {
+        self.redelivery.ack(seq);
+        self.owners.remove(&seq);
+        if let Some(key) = self.message_keys.remove(&seq) {
+            self.release_key(&key);
+        }
+    }
+
+    /// Forget every message at or below `seq` (cumulative ACK).
+    pub fn ack_through(&mut self, seq: i64) {
+        self.acked_through = self.acked_through.max(seq);
+        self.redelivery.ack_through(seq);
+        self.owners.retain(|&s, _| s > seq);
+
+        let pending = self.message_keys.split_off(&(seq + 1));
+        for key in std::mem::replace(&mut self.message_keys, pending).into_values() {
+            self.release_key(&key);
+        }
+    }
+
+    /// Drop one acked message of `key`, freeing the key with its last one.
+    fn release_key(&mut self, key: &str) {
+        if let Some(refs) = self.key_refs.get_mut(key) {
+            *refs -= 1;
+            if *refs == 0 {
+                self.key_refs.remove(key);
+                self.key_owners.remove(key);
+            }
+        }
+    }
+
+    /// Hand back the unacked messages of a member that left the group.
+    ///
+    /// They are queued for immediate redelivery to the remaining members
+    /// without counting a failed attempt, and the member's keys become free
+    /// to be claimed again. Returns how many messages were requeued.
+    pub fn release_member(&mut self, member: MemberId) -> usize {
+        self.key_owners.retain(|_, owner| *owner != member);
+        self.backlogs.remove(&member);
+
+        let now = Instant::now();
+        let mut released = Vec::new();
+        self.owners.retain(|&seq, &mut owner| {
+            if owner == member {
+                released.push(seq);
+                false
+            } else {
+                true
+            }
+        });
+
+        released.retain(|&seq| self.is_outstanding(seq) && !self.redelivery.is_scheduled(seq));
+        for &seq in &released {
+            self.redelivery.requeue(seq, now);
+        }
+        released.len()
+    }
+}
+
+/// Delivery state plus a wake-up signal for the connections using it.
+#[derive(Debug)]
+pub struct GroupDelivery {
+    /// Delivery progress, locked while a connection reads or updates it.
+    pub state: Mutex<DeliveryState>,
+    /// Signalled when messages are returned to the group for redelivery.
+    pub changed: Notify,
+    /// Reset generation of the group when the state was loaded.
+    pub reset_generation: i64,
+}
+
+impl GroupDelivery {
+    /// Wrap delivery state for use by one or more connections.
+    pub fn new(state: DeliveryState, reset_generation: i64) -> Self {
+        Self {
+            state: Mutex::new(state),
+            changed: Notify::new(),
+            reset_generation,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_member_requeues_unacked() {
+        let mut state =
+            DeliveryState::new(0, Some(AckTracker::new(0, [])), RedeliveryQueue::default());
+        let tracker = state.ack_tracker.as_mut().unwrap();
+        for seq in 1..=4 {
+            tracker.record_delivery(seq, format!("msg-{seq}"));
+        }
+        state.cursor = 4;
+        state.assign(1, 1);
+        state.assign(2, 1);
+        state.assign(3, 1);
+        state.assign(4, 2);
+
+        // Acked and already-scheduled messages are not requeued
+        state.ack_tracker.as_mut().unwrap().ack(1);
+        state.ack(1);
+        state.redelivery.schedule(3, Instant::now(), 1);
+
+        assert_eq!(state.release_member(1), 1);
+        assert!(state.redelivery.is_scheduled(2));
+        assert_eq!(state.redelivery.attempt(2), 1);
+        assert!(!state.redelivery.is_scheduled(4));
+        assert_eq!(state.release_member(1), 0);
+    }
+
+    #[test]
+    fn test_keys_stick_to_first_member() {
+        let mut state =
+            DeliveryState::new(0, Some(AckTracker::new(0, [])), RedeliveryQueue::default());
+
+        assert_eq!(state.route(3, Some("order-1"), 1), 1);
+        assert_eq!(state.route(5, Some("order-1"), 2), 1);
+        assert_eq!(state.route(6, Some("order-2"), 2), 2);
+        assert_eq!(state.route(7, Some("order-1"), 2), 1);
+        assert_eq!(state.route(8, None, 2), 2);
+
+        // Member 2 read order-1 messages; they wait for member 1 in order
+        let tracker = state.ack_tracker.as_mut().unwrap();
+        tracker.record_delivery(7, "msg-7".to_string());
+        tracker.record_delivery(5, "msg-5".to_string());
+        state.hand_off(7, 1);
+        state.hand_off(5, 1);
+        assert_eq!(state.peek_backlog(1), Some(5));
+        state.take_backlog(1, 5);
+        assert_eq!(state.peek_backlog(1), Some(7));
+
+        // Leaving frees the key and requeues the backlog
+        assert_eq!(state.release_member(1), 2);
+        assert_eq!(state.peek_backlog(1), None);
+        assert_eq!(state.route(9, Some("order-1"), 2), 2);
+    }
+
+    #[test]
+    fn test_cumulative_acked_messages_are_not_outstanding() {
+        let mut state = DeliveryState::new(0, None, RedeliveryQueue::default());
+        state.cursor = 3;
+        state.ack_through(2);
+
+        assert!(!state.is_outstanding(1));
+        assert!(!state.is_outstanding(2));
+        assert!(state.is_outstanding(3));
+        assert!(!state.is_outstanding(4));
+    }
+
+    #[test]
+    fn test_keys_are_released_once_acked() {
+        // Individual acks
+        let mut state =
+            DeliveryState::new(0, Some(AckTracker::new(0, [])), RedeliveryQueue::default());
+        assert_eq!(state.route(1, Some("order-1"), 1), 1);
+        assert_eq!(state.route(2, Some("order-1"), 1), 1);
+        assert_eq!(state.route(3, Some("order-2"), 2), 2);
+        // Routing the same message again does not count it twice
+        assert_eq!(state.route(1, Some("order-1"), 2), 1);
+        assert_eq!(state.owned_key_count(), 2);
+
+        state.ack(1);
+        assert_eq!(state.owned_key_count(), 2);
+        state.ack(2);
+        state.ack(3);
+        assert_eq!(state.owned_key_count(), 0);
+        assert_eq!(state.route(4, Some("order-1"), 2), 2);
+
+        // Cumulative acks
+        let mut state = DeliveryState::new(0, None, RedeliveryQueue::default());
+        for seq in 1..=100 {
+            state.route(seq, Some(&format!("order-{seq}")), 1);
+        }
+        state.route(101, Some("order-1"), 1);
+        assert_eq!(state.owned_key_count(), 100);
+        state.ack_through(100);
+        assert_eq!(state.owned_key_count(), 1);
+        state.ack_through(101);
+        assert_eq!(state.owned_key_count(), 0);
+    }
+
+    #[test]
+    fn test_partition_cursors_ahead_of_cursor() {
+        let state = DeliveryState::new(5, None, RedeliveryQueue::default())
+            .with_partition_cursors([(0, 5), (1, 9)]);
+
+        assert!(!state.is_partition_acked(0, 6));
+        assert!(state.is_partition_acked(1, 9));
+        assert!(!state.is_partition_acked(1, 10));
+        assert!(!state.is_partition_acked(2, 6));
+    }
+
+    #[test]
+    fn test_filtered_cursor_waits_for_acks() {
+        // Cumulative: the delivered message must be acked first
+        let mut state = DeliveryState::new(0, None, RedeliveryQueue::default());
+        state.record_delivered(1);
+        state.cursor = 1;
+        state.skip_filtered(2);
+        assert_eq!(state.take_filtered_cursor(), None);
+        state.ack_through(1);
+        assert_eq!(state.take_filtered_cursor(), Some(2));
+        assert_eq!(state.take_filtered_cursor(), None);
+
+        // Individual: persisted once nothing is in flight
+        let mut state =
+            DeliveryState::new(0, Some(AckTracker::new(0, [])), RedeliveryQueue::default());
+        state
+            .ack_tracker
+            .as_mut()
+            .unwrap()
+            .record_delivery(1, "msg-1".to_string());
+        state.skip_filtered(2);
+        assert_eq!(state.take_filtered_cursor(), None);
+        state.ack_tracker.as_mut().unwrap().ack(1);
+        assert_eq!(state.take_filtered_cursor(), Some(2));
+    }
+
+    #[test]
+    fn test_settled_deleted_message_is_persisted() {
+        let mut state =
+            DeliveryState::new(0, Some(AckTracker::new(0, [])), RedeliveryQueue::default());
+        let tracker = state.ack_tracker.as_mut().unwrap();
+        tracker.record_delivery(1, "msg-1".to_string());
+        tracker.record_delivery(2, "msg-2".to_string());
+        tracker.ack(2);
+        state.redelivery.lease(1, Instant::now());
+        assert_eq!(state.take_filtered_cursor(), None);
+
+        state.settle_deleted(1);
+        assert!(!state.redelivery.is_leased(1));
+        assert!(!state.is_outstanding(1));
+        assert_eq!(state.take_filtered_cursor(), Some(2));
+    }
+}
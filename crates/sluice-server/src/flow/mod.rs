@@ -2,7 +2,15 @@
 //!
 //! Provides:
 //! - Credit-based flow control for subscriptions
+//! - Per-message acknowledgement tracking
+//! - Delayed redelivery of rejected messages
+//! - Delivery state shared by the connections of a consumer group
+//! - Attribute filters deciding which messages a subscription receives
 //! - Notification bus for waking sleeping subscriptions
 
+pub mod ack;
 pub mod credit;
+pub mod filter;
+pub mod group;
 pub mod notify;
+pub mod redelivery;
@@ -0,0 +1,240 @@
+//! Redelivery scheduling for rejected and expired messages.
+//!
+//! Holds NACKed messages until their requeue delay has elapsed, tracks the
+//! ack deadline of every delivered message, and keeps per-message failure
+//! counts so deliveries can report their attempt number.
+
+use std::collections::{BTreeSet, HashMap};
+use tokio::time::Instant;
+
+/// Pending redeliveries, ack deadlines and failure counts for one subscription.
+#[derive(Debug, Default)]
+pub struct RedeliveryQueue {
+    /// Scheduled redeliveries ordered by due time.
+    due: BTreeSet<(Instant, i64)>,
+    /// Sequence -> due time, for messages currently scheduled.
+    scheduled: HashMap<i64, Instant>,
+    /// Delivered, unacked messages ordered by ack deadline.
+    leases: BTreeSet<(Instant, i64)>,
+    /// Sequence -> ack deadline, for messages currently leased.
+    deadlines: HashMap<i64, Instant>,
+    /// Sequence -> number of failed delivery attempts.
+    failures: HashMap<i64, u32>,
+}
+
+impl RedeliveryQueue {
+    /// Create a queue seeded with persisted failure counts.
+    pub fn new(failures: impl IntoIterator<Item = (i64, u32)>) -> Self {
+        Self {
+            failures: failures.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Attempt number for the next delivery of a message (1-based).
+    pub fn attempt(&self, seq: i64) -> u32 {
+        self.failures.get(&seq).copied().unwrap_or(0) + 1
+    }
+
+    /// Returns true if the message is waiting to be redelivered.
+    pub fn is_scheduled(&self, seq: i64) -> bool {
+        self.scheduled.contains_key(&seq)
+    }
+
+    /// Number of messages waiting to be redelivered.
+    pub fn len(&self) -> usize {
+        self.scheduled.len()
+    }
+
+    /// Returns true if no redeliveries are pending.
+    pub fn is_empty(&self) -> bool {
+        self.scheduled.is_empty()
+    }
+
+    /// Schedule a message for redelivery at `due_at`.
+    ///
+    /// `failures` is the total number of failed attempts so far. Any lease
+    /// on the message is released.
+    pub fn schedule(&mut self, seq: i64, due_at: Instant, failures: u32) {
+        self.release(seq);
+        self.failures.insert(seq, failures);
+        if let Some(previous) = self.scheduled.insert(seq, due_at) {
+            self.due.remove(&(previous, seq));
+        }
+        self.due.insert((due_at, seq));
+    }
+
+    /// Schedule a message for redelivery without counting a failed attempt.
+    pub fn requeue(&mut self, seq: i64, due_at: Instant) {
+        let failures = self.failures.get(&seq).copied().unwrap_or(0);
+        self.schedule(seq, due_at, failures);
+    }
+
+    /// When the earliest pending redelivery becomes due.
+    pub fn next_due(&self) -> Option<Instant> {
+        self.due.first().map(|&(due_at, _)| due_at)
+    }
+
+    /// The earliest message that is due at `now`, without removing it.
+    pub fn peek_due(&self, now: Instant) -> Option<i64> {
+        self.due
+            .first()
+            .filter(|&&(due_at, _)| due_at <= now)
+            .map(|&(_, seq)| seq)
+    }
+
+    /// Remove a message from the schedule (it is being redelivered).
+    pub fn take(&mut self, seq: i64) {
+        if let Some(due_at) = self.scheduled.remove(&seq) {
+            self.due.remove(&(due_at, seq));
+        }
+    }
+
+    /// Lease a delivered message until `deadline`.
+    pub fn lease(&mut self, seq: i64, deadline: Instant) {
+        if let Some(previous) = self.deadlines.insert(seq, deadline) {
+            self.leases.remove(&(previous, seq));
+        }
+        self.leases.insert((deadline, seq));
+    }
+
+    /// Move the deadline of a leased message.
+    ///
+    /// Returns false if the message is not currently leased.
+    pub fn extend_lease(&mut self, seq: i64, deadline: Instant) -> bool {
+        if !self.deadlines.contains_key(&seq) {
+            return false;
+        }
+        self.lease(seq, deadline);
+        true
+    }
+
+    /// Returns true if the message is delivered and its deadline is running.
+    pub fn is_leased(&self, seq: i64) -> bool {
+        self.deadlines.contains_key(&seq)
+    }
+
+    /// Returns true if any delivered message has a running deadline.
+    pub fn has_leases(&self) -> bool {
+        !self.deadlines.is_empty()
+    }
+
+    /// When the earliest ack deadline expires.
+    pub fn next_expiry(&self) -> Option<Instant> {
+        self.leases.first().map(|&(deadline, _)| deadline)
+    }
+
+    /// Remove and return a message whose ack deadline has passed at `now`.
+    pub fn pop_expired(&mut self, now: Instant) -> Option<i64> {
+        let &(deadline, seq) = self.leases.first().filter(|&&(d, _)| d <= now)?;
+        self.leases.remove(&(deadline, seq));
+        self.deadlines.remove(&seq);
+        Some(seq)
+    }
+
+    /// Stop the ack deadline of a message.
+    pub fn release(&mut self, seq: i64) {
+        if let Some(deadline) = self.deadlines.remove(&seq) {
+            self.leases.remove(&(deadline, seq));
+        }
+    }
+
+    /// Forget a message that has been acked.
+    pub fn ack(&mut self, seq: i64) {
+        self.take(seq);
+        self.release(seq);
+        self.failures.remove(&seq);
+    }
+
+    /// Forget every message at or below `seq` (cumulative ACK).
+    pub fn ack_through(&mut self, seq: i64) {
+        self.due.retain(|&(_, s)| s > seq);
+        self.scheduled.retain(|&s, _| s > seq);
+        self.leases.retain(|&(_, s)| s > seq);
+        self.deadlines.retain(|&s, _| s > seq);
+        self.failures.retain(|&s, _| s > seq);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_due_order_and_attempts() {
+        let now = Instant::now();
+        let mut queue = RedeliveryQueue::new([(7, 2)]);
+        assert_eq!(queue.attempt(7), 3);
+        assert_eq!(queue.attempt(8), 1);
+
+        queue.schedule(8, now + Duration::from_secs(5), 1);
+        queue.schedule(7, now, 3);
+        assert_eq!(queue.next_due(), Some(now));
+        assert_eq!(queue.peek_due(now), Some(7));
+
+        queue.take(7);
+        assert_eq!(queue.peek_due(now), None);
+        assert_eq!(queue.peek_due(now + Duration::from_secs(5)), Some(8));
+        assert_eq!(queue.attempt(8), 2);
+    }
+
+    #[test]
+    fn test_reschedule_replaces_due_time() {
+        let now = Instant::now();
+        let mut queue = RedeliveryQueue::default();
+
+        queue.schedule(3, now, 1);
+        queue.schedule(3, now + Duration::from_secs(1), 2);
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.peek_due(now), None);
+        assert_eq!(queue.attempt(3), 3);
+
+        // Requeueing keeps the failure count
+        queue.requeue(3, now);
+        assert_eq!(queue.peek_due(now), Some(3));
+        assert_eq!(queue.attempt(3), 3);
+    }
+
+    #[test]
+    fn test_acks_clear_schedule() {
+        let now = Instant::now();
+        let mut queue = RedeliveryQueue::default();
+
+        queue.schedule(1, now, 1);
+        queue.schedule(2, now, 1);
+        queue.schedule(5, now, 1);
+
+        queue.ack(2);
+        assert!(!queue.is_scheduled(2));
+        assert_eq!(queue.attempt(2), 1);
+
+        queue.ack_through(4);
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.peek_due(now), Some(5));
+    }
+
+    #[test]
+    fn test_leases_expire_in_deadline_order() {
+        let now = Instant::now();
+        let mut queue = RedeliveryQueue::default();
+
+        queue.lease(1, now + Duration::from_secs(10));
+        queue.lease(2, now + Duration::from_secs(5));
+        assert_eq!(queue.next_expiry(), Some(now + Duration::from_secs(5)));
+        assert_eq!(queue.pop_expired(now), None);
+
+        // Extending a lease pushes it behind the other one
+        assert!(queue.extend_lease(2, now + Duration::from_secs(20)));
+        assert!(!queue.extend_lease(3, now));
+        assert_eq!(queue.pop_expired(now + Duration::from_secs(10)), Some(1));
+        assert!(!queue.is_leased(1));
+
+        // Scheduling a redelivery or acking releases the lease
+        queue.lease(4, now);
+        queue.schedule(4, now, 1);
+        assert!(!queue.is_leased(4));
+        queue.ack(2);
+        assert!(!queue.has_leases());
+    }
+}
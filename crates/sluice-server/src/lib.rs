@@ -12,6 +12,7 @@
 //!
 //! # Modules
 //!
+//! - [`auth`]: Bearer token authentication and topic ACLs
 //! - [`config`]: CLI and environment configuration
 //! - [`flow`]: Credit tracking and notification bus
 //! - [`observability`]: Metrics and tracing setup
@@ -33,6 +34,7 @@
     clippy::too_many_lines              // Some functions are inherently long
 )]
 
+pub mod auth;
 pub mod config;
 pub mod flow;
 pub mod observability;
@@ -10,6 +10,12 @@
 //! - sluice_active_subscriptions: Gauge for active subscription count
 //! - sluice_messages_delivered: Counter for messages delivered
 //! - sluice_messages_acked: Counter for messages acknowledged
+//! - sluice_messages_nacked: Counter for messages rejected for redelivery
+//! - sluice_messages_dead_lettered: Counter for messages moved to a dead-letter topic
+//! - sluice_ack_deadline_expired: Counter for deliveries whose ack deadline passed
+//! - sluice_messages_expired: Counter for expired messages skipped by consumer groups
+//! - sluice_messages_pruned: Counter for messages deleted by retention
+//! - sluice_permission_denied: Counter for requests denied by the ACL
 
 use opentelemetry::metrics::{Counter, Gauge, Histogram, Meter};
 use opentelemetry::{global, KeyValue};
@@ -44,8 +50,20 @@ pub struct Metrics {
     pub messages_delivered: Counter<u64>,
     /// Total messages acknowledged by consumers.
     pub messages_acked: Counter<u64>,
+    /// Total messages rejected by consumers for redelivery.
+    pub messages_nacked: Counter<u64>,
+    /// Total messages moved to a dead-letter topic.
+    pub messages_dead_lettered: Counter<u64>,
+    /// Total deliveries whose ack deadline passed without an ACK.
+    pub ack_deadline_expired: Counter<u64>,
+    /// Total expired messages skipped by consumer groups.
+    pub messages_expired: Counter<u64>,
     /// Credits granted to consumers.
     pub credits_granted: Counter<u64>,
+    /// Total messages deleted by retention enforcement.
+    pub messages_pruned: Counter<u64>,
+    /// Total requests denied by the ACL.
+    pub permission_denied: Counter<u64>,
 }
 
 impl Metrics {
@@ -97,11 +115,41 @@ impl Metrics {
                 .with_description("Total messages acknowledged by consumers")
                 .with_unit("1")
                 .init(),
+            messages_nacked: meter
+                .u64_counter("sluice_messages_nacked")
+                .with_description("Total messages rejected by consumers for redelivery")
+                .with_unit("1")
+                .init(),
+            messages_dead_lettered: meter
+                .u64_counter("sluice_messages_dead_lettered")
+                .with_description("Total messages moved to a dead-letter topic")
+                .with_unit("1")
+                .init(),
+            ack_deadline_expired: meter
+                .u64_counter("sluice_ack_deadline_expired")
+                .with_description("Total deliveries whose ack deadline passed without an ACK")
+                .with_unit("1")
+                .init(),
+            messages_expired: meter
+                .u64_counter("sluice_messages_expired")
+                .with_description("Total expired messages skipped by consumer groups")
+                .with_unit("1")
+                .init(),
             credits_granted: meter
                 .u64_counter("sluice_credits_granted")
                 .with_description("Total credits granted to consumers")
                 .with_unit("1")
                 .init(),
+            messages_pruned: meter
+                .u64_counter("sluice_messages_pruned")
+                .with_description("Total messages deleted by retention enforcement")
+                .with_unit("1")
+                .init(),
+            permission_denied: meter
+                .u64_counter("sluice_permission_denied")
+                .with_description("Total requests denied by the ACL")
+                .with_unit("1")
+                .init(),
         }
     }
 }
@@ -257,6 +305,50 @@ pub fn record_ack(topic: &str, consumer_group: &str) {
     }
 }
 
+/// Record a negative acknowledgment.
+pub fn record_nack(topic: &str, consumer_group: &str) {
+    if let Some(m) = METRICS.get() {
+        let attrs = [
+            KeyValue::new("topic", topic.to_string()),
+            KeyValue::new("consumer_group", consumer_group.to_string()),
+        ];
+        m.messages_nacked.add(1, &attrs);
+    }
+}
+
+/// Record a message moved to a dead-letter topic.
+pub fn record_dead_lettered(topic: &str, consumer_group: &str) {
+    if let Some(m) = METRICS.get() {
+        let attrs = [
+            KeyValue::new("topic", topic.to_string()),
+            KeyValue::new("consumer_group", consumer_group.to_string()),
+        ];
+        m.messages_dead_lettered.add(1, &attrs);
+    }
+}
+
+/// Record a delivery whose ack deadline expired.
+pub fn record_ack_deadline_expired(topic: &str, consumer_group: &str) {
+    if let Some(m) = METRICS.get() {
+        let attrs = [
+            KeyValue::new("topic", topic.to_string()),
+            KeyValue::new("consumer_group", consumer_group.to_string()),
+        ];
+        m.ack_deadline_expired.add(1, &attrs);
+    }
+}
+
+/// Record an expired message skipped by a consumer group.
+pub fn record_message_expired(topic: &str, consumer_group: &str) {
+    if let Some(m) = METRICS.get() {
+        let attrs = [
+            KeyValue::new("topic", topic.to_string()),
+            KeyValue::new("consumer_group", consumer_group.to_string()),
+        ];
+        m.messages_expired.add(1, &attrs);
+    }
+}
+
 /// Record credits granted.
 pub fn record_credits_granted(topic: &str, consumer_group: &str, credits: u32) {
     if let Some(m) = METRICS.get() {
@@ -268,6 +360,25 @@ pub fn record_credits_granted(topic: &str, consumer_group: &str, credits: u32) {
     }
 }
 
+/// Record messages deleted by retention enforcement.
+pub fn record_messages_pruned(topic: &str, count: usize) {
+    if let Some(m) = METRICS.get() {
+        let attrs = [KeyValue::new("topic", topic.to_string())];
+        m.messages_pruned.add(count as u64, &attrs);
+    }
+}
+
+/// Record a request denied by the ACL.
+pub fn record_permission_denied(topic: &str, action: &str) {
+    if let Some(m) = METRICS.get() {
+        let attrs = [
+            KeyValue::new("topic", topic.to_string()),
+            KeyValue::new("action", action.to_string()),
+        ];
+        m.permission_denied.add(1, &attrs);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
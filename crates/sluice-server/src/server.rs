@@ -2,22 +2,33 @@
 //!
 //! Configures tonic server with:
 //! - Publish and Subscribe service handlers
+//! - Optional TLS, with client certificate verification for mutual TLS
+//! - Bearer token authentication
 //! - Graceful shutdown support
+//! - Background retention enforcement
+//! - Delivery of scheduled messages
+//! - Deletion of expired messages
 //! - Health check endpoint
 
 use std::net::SocketAddr;
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::watch;
-use tonic::transport::Server;
+use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
 
+use crate::auth::{Auth, AuthInterceptor};
 use crate::config::Config;
 use crate::flow::notify::NotificationBus;
 use crate::observability::metrics::prometheus_registry;
 use crate::observability::prometheus::run_prometheus_server;
 use crate::proto::sluice::v1::sluice_server::SluiceServer;
+use crate::service::request::remove_stale_inboxes;
 use crate::service::{ConnectionRegistry, SluiceService};
 use crate::storage::batch::BatchConfig;
+use crate::storage::expiry::run_expiry_task;
 use crate::storage::reader::ReaderPool;
+use crate::storage::retention::{run_retention_task, RetentionConfig};
+use crate::storage::scheduler::run_scheduler_task;
 use crate::storage::writer::{Writer, WriterHandle};
 
 /// Server state shared across handlers.
@@ -26,6 +37,7 @@ pub struct ServerState {
     pub reader_pool: ReaderPool,
     pub notify_bus: NotificationBus,
     pub connection_registry: ConnectionRegistry,
+    pub auth: Arc<Auth>,
 }
 
 /// Run the Sluice gRPC server.
@@ -43,6 +55,8 @@ pub async fn run_server(
     mut shutdown_rx: watch::Receiver<bool>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let addr: SocketAddr = format!("{}:{}", config.host, config.port).parse()?;
+    let tls = tls_config(&config)?;
+    let auth = Arc::new(Auth::from_config(&config)?);
 
     // Create notification bus
     let notify_bus = NotificationBus::new(config.notify_channel_size);
@@ -57,18 +71,52 @@ pub async fn run_server(
         config.write_channel_size,
         batch_config,
         config.wal_checkpoint_pages,
+        !config.disable_auto_create,
+        config.dedup_window_ms(),
+        config.partition_writers,
     )?;
     let writer_handle = writer.handle();
 
     // Create reader pool
     let reader_pool = ReaderPool::new(config.data_dir.join("sluice.db"), config.reader_pool_size)?;
 
+    // Inboxes of request streams do not outlive the server
+    let stale_inboxes = remove_stale_inboxes(&writer_handle, &reader_pool).await?;
+    if stale_inboxes > 0 {
+        tracing::info!(count = stale_inboxes, "Deleted stale request inboxes");
+    }
+
+    // Spawn retention task
+    let retention_config = RetentionConfig::from_config(
+        config.retention_max_age_secs,
+        config.retention_max_messages,
+        config.retention_max_bytes,
+        config.retention_interval_secs,
+        config.retention_chunk_size,
+    );
+    let retention_task = tokio::spawn(run_retention_task(
+        writer_handle.clone(),
+        reader_pool.clone(),
+        retention_config,
+        shutdown_rx.clone(),
+    ));
+
+    // Spawn scheduled message release task
+    let scheduler_task = tokio::spawn(run_scheduler_task(
+        writer_handle.clone(),
+        shutdown_rx.clone(),
+    ));
+
+    // Spawn expired message deletion task
+    let expiry_task = tokio::spawn(run_expiry_task(writer_handle.clone(), shutdown_rx.clone()));
+
     // Create shared state
     let state = Arc::new(ServerState {
         writer: writer_handle.clone(),
         reader_pool,
         notify_bus,
         connection_registry: ConnectionRegistry::new(),
+        auth: auth.clone(),
     });
 
     // Create service
@@ -76,22 +124,38 @@ pub async fn run_server(
 
     // Spawn Prometheus metrics server if enabled
     if config.metrics_enabled {
-        let metrics_addr: SocketAddr = format!("{}:{}", config.host, config.metrics_port).parse()?;
+        let metrics_addr: SocketAddr =
+            format!("{}:{}", config.host, config.metrics_port).parse()?;
         let registry = prometheus_registry();
         let metrics_shutdown_rx = shutdown_rx.clone();
 
         tokio::spawn(async move {
-            if let Err(e) = run_prometheus_server(metrics_addr, registry, metrics_shutdown_rx).await {
+            if let Err(e) = run_prometheus_server(metrics_addr, registry, metrics_shutdown_rx).await
+            {
                 tracing::error!(error = %e, "Prometheus server error");
             }
         });
     }
 
-    tracing::info!(address = %addr, "Starting Sluice gRPC server");
+    tracing::info!(
+        address = %addr,
+        tls = tls.is_some(),
+        client_auth = config.tls_client_ca.is_some(),
+        token_auth = auth.is_enabled(),
+        "Starting Sluice gRPC server"
+    );
+
+    let mut builder = Server::builder();
+    if let Some(tls) = tls {
+        builder = builder.tls_config(tls)?;
+    }
 
     // Run server with graceful shutdown
-    Server::builder()
-        .add_service(SluiceServer::new(service))
+    builder
+        .add_service(SluiceServer::with_interceptor(
+            service,
+            AuthInterceptor::new(auth),
+        ))
         .serve_with_shutdown(addr, async move {
             // Wait for shutdown signal
             let _ = shutdown_rx.changed().await;
@@ -99,6 +163,11 @@ pub async fn run_server(
         })
         .await?;
 
+    // Stop background tasks before the writer goes away
+    let _ = retention_task.await;
+    let _ = scheduler_task.await;
+    let _ = expiry_task.await;
+
     // Shutdown writer
     tracing::info!("Shutting down writer thread");
     writer_handle.shutdown().await?;
@@ -107,3 +176,29 @@ pub async fn run_server(
     tracing::info!("Server stopped");
     Ok(())
 }
+
+/// Build the listener's TLS settings from `--tls-cert` and `--tls-key`.
+///
+/// Returns `None` when TLS is not configured. With `--tls-client-ca`, clients
+/// must present a certificate signed by one of its CAs.
+pub fn tls_config(config: &Config) -> Result<Option<ServerTlsConfig>, Box<dyn std::error::Error>> {
+    let (Some(cert_path), Some(key_path)) = (&config.tls_cert, &config.tls_key) else {
+        return Ok(None);
+    };
+
+    let cert = read_pem(cert_path, "TLS certificate")?;
+    let key = read_pem(key_path, "TLS key")?;
+    let mut tls = ServerTlsConfig::new().identity(Identity::from_pem(cert, key));
+
+    if let Some(ca_path) = &config.tls_client_ca {
+        let ca = read_pem(ca_path, "TLS client CA")?;
+        tls = tls.client_ca_root(Certificate::from_pem(ca));
+    }
+
+    Ok(Some(tls))
+}
+
+/// Read a PEM file, naming it in the error if it cannot be read.
+fn read_pem(path: &Path, what: &str) -> Result<Vec<u8>, String> {
+    std::fs::read(path).map_err(|e| format!("failed to read {what} {}: {e}", path.display()))
+}
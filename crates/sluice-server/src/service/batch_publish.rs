@@ -6,18 +6,23 @@ use std::sync::Arc;
 use std::time::Instant;
 use tonic::{Request, Response, Status};
 
+use crate::auth::{Action, Principal};
 use crate::generate_message_id;
 use crate::proto::sluice::v1::{
-    BatchPublishRequest, BatchPublishResponse, PublishResult as ProtoPublishResult,
+    BatchMessage, BatchPublishRequest, BatchPublishResponse, PublishResult as ProtoPublishResult,
 };
 use crate::server::ServerState;
-use crate::storage::writer::{BatchMessageInput, WriterError};
+use crate::service::publish::{
+    deliver_at, expected_last_sequence, priority, publish_error_status, ttl_ms,
+    validate_topic_name, MAX_IDEMPOTENCY_KEY_SIZE, MAX_KEY_SIZE,
+};
+use crate::storage::writer::{BatchPublishResultItem, MessageInput};
 
 /// Maximum payload size per message (4MB, gRPC default limit).
 const MAX_PAYLOAD_SIZE: usize = 4 * 1024 * 1024;
 
 /// Maximum number of messages per batch.
-const MAX_BATCH_SIZE: usize = 1000;
+pub(crate) const MAX_BATCH_SIZE: usize = 1000;
 
 /// Handle a BatchPublish RPC request.
 ///
@@ -28,33 +33,16 @@ pub async fn handle_batch_publish(
     request: Request<BatchPublishRequest>,
 ) -> Result<Response<BatchPublishResponse>, Status> {
     let start = Instant::now();
+    let principal = Principal::from_request(&request);
     let req = request.into_inner();
 
-    // Validate topic
-    if req.topic.is_empty() {
-        return Err(Status::invalid_argument("topic cannot be empty"));
-    }
-
-    if req.topic.len() > 255 {
-        return Err(Status::invalid_argument(
-            "topic name too long (max 255 characters)",
-        ));
-    }
-
-    // Validate topic name characters (alphanumeric, dash, underscore, dot)
-    if !req
-        .topic
-        .chars()
-        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
-    {
-        return Err(Status::invalid_argument(
-            "topic name must contain only alphanumeric characters, dashes, underscores, or dots",
-        ));
-    }
+    validate_topic_name(&req.topic)?;
 
     // Validate batch size
     if req.messages.is_empty() {
-        return Err(Status::invalid_argument("batch must contain at least one message"));
+        return Err(Status::invalid_argument(
+            "batch must contain at least one message",
+        ));
     }
 
     if req.messages.len() > MAX_BATCH_SIZE {
@@ -65,15 +53,52 @@ pub async fn handle_batch_publish(
         )));
     }
 
+    let expected_last_sequence = expected_last_sequence(req.expected_last_sequence)?;
+
+    state
+        .auth
+        .authorize(principal.as_ref(), Action::Publish, &req.topic)?;
+
     tracing::Span::current().record("topic", &req.topic);
     tracing::Span::current().record("batch_size", req.messages.len());
 
     // Clone topic for metrics before moving to writer
     let topic_for_metrics = req.topic.clone();
 
-    // Prepare messages for writer
-    let mut messages = Vec::with_capacity(req.messages.len());
-    for msg in req.messages {
+    let messages = prepare_batch_messages(req.messages)?;
+
+    let batch_size = messages.len();
+
+    // Submit to writer
+    let (results, timestamp) = state
+        .writer
+        .batch_publish(req.topic, messages, expected_last_sequence)
+        .await
+        .map_err(publish_error_status)?;
+
+    // Record metrics
+    let latency = start.elapsed().as_secs_f64();
+
+    tracing::debug!(
+        topic = %topic_for_metrics,
+        batch_size,
+        latency_ms = latency * 1000.0,
+        "Batch published"
+    );
+
+    Ok(Response::new(BatchPublishResponse {
+        results: proto_results(results),
+        timestamp,
+    }))
+}
+
+/// Validate batch messages and build their writer input.
+#[allow(clippy::result_large_err)]
+pub(crate) fn prepare_batch_messages(
+    messages: Vec<BatchMessage>,
+) -> Result<Vec<MessageInput>, Status> {
+    let mut prepared = Vec::with_capacity(messages.len());
+    for msg in messages {
         // Validate payload size
         if msg.payload.len() > MAX_PAYLOAD_SIZE {
             return Err(Status::resource_exhausted(format!(
@@ -83,6 +108,18 @@ pub async fn handle_batch_publish(
             )));
         }
 
+        if msg.key.len() > MAX_KEY_SIZE {
+            return Err(Status::invalid_argument(format!(
+                "key too long (max {MAX_KEY_SIZE} bytes)"
+            )));
+        }
+
+        if msg.idempotency_key.len() > MAX_IDEMPOTENCY_KEY_SIZE {
+            return Err(Status::invalid_argument(format!(
+                "idempotency_key too long (max {MAX_IDEMPOTENCY_KEY_SIZE} bytes)"
+            )));
+        }
+
         // Serialize attributes to JSON
         let attributes = if msg.attributes.is_empty() {
             None
@@ -96,7 +133,7 @@ pub async fn handle_batch_publish(
         // Generate message ID
         let message_id = generate_message_id();
 
-        messages.push(BatchMessageInput {
+        prepared.push(MessageInput {
             message_id,
             payload: if msg.payload.is_empty() {
                 None
@@ -104,46 +141,26 @@ pub async fn handle_batch_publish(
                 Some(msg.payload)
             },
             attributes,
+            key: (!msg.key.is_empty()).then_some(msg.key),
+            idempotency_key: (!msg.idempotency_key.is_empty()).then_some(msg.idempotency_key),
+            deliver_at: deliver_at(msg.deliver_at)?,
+            ttl_ms: ttl_ms(msg.ttl_ms)?,
+            priority: priority(msg.priority)?,
         });
     }
+    Ok(prepared)
+}
 
-    let batch_size = messages.len();
-
-    // Submit to writer
-    let (results, timestamp) = state
-        .writer
-        .batch_publish(req.topic, messages)
-        .await
-        .map_err(|e| match e {
-            WriterError::ChannelClosed => Status::unavailable("server is shutting down"),
-            WriterError::Database(msg) if msg.contains("disk") || msg.contains("full") => {
-                Status::unavailable(format!("storage error: {msg}"))
-            }
-            WriterError::Database(msg) => Status::internal(format!("database error: {msg}")),
-            WriterError::ThreadPanic => Status::internal("internal error"),
-        })?;
-
-    // Record metrics
-    let latency = start.elapsed().as_secs_f64();
-
-    tracing::debug!(
-        topic = %topic_for_metrics,
-        batch_size,
-        latency_ms = latency * 1000.0,
-        "Batch published"
-    );
-
-    // Convert results to proto format
-    let proto_results: Vec<ProtoPublishResult> = results
+/// Convert writer results to their proto form.
+pub(crate) fn proto_results(results: Vec<BatchPublishResultItem>) -> Vec<ProtoPublishResult> {
+    results
         .into_iter()
         .map(|r| ProtoPublishResult {
             message_id: r.message_id,
             sequence: r.sequence as u64,
+            partition: r.partition,
+            partition_sequence: r.partition_sequence as u64,
+            duplicate: r.duplicate,
         })
-        .collect();
-
-    Ok(Response::new(BatchPublishResponse {
-        results: proto_results,
-        timestamp,
-    }))
+        .collect()
 }
@@ -0,0 +1,254 @@
+//! Consumer group management services (ListConsumerGroups,
+//! DescribeConsumerGroup, ResetConsumerGroup, DeleteConsumerGroup).
+
+use std::sync::Arc;
+
+use tonic::{Request, Response, Status};
+
+use super::topics::{topic_not_found, writer_error_status};
+use crate::auth::{Action, Principal};
+use crate::proto::sluice::v1::{
+    ConsumerGroupInfo, DeleteConsumerGroupRequest, DeleteConsumerGroupResponse,
+    DescribeConsumerGroupRequest, DescribeConsumerGroupResponse, ListConsumerGroupsRequest,
+    ListConsumerGroupsResponse, ResetConsumerGroupRequest, ResetConsumerGroupResponse,
+    ResetPosition,
+};
+use crate::server::ServerState;
+use crate::storage::reader::ConsumerGroupListing;
+use crate::storage::schema::{get_topic_by_name, Topic};
+use crate::storage::writer::ResetTarget;
+
+/// Handle a ListConsumerGroups RPC request.
+///
+/// Without a topic, only groups of topics the caller administers are listed.
+pub async fn handle_list_consumer_groups(
+    state: &Arc<ServerState>,
+    request: Request<ListConsumerGroupsRequest>,
+) -> Result<Response<ListConsumerGroupsResponse>, Status> {
+    let principal = Principal::from_request(&request);
+    let req = request.into_inner();
+
+    let topic = (!req.topic.is_empty()).then_some(req.topic.as_str());
+    if let Some(topic) = topic {
+        state
+            .auth
+            .authorize(principal.as_ref(), Action::Admin, topic)?;
+    }
+
+    let consumer_groups = list_groups(state, topic)?
+        .into_iter()
+        .filter(|group| {
+            state
+                .auth
+                .allows(principal.as_ref(), Action::Admin, &group.topic)
+        })
+        .map(|group| group_to_proto(state, group))
+        .collect();
+
+    Ok(Response::new(ListConsumerGroupsResponse {
+        consumer_groups,
+    }))
+}
+
+/// Handle a DescribeConsumerGroup RPC request.
+pub async fn handle_describe_consumer_group(
+    state: &Arc<ServerState>,
+    request: Request<DescribeConsumerGroupRequest>,
+) -> Result<Response<DescribeConsumerGroupResponse>, Status> {
+    let principal = Principal::from_request(&request);
+    let req = request.into_inner();
+
+    validate_group(&req.topic, &req.consumer_group)?;
+    state
+        .auth
+        .authorize(principal.as_ref(), Action::Admin, &req.topic)?;
+
+    let group = describe_group(state, &req.topic, &req.consumer_group)?;
+
+    Ok(Response::new(DescribeConsumerGroupResponse {
+        consumer_group: Some(group),
+    }))
+}
+
+/// Handle a ResetConsumerGroup RPC request.
+///
+/// The reset bumps the group's reset generation, so acks in flight from
+/// connections that loaded the group earlier are rejected instead of moving
+/// the cursor past the reset. Those connections are then ended with ABORTED,
+/// and reconnecting consumers resume from the new cursor.
+#[tracing::instrument(skip(state, request))]
+pub async fn handle_reset_consumer_group(
+    state: &Arc<ServerState>,
+    request: Request<ResetConsumerGroupRequest>,
+) -> Result<Response<ResetConsumerGroupResponse>, Status> {
+    let principal = Principal::from_request(&request);
+    let req = request.into_inner();
+
+    validate_group(&req.topic, &req.consumer_group)?;
+    let target = match ResetPosition::try_from(req.position) {
+        Ok(ResetPosition::ResetLatest) => ResetTarget::Latest,
+        Ok(ResetPosition::ResetEarliest) => ResetTarget::Earliest,
+        Ok(ResetPosition::ResetSequence) => {
+            let sequence = i64::try_from(req.sequence)
+                .map_err(|_| Status::invalid_argument("sequence out of range"))?;
+            ResetTarget::Sequence(sequence)
+        }
+        Ok(ResetPosition::ResetTimestamp) => ResetTarget::Timestamp(req.timestamp),
+        Err(_) => {
+            return Err(Status::invalid_argument(format!(
+                "unknown reset position: {}",
+                req.position
+            )))
+        }
+    };
+    state
+        .auth
+        .authorize(principal.as_ref(), Action::Admin, &req.topic)?;
+
+    let topic = lookup_topic(state, &req.topic)?;
+    let subscription = state
+        .writer
+        .reset_consumer_group(topic.id, req.consumer_group.clone(), target)
+        .await
+        .map_err(writer_error_status)?
+        .ok_or_else(|| group_not_found(&req.topic, &req.consumer_group))?;
+
+    let disconnected = state.connection_registry.terminate_group(
+        topic.id,
+        &req.consumer_group,
+        Status::aborted(format!("consumer group '{}' was reset", req.consumer_group)),
+    );
+
+    tracing::info!(
+        topic = %topic.name,
+        consumer_group = %subscription.consumer_group,
+        ?target,
+        cursor_seq = subscription.cursor_seq,
+        disconnected,
+        "Consumer group reset"
+    );
+
+    let group = describe_group(state, &req.topic, &req.consumer_group)?;
+
+    Ok(Response::new(ResetConsumerGroupResponse {
+        consumer_group: Some(group),
+    }))
+}
+
+/// Handle a DeleteConsumerGroup RPC request.
+///
+/// Connected consumers of the group are ended with ABORTED.
+#[tracing::instrument(skip(state, request))]
+pub async fn handle_delete_consumer_group(
+    state: &Arc<ServerState>,
+    request: Request<DeleteConsumerGroupRequest>,
+) -> Result<Response<DeleteConsumerGroupResponse>, Status> {
+    let principal = Principal::from_request(&request);
+    let req = request.into_inner();
+
+    validate_group(&req.topic, &req.consumer_group)?;
+    state
+        .auth
+        .authorize(principal.as_ref(), Action::Admin, &req.topic)?;
+
+    let topic = lookup_topic(state, &req.topic)?;
+    let disconnected = state.connection_registry.terminate_group(
+        topic.id,
+        &req.consumer_group,
+        Status::aborted(format!(
+            "consumer group '{}' was deleted",
+            req.consumer_group
+        )),
+    );
+
+    let deleted = state
+        .writer
+        .delete_consumer_group(topic.id, req.consumer_group.clone())
+        .await
+        .map_err(writer_error_status)?;
+    if !deleted {
+        return Err(group_not_found(&req.topic, &req.consumer_group));
+    }
+
+    tracing::info!(
+        topic = %topic.name,
+        consumer_group = %req.consumer_group,
+        disconnected,
+        "Consumer group deleted"
+    );
+
+    Ok(Response::new(DeleteConsumerGroupResponse {
+        disconnected: disconnected > 0,
+    }))
+}
+
+#[allow(clippy::result_large_err)]
+fn validate_group(topic: &str, consumer_group: &str) -> Result<(), Status> {
+    if topic.is_empty() {
+        return Err(Status::invalid_argument("topic cannot be empty"));
+    }
+    if consumer_group.is_empty() {
+        return Err(Status::invalid_argument("consumer_group cannot be empty"));
+    }
+    Ok(())
+}
+
+#[allow(clippy::result_large_err)]
+fn lookup_topic(state: &ServerState, name: &str) -> Result<Topic, Status> {
+    let conn = state
+        .reader_pool
+        .get()
+        .map_err(|e| Status::internal(format!("failed to read topic: {e}")))?;
+    get_topic_by_name(&conn, name)
+        .map_err(|e| Status::internal(format!("failed to read topic: {e}")))?
+        .ok_or_else(|| topic_not_found(name))
+}
+
+#[allow(clippy::result_large_err)]
+fn list_groups(
+    state: &ServerState,
+    topic: Option<&str>,
+) -> Result<Vec<ConsumerGroupListing>, Status> {
+    state
+        .reader_pool
+        .list_consumer_groups(topic)
+        .map_err(|e| Status::internal(format!("failed to list consumer groups: {e}")))
+}
+
+#[allow(clippy::result_large_err)]
+fn describe_group(
+    state: &ServerState,
+    topic: &str,
+    consumer_group: &str,
+) -> Result<ConsumerGroupInfo, Status> {
+    let groups = list_groups(state, Some(topic))?;
+    if groups.is_empty() {
+        lookup_topic(state, topic)?;
+    }
+    groups
+        .into_iter()
+        .find(|group| group.subscription.consumer_group == consumer_group)
+        .map(|group| group_to_proto(state, group))
+        .ok_or_else(|| group_not_found(topic, consumer_group))
+}
+
+fn group_not_found(topic: &str, consumer_group: &str) -> Status {
+    Status::not_found(format!(
+        "consumer group '{consumer_group}' does not exist on topic '{topic}'"
+    ))
+}
+
+fn group_to_proto(state: &ServerState, group: ConsumerGroupListing) -> ConsumerGroupInfo {
+    let subscription = group.subscription;
+    ConsumerGroupInfo {
+        connected: state
+            .connection_registry
+            .is_connected(group.topic_id, &subscription.consumer_group),
+        name: subscription.consumer_group,
+        cursor_sequence: subscription.cursor_seq as u64,
+        lag: group.lag as u64,
+        updated_at: subscription.updated_at.unwrap_or_default(),
+        topic: group.topic,
+        max_sequence: group.max_seq as u64,
+    }
+}
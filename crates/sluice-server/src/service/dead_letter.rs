@@ -0,0 +1,160 @@
+//! Dead-letter routing for messages that exhaust their delivery attempts.
+//!
+//! A dead-lettered message is republished through the writer's normal
+//! publish path, so the dead-letter topic is an ordinary topic that can be
+//! listed and subscribed to.
+
+use std::collections::HashMap;
+
+use crate::generate_message_id;
+use crate::observability::metrics::record_dead_lettered;
+use crate::storage::dead_letter::{
+    origin_attributes, ATTR_CONSUMER_GROUP, ATTR_DELIVERY_ATTEMPTS, DEFAULT_DLQ_SUFFIX,
+};
+use crate::storage::schema::Message;
+use crate::storage::writer::{MessageInput, PublishResult, WriterError, WriterHandle};
+
+/// Dead-letter settings for one consumer group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadLetterPolicy {
+    /// Failed attempts after which a message is dead-lettered.
+    pub max_delivery_attempts: u32,
+    /// Topic that receives dead-lettered messages.
+    pub topic: String,
+}
+
+impl DeadLetterPolicy {
+    /// Build a policy from subscription settings.
+    ///
+    /// Returns `None` when `max_delivery_attempts` is 0 (disabled). An empty
+    /// `dead_letter_topic` selects `<topic>.dlq`.
+    pub fn new(topic: &str, max_delivery_attempts: u32, dead_letter_topic: &str) -> Option<Self> {
+        if max_delivery_attempts == 0 {
+            return None;
+        }
+
+        let topic = if dead_letter_topic.is_empty() {
+            format!("{topic}{DEFAULT_DLQ_SUFFIX}")
+        } else {
+            dead_letter_topic.to_string()
+        };
+
+        Some(Self {
+            max_delivery_attempts,
+            topic,
+        })
+    }
+
+    /// Returns true if a message with this many failures should be dead-lettered.
+    pub fn is_exhausted(&self, failures: u32) -> bool {
+        failures >= self.max_delivery_attempts
+    }
+}
+
+/// Build the attributes for a dead-lettered copy of `msg`.
+///
+/// The original attributes are kept and the `sluice.dlq.*` attributes
+/// describing where the message came from are added on top.
+pub fn dead_letter_attributes(
+    msg: &Message,
+    topic_name: &str,
+    consumer_group: &str,
+    failures: u32,
+    reason: &str,
+) -> HashMap<String, String> {
+    let mut attributes = origin_attributes(msg, topic_name, reason);
+    attributes.insert(ATTR_CONSUMER_GROUP.to_string(), consumer_group.to_string());
+    attributes.insert(ATTR_DELIVERY_ATTEMPTS.to_string(), failures.to_string());
+    attributes
+}
+
+/// Publish a copy of `msg` to the policy's dead-letter topic.
+pub async fn dead_letter(
+    writer: &WriterHandle,
+    policy: &DeadLetterPolicy,
+    msg: &Message,
+    topic_name: &str,
+    consumer_group: &str,
+    failures: u32,
+    reason: &str,
+) -> Result<PublishResult, WriterError> {
+    let attributes = dead_letter_attributes(msg, topic_name, consumer_group, failures, reason);
+    let attributes = serde_json::to_string(&attributes)
+        .map_err(|e| WriterError::Database(format!("invalid attributes: {e}")))?;
+
+    let result = writer
+        .publish(
+            policy.topic.clone(),
+            MessageInput {
+                message_id: generate_message_id(),
+                payload: msg.payload.clone(),
+                attributes: Some(attributes),
+                key: msg.key.clone(),
+                idempotency_key: None,
+                deliver_at: None,
+                ttl_ms: None,
+                priority: msg.priority,
+            },
+            None,
+        )
+        .await?;
+
+    record_dead_lettered(topic_name, consumer_group);
+    tracing::info!(
+        topic = %topic_name,
+        consumer_group = %consumer_group,
+        dead_letter_topic = %policy.topic,
+        seq = msg.global_seq,
+        failures,
+        "Message dead-lettered"
+    );
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::dead_letter::{
+        ATTR_FAILURE_REASON, ATTR_ORIGINAL_MESSAGE_ID, ATTR_ORIGINAL_SEQUENCE, ATTR_ORIGINAL_TOPIC,
+    };
+
+    #[test]
+    fn test_policy_defaults_to_dlq_suffix() {
+        assert_eq!(DeadLetterPolicy::new("orders", 0, ""), None);
+
+        let policy = DeadLetterPolicy::new("orders", 3, "").unwrap();
+        assert_eq!(policy.topic, "orders.dlq");
+        assert!(!policy.is_exhausted(2));
+        assert!(policy.is_exhausted(3));
+
+        let policy = DeadLetterPolicy::new("orders", 3, "poison").unwrap();
+        assert_eq!(policy.topic, "poison");
+    }
+
+    #[test]
+    fn test_dead_letter_attributes_keep_originals() {
+        let msg = Message {
+            global_seq: 42,
+            topic_id: 1,
+            message_id: "msg-42".to_string(),
+            payload: Some(b"payload".to_vec()),
+            attributes: Some(r#"{"trace":"abc"}"#.to_string()),
+            created_at: 0,
+            key: None,
+            partition: 0,
+            partition_seq: 42,
+            expires_at: None,
+            priority: 0,
+        };
+
+        let attrs = dead_letter_attributes(&msg, "orders", "workers", 3, "bad input");
+        assert_eq!(attrs["trace"], "abc");
+        assert_eq!(attrs[ATTR_ORIGINAL_TOPIC], "orders");
+        assert_eq!(attrs[ATTR_ORIGINAL_SEQUENCE], "42");
+        assert_eq!(attrs[ATTR_ORIGINAL_MESSAGE_ID], "msg-42");
+        assert_eq!(attrs[ATTR_CONSUMER_GROUP], "workers");
+        assert_eq!(attrs[ATTR_DELIVERY_ATTEMPTS], "3");
+        assert_eq!(attrs[ATTR_FAILURE_REASON], "bad input");
+    }
+}
@@ -1,9 +1,15 @@
 //! gRPC service handlers for Sluice.
 
 pub mod batch_publish;
+pub mod consumer_groups;
+pub mod dead_letter;
 pub mod publish;
+pub mod publish_stream;
+pub mod publish_transaction;
 pub mod registry;
+pub mod request;
 pub mod subscribe;
+pub mod subscribe_pattern;
 pub mod topics;
 
 pub use registry::{ConnectionRegistry, ConsumerGroupKey};
@@ -15,8 +21,15 @@ use tonic::{Request, Response, Status, Streaming};
 
 use crate::proto::sluice::v1::sluice_server::Sluice;
 use crate::proto::sluice::v1::{
-    BatchPublishRequest, BatchPublishResponse, ListTopicsRequest, ListTopicsResponse,
-    PublishRequest, PublishResponse, SubscribeDownstream, SubscribeUpstream,
+    BatchPublishRequest, BatchPublishResponse, CreateTopicRequest, CreateTopicResponse,
+    DeleteConsumerGroupRequest, DeleteConsumerGroupResponse, DeleteTopicRequest,
+    DeleteTopicResponse, DescribeConsumerGroupRequest, DescribeConsumerGroupResponse,
+    DescribeTopicRequest, DescribeTopicResponse, ListConsumerGroupsRequest,
+    ListConsumerGroupsResponse, ListTopicsRequest, ListTopicsResponse, PublishRequest,
+    PublishResponse, PublishStreamRequest, PublishStreamResponse, PublishTransactionRequest,
+    PublishTransactionResponse, PurgeTopicRequest, PurgeTopicResponse, RequestDownstream,
+    RequestUpstream, ResetConsumerGroupRequest, ResetConsumerGroupResponse, SubscribeDownstream,
+    SubscribeUpstream, UpdateTopicConfigRequest, UpdateTopicConfigResponse,
 };
 use crate::server::ServerState;
 
@@ -32,6 +45,12 @@ impl SluiceService {
     }
 }
 
+type PublishStreamStream =
+    Pin<Box<dyn Stream<Item = Result<PublishStreamResponse, Status>> + Send + 'static>>;
+
+type RequestStream =
+    Pin<Box<dyn Stream<Item = Result<RequestDownstream, Status>> + Send + 'static>>;
+
 type SubscribeStream =
     Pin<Box<dyn Stream<Item = Result<SubscribeDownstream, Status>> + Send + 'static>>;
 
@@ -51,6 +70,31 @@ impl Sluice for SluiceService {
         batch_publish::handle_batch_publish(&self.state, request).await
     }
 
+    async fn publish_transaction(
+        &self,
+        request: Request<PublishTransactionRequest>,
+    ) -> Result<Response<PublishTransactionResponse>, Status> {
+        publish_transaction::handle_publish_transaction(&self.state, request).await
+    }
+
+    type PublishStreamStream = PublishStreamStream;
+
+    async fn publish_stream(
+        &self,
+        request: Request<Streaming<PublishStreamRequest>>,
+    ) -> Result<Response<Self::PublishStreamStream>, Status> {
+        publish_stream::handle_publish_stream(&self.state, request).await
+    }
+
+    type RequestStream = RequestStream;
+
+    async fn request(
+        &self,
+        request: Request<Streaming<RequestUpstream>>,
+    ) -> Result<Response<Self::RequestStream>, Status> {
+        request::handle_request(&self.state, request).await
+    }
+
     type SubscribeStream = SubscribeStream;
 
     async fn subscribe(
@@ -66,4 +110,67 @@ impl Sluice for SluiceService {
     ) -> Result<Response<ListTopicsResponse>, Status> {
         topics::handle_list_topics(&self.state, request).await
     }
+
+    async fn create_topic(
+        &self,
+        request: Request<CreateTopicRequest>,
+    ) -> Result<Response<CreateTopicResponse>, Status> {
+        topics::handle_create_topic(&self.state, request).await
+    }
+
+    async fn update_topic_config(
+        &self,
+        request: Request<UpdateTopicConfigRequest>,
+    ) -> Result<Response<UpdateTopicConfigResponse>, Status> {
+        topics::handle_update_topic_config(&self.state, request).await
+    }
+
+    async fn delete_topic(
+        &self,
+        request: Request<DeleteTopicRequest>,
+    ) -> Result<Response<DeleteTopicResponse>, Status> {
+        topics::handle_delete_topic(&self.state, request).await
+    }
+
+    async fn purge_topic(
+        &self,
+        request: Request<PurgeTopicRequest>,
+    ) -> Result<Response<PurgeTopicResponse>, Status> {
+        topics::handle_purge_topic(&self.state, request).await
+    }
+
+    async fn describe_topic(
+        &self,
+        request: Request<DescribeTopicRequest>,
+    ) -> Result<Response<DescribeTopicResponse>, Status> {
+        topics::handle_describe_topic(&self.state, request).await
+    }
+
+    async fn list_consumer_groups(
+        &self,
+        request: Request<ListConsumerGroupsRequest>,
+    ) -> Result<Response<ListConsumerGroupsResponse>, Status> {
+        consumer_groups::handle_list_consumer_groups(&self.state, request).await
+    }
+
+    async fn describe_consumer_group(
+        &self,
+        request: Request<DescribeConsumerGroupRequest>,
+    ) -> Result<Response<DescribeConsumerGroupResponse>, Status> {
+        consumer_groups::handle_describe_consumer_group(&self.state, request).await
+    }
+
+    async fn reset_consumer_group(
+        &self,
+        request: Request<ResetConsumerGroupRequest>,
+    ) -> Result<Response<ResetConsumerGroupResponse>, Status> {
+        consumer_groups::handle_reset_consumer_group(&self.state, request).await
+    }
+
+    async fn delete_consumer_group(
+        &self,
+        request: Request<DeleteConsumerGroupRequest>,
+    ) -> Result<Response<DeleteConsumerGroupResponse>, Status> {
+        consumer_groups::handle_delete_consumer_group(&self.state, request).await
+    }
 }
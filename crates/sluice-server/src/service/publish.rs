@@ -6,40 +6,87 @@ use std::sync::Arc;
 use std::time::Instant;
 use tonic::{Request, Response, Status};
 
+use crate::auth::{Action, Principal};
 use crate::generate_message_id;
 use crate::observability::metrics::record_publish;
 use crate::proto::sluice::v1::{PublishRequest, PublishResponse};
 use crate::server::ServerState;
-use crate::storage::writer::WriterError;
+use crate::service::topics::writer_error_status;
+use crate::storage::writer::{MessageInput, PublishResult, WriterError};
 
 /// Maximum payload size (4MB, gRPC default limit).
-const MAX_PAYLOAD_SIZE: usize = 4 * 1024 * 1024;
+pub(crate) const MAX_PAYLOAD_SIZE: usize = 4 * 1024 * 1024;
+
+/// Maximum routing key size in bytes.
+pub(crate) const MAX_KEY_SIZE: usize = 1024;
+
+/// Maximum idempotency key size in bytes.
+pub(crate) const MAX_IDEMPOTENCY_KEY_SIZE: usize = 256;
+
+/// Highest message priority.
+pub(crate) const MAX_PRIORITY: u32 = 9;
+
+/// Response metadata carrying a topic's actual last sequence when an
+/// `expected_last_sequence` check fails.
+pub(crate) const LAST_SEQUENCE_METADATA: &str = "sluice-last-sequence";
+
+/// Convert a requested `expected_last_sequence` to the writer's sequence type.
+#[allow(clippy::result_large_err)]
+pub(crate) fn expected_last_sequence(value: Option<u64>) -> Result<Option<i64>, Status> {
+    value
+        .map(|seq| {
+            i64::try_from(seq)
+                .map_err(|_| Status::invalid_argument("expected_last_sequence out of range"))
+        })
+        .transpose()
+}
 
-/// Handle a Publish RPC request.
-///
-/// Persists the message durably with fsync before returning.
-#[tracing::instrument(skip(state, request), fields(topic))]
-pub async fn handle_publish(
-    state: &Arc<ServerState>,
-    request: Request<PublishRequest>,
-) -> Result<Response<PublishResponse>, Status> {
-    let start = Instant::now();
-    let req = request.into_inner();
+/// Validate a requested delivery time, treating zero as unset.
+#[allow(clippy::result_large_err)]
+pub(crate) fn deliver_at(value: i64) -> Result<Option<i64>, Status> {
+    match value {
+        0 => Ok(None),
+        value if value < 0 => Err(Status::invalid_argument("deliver_at cannot be negative")),
+        value => Ok(Some(value)),
+    }
+}
+
+/// Validate a requested time to live, treating zero as unset.
+#[allow(clippy::result_large_err)]
+pub(crate) fn ttl_ms(value: i64) -> Result<Option<i64>, Status> {
+    match value {
+        0 => Ok(None),
+        value if value < 0 => Err(Status::invalid_argument("ttl_ms cannot be negative")),
+        value => Ok(Some(value)),
+    }
+}
+
+/// Validate a requested priority.
+#[allow(clippy::result_large_err)]
+pub(crate) fn priority(value: u32) -> Result<u32, Status> {
+    if value > MAX_PRIORITY {
+        return Err(Status::invalid_argument(format!(
+            "priority must be between 0 and {MAX_PRIORITY}"
+        )));
+    }
+    Ok(value)
+}
 
-    // Validate topic
-    if req.topic.is_empty() {
+/// Validate the name of a topic to publish to.
+#[allow(clippy::result_large_err)]
+pub(crate) fn validate_topic_name(topic: &str) -> Result<(), Status> {
+    if topic.is_empty() {
         return Err(Status::invalid_argument("topic cannot be empty"));
     }
 
-    if req.topic.len() > 255 {
+    if topic.len() > 255 {
         return Err(Status::invalid_argument(
             "topic name too long (max 255 characters)",
         ));
     }
 
     // Validate topic name characters (alphanumeric, dash, underscore, dot)
-    if !req
-        .topic
+    if !topic
         .chars()
         .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
     {
@@ -48,6 +95,25 @@ pub async fn handle_publish(
         ));
     }
 
+    Ok(())
+}
+
+/// A validated publish request, ready for the writer.
+pub(crate) struct PreparedPublish {
+    pub topic: String,
+    pub message: MessageInput,
+    pub expected_last_sequence: Option<i64>,
+}
+
+/// Validate and authorize a publish request and assign its message ID.
+#[allow(clippy::result_large_err)]
+pub(crate) fn prepare_publish(
+    state: &ServerState,
+    principal: Option<&Principal>,
+    req: PublishRequest,
+) -> Result<PreparedPublish, Status> {
+    validate_topic_name(&req.topic)?;
+
     // Validate payload size
     if req.payload.len() > MAX_PAYLOAD_SIZE {
         return Err(Status::resource_exhausted(format!(
@@ -57,10 +123,26 @@ pub async fn handle_publish(
         )));
     }
 
-    tracing::Span::current().record("topic", &req.topic);
+    if req.key.len() > MAX_KEY_SIZE {
+        return Err(Status::invalid_argument(format!(
+            "key too long (max {MAX_KEY_SIZE} bytes)"
+        )));
+    }
 
-    // Clone topic for metrics before moving to writer
-    let topic_for_metrics = req.topic.clone();
+    if req.idempotency_key.len() > MAX_IDEMPOTENCY_KEY_SIZE {
+        return Err(Status::invalid_argument(format!(
+            "idempotency_key too long (max {MAX_IDEMPOTENCY_KEY_SIZE} bytes)"
+        )));
+    }
+
+    let expected_last_sequence = expected_last_sequence(req.expected_last_sequence)?;
+    let deliver_at = deliver_at(req.deliver_at)?;
+    let ttl_ms = ttl_ms(req.ttl_ms)?;
+    let priority = priority(req.priority)?;
+
+    state
+        .auth
+        .authorize(principal, Action::Publish, &req.topic)?;
 
     // Serialize attributes to JSON
     let attributes = if req.attributes.is_empty() {
@@ -72,31 +154,77 @@ pub async fn handle_publish(
         )
     };
 
-    // Generate message ID
-    let message_id = generate_message_id();
-
-    // Submit to writer
-    let result = state
-        .writer
-        .publish(
-            req.topic,
-            message_id.clone(),
-            if req.payload.is_empty() {
+    Ok(PreparedPublish {
+        topic: req.topic,
+        message: MessageInput {
+            message_id: generate_message_id(),
+            payload: if req.payload.is_empty() {
                 None
             } else {
                 Some(req.payload)
             },
             attributes,
+            key: (!req.key.is_empty()).then_some(req.key),
+            idempotency_key: (!req.idempotency_key.is_empty()).then_some(req.idempotency_key),
+            deliver_at,
+            ttl_ms,
+            priority,
+        },
+        expected_last_sequence,
+    })
+}
+
+/// Map a writer error from a publish to the status returned to the client.
+pub(crate) fn publish_error_status(e: WriterError) -> Status {
+    match e {
+        WriterError::ChannelClosed => Status::unavailable("server is shutting down"),
+        WriterError::Database(msg) if msg.contains("disk") || msg.contains("full") => {
+            Status::unavailable(format!("storage error: {msg}"))
+        }
+        WriterError::Database(msg) => Status::internal(format!("database error: {msg}")),
+        e => writer_error_status(e),
+    }
+}
+
+/// Build the response for a committed publish.
+pub(crate) fn publish_response(result: PublishResult) -> PublishResponse {
+    PublishResponse {
+        message_id: result.message_id,
+        sequence: result.sequence as u64,
+        timestamp: result.timestamp,
+        partition: result.partition,
+        partition_sequence: result.partition_sequence as u64,
+        duplicate: result.duplicate,
+    }
+}
+
+/// Handle a Publish RPC request.
+///
+/// Persists the message durably with fsync before returning.
+#[tracing::instrument(skip(state, request), fields(topic))]
+pub async fn handle_publish(
+    state: &Arc<ServerState>,
+    request: Request<PublishRequest>,
+) -> Result<Response<PublishResponse>, Status> {
+    let start = Instant::now();
+    let principal = Principal::from_request(&request);
+    let prepared = prepare_publish(state, principal.as_ref(), request.into_inner())?;
+
+    tracing::Span::current().record("topic", &prepared.topic);
+
+    // Clone topic for metrics before moving to writer
+    let topic_for_metrics = prepared.topic.clone();
+
+    // Submit to writer
+    let result = state
+        .writer
+        .publish(
+            prepared.topic,
+            prepared.message,
+            prepared.expected_last_sequence,
         )
         .await
-        .map_err(|e| match e {
-            WriterError::ChannelClosed => Status::unavailable("server is shutting down"),
-            WriterError::Database(msg) if msg.contains("disk") || msg.contains("full") => {
-                Status::unavailable(format!("storage error: {msg}"))
-            }
-            WriterError::Database(msg) => Status::internal(format!("database error: {msg}")),
-            WriterError::ThreadPanic => Status::internal("internal error"),
-        })?;
+        .map_err(publish_error_status)?;
 
     // Record metrics
     let latency = start.elapsed().as_secs_f64();
@@ -105,13 +233,10 @@ pub async fn handle_publish(
     tracing::debug!(
         message_id = %result.message_id,
         sequence = result.sequence,
+        duplicate = result.duplicate,
         latency_ms = latency * 1000.0,
         "Message published"
     );
 
-    Ok(Response::new(PublishResponse {
-        message_id: result.message_id,
-        sequence: result.sequence as u64,
-        timestamp: result.timestamp,
-    }))
+    Ok(Response::new(publish_response(result)))
 }
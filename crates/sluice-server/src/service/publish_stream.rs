@@ -0,0 +1,182 @@
+//! PublishStream RPC handler implementation.
+//!
+//! Producers send messages continuously over one stream. Each message is
+//! queued on the writer as soon as it arrives, so many messages share a
+//! group commit, and is acknowledged once its batch is committed.
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::Stream;
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::auth::Principal;
+use crate::observability::metrics::record_publish;
+use crate::proto::sluice::v1::publish_stream_response::Result as StreamResult;
+use crate::proto::sluice::v1::{PublishError, PublishStreamRequest, PublishStreamResponse};
+use crate::server::ServerState;
+use crate::service::publish::{
+    prepare_publish, publish_error_status, publish_response, LAST_SEQUENCE_METADATA,
+};
+use crate::storage::writer::{PublishResult, WriterError};
+
+type PublishStreamStream =
+    Pin<Box<dyn Stream<Item = Result<PublishStreamResponse, Status>> + Send + 'static>>;
+
+/// Maximum messages of one stream that are queued but not yet acknowledged.
+///
+/// Once reached, the server stops reading from the stream until the oldest
+/// message is committed, which pushes back on the producer.
+const MAX_IN_FLIGHT: usize = 1000;
+
+/// A message of the stream that is waiting for its outcome.
+struct InFlight {
+    request_id: u64,
+    topic: String,
+    start: Instant,
+    outcome: Outcome,
+}
+
+enum Outcome {
+    /// Queued on the writer.
+    Queued(oneshot::Receiver<Result<PublishResult, WriterError>>),
+    /// Rejected before reaching the writer.
+    Rejected(Status),
+}
+
+/// Handle a PublishStream RPC request.
+///
+/// Acknowledges every message in request order. A rejected message is
+/// reported in its response and does not end the stream.
+#[tracing::instrument(skip(state, request))]
+pub async fn handle_publish_stream(
+    state: &Arc<ServerState>,
+    request: Request<Streaming<PublishStreamRequest>>,
+) -> Result<Response<PublishStreamStream>, Status> {
+    let principal = Principal::from_request(&request);
+    let inbound = request.into_inner();
+
+    let (in_flight_tx, in_flight_rx) = mpsc::channel(MAX_IN_FLIGHT);
+    let (tx, rx) = mpsc::channel(100);
+
+    tokio::spawn(read_requests(
+        Arc::clone(state),
+        principal,
+        inbound,
+        in_flight_tx,
+    ));
+    tokio::spawn(send_acks(in_flight_rx, tx));
+
+    Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+}
+
+/// Queue each incoming message on the writer until the client closes its side.
+async fn read_requests(
+    state: Arc<ServerState>,
+    principal: Option<Principal>,
+    mut inbound: Streaming<PublishStreamRequest>,
+    in_flight: mpsc::Sender<InFlight>,
+) {
+    loop {
+        let req = match inbound.message().await {
+            Ok(Some(req)) => req,
+            Ok(None) => break,
+            Err(e) => {
+                tracing::debug!(error = %e, "Publish stream closed by client");
+                break;
+            }
+        };
+
+        let start = Instant::now();
+        let Some(message) = req.message else {
+            let rejected = InFlight {
+                request_id: req.request_id,
+                topic: String::new(),
+                start,
+                outcome: Outcome::Rejected(Status::invalid_argument("message is required")),
+            };
+            if in_flight.send(rejected).await.is_err() {
+                break;
+            }
+            continue;
+        };
+
+        let topic = message.topic.clone();
+        let outcome = match prepare_publish(&state, principal.as_ref(), message) {
+            Ok(prepared) => match state
+                .writer
+                .enqueue_publish(
+                    prepared.topic,
+                    prepared.message,
+                    prepared.expected_last_sequence,
+                )
+                .await
+            {
+                Ok(reply) => Outcome::Queued(reply),
+                Err(e) => Outcome::Rejected(publish_error_status(e)),
+            },
+            Err(status) => Outcome::Rejected(status),
+        };
+
+        let entry = InFlight {
+            request_id: req.request_id,
+            topic,
+            start,
+            outcome,
+        };
+        // Blocks while MAX_IN_FLIGHT messages await their commit
+        if in_flight.send(entry).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Send each message's outcome to the client, in request order.
+async fn send_acks(
+    mut in_flight: mpsc::Receiver<InFlight>,
+    tx: mpsc::Sender<Result<PublishStreamResponse, Status>>,
+) {
+    while let Some(entry) = in_flight.recv().await {
+        let outcome = match entry.outcome {
+            Outcome::Queued(reply) => match reply.await {
+                Ok(Ok(result)) => Ok(result),
+                Ok(Err(e)) => Err(publish_error_status(e)),
+                Err(_) => Err(publish_error_status(WriterError::ChannelClosed)),
+            },
+            Outcome::Rejected(status) => Err(status),
+        };
+
+        let result = match outcome {
+            Ok(result) => {
+                let latency = entry.start.elapsed().as_secs_f64();
+                record_publish(&entry.topic, latency);
+                StreamResult::Ack(publish_response(result))
+            }
+            Err(status) => StreamResult::Error(publish_error(&status)),
+        };
+
+        let response = PublishStreamResponse {
+            request_id: entry.request_id,
+            result: Some(result),
+        };
+        if tx.send(Ok(response)).await.is_err() {
+            // Client went away
+            break;
+        }
+    }
+}
+
+/// Describe a failed publish for its stream response.
+pub(crate) fn publish_error(status: &Status) -> PublishError {
+    PublishError {
+        code: status.code() as i32,
+        message: status.message().to_string(),
+        last_sequence: status
+            .metadata()
+            .get(LAST_SEQUENCE_METADATA)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok()),
+    }
+}
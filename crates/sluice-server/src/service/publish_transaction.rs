@@ -0,0 +1,124 @@
+//! PublishTransaction RPC handler implementation.
+//!
+//! Publishes batches to several topics in a single transaction, e.g. to fan
+//! an event out to an `orders` and an `audit` topic without partial failures.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Instant;
+use tonic::{Request, Response, Status};
+
+use crate::auth::{Action, Principal};
+use crate::proto::sluice::v1::{
+    PublishTransactionRequest, PublishTransactionResponse, TopicMessages, TopicPublishResults,
+};
+use crate::server::ServerState;
+use crate::service::batch_publish::{prepare_batch_messages, proto_results, MAX_BATCH_SIZE};
+use crate::service::publish::{expected_last_sequence, publish_error_status, validate_topic_name};
+use crate::storage::writer::TopicBatch;
+
+/// Handle a PublishTransaction RPC request.
+///
+/// Persists the messages of every topic atomically, or none of them.
+#[tracing::instrument(skip(state, request), fields(topic_count, message_count))]
+pub async fn handle_publish_transaction(
+    state: &Arc<ServerState>,
+    request: Request<PublishTransactionRequest>,
+) -> Result<Response<PublishTransactionResponse>, Status> {
+    let start = Instant::now();
+    let principal = Principal::from_request(&request);
+    let req = request.into_inner();
+
+    if req.topics.is_empty() {
+        return Err(Status::invalid_argument(
+            "transaction must contain at least one topic",
+        ));
+    }
+
+    let message_count: usize = req.topics.iter().map(|topic| topic.messages.len()).sum();
+    let batches = prepare_transaction(state, principal.as_ref(), req.topics)?;
+
+    tracing::Span::current().record("topic_count", batches.len());
+    tracing::Span::current().record("message_count", message_count);
+
+    let topics: Vec<String> = batches.iter().map(|batch| batch.topic.clone()).collect();
+
+    // Submit to writer
+    let (results, timestamp) = state
+        .writer
+        .publish_transaction(batches)
+        .await
+        .map_err(publish_error_status)?;
+
+    let latency = start.elapsed().as_secs_f64();
+
+    tracing::debug!(
+        topics = ?topics,
+        message_count,
+        latency_ms = latency * 1000.0,
+        "Transaction published"
+    );
+
+    Ok(Response::new(PublishTransactionResponse {
+        topics: topics
+            .into_iter()
+            .zip(results)
+            .map(|(topic, results)| TopicPublishResults {
+                topic,
+                results: proto_results(results),
+            })
+            .collect(),
+        timestamp,
+    }))
+}
+
+/// Validate and authorize the batches of a transaction and build their
+/// writer input.
+#[allow(clippy::result_large_err)]
+pub(crate) fn prepare_transaction(
+    state: &ServerState,
+    principal: Option<&Principal>,
+    topics: Vec<TopicMessages>,
+) -> Result<Vec<TopicBatch>, Status> {
+    // The limit of a batch applies to the whole transaction
+    let message_count: usize = topics.iter().map(|topic| topic.messages.len()).sum();
+    if message_count > MAX_BATCH_SIZE {
+        return Err(Status::invalid_argument(format!(
+            "transaction too large: {message_count} messages (max {MAX_BATCH_SIZE} messages)"
+        )));
+    }
+
+    let mut seen = HashSet::new();
+    for topic in &topics {
+        validate_topic_name(&topic.topic)?;
+
+        if !seen.insert(topic.topic.as_str()) {
+            return Err(Status::invalid_argument(format!(
+                "topic '{}' appears more than once",
+                topic.topic
+            )));
+        }
+
+        if topic.messages.is_empty() {
+            return Err(Status::invalid_argument(format!(
+                "topic '{}' must have at least one message",
+                topic.topic
+            )));
+        }
+
+        state
+            .auth
+            .authorize(principal, Action::Publish, &topic.topic)?;
+    }
+
+    // Prepare messages for writer
+    let mut batches = Vec::with_capacity(topics.len());
+    for topic in topics {
+        batches.push(TopicBatch {
+            expected_last_sequence: expected_last_sequence(topic.expected_last_sequence)?,
+            messages: prepare_batch_messages(topic.messages)?,
+            topic: topic.topic,
+        });
+    }
+    Ok(batches)
+}
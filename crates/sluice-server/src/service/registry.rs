@@ -2,26 +2,118 @@
 //!
 //! Tracks active consumers per (topic_id, consumer_group) to support
 //! seamless takeover when a new consumer connects with the same group.
+//! Consumers in shared mode join the group instead and compete for its
+//! messages through a common `GroupDelivery`. On partitioned topics a
+//! connection only conflicts with connections that consume one of the
+//! same partitions. Admin operations use the registry to disconnect every
+//! consumer of a topic they change underneath.
 
 use std::collections::HashMap;
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use tokio::sync::oneshot;
+use tonic::Status;
+
+use crate::flow::group::{GroupDelivery, MemberId};
 
 /// Key for identifying a unique consumer group connection.
 #[derive(Clone, Debug, Hash, Eq, PartialEq)]
 pub struct ConsumerGroupKey {
     pub topic_id: i64,
     pub consumer_group: String,
+    /// Partitions consumed, sorted. Empty means every partition.
+    pub partitions: Vec<u32>,
+}
+
+impl ConsumerGroupKey {
+    /// Returns true if both keys consume a common partition for the same group.
+    fn overlaps(&self, other: &ConsumerGroupKey) -> bool {
+        self.topic_id == other.topic_id
+            && self.consumer_group == other.consumer_group
+            && (self.partitions.is_empty()
+                || other.partitions.is_empty()
+                || self.partitions.iter().any(|p| other.partitions.contains(p)))
+    }
+}
+
+/// Identifies one registered connection.
+///
+/// Shared members use their `MemberId`, which is drawn from the same
+/// sequence.
+pub type ConnectionId = u64;
+
+/// Connections registered for one consumer group.
+#[derive(Debug)]
+enum GroupConnections {
+    /// A single connection that is replaced on takeover.
+    Exclusive {
+        connection_id: ConnectionId,
+        tx: oneshot::Sender<Status>,
+    },
+    /// Competing connections sharing one delivery state.
+    Shared {
+        delivery: Arc<GroupDelivery>,
+        members: HashMap<MemberId, oneshot::Sender<Status>>,
+    },
+}
+
+impl GroupConnections {
+    /// Signal every connection in the group to terminate with `status`.
+    fn terminate(self, key: &ConsumerGroupKey, status: &Status) {
+        tracing::info!(
+            topic_id = key.topic_id,
+            consumer_group = %key.consumer_group,
+            reason = status.message(),
+            "Terminating consumer connection"
+        );
+        // Send termination signal (ignore if receiver already dropped)
+        match self {
+            GroupConnections::Exclusive { tx, .. } => {
+                let _ = tx.send(status.clone());
+            }
+            GroupConnections::Shared { members, .. } => {
+                for tx in members.into_values() {
+                    let _ = tx.send(status.clone());
+                }
+            }
+        }
+    }
+}
+
+/// An exclusive connection's registration.
+#[derive(Debug)]
+pub struct ExclusiveRegistration {
+    /// Identifies this connection, so that its cleanup does not remove
+    /// the connection that took over from it.
+    pub connection_id: ConnectionId,
+    /// Signaled with the status to end the stream with when this
+    /// connection should be terminated.
+    pub cancel_rx: oneshot::Receiver<Status>,
+}
+
+/// A connection's place in a shared consumer group.
+#[derive(Debug)]
+pub struct SharedMembership {
+    /// Identifies this connection within the group.
+    pub member_id: MemberId,
+    /// Delivery state common to all members.
+    pub delivery: Arc<GroupDelivery>,
+    /// Signaled with the status to end the stream with when this
+    /// connection should be terminated.
+    pub cancel_rx: oneshot::Receiver<Status>,
 }
 
 /// Registry tracking active consumer connections.
 ///
-/// When a new consumer connects to an existing consumer group,
-/// the prior connection is terminated with ABORTED status.
+/// When a new exclusive consumer connects to an existing consumer group,
+/// the prior connections are terminated with ABORTED status. A shared
+/// consumer joins an existing shared group, or takes over an exclusive one.
 #[derive(Debug, Default)]
 pub struct ConnectionRegistry {
-    /// Map of active connections: key -> cancellation sender
-    active: Mutex<HashMap<ConsumerGroupKey, oneshot::Sender<()>>>,
+    /// Map of active connections: key -> cancellation senders
+    active: Mutex<HashMap<ConsumerGroupKey, GroupConnections>>,
+    /// Source of connection and shared member IDs.
+    next_member_id: AtomicU64,
 }
 
 impl ConnectionRegistry {
@@ -29,56 +121,207 @@ impl ConnectionRegistry {
     pub fn new() -> Self {
         Self {
             active: Mutex::new(HashMap::new()),
+            next_member_id: AtomicU64::new(1),
         }
     }
 
     /// Register a new consumer connection.
     ///
-    /// Returns a receiver that will be signaled when this connection
-    /// should be terminated (due to takeover by another consumer).
+    /// The returned receiver will be signaled when this connection should
+    /// be terminated (due to takeover by another consumer, or an admin
+    /// operation), with the status to end the stream with.
     ///
-    /// If there's already an active connection for this consumer group,
-    /// it will be terminated immediately.
-    pub fn register(&self, key: ConsumerGroupKey) -> oneshot::Receiver<()> {
-        let (tx, rx) = oneshot::channel();
+    /// If there's already an active connection for this consumer group
+    /// (on any of the same partitions), it will be terminated immediately.
+    pub fn register(&self, key: ConsumerGroupKey) -> ExclusiveRegistration {
+        let (tx, cancel_rx) = oneshot::channel();
+        let connection_id = self.next_member_id.fetch_add(1, Ordering::Relaxed);
 
         let mut active = self.active.lock().unwrap();
 
-        // If there's an existing connection, terminate it
-        if let Some(old_tx) = active.remove(&key) {
-            tracing::info!(
-                topic_id = key.topic_id,
-                consumer_group = %key.consumer_group,
-                "Terminating prior consumer connection (takeover)"
-            );
-            // Send termination signal (ignore if receiver already dropped)
-            let _ = old_tx.send(());
-        }
+        // If there are existing connections, terminate them
+        terminate_overlapping(&mut active, &key);
 
         // Register new connection
-        active.insert(key, tx);
+        active.insert(key, GroupConnections::Exclusive { connection_id, tx });
+
+        ExclusiveRegistration {
+            connection_id,
+            cancel_rx,
+        }
+    }
+
+    /// Delivery state of the shared group for `key`, if one is active.
+    pub fn shared_delivery(&self, key: &ConsumerGroupKey) -> Option<Arc<GroupDelivery>> {
+        let active = self.active.lock().unwrap();
+        match active.get(key) {
+            Some(GroupConnections::Shared { delivery, .. }) => Some(Arc::clone(delivery)),
+            _ => None,
+        }
+    }
+
+    /// Join the shared group for `key`.
+    ///
+    /// If the group is already shared on the same partitions the connection
+    /// joins it and `delivery` is discarded in favor of the group's existing
+    /// state. Otherwise any connection on those partitions is terminated
+    /// and a new shared group is started with `delivery`.
+    pub fn join_shared(
+        &self,
+        key: ConsumerGroupKey,
+        delivery: Arc<GroupDelivery>,
+    ) -> SharedMembership {
+        let (tx, cancel_rx) = oneshot::channel();
+        let member_id = self.next_member_id.fetch_add(1, Ordering::Relaxed);
+
+        let mut active = self.active.lock().unwrap();
+
+        if let Some(GroupConnections::Shared { delivery, members }) = active.get_mut(&key) {
+            members.insert(member_id, tx);
+            return SharedMembership {
+                member_id,
+                delivery: Arc::clone(delivery),
+                cancel_rx,
+            };
+        }
+
+        terminate_overlapping(&mut active, &key);
+
+        active.insert(
+            key,
+            GroupConnections::Shared {
+                delivery: Arc::clone(&delivery),
+                members: HashMap::from([(member_id, tx)]),
+            },
+        );
 
-        rx
+        SharedMembership {
+            member_id,
+            delivery,
+            cancel_rx,
+        }
     }
 
     /// Unregister a consumer connection.
     ///
-    /// Called when a consumer disconnects normally.
-    pub fn unregister(&self, key: &ConsumerGroupKey) {
+    /// Called when an exclusive consumer disconnects. Does nothing if
+    /// another connection has taken over the group since.
+    pub fn unregister(&self, key: &ConsumerGroupKey, connection_id: ConnectionId) {
         let mut active = self.active.lock().unwrap();
-        active.remove(key);
+        if let Some(GroupConnections::Exclusive {
+            connection_id: registered,
+            ..
+        }) = active.get(key)
+        {
+            if *registered == connection_id {
+                active.remove(key);
+            }
+        }
+    }
+
+    /// Remove a member from a shared group.
+    ///
+    /// The group's delivery state is dropped with its last member.
+    pub fn leave_shared(&self, key: &ConsumerGroupKey, member_id: MemberId) {
+        let mut active = self.active.lock().unwrap();
+        if let Some(GroupConnections::Shared { members, .. }) = active.get_mut(key) {
+            members.remove(&member_id);
+            if members.is_empty() {
+                active.remove(key);
+            }
+        }
+    }
+
+    /// Returns true if a consumer of the group is connected.
+    pub fn is_connected(&self, topic_id: i64, consumer_group: &str) -> bool {
+        self.active
+            .lock()
+            .unwrap()
+            .keys()
+            .any(|key| key.topic_id == topic_id && key.consumer_group == consumer_group)
+    }
+
+    /// Terminate every connection consuming `topic_id` with `status`.
+    ///
+    /// Returns the number of connection groups that were terminated.
+    pub fn terminate_topic(&self, topic_id: i64, status: Status) -> usize {
+        self.terminate_matching(|key| key.topic_id == topic_id, &status)
+    }
+
+    /// Terminate every connection of a consumer group with `status`.
+    ///
+    /// Returns the number of connection groups that were terminated; a
+    /// group consumed by partition has one per partition set.
+    pub fn terminate_group(&self, topic_id: i64, consumer_group: &str, status: Status) -> usize {
+        self.terminate_matching(
+            |key| key.topic_id == topic_id && key.consumer_group == consumer_group,
+            &status,
+        )
+    }
+
+    fn terminate_matching(
+        &self,
+        matches: impl Fn(&ConsumerGroupKey) -> bool,
+        status: &Status,
+    ) -> usize {
+        let mut active = self.active.lock().unwrap();
+        let keys: Vec<ConsumerGroupKey> =
+            active.keys().filter(|key| matches(key)).cloned().collect();
+
+        for key in &keys {
+            if let Some(group) = active.remove(key) {
+                group.terminate(key, status);
+            }
+        }
+        keys.len()
     }
 
     /// Get the number of active connections.
     #[cfg(test)]
     pub fn active_count(&self) -> usize {
-        self.active.lock().unwrap().len()
+        self.active
+            .lock()
+            .unwrap()
+            .values()
+            .map(|group| match group {
+                GroupConnections::Exclusive { .. } => 1,
+                GroupConnections::Shared { members, .. } => members.len(),
+            })
+            .sum()
+    }
+}
+
+/// Terminate every connection that consumes a partition `key` also consumes.
+fn terminate_overlapping(
+    active: &mut HashMap<ConsumerGroupKey, GroupConnections>,
+    key: &ConsumerGroupKey,
+) {
+    let overlapping: Vec<ConsumerGroupKey> = active
+        .keys()
+        .filter(|other| other.overlaps(key))
+        .cloned()
+        .collect();
+
+    let status = Status::aborted("consumer group takeover");
+    for other in overlapping {
+        if let Some(old) = active.remove(&other) {
+            old.terminate(&other, &status);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::flow::group::DeliveryState;
+    use crate::flow::redelivery::RedeliveryQueue;
+
+    fn new_delivery() -> Arc<GroupDelivery> {
+        Arc::new(GroupDelivery::new(
+            DeliveryState::new(0, None, RedeliveryQueue::default()),
+            0,
+        ))
+    }
 
     #[test]
     fn test_register_unregister() {
@@ -86,28 +329,55 @@ mod tests {
         let key = ConsumerGroupKey {
             topic_id: 1,
             consumer_group: "test".to_string(),
+            partitions: vec![],
         };
 
-        let _rx = registry.register(key.clone());
+        let registration = registry.register(key.clone());
         assert_eq!(registry.active_count(), 1);
 
-        registry.unregister(&key);
+        registry.unregister(&key, registration.connection_id);
         assert_eq!(registry.active_count(), 0);
     }
 
+    #[tokio::test]
+    async fn test_takeover_then_reset() {
+        let registry = ConnectionRegistry::new();
+        let key = ConsumerGroupKey {
+            topic_id: 1,
+            consumer_group: "workers".to_string(),
+            partitions: vec![],
+        };
+
+        let first = registry.register(key.clone());
+        let second = registry.register(key.clone());
+        assert!(first.cancel_rx.await.is_ok());
+
+        // The replaced connection's cleanup leaves the new one registered
+        registry.unregister(&key, first.connection_id);
+        assert!(registry.is_connected(1, "workers"));
+
+        assert_eq!(
+            registry.terminate_group(1, "workers", Status::aborted("consumer group was reset")),
+            1
+        );
+        assert_eq!(second.cancel_rx.await.unwrap().code(), tonic::Code::Aborted);
+        assert!(!registry.is_connected(1, "workers"));
+    }
+
     #[tokio::test]
     async fn test_takeover_signals_prior_connection() {
         let registry = ConnectionRegistry::new();
         let key = ConsumerGroupKey {
             topic_id: 1,
             consumer_group: "workers".to_string(),
+            partitions: vec![],
         };
 
         // First consumer registers
-        let rx1 = registry.register(key.clone());
+        let rx1 = registry.register(key.clone()).cancel_rx;
 
         // Second consumer registers with same key (takeover)
-        let _rx2 = registry.register(key.clone());
+        let _rx2 = registry.register(key.clone()).cancel_rx;
 
         // First consumer should receive termination signal
         // (recv returns Ok(()) when sender sends, Err when sender dropped)
@@ -126,14 +396,16 @@ mod tests {
         let key1 = ConsumerGroupKey {
             topic_id: 1,
             consumer_group: "group-a".to_string(),
+            partitions: vec![],
         };
         let key2 = ConsumerGroupKey {
             topic_id: 1,
             consumer_group: "group-b".to_string(),
+            partitions: vec![],
         };
 
-        let _rx1 = registry.register(key1.clone());
-        let _rx2 = registry.register(key2.clone());
+        let _rx1 = registry.register(key1.clone()).cancel_rx;
+        let _rx2 = registry.register(key2.clone()).cancel_rx;
 
         assert_eq!(registry.active_count(), 2);
     }
@@ -144,15 +416,142 @@ mod tests {
         let key1 = ConsumerGroupKey {
             topic_id: 1,
             consumer_group: "workers".to_string(),
+            partitions: vec![],
         };
         let key2 = ConsumerGroupKey {
             topic_id: 2,
             consumer_group: "workers".to_string(),
+            partitions: vec![],
+        };
+
+        let _rx1 = registry.register(key1.clone()).cancel_rx;
+        let _rx2 = registry.register(key2.clone()).cancel_rx;
+
+        assert_eq!(registry.active_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_shared_members_join_same_group() {
+        let registry = ConnectionRegistry::new();
+        let key = ConsumerGroupKey {
+            topic_id: 1,
+            consumer_group: "workers".to_string(),
+            partitions: vec![],
         };
 
-        let _rx1 = registry.register(key1.clone());
-        let _rx2 = registry.register(key2.clone());
+        let first = registry.join_shared(key.clone(), new_delivery());
+        let second = registry.join_shared(key.clone(), new_delivery());
 
+        assert_ne!(first.member_id, second.member_id);
+        assert!(Arc::ptr_eq(&first.delivery, &second.delivery));
         assert_eq!(registry.active_count(), 2);
+
+        // Exclusive unregister leaves the shared group alone
+        registry.unregister(&key, first.member_id);
+        assert_eq!(registry.active_count(), 2);
+
+        registry.leave_shared(&key, first.member_id);
+        assert!(registry.shared_delivery(&key).is_some());
+        registry.leave_shared(&key, second.member_id);
+        assert!(registry.shared_delivery(&key).is_none());
+        assert_eq!(registry.active_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_exclusive_takes_over_shared_group() {
+        let registry = ConnectionRegistry::new();
+        let key = ConsumerGroupKey {
+            topic_id: 1,
+            consumer_group: "workers".to_string(),
+            partitions: vec![],
+        };
+
+        let first = registry.join_shared(key.clone(), new_delivery());
+        let second = registry.join_shared(key.clone(), new_delivery());
+        let _rx = registry.register(key.clone()).cancel_rx;
+
+        assert!(first.cancel_rx.await.is_ok());
+        assert!(second.cancel_rx.await.is_ok());
+        assert_eq!(registry.active_count(), 1);
+
+        // A shared consumer takes over the exclusive one in turn
+        let rx = registry.register(key.clone()).cancel_rx;
+        let _shared = registry.join_shared(key.clone(), new_delivery());
+        assert!(rx.await.is_ok());
+        assert_eq!(registry.active_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_terminate_topic() {
+        let registry = ConnectionRegistry::new();
+        let key = |topic_id: i64, consumer_group: &str| ConsumerGroupKey {
+            topic_id,
+            consumer_group: consumer_group.to_string(),
+            partitions: vec![],
+        };
+
+        let rx_a = registry.register(key(1, "a")).cancel_rx;
+        let shared = registry.join_shared(key(1, "b"), new_delivery());
+        let mut rx_other = registry.register(key(2, "a")).cancel_rx;
+
+        assert_eq!(
+            registry.terminate_topic(1, Status::not_found("topic deleted")),
+            2
+        );
+        assert_eq!(rx_a.await.unwrap().code(), tonic::Code::NotFound);
+        assert_eq!(shared.cancel_rx.await.unwrap().message(), "topic deleted");
+        assert!(rx_other.try_recv().is_err());
+        assert_eq!(registry.active_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_terminate_group() {
+        let registry = ConnectionRegistry::new();
+        let key = |consumer_group: &str, partitions: Vec<u32>| ConsumerGroupKey {
+            topic_id: 1,
+            consumer_group: consumer_group.to_string(),
+            partitions,
+        };
+
+        let rx0 = registry.register(key("workers", vec![0])).cancel_rx;
+        let rx1 = registry.register(key("workers", vec![1])).cancel_rx;
+        let mut rx_other = registry.register(key("audit", vec![])).cancel_rx;
+        assert!(registry.is_connected(1, "workers"));
+
+        assert_eq!(
+            registry.terminate_group(1, "workers", Status::aborted("consumer group was reset")),
+            2
+        );
+        assert_eq!(rx0.await.unwrap().code(), tonic::Code::Aborted);
+        assert_eq!(rx1.await.unwrap().code(), tonic::Code::Aborted);
+        assert!(rx_other.try_recv().is_err());
+        assert!(!registry.is_connected(1, "workers"));
+        assert!(registry.is_connected(1, "audit"));
+    }
+
+    #[tokio::test]
+    async fn test_disjoint_partitions_run_side_by_side() {
+        let registry = ConnectionRegistry::new();
+        let key = |partitions: Vec<u32>| ConsumerGroupKey {
+            topic_id: 1,
+            consumer_group: "workers".to_string(),
+            partitions,
+        };
+
+        let mut rx0 = registry.register(key(vec![0])).cancel_rx;
+        let rx12 = registry.register(key(vec![1, 2])).cancel_rx;
+        assert!(rx0.try_recv().is_err());
+        assert_eq!(registry.active_count(), 2);
+
+        // Overlapping partitions take over that connection and leave the other
+        let _rx2 = registry.register(key(vec![2, 3])).cancel_rx;
+        assert!(rx12.await.is_ok());
+        assert!(rx0.try_recv().is_err());
+        assert_eq!(registry.active_count(), 2);
+
+        // A connection on every partition takes over the rest
+        let _rx_all = registry.register(key(vec![])).cancel_rx;
+        assert!(rx0.await.is_ok());
+        assert_eq!(registry.active_count(), 1);
     }
 }
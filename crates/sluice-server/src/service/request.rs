@@ -0,0 +1,368 @@
+//! Request RPC handler implementation.
+//!
+//! Request/reply on top of topics. Each stream gets an inbox topic of its
+//! own. Requests are published with a `reply_to` attribute naming the inbox
+//! and a unique `correlation_id`; a responder answers by publishing to the
+//! inbox with the same `correlation_id`. The inbox is deleted when the
+//! stream ends, and inboxes left behind by a previous run are deleted at
+//! startup. The inbox prefix is reserved: CreateTopic rejects it and a
+//! publish never auto-creates an inbox, so a late reply fails with
+//! NOT_FOUND instead of bringing the inbox back.
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tokio::time::{sleep_until, Instant};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::Stream;
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::auth::Principal;
+use crate::generate_message_id;
+use crate::observability::metrics::record_publish;
+use crate::proto::sluice::v1::request_downstream::Result as RequestResult;
+use crate::proto::sluice::v1::{MessageDelivery, RequestDownstream, RequestUpstream};
+use crate::server::ServerState;
+use crate::service::publish::{prepare_publish, publish_error_status};
+use crate::service::publish_stream::publish_error;
+use crate::service::subscribe::message_delivery;
+use crate::service::topics::writer_error_status;
+use crate::storage::reader::ReaderPool;
+use crate::storage::schema::{
+    fetch_messages_from_seq, is_inbox, Topic, TopicSettings, INBOX_PREFIX,
+};
+use crate::storage::writer::{PublishResult, WriterError, WriterHandle};
+
+type RequestStream =
+    Pin<Box<dyn Stream<Item = Result<RequestDownstream, Status>> + Send + 'static>>;
+
+/// Attribute naming the topic a reply should be published to.
+pub const REPLY_TO_ATTRIBUTE: &str = "reply_to";
+
+/// Attribute matching a reply to its request.
+pub const CORRELATION_ID_ATTRIBUTE: &str = "correlation_id";
+
+/// How long a request waits for its reply when it sets no timeout.
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Maximum inbox messages read per query.
+const INBOX_FETCH_LIMIT: i64 = 100;
+
+/// A request waiting for its reply.
+struct PendingRequest {
+    request_id: u64,
+    deadline: Instant,
+    timeout: Duration,
+}
+
+/// Handle a Request RPC request.
+///
+/// Creates the stream's inbox before answering, so a failure to create it
+/// fails the RPC.
+#[tracing::instrument(skip(state, request))]
+pub async fn handle_request(
+    state: &Arc<ServerState>,
+    request: Request<Streaming<RequestUpstream>>,
+) -> Result<Response<RequestStream>, Status> {
+    let principal = Principal::from_request(&request);
+    let inbound = request.into_inner();
+
+    let name = format!("{INBOX_PREFIX}{}", generate_message_id());
+    let inbox = state
+        .writer
+        .create_topic(name.clone(), 1, TopicSettings::default())
+        .await
+        .map_err(writer_error_status)?
+        .ok_or_else(|| Status::internal(format!("inbox '{name}' already exists")))?;
+    tracing::debug!(inbox = %inbox.name, "Request inbox created");
+
+    let (tx, rx) = mpsc::channel(100);
+    let requests = Requests {
+        state: Arc::clone(state),
+        principal,
+        inbox,
+        cursor: 0,
+        pending: HashMap::new(),
+        publishes: JoinSet::new(),
+        tx,
+    };
+    tokio::spawn(requests.run(inbound));
+
+    Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+}
+
+/// The requests of one stream and its inbox.
+struct Requests {
+    state: Arc<ServerState>,
+    principal: Option<Principal>,
+    inbox: Topic,
+    /// Last inbox sequence read.
+    cursor: i64,
+    /// Requests awaiting their reply, by correlation ID.
+    pending: HashMap<String, PendingRequest>,
+    /// Publishes of requests awaiting their commit.
+    publishes: JoinSet<(String, Result<PublishResult, Status>)>,
+    tx: mpsc::Sender<Result<RequestDownstream, Status>>,
+}
+
+impl Requests {
+    /// Publish requests and answer them until the client goes away, then
+    /// delete the inbox.
+    ///
+    /// Once the client closes its side, the stream ends after the replies
+    /// or timeouts of the requests still pending.
+    async fn run(mut self, mut inbound: Streaming<RequestUpstream>) {
+        let mut notifications = self.state.notify_bus.subscribe();
+        let mut reading = true;
+
+        loop {
+            if !reading && self.pending.is_empty() {
+                break;
+            }
+            let next_deadline = self.pending.values().map(|p| p.deadline).min();
+
+            let result = tokio::select! {
+                message = inbound.message(), if reading => match message {
+                    Ok(Some(request)) => self.send(request).await,
+                    Ok(None) => {
+                        reading = false;
+                        Ok(())
+                    }
+                    Err(e) => {
+                        tracing::debug!(error = %e, "Request stream closed by client");
+                        break;
+                    }
+                },
+                notification = notifications.recv() => match notification {
+                    Ok(n) if n.topic_id != self.inbox.id => Ok(()),
+                    Ok(_) | Err(RecvError::Lagged(_)) => self.read_inbox().await,
+                    Err(RecvError::Closed) => break,
+                },
+                Some(joined) = self.publishes.join_next() => match joined {
+                    Ok((correlation_id, result)) => self.published(correlation_id, result).await,
+                    Err(e) => Err(Status::internal(format!("publish task failed: {e}"))),
+                },
+                _ = sleep_until(next_deadline.unwrap_or_else(Instant::now)),
+                    if next_deadline.is_some() => self.expire().await,
+                _ = self.tx.closed() => break,
+            };
+
+            if let Err(status) = result {
+                if status.code() != tonic::Code::Cancelled {
+                    tracing::warn!(error = %status, "Request stream failed");
+                    let _ = self.tx.send(Err(status)).await;
+                }
+                break;
+            }
+        }
+
+        self.publishes.abort_all();
+        remove_inbox(&self.state, &self.inbox.name).await;
+    }
+
+    /// Publish a request with the inbox as its `reply_to`.
+    async fn send(&mut self, request: RequestUpstream) -> Result<(), Status> {
+        let Some(mut message) = request.message else {
+            let status = Status::invalid_argument("message is required");
+            return self.respond(request.request_id, Err(status)).await;
+        };
+
+        let correlation_id = generate_message_id();
+        message
+            .attributes
+            .insert(REPLY_TO_ATTRIBUTE.to_string(), self.inbox.name.clone());
+        message
+            .attributes
+            .insert(CORRELATION_ID_ATTRIBUTE.to_string(), correlation_id.clone());
+
+        let topic = message.topic.clone();
+        let prepared = match prepare_publish(&self.state, self.principal.as_ref(), message) {
+            Ok(prepared) => prepared,
+            Err(status) => return self.respond(request.request_id, Err(status)).await,
+        };
+        let reply = match self
+            .state
+            .writer
+            .enqueue_publish(
+                prepared.topic,
+                prepared.message,
+                prepared.expected_last_sequence,
+            )
+            .await
+        {
+            Ok(reply) => reply,
+            Err(e) => {
+                let status = publish_error_status(e);
+                return self.respond(request.request_id, Err(status)).await;
+            }
+        };
+
+        // Registered before the commit so that a fast reply is not missed
+        let timeout = match request.timeout_ms {
+            0 => DEFAULT_REQUEST_TIMEOUT,
+            ms => Duration::from_millis(u64::from(ms)),
+        };
+        self.pending.insert(
+            correlation_id.clone(),
+            PendingRequest {
+                request_id: request.request_id,
+                deadline: Instant::now() + timeout,
+                timeout,
+            },
+        );
+
+        let start = std::time::Instant::now();
+        self.publishes.spawn(async move {
+            let result = match reply.await {
+                Ok(Ok(result)) => {
+                    record_publish(&topic, start.elapsed().as_secs_f64());
+                    Ok(result)
+                }
+                Ok(Err(e)) => Err(publish_error_status(e)),
+                Err(_) => Err(publish_error_status(WriterError::ChannelClosed)),
+            };
+            (correlation_id, result)
+        });
+        Ok(())
+    }
+
+    /// Fail a request whose publish was not committed.
+    async fn published(
+        &mut self,
+        correlation_id: String,
+        result: Result<PublishResult, Status>,
+    ) -> Result<(), Status> {
+        let Err(status) = result else {
+            return Ok(());
+        };
+        match self.pending.remove(&correlation_id) {
+            Some(pending) => self.respond(pending.request_id, Err(status)).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Answer the pending requests that have replies in the inbox.
+    ///
+    /// Replies without a pending request, such as a second reply or one
+    /// that arrived after its timeout, are dropped.
+    async fn read_inbox(&mut self) -> Result<(), Status> {
+        loop {
+            let messages = {
+                let conn = self
+                    .state
+                    .reader_pool
+                    .get()
+                    .map_err(|e| Status::internal(format!("database error: {e}")))?;
+                fetch_messages_from_seq(&conn, self.inbox.id, &[], self.cursor, INBOX_FETCH_LIMIT)
+                    .map_err(|e| Status::internal(format!("database error: {e}")))?
+            };
+            let count = messages.len() as i64;
+
+            for msg in messages {
+                self.cursor = msg.global_seq;
+                let reply = message_delivery(&self.inbox.name, msg, 1);
+                let pending = reply
+                    .attributes
+                    .get(CORRELATION_ID_ATTRIBUTE)
+                    .and_then(|id| self.pending.remove(id));
+                match pending {
+                    Some(pending) => self.respond(pending.request_id, Ok(reply)).await?,
+                    None => tracing::debug!(
+                        inbox = %self.inbox.name,
+                        message_id = %reply.message_id,
+                        "Dropping reply without a pending request"
+                    ),
+                }
+            }
+
+            if count < INBOX_FETCH_LIMIT {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Fail the requests whose timeout has passed.
+    async fn expire(&mut self) -> Result<(), Status> {
+        let now = Instant::now();
+        let expired: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| pending.deadline <= now)
+            .map(|(correlation_id, _)| correlation_id.clone())
+            .collect();
+
+        for correlation_id in expired {
+            if let Some(pending) = self.pending.remove(&correlation_id) {
+                let status = Status::deadline_exceeded(format!(
+                    "no reply within {} ms",
+                    pending.timeout.as_millis()
+                ));
+                self.respond(pending.request_id, Err(status)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Send the outcome of a request to the client.
+    async fn respond(
+        &self,
+        request_id: u64,
+        outcome: Result<MessageDelivery, Status>,
+    ) -> Result<(), Status> {
+        let result = match outcome {
+            Ok(reply) => RequestResult::Reply(reply),
+            Err(status) => RequestResult::Error(publish_error(&status)),
+        };
+        let response = RequestDownstream {
+            request_id,
+            result: Some(result),
+        };
+        self.tx
+            .send(Ok(response))
+            .await
+            .map_err(|_| Status::cancelled("client disconnected"))
+    }
+}
+
+/// Delete an inbox topic and disconnect anyone subscribed to it.
+async fn remove_inbox(state: &ServerState, name: &str) {
+    match state.writer.delete_topic(name.to_string()).await {
+        Ok(Some((topic, _))) => {
+            state.connection_registry.terminate_topic(
+                topic.id,
+                Status::not_found(format!("topic '{}' was deleted", topic.name)),
+            );
+            tracing::debug!(inbox = %name, "Request inbox deleted");
+        }
+        Ok(None) => {}
+        Err(e) => tracing::warn!(inbox = %name, error = %e, "Failed to delete request inbox"),
+    }
+}
+
+/// Delete the inboxes of request streams that did not end cleanly, e.g.
+/// because the server stopped.
+///
+/// Returns the number of inboxes deleted.
+pub async fn remove_stale_inboxes(
+    writer: &WriterHandle,
+    reader_pool: &ReaderPool,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let inboxes: Vec<String> = reader_pool
+        .list_topics()?
+        .into_iter()
+        .map(|topic| topic.name)
+        .filter(|name| is_inbox(name))
+        .collect();
+
+    let mut deleted = 0;
+    for name in inboxes {
+        if writer.delete_topic(name).await?.is_some() {
+            deleted += 1;
+        }
+    }
+    Ok(deleted)
+}
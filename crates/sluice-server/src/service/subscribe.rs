@@ -2,39 +2,70 @@
 //!
 //! Handles bidirectional streaming for message consumption with credit-based flow control.
 
+use std::collections::HashMap;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
 use tokio_stream::wrappers::ReceiverStream;
-use tokio_stream::Stream;
+use tokio_stream::{Stream, StreamExt};
 use tonic::{Request, Response, Status, Streaming};
 
+use crate::auth::{Action, Principal};
+use crate::flow::ack::{AckOutcome, AckTracker};
 use crate::flow::credit::CreditBalance;
+use crate::flow::filter::Filter;
+use crate::flow::group::{DeliveryState, GroupDelivery, MemberId};
+use crate::flow::redelivery::RedeliveryQueue;
 use crate::generate_message_id;
-use crate::observability::metrics::{record_backpressure, record_subscription_lag};
+use crate::now_millis;
+use crate::observability::metrics::{
+    record_ack_deadline_expired, record_backpressure, record_message_expired, record_nack,
+    record_subscription_lag,
+};
 use crate::proto::sluice::v1::subscribe_downstream::Response as DownstreamResponse;
 use crate::proto::sluice::v1::subscribe_upstream::Request as UpstreamRequest;
 use crate::proto::sluice::v1::{
-    Heartbeat, InitialPosition, MessageDelivery, SubscribeDownstream, SubscribeUpstream,
+    AckAndPublish, AckAndPublishResult, AckMode, Heartbeat, InitialPosition, MessageDelivery,
+    ModifyAckDeadline, Nack, SubscribeDownstream, SubscribeUpstream, SubscriptionInit,
+    SubscriptionMode, TopicPublishResults,
 };
 use crate::server::ServerState;
+use crate::service::batch_publish::proto_results;
+use crate::service::dead_letter::{dead_letter, DeadLetterPolicy};
+use crate::service::publish::publish_error_status;
+use crate::service::publish_stream::publish_error;
+use crate::service::publish_transaction::prepare_transaction;
+use crate::service::registry::ConnectionId;
+use crate::service::subscribe_pattern::{start_pattern_subscription, TopicPattern};
+use crate::service::topics::writer_error_status;
 use crate::service::ConsumerGroupKey;
 use crate::storage::schema::{
-    fetch_messages_from_seq, get_message_seq_by_id, get_topic_by_name, get_topic_max_seq,
+    fetch_messages_by_priority, fetch_messages_from_seq, fetch_seqs_from_seq,
+    first_seq_at_or_after, get_delivery_failures, get_message_by_seq, get_message_seq_by_id,
+    get_partition_cursors, get_subscription_acks, get_topic_by_name, get_topic_max_seq,
+    get_topic_settings, Message,
 };
+use crate::storage::writer::{GroupAck, TopicBatch};
 
 type SubscribeStream =
     Pin<Box<dyn Stream<Item = Result<SubscribeDownstream, Status>> + Send + 'static>>;
 
+/// Sequences read per query when moving the read position of a priority
+/// subscription.
+const READ_SCAN_LIMIT: i64 = 1000;
+
 /// Handle a Subscribe RPC request.
 ///
-/// Establishes a bidirectional stream for message consumption.
+/// Establishes a bidirectional stream for message consumption. A topic
+/// pattern such as `orders.*` consumes every matching topic on one stream.
 #[tracing::instrument(skip(state, request))]
 pub async fn handle_subscribe(
     state: &Arc<ServerState>,
     request: Request<Streaming<SubscribeUpstream>>,
 ) -> Result<Response<SubscribeStream>, Status> {
+    let principal = Principal::from_request(&request);
     let mut inbound = request.into_inner();
 
     // Wait for SubscriptionInit as first message
@@ -59,37 +90,189 @@ pub async fn handle_subscribe(
             "topic name exceeds 255 characters",
         ));
     }
-    // Only allow alphanumeric, dash, underscore, and dot
-    if !init
-        .topic
-        .chars()
-        .all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.')
-    {
-        return Err(Status::invalid_argument(
-            "topic name contains invalid characters (only alphanumeric, dash, underscore, dot allowed)",
-        ));
+    let pattern = if TopicPattern::is_pattern(&init.topic) {
+        Some(TopicPattern::parse(&init.topic)?)
+    } else {
+        // Only allow alphanumeric, dash, underscore, and dot
+        if !init
+            .topic
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.')
+        {
+            return Err(Status::invalid_argument(
+                "topic name contains invalid characters (only alphanumeric, dash, underscore, dot allowed)",
+            ));
+        }
+        None
+    };
+
+    let settings = SubscribeSettings::from_init(init, principal)?;
+
+    // Create response channel
+    let (tx, rx) = mpsc::channel(100);
+
+    match pattern {
+        Some(pattern) => {
+            start_pattern_subscription(state, settings, pattern, inbound, tx).await?;
+        }
+        None => {
+            let topic =
+                start_topic(state, &settings, &settings.topic, settings.initial_position).await?;
+
+            // Spawn subscription handler task
+            tokio::spawn(run_topic(
+                topic,
+                inbound,
+                tx,
+                Arc::new(CreditBalance::new()),
+            ));
+        }
     }
 
-    // Extract initial_position early before moving other fields
-    let initial_position = init.initial_position();
-    let topic_name = init.topic.clone();
+    Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+}
 
-    let consumer_group = if init.consumer_group.is_empty() {
-        "default".to_string()
-    } else {
-        init.consumer_group
-    };
+/// Settings from `SubscriptionInit` shared by every topic of a subscription.
+pub(crate) struct SubscribeSettings {
+    /// Topic name or pattern.
+    pub topic: String,
+    pub consumer_group: String,
+    pub consumer_id: String,
+    pub initial_position: InitialPosition,
+    pub offset: u64,
+    pub start_timestamp: i64,
+    pub mode: SubscriptionMode,
+    pub ack_mode: AckMode,
+    pub max_delivery_attempts: u32,
+    pub dead_letter_topic: String,
+    pub ack_deadline: Option<Duration>,
+    /// Partitions as requested, before checking them against a topic.
+    pub partitions: Vec<u32>,
+    pub filter: Option<Filter>,
+    pub principal: Option<Principal>,
+}
 
-    let consumer_id = if init.consumer_id.is_empty() {
-        generate_message_id()
-    } else {
-        init.consumer_id
-    };
+impl SubscribeSettings {
+    /// Validate the topic-independent parts of `init`.
+    #[allow(clippy::result_large_err)]
+    fn from_init(init: SubscriptionInit, principal: Option<Principal>) -> Result<Self, Status> {
+        // Extract enum fields early before moving other fields
+        let initial_position = init.initial_position();
+        let mode = init.mode();
+        // Members of a shared group ack the messages they were handed
+        let ack_mode = match mode {
+            SubscriptionMode::Exclusive => init.ack_mode(),
+            SubscriptionMode::Shared => AckMode::Individual,
+        };
+
+        let consumer_group = if init.consumer_group.is_empty() {
+            "default".to_string()
+        } else {
+            init.consumer_group
+        };
+
+        let filter = if init.filter.trim().is_empty() {
+            None
+        } else {
+            Some(Filter::parse(&init.filter).map_err(|e| Status::invalid_argument(e.to_string()))?)
+        };
+
+        let consumer_id = if init.consumer_id.is_empty() {
+            generate_message_id()
+        } else {
+            init.consumer_id
+        };
+
+        Ok(Self {
+            topic: init.topic,
+            consumer_group,
+            consumer_id,
+            initial_position,
+            offset: init.offset,
+            start_timestamp: init.start_timestamp,
+            mode,
+            ack_mode,
+            max_delivery_attempts: init.max_delivery_attempts,
+            dead_letter_topic: init.dead_letter_topic,
+            ack_deadline: (init.ack_deadline_ms > 0)
+                .then(|| Duration::from_millis(u64::from(init.ack_deadline_ms))),
+            partitions: init.partitions,
+            filter,
+            principal,
+        })
+    }
+}
+
+/// A subscription to one topic, ready to run.
+pub(crate) struct TopicSubscription {
+    ctx: SubscriptionContext,
+    consumer_group_key: ConsumerGroupKey,
+    /// Registration to remove when the subscription ends.
+    connection_id: ConnectionId,
+    /// Fires when the connection is taken over or terminated by an admin.
+    cancel_rx: oneshot::Receiver<Status>,
+}
+
+/// Join `topic_name` with `settings`: check access, load the consumer
+/// group's position and register the connection.
+pub(crate) async fn start_topic(
+    state: &Arc<ServerState>,
+    settings: &SubscribeSettings,
+    topic_name: &str,
+    initial_position: InitialPosition,
+) -> Result<TopicSubscription, Status> {
+    let consumer_group = settings.consumer_group.clone();
+    let mode = settings.mode;
+    let ack_mode = settings.ack_mode;
+    let principal = settings.principal.as_ref();
+
+    let dead_letter_policy = DeadLetterPolicy::new(
+        topic_name,
+        settings.max_delivery_attempts,
+        &settings.dead_letter_topic,
+    );
+    if let Some(policy) = &dead_letter_policy {
+        if policy.topic.len() > 255 {
+            return Err(Status::invalid_argument(
+                "dead-letter topic name exceeds 255 characters",
+            ));
+        }
+        if !policy
+            .topic
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.')
+        {
+            return Err(Status::invalid_argument(
+                "dead-letter topic name contains invalid characters (only alphanumeric, dash, underscore, dot allowed)",
+            ));
+        }
+        if policy.topic == topic_name {
+            return Err(Status::invalid_argument(
+                "dead-letter topic must differ from the subscribed topic",
+            ));
+        }
+    }
+
+    state
+        .auth
+        .authorize(principal, Action::Subscribe, topic_name)?;
+    // Dead-lettering republishes on the consumer's behalf
+    if let Some(policy) = &dead_letter_policy {
+        state
+            .auth
+            .authorize(principal, Action::Publish, &policy.topic)?;
+    }
 
     tracing::info!(
         topic = %topic_name,
         consumer_group = %consumer_group,
-        consumer_id = %consumer_id,
+        consumer_id = %settings.consumer_id,
+        mode = ?mode,
+        ack_mode = ?ack_mode,
+        ack_deadline_ms = settings.ack_deadline.map_or(0, |d| d.as_millis() as u64),
+        dead_letter_topic = dead_letter_policy.as_ref().map(|p| p.topic.as_str()),
+        partitions = ?settings.partitions,
+        filter = ?settings.filter,
         "Subscription init"
     );
 
@@ -100,7 +283,7 @@ pub async fn handle_subscribe(
             .get()
             .map_err(|e| Status::internal(format!("database error: {e}")))?;
 
-        get_topic_by_name(&conn, &topic_name)
+        get_topic_by_name(&conn, topic_name)
             .map_err(|e| Status::internal(format!("database error: {e}")))?
             .ok_or_else(|| {
                 if initial_position == InitialPosition::Earliest {
@@ -112,6 +295,20 @@ pub async fn handle_subscribe(
             })?
     };
 
+    // Consuming every partition is the same as not choosing any
+    let mut partitions = settings.partitions.clone();
+    partitions.sort_unstable();
+    partitions.dedup();
+    if let Some(&partition) = partitions.iter().find(|&&p| p >= topic.partition_count) {
+        return Err(Status::invalid_argument(format!(
+            "partition {partition} does not exist (topic has {} partitions)",
+            topic.partition_count
+        )));
+    }
+    if partitions.len() == topic.partition_count as usize {
+        partitions.clear();
+    }
+
     // Get or create subscription via writer (requires write access)
     let subscription = state
         .writer
@@ -119,11 +316,41 @@ pub async fn handle_subscribe(
         .await
         .map_err(|e| Status::internal(format!("database error: {e}")))?;
 
+    // On a partitioned topic, resume from the slowest partition consumed
+    let partition_cursors: Vec<(u32, i64)> = {
+        let conn = state
+            .reader_pool
+            .get()
+            .map_err(|e| Status::internal(format!("database error: {e}")))?;
+        get_partition_cursors(&conn, topic.id, &consumer_group)
+            .map_err(|e| Status::internal(format!("database error: {e}")))?
+            .into_iter()
+            .filter(|(p, _)| partitions.is_empty() || partitions.contains(p))
+            .collect()
+    };
+    // Priority order needs per-message acks, and would break the key order
+    // of a shared group
+    let by_priority = mode == SubscriptionMode::Exclusive && ack_mode == AckMode::Individual && {
+        let conn = state
+            .reader_pool
+            .get()
+            .map_err(|e| Status::internal(format!("database error: {e}")))?;
+        get_topic_settings(&conn, topic.id)
+            .map_err(|e| Status::internal(format!("database error: {e}")))?
+            .priority_delivery
+    };
+
+    let stored_cursor = partition_cursors
+        .iter()
+        .map(|&(_, cursor)| cursor)
+        .min()
+        .unwrap_or(subscription.cursor_seq);
+
     // Determine starting cursor
     let start_cursor = match initial_position {
         InitialPosition::Latest => {
             // Start from max sequence (new messages only)
-            if subscription.cursor_seq == 0 {
+            if stored_cursor == 0 {
                 let conn = state
                     .reader_pool
                     .get()
@@ -131,127 +358,341 @@ pub async fn handle_subscribe(
                 get_topic_max_seq(&conn, topic.id)
                     .map_err(|e| Status::internal(format!("database error: {e}")))?
             } else {
-                subscription.cursor_seq
+                stored_cursor
             }
         }
         InitialPosition::Earliest => {
             // Use existing cursor or start from 0
-            subscription.cursor_seq
+            stored_cursor
         }
         InitialPosition::Offset => {
             // Start from specific offset provided in init message
-            if init.offset == 0 {
+            if settings.offset == 0 {
                 return Err(Status::invalid_argument(
                     "offset must be provided and > 0 for OFFSET position",
                 ));
             }
             // Use the offset as cursor position (will start reading from offset + 1)
-            init.offset as i64
+            settings.offset as i64
+        }
+        InitialPosition::Timestamp => {
+            if settings.start_timestamp <= 0 {
+                return Err(Status::invalid_argument(
+                    "start_timestamp must be provided and > 0 for TIMESTAMP position",
+                ));
+            }
+            // Start just before the first message at or after the timestamp,
+            // or after the newest message if there is none yet
+            let conn = state
+                .reader_pool
+                .get()
+                .map_err(|e| Status::internal(format!("database error: {e}")))?;
+            match first_seq_at_or_after(&conn, topic.id, settings.start_timestamp)
+                .map_err(|e| Status::internal(format!("database error: {e}")))?
+            {
+                Some(seq) => seq - 1,
+                None => get_topic_max_seq(&conn, topic.id)
+                    .map_err(|e| Status::internal(format!("database error: {e}")))?,
+            }
         }
     };
 
-    // Register connection for takeover handling
+    // Register connection for takeover handling, or join the shared group.
+    // Members joining an active shared group continue from its position.
     let consumer_group_key = ConsumerGroupKey {
         topic_id: topic.id,
         consumer_group: consumer_group.clone(),
+        partitions: partitions.clone(),
+    };
+    let (delivery, member_id, connection_id, cancel_rx) = match mode {
+        SubscriptionMode::Exclusive => {
+            let delivery = load_delivery_state(
+                state,
+                topic.id,
+                &consumer_group,
+                start_cursor,
+                ack_mode,
+                by_priority,
+                partition_cursors,
+            )?;
+            let registration = state
+                .connection_registry
+                .register(consumer_group_key.clone());
+            (
+                Arc::new(GroupDelivery::new(delivery, subscription.reset_generation)),
+                None,
+                registration.connection_id,
+                registration.cancel_rx,
+            )
+        }
+        SubscriptionMode::Shared => {
+            let delivery = match state
+                .connection_registry
+                .shared_delivery(&consumer_group_key)
+            {
+                Some(delivery) => delivery,
+                None => Arc::new(GroupDelivery::new(
+                    load_delivery_state(
+                        state,
+                        topic.id,
+                        &consumer_group,
+                        start_cursor,
+                        ack_mode,
+                        false,
+                        partition_cursors,
+                    )?,
+                    subscription.reset_generation,
+                )),
+            };
+            let membership = state
+                .connection_registry
+                .join_shared(consumer_group_key.clone(), delivery);
+            (
+                membership.delivery,
+                Some(membership.member_id),
+                membership.member_id,
+                membership.cancel_rx,
+            )
+        }
     };
-    let cancel_rx = state
-        .connection_registry
-        .register(consumer_group_key.clone());
 
-    // Create response channel
-    let (tx, rx) = mpsc::channel(100);
+    let ctx = SubscriptionContext {
+        state: Arc::clone(state),
+        topic_id: topic.id,
+        topic_name: topic.name,
+        consumer_group,
+        consumer_id: settings.consumer_id.clone(),
+        partitions,
+        member_id,
+        delivery,
+        ack_deadline: settings.ack_deadline,
+        dead_letter_policy,
+        principal: settings.principal.clone(),
+        filter: settings.filter.clone(),
+        by_priority,
+    };
 
-    // Create credit balance
-    let credits = Arc::new(CreditBalance::new());
-
-    // Clone state for spawned task
-    let state = Arc::clone(state);
-    let credits_clone = Arc::clone(&credits);
-
-    // Spawn subscription handler task
-    tokio::spawn(async move {
-        let result = subscription_loop(
-            Arc::clone(&state),
-            inbound,
-            tx,
-            topic.id,
-            topic_name.clone(),
-            consumer_group.clone(),
-            consumer_id,
-            start_cursor,
-            credits_clone,
-            cancel_rx,
-        )
-        .await;
+    Ok(TopicSubscription {
+        ctx,
+        consumer_group_key,
+        connection_id,
+        cancel_rx,
+    })
+}
 
-        // Unregister connection when done
-        state.connection_registry.unregister(&consumer_group_key);
+/// Run a topic subscription until it ends, then leave the consumer group.
+///
+/// `credits` may be shared with the other topics of a pattern subscription.
+pub(crate) async fn run_topic<S>(
+    topic: TopicSubscription,
+    inbound: S,
+    tx: mpsc::Sender<Result<SubscribeDownstream, Status>>,
+    credits: Arc<CreditBalance>,
+) -> Result<(), Status>
+where
+    S: Stream<Item = Result<SubscribeUpstream, Status>> + Unpin,
+{
+    let TopicSubscription {
+        ctx,
+        consumer_group_key,
+        connection_id,
+        cancel_rx,
+    } = topic;
+
+    let state = Arc::clone(&ctx.state);
+    let delivery = Arc::clone(&ctx.delivery);
+    let member_id = ctx.member_id;
+    let result = subscription_loop(ctx, inbound, tx, credits, cancel_rx).await;
+
+    // Unregister connection when done
+    match member_id {
+        Some(member_id) => {
+            state
+                .connection_registry
+                .leave_shared(&consumer_group_key, member_id);
 
-        if let Err(e) = result {
-            tracing::warn!(error = %e, "Subscription ended with error");
+            // Hand this member's unacked messages to the rest of the group
+            let released = delivery.state.lock().await.release_member(member_id);
+            if released > 0 {
+                tracing::debug!(member_id, released, "Requeued messages of departed member");
+                delivery.changed.notify_waiters();
+            }
         }
-    });
+        None => state
+            .connection_registry
+            .unregister(&consumer_group_key, connection_id),
+    }
 
-    Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    if let Err(e) = &result {
+        tracing::warn!(error = %e, "Subscription ended with error");
+    }
+    result
 }
 
-/// Main subscription loop handling bidirectional communication.
-#[allow(clippy::too_many_arguments)]
-async fn subscription_loop(
+/// Build a consumer group's delivery state from storage.
+///
+/// Resumes failed-attempt counts, and in individual mode skips messages
+/// that were acked out of order before the last disconnect. Messages of
+/// partitions whose cursor is ahead of `start_cursor` are skipped too.
+/// `by_priority` reads messages out of sequence order.
+#[allow(clippy::result_large_err)]
+fn load_delivery_state(
+    state: &ServerState,
+    topic_id: i64,
+    consumer_group: &str,
+    start_cursor: i64,
+    ack_mode: AckMode,
+    by_priority: bool,
+    partition_cursors: Vec<(u32, i64)>,
+) -> Result<DeliveryState, Status> {
+    let conn = state
+        .reader_pool
+        .get()
+        .map_err(|e| Status::internal(format!("database error: {e}")))?;
+
+    let failures = get_delivery_failures(&conn, topic_id, consumer_group, start_cursor)
+        .map_err(|e| Status::internal(format!("database error: {e}")))?;
+
+    let ack_tracker = match ack_mode {
+        AckMode::Cumulative => None,
+        AckMode::Individual => {
+            let acked = get_subscription_acks(&conn, topic_id, consumer_group)
+                .map_err(|e| Status::internal(format!("database error: {e}")))?;
+            let tracker = AckTracker::new(start_cursor, acked);
+            Some(if by_priority {
+                tracker.with_unordered_reads()
+            } else {
+                tracker
+            })
+        }
+    };
+
+    Ok(
+        DeliveryState::new(start_cursor, ack_tracker, RedeliveryQueue::new(failures))
+            .with_partition_cursors(partition_cursors),
+    )
+}
+
+/// Settings for one subscription stream, fixed at init.
+struct SubscriptionContext {
     state: Arc<ServerState>,
-    mut inbound: Streaming<SubscribeUpstream>,
-    tx: mpsc::Sender<Result<SubscribeDownstream, Status>>,
     topic_id: i64,
     topic_name: String,
     consumer_group: String,
     consumer_id: String,
-    initial_cursor: i64,
+    /// Partitions consumed, sorted. Empty means every partition.
+    partitions: Vec<u32>,
+    /// This connection's ID in a shared group, `None` when exclusive.
+    member_id: Option<MemberId>,
+    /// Delivery progress, shared with the other members in shared mode.
+    delivery: Arc<GroupDelivery>,
+    /// How long a delivered message may stay unacked before redelivery.
+    ack_deadline: Option<Duration>,
+    dead_letter_policy: Option<DeadLetterPolicy>,
+    /// Authenticated client, for publishes made over the stream.
+    principal: Option<Principal>,
+    /// Attribute filter; messages it rejects are skipped.
+    filter: Option<Filter>,
+    /// Read higher-priority messages first (topic with priority delivery,
+    /// exclusive mode with individual acks).
+    by_priority: bool,
+}
+
+/// Main subscription loop handling bidirectional communication.
+async fn subscription_loop<S>(
+    ctx: SubscriptionContext,
+    mut inbound: S,
+    tx: mpsc::Sender<Result<SubscribeDownstream, Status>>,
     credits: Arc<CreditBalance>,
-    mut cancel_rx: tokio::sync::oneshot::Receiver<()>,
-) -> Result<(), Status> {
-    let mut cursor = initial_cursor;
-    let mut notify_rx = state.notify_bus.subscribe();
+    mut cancel_rx: oneshot::Receiver<Status>,
+) -> Result<(), Status>
+where
+    S: Stream<Item = Result<SubscribeUpstream, Status>> + Unpin,
+{
+    let mut notify_rx = ctx.state.notify_bus.subscribe();
 
     // Heartbeat interval (30 seconds)
     let mut heartbeat_interval = tokio::time::interval(Duration::from_secs(30));
     // Skip first immediate tick
     heartbeat_interval.tick().await;
 
+    let cursor = ctx.delivery.state.lock().await.cursor;
     tracing::debug!(
-        topic_id,
-        consumer_group = %consumer_group,
+        topic_id = ctx.topic_id,
+        consumer_group = %ctx.consumer_group,
+        member_id = ctx.member_id,
         cursor,
         "Subscription loop started"
     );
 
     loop {
+        // Register for wake-ups before claiming messages and reading the
+        // schedule so a requeue or hand-off by another member is not missed
+        let changed = ctx.delivery.changed.notified();
+        tokio::pin!(changed);
+        changed.as_mut().enable();
+
+        // Try to deliver messages if we have credits
+        if credits.available() > 0 {
+            deliver_messages(&ctx, &tx, &credits).await?;
+        }
+
+        let (next_expiry, next_due) = {
+            let delivery = ctx.delivery.state.lock().await;
+            (
+                delivery.redelivery.next_expiry(),
+                delivery.redelivery.next_due(),
+            )
+        };
+
         tokio::select! {
-            // Handle consumer group takeover (cancellation)
-            _ = &mut cancel_rx => {
+            // Handle consumer group takeover or admin changes (cancellation)
+            status = &mut cancel_rx => {
+                let status =
+                    status.unwrap_or_else(|_| Status::aborted("consumer group takeover"));
                 tracing::info!(
-                    consumer_id = %consumer_id,
-                    consumer_group = %consumer_group,
-                    "Connection terminated due to consumer group takeover"
+                    consumer_id = %ctx.consumer_id,
+                    consumer_group = %ctx.consumer_group,
+                    reason = status.message(),
+                    "Connection terminated"
                 );
-                // Send ABORTED status to client
-                let _ = tx.send(Err(Status::aborted("consumer group takeover"))).await;
-                return Err(Status::aborted("consumer group takeover"));
+                // Send the termination status to client
+                let _ = tx.send(Err(status.clone())).await;
+                return Err(status);
             }
 
-            // Handle inbound messages (CreditGrant, Ack)
-            msg = inbound.message() => {
+            // Handle inbound messages (CreditGrant, Ack, Nack, ModifyAckDeadline, AckAndPublish)
+            msg = inbound.next() => {
                 match msg {
-                    Ok(Some(upstream)) => {
+                    Some(Ok(upstream)) => {
                         match upstream.request {
-                            Some(UpstreamRequest::Credit(grant)) => {
-                                if grant.credits > 0 {
-                                    credits.add(grant.credits);
-                                    tracing::debug!(credits = grant.credits, "Credits granted");
-                                }
+                            Some(UpstreamRequest::Credit(grant)) if grant.credits > 0 => {
+                                credits.add(grant.credits);
+                                tracing::debug!(credits = grant.credits, "Credits granted");
                             }
+                            Some(UpstreamRequest::Credit(_)) => {}
                             Some(UpstreamRequest::Ack(ack)) => {
-                                handle_ack(&state, topic_id, &consumer_group, &ack.message_id, &mut cursor).await?;
+                                let mut delivery = ctx.delivery.state.lock().await;
+                                handle_ack(&ctx, &ack.message_id, &mut delivery).await?;
+                            }
+                            Some(UpstreamRequest::Nack(nack)) => {
+                                let mut delivery = ctx.delivery.state.lock().await;
+                                handle_nack(&ctx, &nack, &mut delivery).await?;
+                            }
+                            Some(UpstreamRequest::ModifyAckDeadline(modify)) => {
+                                let mut delivery = ctx.delivery.state.lock().await;
+                                handle_modify_ack_deadline(&ctx, &modify, &mut delivery).await?;
+                            }
+                            Some(UpstreamRequest::AckAndPublish(request)) => {
+                                let result = {
+                                    let mut delivery = ctx.delivery.state.lock().await;
+                                    handle_ack_and_publish(&ctx, request, &mut delivery).await?
+                                };
+                                if tx.send(Ok(SubscribeDownstream {
+                                    response: Some(DownstreamResponse::AckAndPublishResult(result)),
+                                })).await.is_err() {
+                                    return Err(Status::cancelled("client disconnected"));
+                                }
                             }
                             Some(UpstreamRequest::Init(_)) => {
                                 return Err(Status::invalid_argument("unexpected SubscriptionInit"));
@@ -259,26 +700,41 @@ async fn subscription_loop(
                             None => {}
                         }
                     }
-                    Ok(None) => {
-                        tracing::info!(consumer_id = %consumer_id, "Client disconnected");
+                    None => {
+                        tracing::info!(consumer_id = %ctx.consumer_id, "Client disconnected");
                         return Ok(());
                     }
-                    Err(e) => {
+                    Some(Err(e)) => {
                         tracing::warn!(error = %e, "Inbound stream error");
                         return Err(e);
                     }
                 }
             }
 
+            // Return messages whose ack deadline passed to the deliverable pool
+            _ = tokio::time::sleep_until(next_expiry.unwrap_or_else(Instant::now)),
+                if next_expiry.is_some() => {
+                let mut delivery = ctx.delivery.state.lock().await;
+                expire_ack_deadlines(&ctx, &mut delivery).await?;
+            }
+
+            // Wake up when a NACKed message is due and can be delivered
+            _ = tokio::time::sleep_until(next_due.unwrap_or_else(Instant::now)),
+                if next_due.is_some() && credits.available() > 0 => {}
+
+            // Wake up when another member returned messages to the group
+            _ = &mut changed => {}
+
             // Send heartbeat periodically
             _ = heartbeat_interval.tick() => {
                 // Get latest sequence for the topic
                 let max_seq = {
-                    let conn = state
+                    let conn = ctx
+                        .state
                         .reader_pool
                         .get()
                         .map_err(|e| Status::internal(format!("database error: {e}")))?;
-                    get_topic_max_seq(&conn, topic_id)
+                    get_topic_max_seq(&conn, ctx.topic_id)
                         .map_err(|e| Status::internal(format!("database error: {e}")))?
                 };
 
@@ -296,15 +752,15 @@ async fn subscription_loop(
                     return Err(Status::cancelled("client disconnected"));
                 }
 
-                tracing::trace!(topic_id, max_seq, "Heartbeat sent");
+                tracing::trace!(topic_id = ctx.topic_id, max_seq, "Heartbeat sent");
             }
 
             // Handle notifications about new data
             notification = notify_rx.recv() => {
                 match notification {
-                    Ok(notif) if notif.topic_id == topic_id => {
+                    Ok(notif) if notif.topic_id == ctx.topic_id => {
                         // New data available, try to deliver
-                        deliver_messages(&state, &tx, topic_id, &topic_name, &consumer_group, &mut cursor, &credits).await?;
+                        deliver_messages(&ctx, &tx, &credits).await?;
                     }
                     Ok(_) => {
                         // Notification for different topic, ignore
@@ -312,7 +768,7 @@ async fn subscription_loop(
                     Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
                         tracing::warn!(lagged = n, "Notification receiver lagged");
                         // Try to deliver anyway
-                        deliver_messages(&state, &tx, topic_id, &topic_name, &consumer_group, &mut cursor, &credits).await?;
+                        deliver_messages(&ctx, &tx, &credits).await?;
                     }
                     Err(tokio::sync::broadcast::error::RecvError::Closed) => {
                         tracing::info!("Notification bus closed");
@@ -321,130 +777,540 @@ async fn subscription_loop(
                 }
             }
         }
-
-        // Try to deliver messages if we have credits
-        if credits.available() > 0 {
-            deliver_messages(
-                &state,
-                &tx,
-                topic_id,
-                &topic_name,
-                &consumer_group,
-                &mut cursor,
-                &credits,
-            )
-            .await?;
-        }
     }
 }
 
 /// Deliver available messages to the client.
-#[allow(clippy::too_many_arguments)]
+///
+/// Messages are claimed while holding the group's delivery state and sent
+/// after releasing it, so a slow client does not hold up other members.
 async fn deliver_messages(
-    state: &Arc<ServerState>,
+    ctx: &SubscriptionContext,
     tx: &mpsc::Sender<Result<SubscribeDownstream, Status>>,
-    topic_id: i64,
-    topic_name: &str,
-    consumer_group: &str,
-    cursor: &mut i64,
     credits: &Arc<CreditBalance>,
 ) -> Result<(), Status> {
-    let available_credits = credits.available();
-    if available_credits == 0 {
-        return Ok(());
+    let batch = {
+        let mut delivery = ctx.delivery.state.lock().await;
+        let batch = claim_messages(ctx, credits, &mut delivery)?;
+
+        if let Some(cursor) = delivery.take_filtered_cursor() {
+            ctx.state
+                .writer
+                .update_cursor(
+                    ctx.topic_id,
+                    ctx.consumer_group.clone(),
+                    ctx.delivery.reset_generation,
+                    ctx.partitions.clone(),
+                    cursor,
+                )
+                .await
+                .map_err(writer_error_status)?;
+            tracing::trace!(cursor, "Cursor moved past filtered messages");
+        }
+        batch
+    };
+
+    for (msg, attempt) in batch {
+        send_delivery(tx, &ctx.topic_name, msg, attempt).await?;
     }
+    Ok(())
+}
 
-    // Fetch messages from database
-    let conn = state
+/// Claim as many messages as there are credits for.
+///
+/// NACKed and expired messages that are due are redelivered first, then
+/// keyed messages other members set aside for this one, then new messages
+/// are read from the consumed partitions. Messages that were already acked,
+/// that the filter rejects, or that have expired are skipped without
+/// consuming a credit. In
+/// shared mode, messages whose key belongs to another member are handed off
+/// to it instead. With priority delivery, new messages are read highest
+/// priority first.
+#[allow(clippy::result_large_err)]
+fn claim_messages(
+    ctx: &SubscriptionContext,
+    credits: &CreditBalance,
+    delivery: &mut DeliveryState,
+) -> Result<Vec<(Message, u32)>, Status> {
+    let mut batch = Vec::new();
+
+    let now = Instant::now();
+    while let Some(seq) = delivery.redelivery.peek_due(now) {
+        if delivery
+            .ack_tracker
+            .as_ref()
+            .is_some_and(|t| t.is_acked(seq))
+        {
+            delivery.redelivery.take(seq);
+            continue;
+        }
+
+        let msg = {
+            let conn = ctx
+                .state
+                .reader_pool
+                .get()
+                .map_err(|e| Status::internal(format!("database error: {e}")))?;
+            get_message_by_seq(&conn, ctx.topic_id, seq)
+                .map_err(|e| Status::internal(format!("database error: {e}")))?
+        };
+
+        // Expired or deleted by retention since it was delivered
+        let Some(msg) = msg else {
+            delivery.settle_deleted(seq);
+            continue;
+        };
+
+        if let Some(owner) = other_key_owner(ctx, delivery, &msg) {
+            delivery.redelivery.take(seq);
+            delivery.hand_off(seq, owner);
+            ctx.delivery.changed.notify_waiters();
+            continue;
+        }
+
+        if !credits.try_consume() {
+            return Ok(batch);
+        }
+        delivery.redelivery.take(seq);
+
+        record_delivery(ctx, delivery, seq, &msg.message_id);
+        let attempt = delivery.redelivery.attempt(seq);
+        batch.push((msg, attempt));
+    }
+
+    if let Some(member_id) = ctx.member_id {
+        let was_full = !delivery.has_backlog_room(member_id);
+        while let Some(seq) = delivery.peek_backlog(member_id) {
+            let msg = if delivery
+                .ack_tracker
+                .as_ref()
+                .is_some_and(|t| t.is_acked(seq))
+            {
+                None
+            } else {
+                let conn = ctx
+                    .state
+                    .reader_pool
+                    .get()
+                    .map_err(|e| Status::internal(format!("database error: {e}")))?;
+                get_message_by_seq(&conn, ctx.topic_id, seq)
+                    .map_err(|e| Status::internal(format!("database error: {e}")))?
+            };
+
+            // Acked elsewhere, or expired or deleted by retention while waiting
+            let Some(msg) = msg else {
+                delivery.take_backlog(member_id, seq);
+                delivery.settle_deleted(seq);
+                continue;
+            };
+
+            if !credits.try_consume() {
+                break;
+            }
+            delivery.take_backlog(member_id, seq);
+
+            record_delivery(ctx, delivery, seq, &msg.message_id);
+            let attempt = delivery.redelivery.attempt(seq);
+            batch.push((msg, attempt));
+        }
+
+        // Members that stopped reading for lack of room can continue
+        if was_full && delivery.has_backlog_room(member_id) {
+            ctx.delivery.changed.notify_waiters();
+        }
+    }
+
+    if ctx.by_priority {
+        return claim_by_priority(ctx, credits, delivery, batch);
+    }
+
+    loop {
+        let available_credits = credits.available();
+        if available_credits == 0 {
+            return Ok(batch);
+        }
+
+        // Fetch messages from database
+        let conn = ctx
+            .state
+            .reader_pool
+            .get()
+            .map_err(|e| Status::internal(format!("database error: {e}")))?;
+
+        let messages = fetch_messages_from_seq(
+            &conn,
+            ctx.topic_id,
+            &ctx.partitions,
+            delivery.cursor,
+            available_credits as i64,
+        )
+        .map_err(|e| Status::internal(format!("database error: {e}")))?;
+
+        // Get max sequence for lag calculation
+        let max_seq = get_topic_max_seq(&conn, ctx.topic_id)
+            .map_err(|e| Status::internal(format!("database error: {e}")))?;
+
+        drop(conn);
+
+        // Record subscription lag
+        let lag = max_seq - delivery.cursor;
+        record_subscription_lag(&ctx.topic_name, &ctx.consumer_group, lag);
+
+        // Record backpressure state (active if no credits and there's lag)
+        let has_backpressure = available_credits == 0 && lag > 0;
+        record_backpressure(&ctx.topic_name, &ctx.consumer_group, has_backpressure);
+
+        if messages.is_empty() {
+            return Ok(batch);
+        }
+
+        let now_ms = now_millis();
+        for msg in messages {
+            let seq = msg.global_seq;
+
+            let acked = delivery.is_partition_acked(msg.partition, seq)
+                || delivery
+                    .ack_tracker
+                    .as_ref()
+                    .is_some_and(|t| t.is_acked(seq));
+            if acked {
+                if let Some(tracker) = delivery.ack_tracker.as_mut() {
+                    tracker.record_skip(seq);
+                }
+                delivery.cursor = seq;
+                continue;
+            }
+
+            if let Some(filter) = &ctx.filter {
+                if !filter.matches(&message_attributes(&msg)) {
+                    delivery.skip_filtered(seq);
+                    continue;
+                }
+            }
+
+            // Skipped like a filtered-out message, until storage deletes it
+            if msg
+                .expires_at
+                .is_some_and(|expires_at| expires_at <= now_ms)
+            {
+                delivery.skip_filtered(seq);
+                record_message_expired(&ctx.topic_name, &ctx.consumer_group);
+                continue;
+            }
+
+            if let Some(owner) = other_key_owner(ctx, delivery, &msg) {
+                // Reading past it would let the owner see the key out of order
+                if !delivery.has_backlog_room(owner) {
+                    return Ok(batch);
+                }
+
+                // Counted as in flight so the group cursor cannot pass it
+                if let Some(tracker) = delivery.ack_tracker.as_mut() {
+                    tracker.record_delivery(seq, msg.message_id.clone());
+                }
+                delivery.hand_off(seq, owner);
+                delivery.cursor = seq;
+                ctx.delivery.changed.notify_waiters();
+                continue;
+            }
+
+            // Try to consume a credit
+            if !credits.try_consume() {
+                return Ok(batch);
+            }
+
+            record_delivery(ctx, delivery, seq, &msg.message_id);
+            let attempt = delivery.redelivery.attempt(seq);
+            batch.push((msg, attempt));
+
+            // Update local cursor (but don't persist until ACK)
+            delivery.cursor = seq;
+        }
+    }
+}
+
+/// Claim new messages highest priority first, adding them to `batch`.
+///
+/// Every unacked message above the ack cursor is scanned in priority
+/// order, passing over those already in flight. The read position then
+/// moves over the run of messages that have all been read, so the cursor
+/// never passes a lower-priority message that is still waiting.
+#[allow(clippy::result_large_err)]
+fn claim_by_priority(
+    ctx: &SubscriptionContext,
+    credits: &CreditBalance,
+    delivery: &mut DeliveryState,
+    mut batch: Vec<(Message, u32)>,
+) -> Result<Vec<(Message, u32)>, Status> {
+    let Some(after_seq) = delivery.ack_tracker.as_ref().map(AckTracker::cursor) else {
+        return Ok(batch);
+    };
+
+    let conn = ctx
+        .state
         .reader_pool
         .get()
         .map_err(|e| Status::internal(format!("database error: {e}")))?;
 
-    let messages = fetch_messages_from_seq(&conn, topic_id, *cursor, available_credits as i64)
-        .map_err(|e| Status::internal(format!("database error: {e}")))?;
+    let now_ms = now_millis();
+    let mut resume_after = None;
+    'scan: loop {
+        let available_credits = credits.available();
+        if available_credits == 0 {
+            break;
+        }
 
-    // Get max sequence for lag calculation
-    let max_seq = get_topic_max_seq(&conn, topic_id)
+        let messages = fetch_messages_by_priority(
+            &conn,
+            ctx.topic_id,
+            &ctx.partitions,
+            after_seq,
+            resume_after,
+            available_credits as i64,
+        )
         .map_err(|e| Status::internal(format!("database error: {e}")))?;
+        let Some(last) = messages.last() else {
+            break;
+        };
+        resume_after = Some((last.priority, last.global_seq));
 
-    drop(conn);
+        for msg in messages {
+            let seq = msg.global_seq;
 
-    // Record subscription lag
-    let lag = max_seq - *cursor;
-    record_subscription_lag(topic_name, consumer_group, lag);
+            if delivery
+                .ack_tracker
+                .as_ref()
+                .is_some_and(|t| t.is_read(seq))
+            {
+                continue;
+            }
 
-    // Record backpressure state (active if no credits and there's lag)
-    let has_backpressure = available_credits == 0 && lag > 0;
-    record_backpressure(topic_name, consumer_group, has_backpressure);
+            // Acked through its partition's cursor, or filtered out: both
+            // count as acked
+            if delivery.is_partition_acked(msg.partition, seq) {
+                delivery.skip_filtered(seq);
+                continue;
+            }
+            if let Some(filter) = &ctx.filter {
+                if !filter.matches(&message_attributes(&msg)) {
+                    delivery.skip_filtered(seq);
+                    continue;
+                }
+            }
+            if msg
+                .expires_at
+                .is_some_and(|expires_at| expires_at <= now_ms)
+            {
+                delivery.skip_filtered(seq);
+                record_message_expired(&ctx.topic_name, &ctx.consumer_group);
+                continue;
+            }
 
-    for msg in messages {
-        // Try to consume a credit
-        if !credits.try_consume() {
+            if !credits.try_consume() {
+                break 'scan;
+            }
+
+            record_delivery(ctx, delivery, seq, &msg.message_id);
+            let attempt = delivery.redelivery.attempt(seq);
+            batch.push((msg, attempt));
+        }
+    }
+
+    // Move the read position over every message read so far
+    let Some(tracker) = delivery.ack_tracker.as_ref() else {
+        return Ok(batch);
+    };
+    let mut read_through = tracker.read_through().max(tracker.cursor());
+    'advance: loop {
+        let seqs = fetch_seqs_from_seq(
+            &conn,
+            ctx.topic_id,
+            &ctx.partitions,
+            read_through,
+            READ_SCAN_LIMIT,
+        )
+        .map_err(|e| Status::internal(format!("database error: {e}")))?;
+        let scanned = seqs.len() as i64;
+        for seq in seqs {
+            if !tracker.is_read(seq) {
+                break 'advance;
+            }
+            read_through = seq;
+        }
+        if scanned < READ_SCAN_LIMIT {
             break;
         }
+    }
+    delivery.advance_read_position(read_through);
 
-        // Parse attributes from JSON
-        let attributes = msg
-            .attributes
-            .as_ref()
-            .and_then(|s| serde_json::from_str(s).ok())
-            .unwrap_or_default();
-
-        let delivery = MessageDelivery {
-            message_id: msg.message_id,
-            sequence: msg.global_seq as u64,
-            payload: msg.payload.unwrap_or_default(),
-            attributes,
-            timestamp: msg.created_at,
-        };
+    let max_seq = get_topic_max_seq(&conn, ctx.topic_id)
+        .map_err(|e| Status::internal(format!("database error: {e}")))?;
+    record_subscription_lag(
+        &ctx.topic_name,
+        &ctx.consumer_group,
+        max_seq - delivery.cursor,
+    );
 
-        // Send to client
-        if tx
-            .send(Ok(SubscribeDownstream {
-                response: Some(DownstreamResponse::Delivery(delivery)),
-            }))
-            .await
-            .is_err()
-        {
-            return Err(Status::cancelled("client disconnected"));
-        }
+    Ok(batch)
+}
 
-        // Update local cursor (but don't persist until ACK)
-        *cursor = msg.global_seq;
+/// The member that owns `msg`'s key, if it is not this connection.
+///
+/// Always `None` outside shared mode. An unowned key is claimed by this
+/// connection.
+fn other_key_owner(
+    ctx: &SubscriptionContext,
+    delivery: &mut DeliveryState,
+    msg: &Message,
+) -> Option<MemberId> {
+    let member_id = ctx.member_id?;
+    let owner = delivery.route(msg.global_seq, msg.key.as_deref(), member_id);
+    (owner != member_id).then_some(owner)
+}
+
+/// Track a message as delivered to this connection and start its ack deadline.
+fn record_delivery(
+    ctx: &SubscriptionContext,
+    delivery: &mut DeliveryState,
+    seq: i64,
+    message_id: &str,
+) {
+    if let Some(tracker) = delivery.ack_tracker.as_mut() {
+        tracker.record_delivery(seq, message_id.to_string());
     }
+    delivery.record_delivered(seq);
+    if let Some(member_id) = ctx.member_id {
+        delivery.assign(seq, member_id);
+    }
+    if let Some(deadline) = ctx.ack_deadline {
+        delivery.redelivery.lease(seq, Instant::now() + deadline);
+    }
+}
 
-    Ok(())
+/// Send a single message to the client.
+async fn send_delivery(
+    tx: &mpsc::Sender<Result<SubscribeDownstream, Status>>,
+    topic: &str,
+    msg: Message,
+    delivery_attempt: u32,
+) -> Result<(), Status> {
+    let delivery = message_delivery(topic, msg, delivery_attempt);
+
+    tx.send(Ok(SubscribeDownstream {
+        response: Some(DownstreamResponse::Delivery(delivery)),
+    }))
+    .await
+    .map_err(|_| Status::cancelled("client disconnected"))
+}
+
+/// Build the delivery of a stored message of `topic`.
+pub(crate) fn message_delivery(
+    topic: &str,
+    msg: Message,
+    delivery_attempt: u32,
+) -> MessageDelivery {
+    let attributes = message_attributes(&msg);
+
+    MessageDelivery {
+        message_id: msg.message_id,
+        sequence: msg.global_seq as u64,
+        payload: msg.payload.unwrap_or_default(),
+        attributes,
+        timestamp: msg.created_at,
+        delivery_attempt,
+        key: msg.key.unwrap_or_default(),
+        partition: msg.partition,
+        partition_sequence: msg.partition_seq as u64,
+        topic: topic.to_string(),
+        priority: msg.priority,
+    }
+}
+
+/// Parse a message's attributes from their JSON form.
+fn message_attributes(msg: &Message) -> HashMap<String, String> {
+    msg.attributes
+        .as_ref()
+        .and_then(|s| serde_json::from_str(s).ok())
+        .unwrap_or_default()
+}
+
+/// Look up a message's sequence number by its message_id.
+///
+/// A message of another topic is unknown here, so its ack is ignored.
+#[allow(clippy::result_large_err)]
+fn lookup_seq(ctx: &SubscriptionContext, message_id: &str) -> Result<Option<i64>, Status> {
+    let conn = ctx
+        .state
+        .reader_pool
+        .get()
+        .map_err(|e| Status::internal(format!("database error: {e}")))?;
+
+    get_message_seq_by_id(&conn, ctx.topic_id, message_id)
+        .map_err(|e| Status::internal(format!("database error: {e}")))
 }
 
 /// Handle an ACK message.
+///
+/// In cumulative mode the cursor jumps to the acked message. In individual
+/// mode only this message is acked, and the durable cursor advances to the
+/// highest contiguously acked sequence.
 async fn handle_ack(
-    state: &Arc<ServerState>,
-    topic_id: i64,
-    consumer_group: &str,
+    ctx: &SubscriptionContext,
     message_id: &str,
-    cursor: &mut i64,
+    delivery: &mut DeliveryState,
 ) -> Result<(), Status> {
-    // Look up message sequence using reader pool
-    let seq = {
-        let conn = state
-            .reader_pool
-            .get()
-            .map_err(|e| Status::internal(format!("database error: {e}")))?;
+    let seq = lookup_seq(ctx, message_id)?;
 
-        get_message_seq_by_id(&conn, message_id)
-            .map_err(|e| Status::internal(format!("database error: {e}")))?
-    };
+    if let Some(tracker) = delivery.ack_tracker.as_ref() {
+        // The acked message itself may be gone already
+        let deleted = seq
+            .is_none()
+            .then(|| tracker.find_in_flight(message_id))
+            .flatten();
+        if let Some(deleted) = deleted {
+            tracing::debug!(message_id, seq = deleted, "ACK for deleted message");
+            delivery.settle_deleted(deleted);
+        }
+        settle_deleted_in_flight(ctx, delivery, seq.as_slice())?;
+        if deleted.is_some() {
+            return Ok(());
+        }
+    }
+
+    if let (Some(tracker), Some(seq)) = (delivery.ack_tracker.as_mut(), seq) {
+        let outcome = ack_individual(ctx, seq, tracker).await?;
+        delivery.ack(seq);
+        match outcome {
+            AckOutcome::Acked { new_cursor } => {
+                tracing::debug!(message_id, seq, ?new_cursor, "Message acked");
+            }
+            AckOutcome::Duplicate => {
+                tracing::debug!(message_id, seq, "Duplicate ACK ignored");
+            }
+            AckOutcome::Unknown => {
+                tracing::warn!(message_id, seq, "ACK for message not in flight");
+            }
+        }
+        return Ok(());
+    }
 
     match seq {
         Some(seq) => {
             // Update cursor via writer (requires write access)
-            state
+            ctx.state
                 .writer
-                .update_cursor(topic_id, consumer_group.to_string(), seq)
+                .update_cursor(
+                    ctx.topic_id,
+                    ctx.consumer_group.clone(),
+                    ctx.delivery.reset_generation,
+                    ctx.partitions.clone(),
+                    seq,
+                )
                 .await
-                .map_err(|e| Status::internal(format!("database error: {e}")))?;
+                .map_err(writer_error_status)?;
 
-            *cursor = seq;
+            delivery.cursor = seq;
+            delivery.ack_through(seq);
             tracing::debug!(message_id, seq, "Cursor updated");
         }
         None => {
@@ -454,3 +1320,346 @@ async fn handle_ack(
 
     Ok(())
 }
+
+/// Settle the oldest in-flight messages that storage deleted before they
+/// were acked.
+///
+/// Expired and retention-pruned messages can no longer be acked, so they
+/// would hold the cursor back until the consumer reconnects. In-flight
+/// messages not being acked are checked lowest first, up to the first one
+/// that still exists.
+#[allow(clippy::result_large_err)]
+fn settle_deleted_in_flight(
+    ctx: &SubscriptionContext,
+    delivery: &mut DeliveryState,
+    acked: &[i64],
+) -> Result<(), Status> {
+    let Some(tracker) = delivery.ack_tracker.as_ref() else {
+        return Ok(());
+    };
+    let in_flight: Vec<i64> = tracker
+        .in_flight_seqs()
+        .filter(|seq| !acked.contains(seq))
+        .collect();
+    if in_flight.is_empty() {
+        return Ok(());
+    }
+
+    let conn = ctx
+        .state
+        .reader_pool
+        .get()
+        .map_err(|e| Status::internal(format!("database error: {e}")))?;
+    for seq in in_flight {
+        let exists = get_message_by_seq(&conn, ctx.topic_id, seq)
+            .map_err(|e| Status::internal(format!("database error: {e}")))?
+            .is_some();
+        if exists {
+            break;
+        }
+        tracing::debug!(seq, "In-flight message was deleted");
+        delivery.settle_deleted(seq);
+    }
+    Ok(())
+}
+
+/// Ack a single message in individual mode and persist the result.
+async fn ack_individual(
+    ctx: &SubscriptionContext,
+    seq: i64,
+    tracker: &mut AckTracker,
+) -> Result<AckOutcome, Status> {
+    let outcome = tracker.ack(seq);
+    if let AckOutcome::Acked { .. } = outcome {
+        ctx.state
+            .writer
+            .record_individual_ack(
+                ctx.topic_id,
+                ctx.consumer_group.clone(),
+                ctx.delivery.reset_generation,
+                ctx.partitions.clone(),
+                seq,
+                tracker.cursor(),
+            )
+            .await
+            .map_err(writer_error_status)?;
+    }
+    Ok(outcome)
+}
+
+/// Handle an AckAndPublish message.
+///
+/// The acks are applied to a copy of the ack state, which replaces the
+/// original only once the writer has committed them together with the
+/// publishes. A failed transaction is reported in the result and leaves
+/// the messages in flight.
+async fn handle_ack_and_publish(
+    ctx: &SubscriptionContext,
+    request: AckAndPublish,
+    delivery: &mut DeliveryState,
+) -> Result<AckAndPublishResult, Status> {
+    let transaction_id = request.transaction_id;
+
+    let mut seqs = Vec::with_capacity(request.ack_message_ids.len());
+    for message_id in &request.ack_message_ids {
+        match lookup_seq(ctx, message_id)? {
+            Some(seq) => seqs.push(seq),
+            None => match delivery
+                .ack_tracker
+                .as_ref()
+                .and_then(|tracker| tracker.find_in_flight(message_id))
+            {
+                Some(deleted) => {
+                    tracing::debug!(message_id, seq = deleted, "ACK for deleted message");
+                    delivery.settle_deleted(deleted);
+                }
+                None => tracing::warn!(message_id, "ACK for unknown message"),
+            },
+        }
+    }
+    settle_deleted_in_flight(ctx, delivery, &seqs)?;
+
+    let outcome = match prepare_transaction(&ctx.state, ctx.principal.as_ref(), request.publish) {
+        Ok(batches) => commit_ack_and_publish(ctx, seqs, batches, delivery).await,
+        Err(status) => Err(status),
+    };
+
+    Ok(match outcome {
+        Ok(topics) => {
+            tracing::debug!(transaction_id, "Acked and published");
+            AckAndPublishResult {
+                transaction_id,
+                topics,
+                error: None,
+            }
+        }
+        Err(status) => {
+            tracing::debug!(transaction_id, error = %status, "AckAndPublish failed");
+            AckAndPublishResult {
+                transaction_id,
+                topics: Vec::new(),
+                error: Some(publish_error(&status)),
+            }
+        }
+    })
+}
+
+/// Commit acks of `seqs` with the publishes, then apply the acks in memory.
+async fn commit_ack_and_publish(
+    ctx: &SubscriptionContext,
+    seqs: Vec<i64>,
+    batches: Vec<TopicBatch>,
+    delivery: &mut DeliveryState,
+) -> Result<Vec<TopicPublishResults>, Status> {
+    let topics: Vec<String> = batches.iter().map(|batch| batch.topic.clone()).collect();
+
+    let any_acks = !seqs.is_empty();
+    let mut tracker = delivery.ack_tracker.clone();
+    let (cursor_seq, acked) = match tracker.as_mut() {
+        Some(tracker) => {
+            let acked: Vec<i64> = seqs
+                .into_iter()
+                .filter(|&seq| matches!(tracker.ack(seq), AckOutcome::Acked { .. }))
+                .collect();
+            (tracker.cursor(), acked)
+        }
+        None => (seqs.into_iter().max().unwrap_or(0), Vec::new()),
+    };
+
+    // Nothing to persist if every ack was a duplicate
+    let advances = match tracker {
+        Some(_) => !acked.is_empty(),
+        None => any_acks,
+    };
+    let ack = advances.then(|| GroupAck {
+        topic_id: ctx.topic_id,
+        consumer_group: ctx.consumer_group.clone(),
+        reset_generation: ctx.delivery.reset_generation,
+        partitions: ctx.partitions.clone(),
+        cursor_seq,
+        seqs: acked.clone(),
+    });
+
+    let (results, _) = ctx
+        .state
+        .writer
+        .ack_and_publish(ack, batches)
+        .await
+        .map_err(publish_error_status)?;
+
+    // Committed: apply the acks to the delivery state
+    match tracker {
+        Some(tracker) => {
+            delivery.ack_tracker = Some(tracker);
+            for seq in acked {
+                delivery.ack(seq);
+            }
+        }
+        None if advances => {
+            delivery.cursor = cursor_seq;
+            delivery.ack_through(cursor_seq);
+        }
+        None => {}
+    }
+
+    Ok(topics
+        .into_iter()
+        .zip(results)
+        .map(|(topic, results)| TopicPublishResults {
+            topic,
+            results: proto_results(results),
+        })
+        .collect())
+}
+
+/// Handle a NACK message.
+///
+/// The message stays unacked and is scheduled for redelivery once the
+/// requested delay has elapsed.
+async fn handle_nack(
+    ctx: &SubscriptionContext,
+    nack: &Nack,
+    delivery: &mut DeliveryState,
+) -> Result<(), Status> {
+    let message_id = nack.message_id.as_str();
+
+    let Some(seq) = lookup_seq(ctx, message_id)? else {
+        tracing::warn!(message_id, "NACK for unknown message");
+        return Ok(());
+    };
+
+    // Only delivered messages that are not yet requeued can be NACKed
+    if !delivery.is_outstanding(seq) || delivery.redelivery.is_scheduled(seq) {
+        tracing::debug!(message_id, seq, "NACK ignored");
+        return Ok(());
+    }
+
+    record_nack(&ctx.topic_name, &ctx.consumer_group);
+
+    let reason = if nack.reason.is_empty() {
+        "nacked"
+    } else {
+        nack.reason.as_str()
+    };
+    let delay = Duration::from_millis(u64::from(nack.requeue_delay_ms));
+    fail_delivery(ctx, delivery, seq, delay, reason).await?;
+
+    tracing::debug!(
+        message_id,
+        seq,
+        delay_ms = nack.requeue_delay_ms,
+        "Message NACKed"
+    );
+
+    Ok(())
+}
+
+/// Handle a ModifyAckDeadline message.
+///
+/// Moves the message's ack deadline to `ack_deadline_ms` from now. A zero
+/// deadline expires it on the next pass of the subscription loop.
+async fn handle_modify_ack_deadline(
+    ctx: &SubscriptionContext,
+    modify: &ModifyAckDeadline,
+    delivery: &mut DeliveryState,
+) -> Result<(), Status> {
+    let message_id = modify.message_id.as_str();
+
+    let Some(seq) = lookup_seq(ctx, message_id)? else {
+        tracing::warn!(message_id, "ModifyAckDeadline for unknown message");
+        return Ok(());
+    };
+
+    let deadline = Instant::now() + Duration::from_millis(u64::from(modify.ack_deadline_ms));
+    if delivery.redelivery.extend_lease(seq, deadline) {
+        tracing::debug!(
+            message_id,
+            seq,
+            ack_deadline_ms = modify.ack_deadline_ms,
+            "Ack deadline modified"
+        );
+    } else {
+        tracing::debug!(message_id, seq, "ModifyAckDeadline ignored");
+    }
+
+    Ok(())
+}
+
+/// Redeliver every message whose ack deadline has passed.
+async fn expire_ack_deadlines(
+    ctx: &SubscriptionContext,
+    delivery: &mut DeliveryState,
+) -> Result<(), Status> {
+    let now = Instant::now();
+    while let Some(seq) = delivery.redelivery.pop_expired(now) {
+        record_ack_deadline_expired(&ctx.topic_name, &ctx.consumer_group);
+        tracing::debug!(seq, "Ack deadline expired");
+        fail_delivery(ctx, delivery, seq, Duration::ZERO, "ack deadline exceeded").await?;
+    }
+    Ok(())
+}
+
+/// Record a failed delivery attempt and schedule the message for redelivery.
+///
+/// The failure is persisted so the delivery attempt counter survives
+/// reconnects. Once the consumer group's `max_delivery_attempts` is reached
+/// the message is moved to the dead-letter topic and treated as acked
+/// instead.
+async fn fail_delivery(
+    ctx: &SubscriptionContext,
+    delivery: &mut DeliveryState,
+    seq: i64,
+    delay: Duration,
+    reason: &str,
+) -> Result<(), Status> {
+    let failures = ctx
+        .state
+        .writer
+        .record_delivery_failure(ctx.topic_id, ctx.consumer_group.clone(), seq)
+        .await
+        .map_err(|e| Status::internal(format!("database error: {e}")))?;
+
+    let Some(policy) = ctx
+        .dead_letter_policy
+        .as_ref()
+        .filter(|p| p.is_exhausted(failures))
+    else {
+        delivery
+            .redelivery
+            .schedule(seq, Instant::now() + delay, failures);
+        ctx.delivery.changed.notify_waiters();
+        return Ok(());
+    };
+
+    let msg = {
+        let conn = ctx
+            .state
+            .reader_pool
+            .get()
+            .map_err(|e| Status::internal(format!("database error: {e}")))?;
+        get_message_by_seq(&conn, ctx.topic_id, seq)
+            .map_err(|e| Status::internal(format!("database error: {e}")))?
+    };
+
+    // Already gone if retention deleted it since delivery
+    if let Some(msg) = msg {
+        dead_letter(
+            &ctx.state.writer,
+            policy,
+            &msg,
+            &ctx.topic_name,
+            &ctx.consumer_group,
+            failures,
+            reason,
+        )
+        .await
+        .map_err(|e| Status::internal(format!("database error: {e}")))?;
+    }
+
+    // The dead-letter copy replaces this delivery
+    if let Some(tracker) = delivery.ack_tracker.as_mut() {
+        ack_individual(ctx, seq, tracker).await?;
+    }
+    delivery.ack(seq);
+    Ok(())
+}
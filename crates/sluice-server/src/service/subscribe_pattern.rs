@@ -0,0 +1,444 @@
+//! Subscriptions to every topic matching a pattern.
+//!
+//! Topic names are dot-separated tokens. In a pattern, `*` matches exactly
+//! one token and `>` matches one or more trailing tokens, so `orders.*`
+//! matches `orders.eu` and `audit.>` matches `audit.eu.login`.
+//!
+//! Each matching topic runs as its own topic subscription with its own
+//! cursor. A dispatcher owns the client's stream: credits go to a balance
+//! shared by every topic, and acks are routed to the topic of the message.
+//! Topics created later join once their first message is published.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinSet;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Status, Streaming};
+
+use crate::auth::Action;
+use crate::flow::credit::CreditBalance;
+use crate::flow::notify::NewDataNotification;
+use crate::proto::sluice::v1::subscribe_downstream::Response as DownstreamResponse;
+use crate::proto::sluice::v1::subscribe_upstream::Request as UpstreamRequest;
+use crate::proto::sluice::v1::{
+    AckAndPublishResult, CreditGrant, InitialPosition, SubscribeDownstream, SubscribeUpstream,
+};
+use crate::server::ServerState;
+use crate::service::publish_stream::publish_error;
+use crate::service::subscribe::{run_topic, start_topic, SubscribeSettings};
+use crate::storage::dead_letter::DEFAULT_DLQ_SUFFIX;
+use crate::storage::schema::{get_message_topic_id, is_inbox, list_topic_ids};
+
+/// A parsed topic pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopicPattern {
+    tokens: Vec<PatternToken>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PatternToken {
+    Literal(String),
+    /// `*`: exactly one token.
+    One,
+    /// `>`: one or more trailing tokens.
+    Rest,
+}
+
+impl TopicPattern {
+    /// Returns true if `topic` contains wildcards.
+    pub fn is_pattern(topic: &str) -> bool {
+        topic.contains(['*', '>'])
+    }
+
+    /// Parse a pattern such as `orders.*` or `audit.>`.
+    #[allow(clippy::result_large_err)]
+    pub fn parse(pattern: &str) -> Result<Self, Status> {
+        let parts: Vec<&str> = pattern.split('.').collect();
+        let mut tokens = Vec::with_capacity(parts.len());
+
+        for (i, part) in parts.iter().enumerate() {
+            let token = match *part {
+                "*" => PatternToken::One,
+                ">" if i == parts.len() - 1 => PatternToken::Rest,
+                ">" => {
+                    return Err(Status::invalid_argument(
+                        "'>' must be the last token of a topic pattern",
+                    ))
+                }
+                "" => {
+                    return Err(Status::invalid_argument(
+                        "topic pattern contains an empty token",
+                    ))
+                }
+                part if part
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '-' || c == '_') =>
+                {
+                    PatternToken::Literal(part.to_string())
+                }
+                _ => {
+                    return Err(Status::invalid_argument(
+                        "topic pattern tokens must be '*', '>' or a name (only alphanumeric, dash, underscore allowed)",
+                    ))
+                }
+            };
+            tokens.push(token);
+        }
+
+        Ok(Self { tokens })
+    }
+
+    /// Returns true if the topic name matches the pattern.
+    pub fn matches(&self, topic: &str) -> bool {
+        let mut parts = topic.split('.');
+        for token in &self.tokens {
+            let Some(part) = parts.next() else {
+                return false;
+            };
+            match token {
+                PatternToken::Literal(literal) if part != literal => return false,
+                PatternToken::Literal(_) | PatternToken::One => {}
+                PatternToken::Rest => return true,
+            }
+        }
+        parts.next().is_none()
+    }
+}
+
+/// Start consuming every topic that matches `pattern`.
+///
+/// Topics that exist now start at `settings.initial_position`; topics that
+/// appear later start at their first message. Topics the client may not
+/// subscribe to are left out.
+pub(crate) async fn start_pattern_subscription(
+    state: &Arc<ServerState>,
+    settings: SubscribeSettings,
+    pattern: TopicPattern,
+    inbound: Streaming<SubscribeUpstream>,
+    tx: mpsc::Sender<Result<SubscribeDownstream, Status>>,
+) -> Result<(), Status> {
+    if !settings.partitions.is_empty() {
+        return Err(Status::invalid_argument(
+            "partitions cannot be combined with a topic pattern",
+        ));
+    }
+
+    // Listen before listing so a topic created in between is not missed
+    let notify_rx = state.notify_bus.subscribe();
+
+    let mut dispatcher = Dispatcher {
+        state: Arc::clone(state),
+        settings,
+        pattern,
+        tx,
+        credits: Arc::new(CreditBalance::new()),
+        topics: HashMap::new(),
+        ignored: HashSet::new(),
+        tasks: JoinSet::new(),
+    };
+
+    let position = dispatcher.settings.initial_position;
+    for (topic_id, name) in dispatcher.list_topics()? {
+        if let Some(name) = dispatcher.admit(topic_id, name) {
+            dispatcher.start(topic_id, &name, position).await?;
+        }
+    }
+
+    tracing::info!(
+        pattern = %dispatcher.settings.topic,
+        consumer_group = %dispatcher.settings.consumer_group,
+        topics = dispatcher.topics.len(),
+        "Pattern subscription started"
+    );
+
+    tokio::spawn(dispatcher.run(inbound, notify_rx));
+    Ok(())
+}
+
+/// Routes one client stream to the topic subscriptions of a pattern.
+struct Dispatcher {
+    state: Arc<ServerState>,
+    settings: SubscribeSettings,
+    pattern: TopicPattern,
+    tx: mpsc::Sender<Result<SubscribeDownstream, Status>>,
+    /// Credits shared by every topic.
+    credits: Arc<CreditBalance>,
+    /// Topic ID -> inbound channel of its subscription.
+    topics: HashMap<i64, mpsc::Sender<Result<SubscribeUpstream, Status>>>,
+    /// Topics known not to be consumed.
+    ignored: HashSet<i64>,
+    tasks: JoinSet<Result<(), Status>>,
+}
+
+impl Dispatcher {
+    /// Route client messages and pick up new topics until the stream ends.
+    async fn run(
+        mut self,
+        mut inbound: Streaming<SubscribeUpstream>,
+        mut notify_rx: broadcast::Receiver<NewDataNotification>,
+    ) {
+        loop {
+            tokio::select! {
+                msg = inbound.message() => match msg {
+                    Ok(Some(upstream)) => {
+                        if let Err(status) = self.route(upstream).await {
+                            let _ = self.tx.send(Err(status)).await;
+                            break;
+                        }
+                    }
+                    Ok(None) => {
+                        tracing::info!(consumer_id = %self.settings.consumer_id, "Client disconnected");
+                        break;
+                    }
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Inbound stream error");
+                        break;
+                    }
+                },
+
+                // A message on a topic we have not seen may mean a new topic
+                notification = notify_rx.recv() => match notification {
+                    Ok(notif) => {
+                        if !self.topics.contains_key(&notif.topic_id)
+                            && !self.ignored.contains(&notif.topic_id)
+                        {
+                            self.rescan().await;
+                        }
+                    }
+                    Err(RecvError::Lagged(_)) => self.rescan().await,
+                    Err(RecvError::Closed) => break,
+                },
+
+                // One topic ending (takeover, deletion, error) ends the stream
+                Some(done) = self.tasks.join_next() => {
+                    if !matches!(done, Ok(Ok(()))) {
+                        break;
+                    }
+                }
+            }
+        }
+
+        // Let every topic subscription leave its consumer group
+        self.topics.clear();
+        while self.tasks.join_next().await.is_some() {}
+    }
+
+    /// Handle one message from the client.
+    async fn route(&mut self, upstream: SubscribeUpstream) -> Result<(), Status> {
+        let topic_id = match &upstream.request {
+            Some(UpstreamRequest::Credit(grant)) => {
+                if grant.credits > 0 {
+                    self.credits.add(grant.credits);
+                    tracing::debug!(credits = grant.credits, "Credits granted");
+                }
+                // Wake every topic to spend them
+                let wake = SubscribeUpstream {
+                    request: Some(UpstreamRequest::Credit(CreditGrant { credits: 0 })),
+                };
+                for topic in self.topics.values() {
+                    let _ = topic.send(Ok(wake.clone())).await;
+                }
+                return Ok(());
+            }
+            Some(UpstreamRequest::Ack(ack)) => self.message_topic(&ack.message_id)?,
+            Some(UpstreamRequest::Nack(nack)) => self.message_topic(&nack.message_id)?,
+            Some(UpstreamRequest::ModifyAckDeadline(modify)) => {
+                self.message_topic(&modify.message_id)?
+            }
+            Some(UpstreamRequest::AckAndPublish(request)) => {
+                let mut topic_ids = HashSet::new();
+                for message_id in &request.ack_message_ids {
+                    topic_ids.extend(self.message_topic(message_id)?);
+                }
+                if topic_ids.len() > 1 {
+                    let status = Status::invalid_argument(
+                        "messages acked together must belong to one topic",
+                    );
+                    return self.reply(request.transaction_id, &status).await;
+                }
+                // Without acks any topic can publish
+                match topic_ids
+                    .into_iter()
+                    .next()
+                    .or_else(|| self.topics.keys().next().copied())
+                {
+                    Some(topic_id) => Some(topic_id),
+                    None => {
+                        let status =
+                            Status::failed_precondition("no topic matches the pattern yet");
+                        return self.reply(request.transaction_id, &status).await;
+                    }
+                }
+            }
+            Some(UpstreamRequest::Init(_)) => {
+                return Err(Status::invalid_argument("unexpected SubscriptionInit"));
+            }
+            None => return Ok(()),
+        };
+
+        match topic_id.and_then(|topic_id| self.topics.get(&topic_id)) {
+            Some(topic) => {
+                let _ = topic.send(Ok(upstream)).await;
+            }
+            None => tracing::warn!("Message for a topic outside the subscription"),
+        }
+        Ok(())
+    }
+
+    /// Fail an AckAndPublish without passing it to a topic.
+    async fn reply(&self, transaction_id: u64, status: &Status) -> Result<(), Status> {
+        let result = AckAndPublishResult {
+            transaction_id,
+            topics: Vec::new(),
+            error: Some(publish_error(status)),
+        };
+        self.tx
+            .send(Ok(SubscribeDownstream {
+                response: Some(DownstreamResponse::AckAndPublishResult(result)),
+            }))
+            .await
+            .map_err(|_| Status::cancelled("client disconnected"))
+    }
+
+    /// The topic a message belongs to.
+    #[allow(clippy::result_large_err)]
+    fn message_topic(&self, message_id: &str) -> Result<Option<i64>, Status> {
+        let conn = self
+            .state
+            .reader_pool
+            .get()
+            .map_err(|e| Status::internal(format!("database error: {e}")))?;
+        get_message_topic_id(&conn, message_id)
+            .map_err(|e| Status::internal(format!("database error: {e}")))
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn list_topics(&self) -> Result<Vec<(i64, String)>, Status> {
+        let conn = self
+            .state
+            .reader_pool
+            .get()
+            .map_err(|e| Status::internal(format!("database error: {e}")))?;
+        list_topic_ids(&conn).map_err(|e| Status::internal(format!("database error: {e}")))
+    }
+
+    /// Decide whether a topic not seen before is consumed.
+    ///
+    /// Returns its name if so, and remembers it as ignored otherwise.
+    fn admit(&mut self, topic_id: i64, name: String) -> Option<String> {
+        let admitted = self.pattern.matches(&name)
+            && !self.is_dead_letter_topic(&name)
+            && !is_inbox(&name)
+            && self
+                .state
+                .auth
+                .authorize(self.settings.principal.as_ref(), Action::Subscribe, &name)
+                .is_ok();
+        if !admitted {
+            self.ignored.insert(topic_id);
+            return None;
+        }
+        Some(name)
+    }
+
+    /// Dead-lettered messages are not consumed again by the same subscription.
+    fn is_dead_letter_topic(&self, name: &str) -> bool {
+        if self.settings.max_delivery_attempts == 0 {
+            return false;
+        }
+        if !self.settings.dead_letter_topic.is_empty() {
+            return name == self.settings.dead_letter_topic;
+        }
+        name.strip_suffix(DEFAULT_DLQ_SUFFIX)
+            .is_some_and(|topic| self.pattern.matches(topic))
+    }
+
+    /// Start consuming a topic.
+    async fn start(
+        &mut self,
+        topic_id: i64,
+        name: &str,
+        position: InitialPosition,
+    ) -> Result<(), Status> {
+        let topic = start_topic(&self.state, &self.settings, name, position).await?;
+
+        let (topic_tx, topic_rx) = mpsc::channel(32);
+        self.tasks.spawn(run_topic(
+            topic,
+            ReceiverStream::new(topic_rx),
+            self.tx.clone(),
+            Arc::clone(&self.credits),
+        ));
+        self.topics.insert(topic_id, topic_tx);
+        Ok(())
+    }
+
+    /// Start consuming matching topics created since the last look.
+    async fn rescan(&mut self) {
+        let topics = match self.list_topics() {
+            Ok(topics) => topics,
+            Err(e) => {
+                tracing::warn!(error = %e, "Listing topics failed");
+                return;
+            }
+        };
+
+        for (topic_id, name) in topics {
+            if self.topics.contains_key(&topic_id) || self.ignored.contains(&topic_id) {
+                continue;
+            }
+            let Some(name) = self.admit(topic_id, name) else {
+                continue;
+            };
+
+            // Everything in a topic created after the subscription is new
+            match self.start(topic_id, &name, InitialPosition::Earliest).await {
+                Ok(()) => tracing::info!(topic = %name, "Topic joined pattern subscription"),
+                Err(e) => {
+                    tracing::warn!(topic = %name, error = %e, "Could not subscribe to topic");
+                    self.ignored.insert(topic_id);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, topic: &str) -> bool {
+        TopicPattern::parse(pattern).unwrap().matches(topic)
+    }
+
+    #[test]
+    fn test_single_token_wildcard() {
+        assert!(matches("orders.*", "orders.eu"));
+        assert!(!matches("orders.*", "orders"));
+        assert!(!matches("orders.*", "orders.eu.dlq"));
+        assert!(!matches("orders.*", "audit.eu"));
+        assert!(matches("*.created", "orders.created"));
+        assert!(matches("orders.*.v1", "orders.eu.v1"));
+    }
+
+    #[test]
+    fn test_trailing_wildcard() {
+        assert!(matches("audit.>", "audit.login"));
+        assert!(matches("audit.>", "audit.eu.login"));
+        assert!(!matches("audit.>", "audit"));
+        assert!(!matches("audit.>", "auditing.login"));
+        assert!(matches(">", "anything.at.all"));
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        assert!(TopicPattern::is_pattern("orders.*"));
+        assert!(!TopicPattern::is_pattern("orders.eu"));
+
+        assert!(TopicPattern::parse("audit.>.login").is_err());
+        assert!(TopicPattern::parse("orders..*").is_err());
+        assert!(TopicPattern::parse("orders.eu*").is_err());
+    }
+}
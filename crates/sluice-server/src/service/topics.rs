@@ -1,23 +1,392 @@
-//! Topic discovery service (ListTopics).
+//! Topic discovery and management services (ListTopics, CreateTopic,
+//! UpdateTopicConfig, DeleteTopic, PurgeTopic, DescribeTopic).
 
 use std::sync::Arc;
 
+use tonic::metadata::MetadataValue;
 use tonic::{Request, Response, Status};
 
-use crate::proto::sluice::v1::{ListTopicsRequest, ListTopicsResponse, Topic};
+use crate::auth::{Action, Principal};
+use crate::proto::sluice::v1::{
+    ConsumerGroupInfo, CreateTopicRequest, CreateTopicResponse, DeleteTopicRequest,
+    DeleteTopicResponse, DescribeTopicRequest, DescribeTopicResponse, ListTopicsRequest,
+    ListTopicsResponse, PartitionInfo, PurgeTopicRequest, PurgeTopicResponse, Topic, TopicConfig,
+    UpdateTopicConfigRequest, UpdateTopicConfigResponse,
+};
 use crate::server::ServerState;
+use crate::service::publish::{LAST_SEQUENCE_METADATA, MAX_PAYLOAD_SIZE};
+use crate::storage::partition::MAX_PARTITIONS;
+use crate::storage::reader::TopicListing;
+use crate::storage::schema::{is_inbox, RetentionPolicy, TopicSettings, INBOX_PREFIX};
+use crate::storage::writer::WriterError;
 
+/// Handle a ListTopics RPC request.
+///
+/// Only topics the caller has some access to are listed.
 pub async fn handle_list_topics(
     state: &Arc<ServerState>,
-    _request: Request<ListTopicsRequest>,
+    request: Request<ListTopicsRequest>,
 ) -> Result<Response<ListTopicsResponse>, Status> {
+    let principal = Principal::from_request(&request);
     let topics = state
         .reader_pool
         .list_topics()
         .map_err(|e| Status::internal(format!("failed to list topics: {e}")))?
         .into_iter()
-        .map(|(name, created_at)| Topic { name, created_at })
+        .filter(|topic| state.auth.can_see(principal.as_ref(), &topic.name))
+        .map(topic_to_proto)
         .collect::<Vec<_>>();
 
     Ok(Response::new(ListTopicsResponse { topics }))
 }
+
+/// Handle a CreateTopic RPC request.
+///
+/// Fails with ALREADY_EXISTS if the topic exists, whatever its partitions.
+#[tracing::instrument(skip(state, request))]
+pub async fn handle_create_topic(
+    state: &Arc<ServerState>,
+    request: Request<CreateTopicRequest>,
+) -> Result<Response<CreateTopicResponse>, Status> {
+    let principal = Principal::from_request(&request);
+    let req = request.into_inner();
+
+    if req.name.is_empty() {
+        return Err(Status::invalid_argument("topic cannot be empty"));
+    }
+
+    if req.name.len() > 255 {
+        return Err(Status::invalid_argument(
+            "topic name too long (max 255 characters)",
+        ));
+    }
+
+    // Validate topic name characters (alphanumeric, dash, underscore, dot)
+    if !req
+        .name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+    {
+        return Err(Status::invalid_argument(
+            "topic name must contain only alphanumeric characters, dashes, underscores, or dots",
+        ));
+    }
+
+    if is_inbox(&req.name) {
+        return Err(Status::invalid_argument(format!(
+            "topic names starting with '{INBOX_PREFIX}' are reserved for request inboxes"
+        )));
+    }
+
+    if req.partitions > MAX_PARTITIONS {
+        return Err(Status::invalid_argument(format!(
+            "too many partitions: {} (max {MAX_PARTITIONS})",
+            req.partitions
+        )));
+    }
+    let partition_count = req.partitions.max(1);
+    let settings = settings_from_proto(req.config.unwrap_or_default())?;
+
+    state
+        .auth
+        .authorize(principal.as_ref(), Action::Admin, &req.name)?;
+
+    let topic = state
+        .writer
+        .create_topic(req.name.clone(), partition_count, settings.clone())
+        .await
+        .map_err(writer_error_status)?
+        .ok_or_else(|| Status::already_exists(format!("topic '{}' already exists", req.name)))?;
+
+    tracing::info!(topic = %topic.name, partition_count, ?settings, "Topic created");
+
+    Ok(Response::new(CreateTopicResponse {
+        topic: Some(topic_to_proto(TopicListing {
+            name: topic.name,
+            created_at: topic.created_at,
+            partition_max_seqs: vec![0; partition_count as usize],
+        })),
+        config: Some(settings_to_proto(settings)),
+    }))
+}
+
+/// Handle an UpdateTopicConfig RPC request.
+///
+/// The new configuration replaces the old one; a new size limit applies to
+/// the next publish and new retention limits to the next retention pass.
+#[tracing::instrument(skip(state, request))]
+pub async fn handle_update_topic_config(
+    state: &Arc<ServerState>,
+    request: Request<UpdateTopicConfigRequest>,
+) -> Result<Response<UpdateTopicConfigResponse>, Status> {
+    let principal = Principal::from_request(&request);
+    let req = request.into_inner();
+
+    if req.name.is_empty() {
+        return Err(Status::invalid_argument("topic cannot be empty"));
+    }
+    let settings = settings_from_proto(req.config.unwrap_or_default())?;
+    state
+        .auth
+        .authorize(principal.as_ref(), Action::Admin, &req.name)?;
+
+    let topic = state
+        .writer
+        .update_topic_config(req.name.clone(), settings.clone())
+        .await
+        .map_err(writer_error_status)?
+        .ok_or_else(|| topic_not_found(&req.name))?;
+
+    tracing::info!(topic = %topic.name, ?settings, "Topic config updated");
+
+    Ok(Response::new(UpdateTopicConfigResponse {
+        config: Some(settings_to_proto(settings)),
+    }))
+}
+
+/// Handle a DeleteTopic RPC request.
+///
+/// Connected subscribers are ended with NOT_FOUND.
+#[tracing::instrument(skip(state, request))]
+pub async fn handle_delete_topic(
+    state: &Arc<ServerState>,
+    request: Request<DeleteTopicRequest>,
+) -> Result<Response<DeleteTopicResponse>, Status> {
+    let principal = Principal::from_request(&request);
+    let req = request.into_inner();
+
+    if req.name.is_empty() {
+        return Err(Status::invalid_argument("topic cannot be empty"));
+    }
+    state
+        .auth
+        .authorize(principal.as_ref(), Action::Admin, &req.name)?;
+
+    let (topic, outcome) = state
+        .writer
+        .delete_topic(req.name.clone())
+        .await
+        .map_err(writer_error_status)?
+        .ok_or_else(|| topic_not_found(&req.name))?;
+
+    let disconnected = state.connection_registry.terminate_topic(
+        topic.id,
+        Status::not_found(format!("topic '{}' was deleted", topic.name)),
+    );
+
+    tracing::info!(
+        topic = %topic.name,
+        messages_deleted = outcome.messages_deleted,
+        consumer_groups_deleted = outcome.consumer_groups_deleted,
+        disconnected,
+        "Topic deleted"
+    );
+
+    Ok(Response::new(DeleteTopicResponse {
+        messages_deleted: outcome.messages_deleted as u64,
+        consumer_groups_deleted: outcome.consumer_groups_deleted as u32,
+    }))
+}
+
+/// Handle a PurgeTopic RPC request.
+///
+/// Connected subscribers are ended with ABORTED so that they reconnect from
+/// the moved cursors.
+#[tracing::instrument(skip(state, request))]
+pub async fn handle_purge_topic(
+    state: &Arc<ServerState>,
+    request: Request<PurgeTopicRequest>,
+) -> Result<Response<PurgeTopicResponse>, Status> {
+    let principal = Principal::from_request(&request);
+    let req = request.into_inner();
+
+    if req.name.is_empty() {
+        return Err(Status::invalid_argument("topic cannot be empty"));
+    }
+    state
+        .auth
+        .authorize(principal.as_ref(), Action::Admin, &req.name)?;
+
+    let (topic, outcome) = state
+        .writer
+        .purge_topic(req.name.clone())
+        .await
+        .map_err(writer_error_status)?
+        .ok_or_else(|| topic_not_found(&req.name))?;
+
+    let disconnected = state.connection_registry.terminate_topic(
+        topic.id,
+        Status::aborted(format!("topic '{}' was purged", topic.name)),
+    );
+
+    tracing::info!(
+        topic = %topic.name,
+        messages_deleted = outcome.deleted,
+        through_seq = outcome.through_seq,
+        disconnected,
+        "Topic purged"
+    );
+
+    Ok(Response::new(PurgeTopicResponse {
+        messages_deleted: outcome.deleted as u64,
+    }))
+}
+
+/// Handle a DescribeTopic RPC request.
+pub async fn handle_describe_topic(
+    state: &Arc<ServerState>,
+    request: Request<DescribeTopicRequest>,
+) -> Result<Response<DescribeTopicResponse>, Status> {
+    let principal = Principal::from_request(&request);
+    let req = request.into_inner();
+
+    if req.name.is_empty() {
+        return Err(Status::invalid_argument("topic cannot be empty"));
+    }
+    state
+        .auth
+        .authorize(principal.as_ref(), Action::Admin, &req.name)?;
+
+    let description = state
+        .writer
+        .describe_topic(req.name.clone())
+        .await
+        .map_err(writer_error_status)?
+        .ok_or_else(|| topic_not_found(&req.name))?;
+    let stats = description.stats;
+    let topic_id = description.topic.id;
+    let topic_name = description.topic.name.clone();
+
+    Ok(Response::new(DescribeTopicResponse {
+        topic: Some(topic_to_proto(TopicListing {
+            name: description.topic.name,
+            created_at: description.topic.created_at,
+            partition_max_seqs: description.partition_max_seqs,
+        })),
+        message_count: stats.message_count as u64,
+        min_sequence: stats.min_seq as u64,
+        max_sequence: stats.max_seq as u64,
+        size_bytes: stats.size_bytes as u64,
+        oldest_timestamp: stats.oldest_created_at,
+        newest_timestamp: stats.newest_created_at,
+        scheduled_count: stats.scheduled_count as u64,
+        consumer_groups: description
+            .consumer_groups
+            .into_iter()
+            .map(|(sub, lag)| ConsumerGroupInfo {
+                connected: state
+                    .connection_registry
+                    .is_connected(topic_id, &sub.consumer_group),
+                name: sub.consumer_group,
+                cursor_sequence: sub.cursor_seq as u64,
+                lag: lag as u64,
+                updated_at: sub.updated_at.unwrap_or_default(),
+                topic: topic_name.clone(),
+                max_sequence: stats.max_seq as u64,
+            })
+            .collect(),
+        config: Some(settings_to_proto(description.settings)),
+    }))
+}
+
+pub(crate) fn topic_not_found(name: &str) -> Status {
+    Status::not_found(format!("topic '{name}' does not exist"))
+}
+
+pub(crate) fn writer_error_status(e: WriterError) -> Status {
+    match e {
+        WriterError::ChannelClosed => Status::unavailable("server is shutting down"),
+        WriterError::Database(msg) => Status::internal(format!("database error: {msg}")),
+        WriterError::ThreadPanic => Status::internal("internal error"),
+        WriterError::TopicNotFound(name) => Status::not_found(format!(
+            "topic '{name}' does not exist and auto-creation is disabled"
+        )),
+        WriterError::MessageTooLarge { topic, size, max } => Status::resource_exhausted(format!(
+            "payload too large: {size} bytes (max {max} bytes for topic '{topic}')"
+        )),
+        WriterError::SequenceMismatch {
+            topic,
+            expected,
+            actual,
+        } => {
+            let mut status = Status::failed_precondition(format!(
+                "last sequence of topic '{topic}' is {actual}, expected {expected}"
+            ));
+            status
+                .metadata_mut()
+                .insert(LAST_SEQUENCE_METADATA, MetadataValue::from(actual));
+            status
+        }
+        WriterError::ConsumerGroupReset(group) => {
+            Status::aborted(format!("consumer group '{group}' was reset"))
+        }
+    }
+}
+
+/// Convert a requested topic configuration, treating zero as unset.
+#[allow(clippy::result_large_err)]
+fn settings_from_proto(config: TopicConfig) -> Result<TopicSettings, Status> {
+    fn limit(value: u64, name: &str) -> Result<Option<i64>, Status> {
+        match value {
+            0 => Ok(None),
+            value => i64::try_from(value)
+                .map(Some)
+                .map_err(|_| Status::invalid_argument(format!("{name} out of range"))),
+        }
+    }
+
+    let max_age_ms = limit(
+        config.retention_max_age_secs.saturating_mul(1000),
+        "retention_max_age_secs",
+    )?;
+    let max_message_bytes = limit(config.max_message_bytes, "max_message_bytes")?;
+    if max_message_bytes.is_some_and(|max| max > MAX_PAYLOAD_SIZE as i64) {
+        return Err(Status::invalid_argument(format!(
+            "max_message_bytes exceeds the server limit of {MAX_PAYLOAD_SIZE} bytes"
+        )));
+    }
+
+    Ok(TopicSettings {
+        retention: RetentionPolicy {
+            max_age_ms,
+            max_messages: limit(config.retention_max_messages, "retention_max_messages")?,
+            max_bytes: limit(config.retention_max_bytes, "retention_max_bytes")?,
+        },
+        max_message_bytes,
+        description: (!config.description.is_empty()).then_some(config.description),
+        dead_letter_expired: config.dead_letter_expired,
+        priority_delivery: config.priority_delivery,
+        auto_create: config.auto_create,
+    })
+}
+
+fn settings_to_proto(settings: TopicSettings) -> TopicConfig {
+    let retention = settings.retention;
+    TopicConfig {
+        retention_max_age_secs: retention.max_age_ms.map_or(0, |ms| ms as u64 / 1000),
+        retention_max_messages: retention.max_messages.unwrap_or_default() as u64,
+        retention_max_bytes: retention.max_bytes.unwrap_or_default() as u64,
+        max_message_bytes: settings.max_message_bytes.unwrap_or_default() as u64,
+        description: settings.description.unwrap_or_default(),
+        dead_letter_expired: settings.dead_letter_expired,
+        priority_delivery: settings.priority_delivery,
+        auto_create: settings.auto_create,
+    }
+}
+
+fn topic_to_proto(topic: TopicListing) -> Topic {
+    Topic {
+        name: topic.name,
+        created_at: topic.created_at,
+        partition_count: topic.partition_max_seqs.len() as u32,
+        partitions: topic
+            .partition_max_seqs
+            .into_iter()
+            .enumerate()
+            .map(|(partition, max_seq)| PartitionInfo {
+                partition: partition as u32,
+                max_sequence: max_seq as u64,
+            })
+            .collect(),
+    }
+}
@@ -0,0 +1,86 @@
+//! Naming and attributes of dead-lettered messages.
+//!
+//! Shared by the writer, which dead-letters expired messages, and the
+//! subscribe service, which dead-letters messages that exhaust their
+//! delivery attempts.
+
+use std::collections::HashMap;
+
+use crate::storage::schema::Message;
+
+/// Suffix appended to a topic name to form its default dead-letter topic.
+pub const DEFAULT_DLQ_SUFFIX: &str = ".dlq";
+
+/// Attribute holding the topic the message was originally published to.
+pub const ATTR_ORIGINAL_TOPIC: &str = "sluice.dlq.original_topic";
+/// Attribute holding the message's sequence in the original topic.
+pub const ATTR_ORIGINAL_SEQUENCE: &str = "sluice.dlq.original_sequence";
+/// Attribute holding the message's ID in the original topic.
+pub const ATTR_ORIGINAL_MESSAGE_ID: &str = "sluice.dlq.original_message_id";
+/// Attribute holding the consumer group that gave up on the message.
+pub const ATTR_CONSUMER_GROUP: &str = "sluice.dlq.consumer_group";
+/// Attribute holding the number of failed delivery attempts.
+pub const ATTR_DELIVERY_ATTEMPTS: &str = "sluice.dlq.delivery_attempts";
+/// Attribute holding the reason given for the last failure.
+pub const ATTR_FAILURE_REASON: &str = "sluice.dlq.failure_reason";
+
+/// Failure reason of messages dead-lettered because they expired.
+pub const EXPIRED_REASON: &str = "expired";
+
+/// Build the attributes for a dead-lettered copy of `msg` that expired
+/// before it was consumed.
+///
+/// No consumer group gave up on the message, so only its origin and
+/// [`EXPIRED_REASON`] are added.
+pub fn expired_attributes(msg: &Message, topic_name: &str) -> HashMap<String, String> {
+    origin_attributes(msg, topic_name, EXPIRED_REASON)
+}
+
+/// The original attributes of `msg` plus where it came from and why it
+/// was dead-lettered.
+pub fn origin_attributes(msg: &Message, topic_name: &str, reason: &str) -> HashMap<String, String> {
+    let mut attributes: HashMap<String, String> = msg
+        .attributes
+        .as_deref()
+        .and_then(|s| serde_json::from_str(s).ok())
+        .unwrap_or_default();
+
+    attributes.insert(ATTR_ORIGINAL_TOPIC.to_string(), topic_name.to_string());
+    attributes.insert(
+        ATTR_ORIGINAL_SEQUENCE.to_string(),
+        msg.global_seq.to_string(),
+    );
+    attributes.insert(ATTR_ORIGINAL_MESSAGE_ID.to_string(), msg.message_id.clone());
+    attributes.insert(ATTR_FAILURE_REASON.to_string(), reason.to_string());
+
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expired_attributes_keep_originals() {
+        let msg = Message {
+            global_seq: 42,
+            topic_id: 1,
+            message_id: "msg-42".to_string(),
+            payload: None,
+            attributes: Some(r#"{"trace":"abc"}"#.to_string()),
+            created_at: 0,
+            key: None,
+            partition: 0,
+            partition_seq: 42,
+            expires_at: Some(0),
+            priority: 0,
+        };
+
+        let attrs = expired_attributes(&msg, "orders");
+        assert_eq!(attrs["trace"], "abc");
+        assert_eq!(attrs[ATTR_ORIGINAL_TOPIC], "orders");
+        assert_eq!(attrs[ATTR_ORIGINAL_SEQUENCE], "42");
+        assert_eq!(attrs[ATTR_FAILURE_REASON], EXPIRED_REASON);
+        assert!(!attrs.contains_key(ATTR_CONSUMER_GROUP));
+    }
+}
@@ -0,0 +1,53 @@
+//! Background deletion of expired messages.
+//!
+//! Messages published with a time to live are skipped by subscribers once
+//! it has passed. This task sleeps until the earliest of them expires and
+//! then has the writer delete every expired message, or move it to the
+//! dead-letter topic if its topic is configured to.
+
+use std::time::Duration;
+use tokio::sync::watch;
+
+use super::writer::WriterHandle;
+use crate::now_millis;
+
+/// Delay before retrying after the writer failed to expire messages.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Delete messages as they expire until shutdown is signaled.
+pub async fn run_expiry_task(writer: WriterHandle, mut shutdown_rx: watch::Receiver<bool>) {
+    let mut next_expiry = writer.next_expiry();
+
+    tracing::info!("Expiry task started");
+
+    loop {
+        let next = *next_expiry.borrow_and_update();
+        let wait = next.map(|expires_at| {
+            Duration::from_millis(u64::try_from(expires_at - now_millis()).unwrap_or(0))
+        });
+
+        tokio::select! {
+            _ = tokio::time::sleep(wait.unwrap_or_default()), if wait.is_some() => {
+                match writer.expire_messages().await {
+                    Ok(expired) => {
+                        tracing::debug!(expired, "Expired messages deleted");
+                    }
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Deleting expired messages failed");
+                        tokio::time::sleep(RETRY_DELAY).await;
+                    }
+                }
+            }
+            changed = next_expiry.changed() => {
+                // The writer is gone
+                if changed.is_err() {
+                    break;
+                }
+            }
+            _ = shutdown_rx.changed() => {
+                tracing::info!("Expiry task shutting down");
+                break;
+            }
+        }
+    }
+}
@@ -5,8 +5,18 @@
 //! - Dedicated writer thread with group commit
 //! - Read connection pool for subscriptions
 //! - Batch commit logic for high throughput
+//! - Partition assignment for published messages
+//! - Background retention enforcement
+//! - Background release of scheduled messages
+//! - Background deletion of expired messages
+//! - Naming and attributes of dead-lettered messages
 
 pub mod batch;
+pub mod dead_letter;
+pub mod expiry;
+pub mod partition;
 pub mod reader;
+pub mod retention;
+pub mod scheduler;
 pub mod schema;
 pub mod writer;
@@ -0,0 +1,90 @@
+//! Partition assignment for published messages.
+//!
+//! A message with a key goes to the partition picked by a hash of the key,
+//! so all messages with the same key share a partition and keep their
+//! publish order. Messages without a key are spread round-robin.
+//!
+//! Each partition has its own sequence, and consumers can read a subset of
+//! them. Publishes to a partitioned topic are written by the writer's
+//! partition writers, which batch and commit their partitions in parallel.
+
+use std::collections::HashMap;
+
+/// Largest number of partitions a topic may have.
+pub const MAX_PARTITIONS: u32 = 1024;
+
+/// Chooses the partition for each message published to a topic.
+#[derive(Debug, Default)]
+pub struct Partitioner {
+    /// Topic ID -> next partition for a message without a key.
+    next: HashMap<i64, u32>,
+}
+
+impl Partitioner {
+    /// Pick a partition for a message on a topic with `partition_count` partitions.
+    pub fn assign(&mut self, topic_id: i64, partition_count: u32, key: Option<&str>) -> u32 {
+        if partition_count <= 1 {
+            return 0;
+        }
+
+        match key {
+            Some(key) => partition_for_key(key, partition_count),
+            None => {
+                let next = self.next.entry(topic_id).or_insert(0);
+                let partition = *next % partition_count;
+                *next = (partition + 1) % partition_count;
+                partition
+            }
+        }
+    }
+}
+
+/// Partition for a message key on a topic with `partition_count` partitions.
+///
+/// Uses 64-bit FNV-1a, which is stable across builds and restarts so a key
+/// keeps its partition for the lifetime of the topic.
+pub fn partition_for_key(key: &str, partition_count: u32) -> u32 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let hash = key.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    });
+    (hash % u64::from(partition_count.max(1))) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyed_messages_keep_their_partition() {
+        let mut partitioner = Partitioner::default();
+
+        let first = partitioner.assign(1, 8, Some("order-1"));
+        for _ in 0..10 {
+            assert_eq!(partitioner.assign(1, 8, Some("order-1")), first);
+        }
+        assert_eq!(partition_for_key("order-1", 8), first);
+
+        // Known FNV-1a values, so the mapping cannot drift between releases
+        assert_eq!(
+            partition_for_key("", 1000),
+            (0xcbf2_9ce4_8422_2325_u64 % 1000) as u32
+        );
+        assert_eq!(
+            partition_for_key("a", 1000),
+            (0xaf63_dc4c_8601_ec8c_u64 % 1000) as u32
+        );
+    }
+
+    #[test]
+    fn test_unkeyed_messages_round_robin_per_topic() {
+        let mut partitioner = Partitioner::default();
+
+        let assigned: Vec<u32> = (0..5).map(|_| partitioner.assign(1, 3, None)).collect();
+        assert_eq!(assigned, vec![0, 1, 2, 0, 1]);
+        assert_eq!(partitioner.assign(2, 3, None), 0);
+        assert_eq!(partitioner.assign(3, 1, None), 0);
+    }
+}
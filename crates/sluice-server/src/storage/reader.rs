@@ -9,7 +9,10 @@ use rusqlite::OpenFlags;
 use std::path::Path;
 use thiserror::Error;
 
-use super::schema::apply_reader_pragmas;
+use super::schema::{
+    apply_reader_pragmas, count_messages_after, get_partition_max_seqs, get_topic_max_seq,
+    list_subscriptions, Subscription,
+};
 
 /// Error type for reader pool operations.
 #[derive(Debug, Error)]
@@ -21,6 +24,27 @@ pub enum ReaderError {
     Database(#[from] rusqlite::Error),
 }
 
+/// A topic as reported by ListTopics.
+#[derive(Debug, Clone)]
+pub struct TopicListing {
+    pub name: String,
+    pub created_at: i64,
+    /// Highest sequence assigned in each partition, indexed by partition.
+    pub partition_max_seqs: Vec<i64>,
+}
+
+/// A consumer group as reported by ListConsumerGroups.
+#[derive(Debug, Clone)]
+pub struct ConsumerGroupListing {
+    pub topic_id: i64,
+    pub topic: String,
+    pub subscription: Subscription,
+    /// Highest sequence stored in the topic.
+    pub max_seq: i64,
+    /// Number of stored messages after the group's cursor.
+    pub lag: i64,
+}
+
 /// Read connection pool for subscription queries.
 ///
 /// Provides pooled read-only connections for concurrent access.
@@ -66,15 +90,62 @@ impl ReaderPool {
     /// List topics known to the server.
     ///
     /// Returns topics in lexicographic order by name for stable UI ordering.
-    pub fn list_topics(&self) -> Result<Vec<(String, i64)>, ReaderError> {
+    pub fn list_topics(&self) -> Result<Vec<TopicListing>, ReaderError> {
         let conn = self.get()?;
-        let mut stmt = conn.prepare("SELECT name, created_at FROM topics ORDER BY name ASC")?;
+        let mut stmt = conn.prepare("SELECT id, name, created_at FROM topics ORDER BY name ASC")?;
         let rows = stmt
             .query_map([], |row| {
-                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                ))
             })?
             .collect::<Result<Vec<_>, _>>()?;
-        Ok(rows)
+
+        rows.into_iter()
+            .map(|(id, name, created_at)| {
+                Ok(TopicListing {
+                    name,
+                    created_at,
+                    partition_max_seqs: get_partition_max_seqs(&conn, id)?,
+                })
+            })
+            .collect()
+    }
+
+    /// List consumer groups, of one topic or of every topic.
+    ///
+    /// Returns groups ordered by topic name, then group name.
+    pub fn list_consumer_groups(
+        &self,
+        topic: Option<&str>,
+    ) -> Result<Vec<ConsumerGroupListing>, ReaderError> {
+        let conn = self.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, name FROM topics WHERE ?1 IS NULL OR name = ?1 ORDER BY name ASC",
+        )?;
+        let topics = stmt
+            .query_map([topic], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut groups = Vec::new();
+        for (topic_id, name) in topics {
+            let max_seq = get_topic_max_seq(&conn, topic_id)?;
+            for subscription in list_subscriptions(&conn, topic_id)? {
+                let lag = count_messages_after(&conn, topic_id, subscription.cursor_seq)?;
+                groups.push(ConsumerGroupListing {
+                    topic_id,
+                    topic: name.clone(),
+                    subscription,
+                    max_seq,
+                    lag,
+                });
+            }
+        }
+        Ok(groups)
     }
 }
 
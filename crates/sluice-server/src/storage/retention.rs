@@ -0,0 +1,232 @@
+//! Background retention enforcement.
+//!
+//! Periodically computes, for every topic, the range of messages that
+//! falls outside its retention policy and deletes it in bounded chunks
+//! through the writer thread so pruning never blocks publishes for long.
+
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::watch;
+
+use super::reader::{ReaderError, ReaderPool};
+use super::schema::{get_topic_retention, list_topic_ids, retention_cutoff_seq, RetentionPolicy};
+use super::writer::{WriterError, WriterHandle};
+use crate::now_millis;
+use crate::observability::metrics::record_messages_pruned;
+
+/// Error type for retention enforcement.
+#[derive(Debug, Error)]
+pub enum RetentionError {
+    #[error("Reader error: {0}")]
+    Reader(#[from] ReaderError),
+
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
+    #[error("Writer error: {0}")]
+    Writer(#[from] WriterError),
+}
+
+/// Configuration for the retention task.
+#[derive(Debug, Clone, Copy)]
+pub struct RetentionConfig {
+    /// Server-wide limits applied when a topic has no override.
+    pub defaults: RetentionPolicy,
+    /// How often to check topics for expired messages.
+    pub interval: Duration,
+    /// Maximum number of messages deleted per writer transaction.
+    pub chunk_size: i64,
+}
+
+impl RetentionConfig {
+    /// Create a RetentionConfig from application config values.
+    ///
+    /// A value of `None` or `0` disables the corresponding default limit.
+    pub fn from_config(
+        max_age_secs: Option<u64>,
+        max_messages: Option<u64>,
+        max_bytes: Option<u64>,
+        interval_secs: u64,
+        chunk_size: usize,
+    ) -> Self {
+        let non_zero = |v: Option<u64>| v.filter(|&v| v > 0).map(|v| v as i64);
+        Self {
+            defaults: RetentionPolicy {
+                max_age_ms: non_zero(max_age_secs).map(|secs| secs * 1000),
+                max_messages: non_zero(max_messages),
+                max_bytes: non_zero(max_bytes),
+            },
+            interval: Duration::from_secs(interval_secs.max(1)),
+            chunk_size: chunk_size.max(1) as i64,
+        }
+    }
+}
+
+/// Run one retention pass over every topic.
+///
+/// Returns the total number of messages deleted.
+pub async fn enforce_retention(
+    writer: &WriterHandle,
+    reader_pool: &ReaderPool,
+    config: &RetentionConfig,
+) -> Result<usize, RetentionError> {
+    let now = now_millis();
+
+    // Resolve cutoffs up front so the reader connection is not held
+    // across writer round-trips.
+    let cutoffs = {
+        let conn = reader_pool.get()?;
+        let mut cutoffs = Vec::new();
+        for (topic_id, name) in list_topic_ids(&conn)? {
+            let policy = get_topic_retention(&conn, topic_id)?.with_defaults(&config.defaults);
+            if policy.is_unlimited() {
+                continue;
+            }
+            if let Some(cutoff) = retention_cutoff_seq(&conn, topic_id, &policy, now)? {
+                cutoffs.push((topic_id, name, cutoff));
+            }
+        }
+        cutoffs
+    };
+
+    let mut total = 0;
+    for (topic_id, name, cutoff) in cutoffs {
+        let mut pruned = 0;
+        loop {
+            let outcome = writer
+                .prune_messages(topic_id, cutoff, config.chunk_size)
+                .await?;
+            pruned += outcome.deleted;
+            if outcome.deleted == 0 || outcome.through_seq >= cutoff {
+                break;
+            }
+            // Let queued publishes through between chunks
+            tokio::task::yield_now().await;
+        }
+
+        if pruned > 0 {
+            record_messages_pruned(&name, pruned);
+            tracing::info!(topic = %name, pruned, through_seq = cutoff, "Retention applied");
+        }
+        total += pruned;
+    }
+
+    Ok(total)
+}
+
+/// Run retention enforcement periodically until shutdown is signaled.
+pub async fn run_retention_task(
+    writer: WriterHandle,
+    reader_pool: ReaderPool,
+    config: RetentionConfig,
+    mut shutdown_rx: watch::Receiver<bool>,
+) {
+    let mut interval = tokio::time::interval(config.interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    tracing::info!(
+        interval_secs = config.interval.as_secs(),
+        chunk_size = config.chunk_size,
+        "Retention task started"
+    );
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                if let Err(e) = enforce_retention(&writer, &reader_pool, &config).await {
+                    tracing::warn!(error = %e, "Retention pass failed");
+                }
+            }
+            _ = shutdown_rx.changed() => {
+                tracing::info!("Retention task shutting down");
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flow::notify::NotificationBus;
+    use crate::storage::batch::BatchConfig;
+    use crate::storage::schema::{fetch_messages_from_seq, get_topic_by_name};
+    use crate::storage::writer::{MessageInput, Writer};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_enforce_retention_prunes_in_chunks() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+
+        let writer = Writer::spawn(
+            &db_path,
+            NotificationBus::new(16),
+            100,
+            BatchConfig::test_config(),
+            100,
+            true,
+            0,
+            0,
+        )
+        .unwrap();
+        let handle = writer.handle();
+
+        for i in 0..10 {
+            handle
+                .publish(
+                    "orders".into(),
+                    MessageInput {
+                        message_id: format!("msg-{i}"),
+                        ..Default::default()
+                    },
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+        handle
+            .publish(
+                "events".into(),
+                MessageInput {
+                    message_id: "evt-0".into(),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let reader_pool = ReaderPool::new(&db_path, 2).unwrap();
+        let orders_id = get_topic_by_name(&reader_pool.get().unwrap(), "orders")
+            .unwrap()
+            .unwrap()
+            .id;
+
+        // Server default keeps 5 messages; "orders" overrides it to 2
+        handle
+            .set_topic_retention(
+                orders_id,
+                RetentionPolicy {
+                    max_messages: Some(2),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let config = RetentionConfig::from_config(None, Some(5), None, 60, 3);
+        let pruned = enforce_retention(&handle, &reader_pool, &config)
+            .await
+            .unwrap();
+        assert_eq!(pruned, 8);
+
+        let remaining =
+            fetch_messages_from_seq(&reader_pool.get().unwrap(), orders_id, &[], 0, 100).unwrap();
+        let ids: Vec<_> = remaining.iter().map(|m| m.message_id.as_str()).collect();
+        assert_eq!(ids, vec!["msg-8", "msg-9"]);
+
+        handle.shutdown().await.unwrap();
+        writer.join().unwrap();
+    }
+}
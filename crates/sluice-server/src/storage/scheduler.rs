@@ -0,0 +1,53 @@
+//! Background release of scheduled messages.
+//!
+//! Messages published with a future `deliver_at` wait outside their topic.
+//! This task sleeps until the earliest of them is due and then has the
+//! writer append every due message, which wakes subscribers through the
+//! notification bus like any other publish.
+
+use std::time::Duration;
+use tokio::sync::watch;
+
+use super::writer::WriterHandle;
+use crate::now_millis;
+
+/// Delay before retrying after the writer failed to release messages.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Release scheduled messages as they become due until shutdown is signaled.
+pub async fn run_scheduler_task(writer: WriterHandle, mut shutdown_rx: watch::Receiver<bool>) {
+    let mut next_scheduled = writer.next_scheduled();
+
+    tracing::info!("Scheduler task started");
+
+    loop {
+        let next = *next_scheduled.borrow_and_update();
+        let wait = next.map(|deliver_at| {
+            Duration::from_millis(u64::try_from(deliver_at - now_millis()).unwrap_or(0))
+        });
+
+        tokio::select! {
+            _ = tokio::time::sleep(wait.unwrap_or_default()), if wait.is_some() => {
+                match writer.release_scheduled().await {
+                    Ok(released) => {
+                        tracing::debug!(released, "Scheduled messages released");
+                    }
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Releasing scheduled messages failed");
+                        tokio::time::sleep(RETRY_DELAY).await;
+                    }
+                }
+            }
+            changed = next_scheduled.changed() => {
+                // The writer is gone
+                if changed.is_err() {
+                    break;
+                }
+            }
+            _ = shutdown_rx.changed() => {
+                tracing::info!("Scheduler task shutting down");
+                break;
+            }
+        }
+    }
+}
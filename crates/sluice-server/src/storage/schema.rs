@@ -3,7 +3,13 @@
 //! Defines the database schema and provides CRUD operations for:
 //! - Topics (auto-created on first publish)
 //! - Messages (immutable after creation)
+//! - Topic partitions (per-partition sequence counters)
 //! - Subscriptions (cursor tracking for consumer groups)
+//! - Partition cursors (per-partition progress on partitioned topics)
+//! - Subscription acks (individual ACKs above the cursor)
+//! - Topic configuration (retention overrides, size limit, description)
+//! - Topic statistics and deletion for the admin RPCs
+//! - Idempotency keys (publish deduplication within a window)
 
 use rusqlite::{params, Connection, OptionalExtension, Result};
 
@@ -12,7 +18,12 @@ use rusqlite::{params, Connection, OptionalExtension, Result};
 /// Includes:
 /// - Topics table (auto-created streams)
 /// - Messages table (durable message storage)
+/// - Topic partitions table (last sequence assigned per partition)
 /// - Subscriptions table (cursor tracking)
+/// - Partition cursors table (cursor tracking per partition)
+/// - Subscription acks table (out-of-order individual ACKs)
+/// - Topic config table (per-topic overrides and description)
+/// - Idempotency keys table (recent publishes by idempotency key)
 const SCHEMA: &str = r#"
 -- Pragma configuration (applied separately on connection open)
 
@@ -20,7 +31,9 @@ const SCHEMA: &str = r#"
 CREATE TABLE IF NOT EXISTS topics (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     name TEXT NOT NULL UNIQUE,
-    created_at INTEGER NOT NULL
+    created_at INTEGER NOT NULL,
+    partition_count INTEGER NOT NULL DEFAULT 1,
+    last_seq INTEGER NOT NULL DEFAULT 0
 );
 
 -- Messages table
@@ -30,13 +43,30 @@ CREATE TABLE IF NOT EXISTS messages (
     message_id TEXT NOT NULL,
     payload BLOB,
     attributes TEXT,
-    created_at INTEGER NOT NULL
+    created_at INTEGER NOT NULL,
+    key TEXT,
+    partition INTEGER NOT NULL DEFAULT 0,
+    partition_seq INTEGER NOT NULL DEFAULT 0,
+    expires_at INTEGER,
+    priority INTEGER NOT NULL DEFAULT 0
 );
 
 -- Index for subscription seeking: fetch messages for topic after cursor
 CREATE INDEX IF NOT EXISTS idx_messages_topic_seq
 ON messages(topic_id, global_seq);
 
+-- Index for time-based seeking: find the first message at or after a time
+CREATE INDEX IF NOT EXISTS idx_messages_topic_created
+ON messages(topic_id, created_at);
+
+-- Last sequence assigned in each partition (survives retention)
+CREATE TABLE IF NOT EXISTS topic_partitions (
+    topic_id INTEGER NOT NULL REFERENCES topics(id),
+    partition INTEGER NOT NULL,
+    last_seq INTEGER NOT NULL,
+    PRIMARY KEY (topic_id, partition)
+);
+
 -- Subscriptions table
 CREATE TABLE IF NOT EXISTS subscriptions (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -44,12 +74,90 @@ CREATE TABLE IF NOT EXISTS subscriptions (
     consumer_group TEXT NOT NULL,
     cursor_seq INTEGER NOT NULL DEFAULT 0,
     updated_at INTEGER,
+    reset_generation INTEGER NOT NULL DEFAULT 0,
     UNIQUE(topic_id, consumer_group)
 );
 
 -- Index for subscription lookups by topic and consumer group
 CREATE INDEX IF NOT EXISTS idx_subscriptions_topic_group
 ON subscriptions(topic_id, consumer_group);
+
+-- Per-partition cursors of consumer groups on partitioned topics. The
+-- group's cursor in subscriptions trails the slowest partition.
+CREATE TABLE IF NOT EXISTS partition_cursors (
+    topic_id INTEGER NOT NULL REFERENCES topics(id),
+    consumer_group TEXT NOT NULL,
+    partition INTEGER NOT NULL,
+    cursor_seq INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (topic_id, consumer_group, partition)
+);
+
+-- Individual ACKs above a consumer group's cursor (AckMode::INDIVIDUAL)
+CREATE TABLE IF NOT EXISTS subscription_acks (
+    topic_id INTEGER NOT NULL REFERENCES topics(id),
+    consumer_group TEXT NOT NULL,
+    global_seq INTEGER NOT NULL,
+    PRIMARY KEY (topic_id, consumer_group, global_seq)
+);
+
+-- Failed delivery attempts above a consumer group's cursor
+CREATE TABLE IF NOT EXISTS delivery_attempts (
+    topic_id INTEGER NOT NULL REFERENCES topics(id),
+    consumer_group TEXT NOT NULL,
+    global_seq INTEGER NOT NULL,
+    failed_attempts INTEGER NOT NULL,
+    PRIMARY KEY (topic_id, consumer_group, global_seq)
+);
+
+-- Per-topic configuration (NULL columns fall back to server defaults)
+CREATE TABLE IF NOT EXISTS topic_config (
+    topic_id INTEGER PRIMARY KEY REFERENCES topics(id),
+    retention_max_age_ms INTEGER,
+    retention_max_messages INTEGER,
+    retention_max_bytes INTEGER,
+    updated_at INTEGER NOT NULL,
+    max_message_bytes INTEGER,
+    description TEXT,
+    dead_letter_expired INTEGER NOT NULL DEFAULT 0,
+    priority_delivery INTEGER NOT NULL DEFAULT 0,
+    auto_create INTEGER
+);
+
+-- Publishes remembered by idempotency key for the dedup window
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    topic_id INTEGER NOT NULL REFERENCES topics(id),
+    idempotency_key TEXT NOT NULL,
+    message_id TEXT NOT NULL,
+    global_seq INTEGER NOT NULL,
+    partition INTEGER NOT NULL,
+    partition_seq INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (topic_id, idempotency_key)
+);
+
+-- Index for pruning idempotency keys that left the dedup window
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_created
+ON idempotency_keys(created_at);
+
+-- Messages published with a future delivery time. They are moved into
+-- messages, and only then sequenced, once they are due.
+CREATE TABLE IF NOT EXISTS scheduled_messages (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    topic_id INTEGER NOT NULL REFERENCES topics(id),
+    message_id TEXT NOT NULL,
+    payload BLOB,
+    attributes TEXT,
+    key TEXT,
+    partition INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    deliver_at INTEGER NOT NULL,
+    ttl_ms INTEGER,
+    priority INTEGER NOT NULL DEFAULT 0
+);
+
+-- Index for releasing scheduled messages in due order
+CREATE INDEX IF NOT EXISTS idx_scheduled_messages_due
+ON scheduled_messages(deliver_at, id);
 "#;
 
 /// Apply SQLite pragmas for optimal performance and durability.
@@ -81,9 +189,111 @@ pub fn apply_reader_pragmas(conn: &Connection) -> Result<()> {
 
 /// Initialize the database schema.
 ///
-/// Creates tables and indexes if they don't exist.
+/// Creates tables and indexes if they don't exist, and adds columns that
+/// were introduced after a database was first created.
 pub fn initialize_schema(conn: &Connection) -> Result<()> {
-    conn.execute_batch(SCHEMA)
+    conn.execute_batch(SCHEMA)?;
+    add_column_if_missing(conn, "messages", "key", "TEXT")?;
+    add_column_if_missing(
+        conn,
+        "topics",
+        "partition_count",
+        "INTEGER NOT NULL DEFAULT 1",
+    )?;
+    add_column_if_missing(conn, "messages", "partition", "INTEGER NOT NULL DEFAULT 0")?;
+    if add_column_if_missing(
+        conn,
+        "messages",
+        "partition_seq",
+        "INTEGER NOT NULL DEFAULT 0",
+    )? {
+        // Existing messages all live in partition 0 and keep their global
+        // sequence, so new messages continue above them
+        conn.execute_batch(
+            "UPDATE messages SET partition_seq = global_seq;
+             INSERT OR IGNORE INTO topic_partitions (topic_id, partition, last_seq)
+             SELECT topic_id, 0, MAX(global_seq) FROM messages GROUP BY topic_id;",
+        )?;
+    }
+    add_column_if_missing(conn, "topic_config", "max_message_bytes", "INTEGER")?;
+    add_column_if_missing(conn, "topic_config", "description", "TEXT")?;
+    add_column_if_missing(conn, "messages", "expires_at", "INTEGER")?;
+    add_column_if_missing(
+        conn,
+        "topic_config",
+        "dead_letter_expired",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+    add_column_if_missing(conn, "scheduled_messages", "ttl_ms", "INTEGER")?;
+    add_column_if_missing(conn, "messages", "priority", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(
+        conn,
+        "topic_config",
+        "priority_delivery",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+    add_column_if_missing(conn, "topic_config", "auto_create", "INTEGER")?;
+    add_column_if_missing(
+        conn,
+        "subscriptions",
+        "reset_generation",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+    add_column_if_missing(
+        conn,
+        "scheduled_messages",
+        "priority",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+    if add_column_if_missing(conn, "topics", "last_seq", "INTEGER NOT NULL DEFAULT 0")? {
+        // Deleted messages are lost, so this is the best that can be known
+        conn.execute(
+            "UPDATE topics SET last_seq = COALESCE((SELECT MAX(global_seq) FROM messages WHERE topic_id = topics.id), 0)",
+            [],
+        )?;
+    }
+    // Created here rather than in SCHEMA, which runs before expires_at and
+    // priority are added to an older database
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_messages_expires
+         ON messages(expires_at) WHERE expires_at IS NOT NULL;
+         CREATE INDEX IF NOT EXISTS idx_messages_topic_priority
+         ON messages(topic_id, priority DESC, global_seq);",
+    )?;
+    Ok(())
+}
+
+/// Add a column to an existing table unless it is already there.
+///
+/// Returns true if the column was added.
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<bool> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2)",
+        params![table, column],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        conn.execute_batch(&format!(
+            "ALTER TABLE {table} ADD COLUMN {column} {definition}"
+        ))?;
+    }
+    Ok(!exists)
+}
+
+/// Name prefix of the inbox topics of request streams.
+///
+/// Reserved: only the request service creates such topics, and they are
+/// never auto-created by a publish.
+pub const INBOX_PREFIX: &str = "_inbox.";
+
+/// Whether `name` is the inbox topic of a request stream.
+pub fn is_inbox(name: &str) -> bool {
+    name.starts_with(INBOX_PREFIX)
 }
 
 /// Topic entity for database operations.
@@ -92,6 +302,7 @@ pub struct Topic {
     pub id: i64,
     pub name: String,
     pub created_at: i64,
+    pub partition_count: u32,
 }
 
 /// Message entity for database operations.
@@ -103,6 +314,30 @@ pub struct Message {
     pub payload: Option<Vec<u8>>,
     pub attributes: Option<String>,
     pub created_at: i64,
+    pub key: Option<String>,
+    pub partition: u32,
+    pub partition_seq: i64,
+    /// Time (Unix epoch ms) after which the message is never delivered.
+    pub expires_at: Option<i64>,
+    /// Delivery priority, 0 (default) to 9.
+    pub priority: u32,
+}
+
+/// A message waiting for its delivery time.
+#[derive(Debug, Clone)]
+pub struct ScheduledMessage {
+    pub id: i64,
+    pub topic_id: i64,
+    pub message_id: String,
+    pub payload: Option<Vec<u8>>,
+    pub attributes: Option<String>,
+    pub key: Option<String>,
+    pub partition: u32,
+    pub created_at: i64,
+    pub deliver_at: i64,
+    /// Time to live, counted from `deliver_at`.
+    pub ttl_ms: Option<i64>,
+    pub priority: u32,
 }
 
 /// Subscription entity for database operations.
@@ -113,6 +348,106 @@ pub struct Subscription {
     pub consumer_group: String,
     pub cursor_seq: i64,
     pub updated_at: Option<i64>,
+    /// Number of times the group was reset; cursor updates made by a
+    /// connection that loaded an earlier generation are rejected.
+    pub reset_generation: i64,
+}
+
+/// Retention limits for a topic.
+///
+/// Each limit is optional; `None` means "no limit" for server-wide defaults
+/// and "use the server default" for per-topic overrides.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Delete messages older than this many milliseconds.
+    pub max_age_ms: Option<i64>,
+    /// Keep at most this many messages.
+    pub max_messages: Option<i64>,
+    /// Keep at most this many bytes of payload and attributes.
+    pub max_bytes: Option<i64>,
+}
+
+impl RetentionPolicy {
+    /// Returns true if no limit is configured.
+    pub fn is_unlimited(&self) -> bool {
+        self.max_age_ms.is_none() && self.max_messages.is_none() && self.max_bytes.is_none()
+    }
+
+    /// Fill unset limits from `defaults`.
+    pub fn with_defaults(self, defaults: &RetentionPolicy) -> Self {
+        Self {
+            max_age_ms: self.max_age_ms.or(defaults.max_age_ms),
+            max_messages: self.max_messages.or(defaults.max_messages),
+            max_bytes: self.max_bytes.or(defaults.max_bytes),
+        }
+    }
+}
+
+/// Configuration stored for a topic.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TopicSettings {
+    /// Retention overrides; unset limits use the server defaults.
+    pub retention: RetentionPolicy,
+    /// Largest payload accepted by publishes, in bytes.
+    pub max_message_bytes: Option<i64>,
+    pub description: Option<String>,
+    /// Move expired messages to the dead-letter topic instead of deleting
+    /// them.
+    pub dead_letter_expired: bool,
+    /// Deliver higher-priority messages first to subscribers using
+    /// individual acks.
+    pub priority_delivery: bool,
+    /// Whether publishing to an unknown topic under this one creates it;
+    /// unset follows the server setting.
+    pub auto_create: Option<bool>,
+}
+
+/// A publish remembered under its idempotency key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdempotentPublish {
+    pub message_id: String,
+    pub global_seq: i64,
+    pub partition: u32,
+    pub partition_seq: i64,
+    pub created_at: i64,
+}
+
+/// Storage statistics of a topic's messages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TopicStats {
+    pub message_count: i64,
+    /// Lowest stored sequence (0 if the topic is empty).
+    pub min_seq: i64,
+    /// Highest stored sequence (0 if the topic is empty).
+    pub max_seq: i64,
+    /// Payload and attribute bytes, as counted by retention.
+    pub size_bytes: i64,
+    /// Creation time of the oldest stored message (0 if the topic is empty).
+    pub oldest_created_at: i64,
+    /// Creation time of the newest stored message (0 if the topic is empty).
+    pub newest_created_at: i64,
+    /// Messages waiting for their delivery time, not counted above.
+    pub scheduled_count: i64,
+}
+
+/// Outcome of deleting a topic.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DeleteOutcome {
+    /// Number of messages deleted.
+    pub messages_deleted: usize,
+    /// Number of consumer groups deleted.
+    pub consumer_groups_deleted: usize,
+}
+
+/// Outcome of pruning one chunk of messages.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PruneOutcome {
+    /// Number of messages deleted.
+    pub deleted: usize,
+    /// Highest sequence deleted (0 if nothing was deleted).
+    pub through_seq: i64,
+    /// Number of subscriptions whose cursor was moved forward.
+    pub cursors_advanced: usize,
 }
 
 /// Get or create a topic by name.
@@ -134,38 +469,264 @@ pub fn insert_or_get_topic(conn: &Connection, name: &str, created_at: i64) -> Re
     )
 }
 
+/// Create a topic with a fixed number of partitions.
+///
+/// Returns `None` if a topic with this name already exists.
+pub fn create_topic(
+    conn: &Connection,
+    name: &str,
+    partition_count: u32,
+    created_at: i64,
+) -> Result<Option<Topic>> {
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO topics (name, created_at, partition_count) VALUES (?1, ?2, ?3)",
+        params![name, created_at, partition_count],
+    )?;
+    if inserted == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(Topic {
+        id: conn.last_insert_rowid(),
+        name: name.to_string(),
+        created_at,
+        partition_count,
+    }))
+}
+
 /// Get a topic by name.
 pub fn get_topic_by_name(conn: &Connection, name: &str) -> Result<Option<Topic>> {
     conn.query_row(
-        "SELECT id, name, created_at FROM topics WHERE name = ?1",
+        "SELECT id, name, created_at, partition_count FROM topics WHERE name = ?1",
         params![name],
         |row| {
             Ok(Topic {
                 id: row.get(0)?,
                 name: row.get(1)?,
                 created_at: row.get(2)?,
+                partition_count: row.get(3)?,
             })
         },
     )
     .optional()
 }
 
-/// Insert a message and return the global sequence number.
+/// Get the name of a topic by ID.
+pub fn get_topic_name(conn: &Connection, topic_id: i64) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT name FROM topics WHERE id = ?1",
+        params![topic_id],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Get the number of partitions of a topic.
+pub fn get_topic_partition_count(conn: &Connection, topic_id: i64) -> Result<u32> {
+    conn.query_row(
+        "SELECT partition_count FROM topics WHERE id = ?1",
+        params![topic_id],
+        |row| row.get(0),
+    )
+}
+
+/// Get the last sequence assigned in each partition of a topic.
+///
+/// Returns one entry per partition, indexed by partition number.
+pub fn get_partition_max_seqs(conn: &Connection, topic_id: i64) -> Result<Vec<i64>> {
+    let partition_count = get_topic_partition_count(conn, topic_id)?;
+    let mut max_seqs = vec![0; partition_count as usize];
+
+    let mut stmt =
+        conn.prepare("SELECT partition, last_seq FROM topic_partitions WHERE topic_id = ?1")?;
+    let rows = stmt.query_map(params![topic_id], |row| {
+        Ok((row.get::<_, usize>(0)?, row.get::<_, i64>(1)?))
+    })?;
+    for row in rows {
+        let (partition, last_seq) = row?;
+        if let Some(max_seq) = max_seqs.get_mut(partition) {
+            *max_seq = last_seq;
+        }
+    }
+
+    Ok(max_seqs)
+}
+
+/// Insert a message into a partition.
+///
+/// `expires_at` is the time (Unix epoch ms) after which the message is
+/// never delivered. `priority` only matters on topics with priority
+/// delivery.
+///
+/// Returns the global sequence number and the sequence number within the
+/// partition.
+#[allow(clippy::too_many_arguments)]
 pub fn insert_message(
     conn: &Connection,
     topic_id: i64,
+    partition: u32,
     message_id: &str,
     payload: Option<&[u8]>,
     attributes: Option<&str>,
+    key: Option<&str>,
     created_at: i64,
-) -> Result<i64> {
+    expires_at: Option<i64>,
+    priority: u32,
+) -> Result<(i64, i64)> {
+    let partition_seq: i64 = conn.query_row(
+        "INSERT INTO topic_partitions (topic_id, partition, last_seq) VALUES (?1, ?2, 1)
+         ON CONFLICT (topic_id, partition) DO UPDATE SET last_seq = last_seq + 1
+         RETURNING last_seq",
+        params![topic_id, partition],
+        |row| row.get(0),
+    )?;
+
+    conn.execute(
+        "INSERT INTO messages (topic_id, message_id, payload, attributes, key, partition, partition_seq, created_at, expires_at, priority) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![topic_id, message_id, payload, attributes, key, partition, partition_seq, created_at, expires_at, priority],
+    )?;
+    let global_seq = conn.last_insert_rowid();
+
     conn.execute(
-        "INSERT INTO messages (topic_id, message_id, payload, attributes, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
-        params![topic_id, message_id, payload, attributes, created_at],
+        "UPDATE topics SET last_seq = ?1 WHERE id = ?2",
+        params![global_seq, topic_id],
+    )?;
+    Ok((global_seq, partition_seq))
+}
+
+/// Store a message to be moved into `messages` at `deliver_at`.
+pub fn insert_scheduled_message(conn: &Connection, msg: &ScheduledMessage) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO scheduled_messages (topic_id, message_id, payload, attributes, key, partition, created_at, deliver_at, ttl_ms, priority)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            msg.topic_id,
+            msg.message_id,
+            msg.payload,
+            msg.attributes,
+            msg.key,
+            msg.partition,
+            msg.created_at,
+            msg.deliver_at,
+            msg.ttl_ms,
+            msg.priority
+        ],
     )?;
     Ok(conn.last_insert_rowid())
 }
 
+/// Remove and return up to `limit` scheduled messages due at `now`, in due
+/// order.
+pub fn take_due_scheduled_messages(
+    conn: &Connection,
+    now: i64,
+    limit: i64,
+) -> Result<Vec<ScheduledMessage>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, topic_id, message_id, payload, attributes, key, partition, created_at, deliver_at, ttl_ms, priority
+         FROM scheduled_messages WHERE deliver_at <= ?1 ORDER BY deliver_at, id LIMIT ?2",
+    )?;
+    let due = stmt
+        .query_map(params![now, limit], |row| {
+            Ok(ScheduledMessage {
+                id: row.get(0)?,
+                topic_id: row.get(1)?,
+                message_id: row.get(2)?,
+                payload: row.get(3)?,
+                attributes: row.get(4)?,
+                key: row.get(5)?,
+                partition: row.get(6)?,
+                created_at: row.get(7)?,
+                deliver_at: row.get(8)?,
+                ttl_ms: row.get(9)?,
+                priority: row.get(10)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    // Everything up to the last message taken, in due order
+    if let Some(last) = due.last() {
+        conn.execute(
+            "DELETE FROM scheduled_messages WHERE deliver_at < ?1 OR (deliver_at = ?1 AND id <= ?2)",
+            params![last.deliver_at, last.id],
+        )?;
+    }
+    Ok(due)
+}
+
+/// Earliest delivery time of any scheduled message.
+pub fn next_scheduled_delivery(conn: &Connection) -> Result<Option<i64>> {
+    conn.query_row(
+        "SELECT MIN(deliver_at) FROM scheduled_messages",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// Delete a topic's scheduled messages, returning how many there were.
+pub fn delete_scheduled_messages(conn: &Connection, topic_id: i64) -> Result<usize> {
+    conn.execute(
+        "DELETE FROM scheduled_messages WHERE topic_id = ?1",
+        params![topic_id],
+    )
+}
+
+/// Find the publish recorded under an idempotency key at or after `since`.
+pub fn get_idempotent_publish(
+    conn: &Connection,
+    topic_id: i64,
+    idempotency_key: &str,
+    since: i64,
+) -> Result<Option<IdempotentPublish>> {
+    conn.query_row(
+        "SELECT message_id, global_seq, partition, partition_seq, created_at FROM idempotency_keys
+         WHERE topic_id = ?1 AND idempotency_key = ?2 AND created_at >= ?3",
+        params![topic_id, idempotency_key, since],
+        |row| {
+            Ok(IdempotentPublish {
+                message_id: row.get(0)?,
+                global_seq: row.get(1)?,
+                partition: row.get(2)?,
+                partition_seq: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        },
+    )
+    .optional()
+}
+
+/// Remember a publish under its idempotency key.
+///
+/// Replaces an expired entry for the same key that was not pruned yet.
+pub fn record_idempotency_key(
+    conn: &Connection,
+    topic_id: i64,
+    idempotency_key: &str,
+    publish: &IdempotentPublish,
+) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO idempotency_keys (topic_id, idempotency_key, message_id, global_seq, partition, partition_seq, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            topic_id,
+            idempotency_key,
+            publish.message_id,
+            publish.global_seq,
+            publish.partition,
+            publish.partition_seq,
+            publish.created_at
+        ],
+    )?;
+    Ok(())
+}
+
+/// Forget idempotency keys recorded before `before`, returning how many.
+pub fn prune_idempotency_keys(conn: &Connection, before: i64) -> Result<usize> {
+    conn.execute(
+        "DELETE FROM idempotency_keys WHERE created_at < ?1",
+        params![before],
+    )
+}
+
 /// Get or create a subscription, returning the subscription info.
 pub fn get_or_create_subscription(
     conn: &Connection,
@@ -179,9 +740,21 @@ pub fn get_or_create_subscription(
         params![topic_id, consumer_group, now],
     )?;
 
+    // Partitioned topics track each partition's progress separately
+    let partition_count = get_topic_partition_count(conn, topic_id)?;
+    if partition_count > 1 {
+        for partition in 0..partition_count {
+            conn.execute(
+                "INSERT OR IGNORE INTO partition_cursors (topic_id, consumer_group, partition, cursor_seq)
+                 SELECT topic_id, consumer_group, ?3, cursor_seq FROM subscriptions WHERE topic_id = ?1 AND consumer_group = ?2",
+                params![topic_id, consumer_group, partition],
+            )?;
+        }
+    }
+
     // Fetch the subscription
     conn.query_row(
-        "SELECT id, topic_id, consumer_group, cursor_seq, updated_at FROM subscriptions WHERE topic_id = ?1 AND consumer_group = ?2",
+        "SELECT id, topic_id, consumer_group, cursor_seq, updated_at, reset_generation FROM subscriptions WHERE topic_id = ?1 AND consumer_group = ?2",
         params![topic_id, consumer_group],
         |row| {
             Ok(Subscription {
@@ -190,6 +763,7 @@ pub fn get_or_create_subscription(
                 consumer_group: row.get(2)?,
                 cursor_seq: row.get(3)?,
                 updated_at: row.get(4)?,
+                reset_generation: row.get(5)?,
             })
         },
     )
@@ -198,81 +772,757 @@ pub fn get_or_create_subscription(
 /// Update the cursor for a subscription.
 ///
 /// Only advances the cursor if the new position is greater than current.
+/// On a partitioned topic, a non-empty `partitions` limits the move to
+/// those partitions and the subscription's own cursor follows the slowest
+/// partition. Returns the number of subscription cursors moved.
 /// This ensures idempotent ACKs.
 pub fn update_cursor(
     conn: &Connection,
     topic_id: i64,
     consumer_group: &str,
+    partitions: &[u32],
     cursor_seq: i64,
     now: i64,
 ) -> Result<usize> {
+    if partitions.is_empty() {
+        conn.execute(
+            "UPDATE partition_cursors SET cursor_seq = ?1 WHERE topic_id = ?2 AND consumer_group = ?3 AND cursor_seq < ?1",
+            params![cursor_seq, topic_id, consumer_group],
+        )?;
+        return conn.execute(
+            "UPDATE subscriptions SET cursor_seq = ?1, updated_at = ?2 WHERE topic_id = ?3 AND consumer_group = ?4 AND cursor_seq < ?1",
+            params![cursor_seq, now, topic_id, consumer_group],
+        );
+    }
+
+    for &partition in partitions {
+        conn.execute(
+            "UPDATE partition_cursors SET cursor_seq = ?1 WHERE topic_id = ?2 AND consumer_group = ?3 AND partition = ?4 AND cursor_seq < ?1",
+            params![cursor_seq, topic_id, consumer_group, partition],
+        )?;
+    }
+
     conn.execute(
-        "UPDATE subscriptions SET cursor_seq = ?1, updated_at = ?2 WHERE topic_id = ?3 AND consumer_group = ?4 AND cursor_seq < ?1",
-        params![cursor_seq, now, topic_id, consumer_group],
+        "UPDATE subscriptions SET cursor_seq = slowest.cursor_seq, updated_at = ?1
+         FROM (SELECT MIN(cursor_seq) AS cursor_seq FROM partition_cursors WHERE topic_id = ?2 AND consumer_group = ?3) AS slowest
+         WHERE topic_id = ?2 AND consumer_group = ?3 AND subscriptions.cursor_seq < slowest.cursor_seq",
+        params![now, topic_id, consumer_group],
     )
 }
 
-/// Fetch messages for a subscription starting after a given sequence.
-pub fn fetch_messages_from_seq(
+/// Get the per-partition cursors of a consumer group.
+///
+/// Empty unless the topic is partitioned. Returns `(partition, cursor_seq)`
+/// pairs in partition order.
+pub fn get_partition_cursors(
     conn: &Connection,
     topic_id: i64,
-    after_seq: i64,
-    limit: i64,
-) -> Result<Vec<Message>> {
+    consumer_group: &str,
+) -> Result<Vec<(u32, i64)>> {
     let mut stmt = conn.prepare(
-        "SELECT global_seq, topic_id, message_id, payload, attributes, created_at FROM messages WHERE topic_id = ?1 AND global_seq > ?2 ORDER BY global_seq ASC LIMIT ?3",
+        "SELECT partition, cursor_seq FROM partition_cursors WHERE topic_id = ?1 AND consumer_group = ?2 ORDER BY partition ASC",
     )?;
-
-    let rows = stmt.query_map(params![topic_id, after_seq, limit], |row| {
-        Ok(Message {
-            global_seq: row.get(0)?,
-            topic_id: row.get(1)?,
-            message_id: row.get(2)?,
-            payload: row.get(3)?,
-            attributes: row.get(4)?,
-            created_at: row.get(5)?,
-        })
+    let rows = stmt.query_map(params![topic_id, consumer_group], |row| {
+        Ok((row.get(0)?, row.get(1)?))
     })?;
+    rows.collect()
+}
+
+/// Record an individual ACK and move the cursor to `cursor_seq`.
+///
+/// ACKs at or below the cursor are implied by it and are not stored;
+/// stored ACKs that the cursor has caught up with are removed. See
+/// [`update_cursor`] for `partitions`.
+pub fn record_individual_ack(
+    conn: &Connection,
+    topic_id: i64,
+    consumer_group: &str,
+    partitions: &[u32],
+    seq: i64,
+    cursor_seq: i64,
+    now: i64,
+) -> Result<()> {
+    if seq > cursor_seq {
+        conn.execute(
+            "INSERT OR IGNORE INTO subscription_acks (topic_id, consumer_group, global_seq) VALUES (?1, ?2, ?3)",
+            params![topic_id, consumer_group, seq],
+        )?;
+    }
+
+    conn.execute(
+        "DELETE FROM delivery_attempts WHERE topic_id = ?1 AND consumer_group = ?2 AND global_seq = ?3",
+        params![topic_id, consumer_group, seq],
+    )?;
+
+    if update_cursor(conn, topic_id, consumer_group, partitions, cursor_seq, now)? > 0 {
+        conn.execute(
+            "DELETE FROM subscription_acks WHERE topic_id = ?1 AND consumer_group = ?2 AND global_seq <= (SELECT cursor_seq FROM subscriptions WHERE topic_id = ?1 AND consumer_group = ?2)",
+            params![topic_id, consumer_group],
+        )?;
+    }
+
+    Ok(())
+}
 
+/// Get the individual ACKs stored above a consumer group's cursor.
+pub fn get_subscription_acks(
+    conn: &Connection,
+    topic_id: i64,
+    consumer_group: &str,
+) -> Result<Vec<i64>> {
+    let mut stmt = conn.prepare(
+        "SELECT global_seq FROM subscription_acks WHERE topic_id = ?1 AND consumer_group = ?2 ORDER BY global_seq ASC",
+    )?;
+    let rows = stmt.query_map(params![topic_id, consumer_group], |row| row.get(0))?;
     rows.collect()
 }
 
-/// Get the maximum sequence number for a topic.
+/// Record a failed delivery attempt and return the new failure count.
 ///
-/// Used for LATEST initial position in subscriptions.
-pub fn get_topic_max_seq(conn: &Connection, topic_id: i64) -> Result<i64> {
+/// Counts for messages the consumer group has since acked past are
+/// dropped at the same time.
+pub fn record_delivery_failure(
+    conn: &Connection,
+    topic_id: i64,
+    consumer_group: &str,
+    seq: i64,
+) -> Result<u32> {
+    conn.execute(
+        "DELETE FROM delivery_attempts WHERE topic_id = ?1 AND consumer_group = ?2 AND global_seq <= (SELECT cursor_seq FROM subscriptions WHERE topic_id = ?1 AND consumer_group = ?2)",
+        params![topic_id, consumer_group],
+    )?;
+
     conn.query_row(
-        "SELECT COALESCE(MAX(global_seq), 0) FROM messages WHERE topic_id = ?1",
-        params![topic_id],
+        "INSERT INTO delivery_attempts (topic_id, consumer_group, global_seq, failed_attempts) VALUES (?1, ?2, ?3, 1)
+         ON CONFLICT (topic_id, consumer_group, global_seq) DO UPDATE SET failed_attempts = failed_attempts + 1
+         RETURNING failed_attempts",
+        params![topic_id, consumer_group, seq],
         |row| row.get(0),
     )
 }
 
-/// Look up a message by its message_id to get its sequence number.
-pub fn get_message_seq_by_id(conn: &Connection, message_id: &str) -> Result<Option<i64>> {
+/// Get failed delivery counts for a consumer group above `after_seq`.
+pub fn get_delivery_failures(
+    conn: &Connection,
+    topic_id: i64,
+    consumer_group: &str,
+    after_seq: i64,
+) -> Result<Vec<(i64, u32)>> {
+    let mut stmt = conn.prepare(
+        "SELECT global_seq, failed_attempts FROM delivery_attempts WHERE topic_id = ?1 AND consumer_group = ?2 AND global_seq > ?3",
+    )?;
+    let rows = stmt.query_map(params![topic_id, consumer_group, after_seq], |row| {
+        Ok((row.get(0)?, row.get(1)?))
+    })?;
+    rows.collect()
+}
+
+/// Columns read into a [`Message`], in the order `message_from_row` expects.
+const MESSAGE_COLUMNS: &str =
+    "global_seq, topic_id, message_id, payload, attributes, created_at, key, partition, partition_seq, expires_at, priority";
+
+fn message_from_row(row: &rusqlite::Row<'_>) -> Result<Message> {
+    Ok(Message {
+        global_seq: row.get(0)?,
+        topic_id: row.get(1)?,
+        message_id: row.get(2)?,
+        payload: row.get(3)?,
+        attributes: row.get(4)?,
+        created_at: row.get(5)?,
+        key: row.get(6)?,
+        partition: row.get(7)?,
+        partition_seq: row.get(8)?,
+        expires_at: row.get(9)?,
+        priority: row.get(10)?,
+    })
+}
+
+/// Fetch a single message by sequence number.
+pub fn get_message_by_seq(conn: &Connection, topic_id: i64, seq: i64) -> Result<Option<Message>> {
     conn.query_row(
-        "SELECT global_seq FROM messages WHERE message_id = ?1",
-        params![message_id],
-        |row| row.get(0),
+        &format!("SELECT {MESSAGE_COLUMNS} FROM messages WHERE topic_id = ?1 AND global_seq = ?2"),
+        params![topic_id, seq],
+        message_from_row,
     )
     .optional()
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Fetch messages for a subscription starting after a given sequence.
+///
+/// A non-empty `partitions` only returns messages from those partitions.
+pub fn fetch_messages_from_seq(
+    conn: &Connection,
+    topic_id: i64,
+    partitions: &[u32],
+    after_seq: i64,
+    limit: i64,
+) -> Result<Vec<Message>> {
+    let partition_filter = partition_filter(partitions);
 
-    fn setup_test_db() -> Connection {
-        let conn = Connection::open_in_memory().unwrap();
-        apply_pragmas(&conn).unwrap();
-        initialize_schema(&conn).unwrap();
-        conn
-    }
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {MESSAGE_COLUMNS} FROM messages WHERE topic_id = ?1 AND global_seq > ?2{partition_filter} ORDER BY global_seq ASC LIMIT ?3"
+    ))?;
 
-    #[test]
-    fn test_schema_initialization() {
-        let conn = setup_test_db();
-        // Verify tables exist by querying them
+    let rows = stmt.query_map(params![topic_id, after_seq, limit], message_from_row)?;
+
+    rows.collect()
+}
+
+/// Fetch messages after a given sequence, highest priority first and in
+/// sequence order within a priority.
+///
+/// `resume_after` is the `(priority, global_seq)` of the last message of
+/// the previous page, to continue a scan. A non-empty `partitions` only
+/// returns messages from those partitions.
+pub fn fetch_messages_by_priority(
+    conn: &Connection,
+    topic_id: i64,
+    partitions: &[u32],
+    after_seq: i64,
+    resume_after: Option<(u32, i64)>,
+    limit: i64,
+) -> Result<Vec<Message>> {
+    let partition_filter = partition_filter(partitions);
+    // Past the end of the ordering when not resuming
+    let (priority, seq) = resume_after.map_or((i64::MAX, 0), |(p, s)| (i64::from(p), s));
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {MESSAGE_COLUMNS} FROM messages WHERE topic_id = ?1 AND global_seq > ?2{partition_filter}
+         AND (priority < ?3 OR (priority = ?3 AND global_seq > ?4))
+         ORDER BY priority DESC, global_seq ASC LIMIT ?5"
+    ))?;
+
+    let rows = stmt.query_map(
+        params![topic_id, after_seq, priority, seq, limit],
+        message_from_row,
+    )?;
+
+    rows.collect()
+}
+
+/// Fetch the sequence numbers of messages after a given sequence, in order.
+///
+/// A non-empty `partitions` only returns messages from those partitions.
+pub fn fetch_seqs_from_seq(
+    conn: &Connection,
+    topic_id: i64,
+    partitions: &[u32],
+    after_seq: i64,
+    limit: i64,
+) -> Result<Vec<i64>> {
+    let partition_filter = partition_filter(partitions);
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT global_seq FROM messages WHERE topic_id = ?1 AND global_seq > ?2{partition_filter} ORDER BY global_seq ASC LIMIT ?3"
+    ))?;
+
+    let rows = stmt.query_map(params![topic_id, after_seq, limit], |row| row.get(0))?;
+
+    rows.collect()
+}
+
+/// SQL condition limiting a query to `partitions`, empty for all of them.
+fn partition_filter(partitions: &[u32]) -> String {
+    if partitions.is_empty() {
+        String::new()
+    } else {
+        let list: Vec<String> = partitions.iter().map(u32::to_string).collect();
+        format!(" AND partition IN ({})", list.join(", "))
+    }
+}
+
+/// Get the maximum sequence number for a topic.
+///
+/// Used for LATEST initial position in subscriptions.
+pub fn get_topic_max_seq(conn: &Connection, topic_id: i64) -> Result<i64> {
+    conn.query_row(
+        "SELECT COALESCE(MAX(global_seq), 0) FROM messages WHERE topic_id = ?1",
+        params![topic_id],
+        |row| row.get(0),
+    )
+}
+
+/// Get the last sequence number ever assigned to a message of a topic.
+///
+/// Unlike [`get_topic_max_seq`] it does not go backwards when messages are
+/// expired, pruned or purged. Returns 0 if nothing was published.
+pub fn get_topic_last_seq(conn: &Connection, topic_id: i64) -> Result<i64> {
+    conn.query_row(
+        "SELECT last_seq FROM topics WHERE id = ?1",
+        params![topic_id],
+        |row| row.get(0),
+    )
+}
+
+/// Look up a message of a topic by its message_id to get its sequence number.
+///
+/// Returns `None` if the message does not exist or belongs to another topic.
+pub fn get_message_seq_by_id(
+    conn: &Connection,
+    topic_id: i64,
+    message_id: &str,
+) -> Result<Option<i64>> {
+    conn.query_row(
+        "SELECT global_seq FROM messages WHERE topic_id = ?1 AND message_id = ?2",
+        params![topic_id, message_id],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Get the topic a message belongs to by its message_id.
+pub fn get_message_topic_id(conn: &Connection, message_id: &str) -> Result<Option<i64>> {
+    conn.query_row(
+        "SELECT topic_id FROM messages WHERE message_id = ?1",
+        params![message_id],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// List all topic IDs with their names.
+pub fn list_topic_ids(conn: &Connection) -> Result<Vec<(i64, String)>> {
+    let mut stmt = conn.prepare("SELECT id, name FROM topics ORDER BY id ASC")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// Get message statistics for a topic.
+pub fn get_topic_stats(conn: &Connection, topic_id: i64) -> Result<TopicStats> {
+    conn.query_row(
+        "SELECT COUNT(*), COALESCE(MIN(global_seq), 0), COALESCE(MAX(global_seq), 0),
+                COALESCE(SUM(COALESCE(LENGTH(payload), 0) + COALESCE(LENGTH(attributes), 0)), 0),
+                COALESCE(MIN(created_at), 0), COALESCE(MAX(created_at), 0),
+                (SELECT COUNT(*) FROM scheduled_messages WHERE topic_id = ?1)
+         FROM messages WHERE topic_id = ?1",
+        params![topic_id],
+        |row| {
+            Ok(TopicStats {
+                message_count: row.get(0)?,
+                min_seq: row.get(1)?,
+                max_seq: row.get(2)?,
+                size_bytes: row.get(3)?,
+                oldest_created_at: row.get(4)?,
+                newest_created_at: row.get(5)?,
+                scheduled_count: row.get(6)?,
+            })
+        },
+    )
+}
+
+/// List the consumer groups of a topic, ordered by name.
+pub fn list_subscriptions(conn: &Connection, topic_id: i64) -> Result<Vec<Subscription>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, topic_id, consumer_group, cursor_seq, updated_at, reset_generation FROM subscriptions WHERE topic_id = ?1 ORDER BY consumer_group ASC",
+    )?;
+    let rows = stmt.query_map(params![topic_id], |row| {
+        Ok(Subscription {
+            id: row.get(0)?,
+            topic_id: row.get(1)?,
+            consumer_group: row.get(2)?,
+            cursor_seq: row.get(3)?,
+            updated_at: row.get(4)?,
+            reset_generation: row.get(5)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Get a consumer group's subscription without creating it.
+pub fn get_subscription(
+    conn: &Connection,
+    topic_id: i64,
+    consumer_group: &str,
+) -> Result<Option<Subscription>> {
+    conn.query_row(
+        "SELECT id, topic_id, consumer_group, cursor_seq, updated_at, reset_generation FROM subscriptions WHERE topic_id = ?1 AND consumer_group = ?2",
+        params![topic_id, consumer_group],
+        |row| {
+            Ok(Subscription {
+                id: row.get(0)?,
+                topic_id: row.get(1)?,
+                consumer_group: row.get(2)?,
+                cursor_seq: row.get(3)?,
+                updated_at: row.get(4)?,
+                reset_generation: row.get(5)?,
+            })
+        },
+    )
+    .optional()
+}
+
+/// Move a consumer group's cursor to `cursor_seq`, forwards or backwards.
+///
+/// Every partition cursor moves with it, stored ACKs and failed delivery
+/// counts are dropped, and the reset generation is incremented. Returns
+/// false if the group does not exist.
+pub fn reset_cursor(
+    conn: &Connection,
+    topic_id: i64,
+    consumer_group: &str,
+    cursor_seq: i64,
+    now: i64,
+) -> Result<bool> {
+    let updated = conn.execute(
+        "UPDATE subscriptions SET cursor_seq = ?1, updated_at = ?2, reset_generation = reset_generation + 1 WHERE topic_id = ?3 AND consumer_group = ?4",
+        params![cursor_seq, now, topic_id, consumer_group],
+    )?;
+    if updated == 0 {
+        return Ok(false);
+    }
+
+    conn.execute(
+        "UPDATE partition_cursors SET cursor_seq = ?1 WHERE topic_id = ?2 AND consumer_group = ?3",
+        params![cursor_seq, topic_id, consumer_group],
+    )?;
+    clear_group_progress(conn, topic_id, consumer_group)?;
+    Ok(true)
+}
+
+/// Delete a consumer group with its cursors and stored ACKs.
+///
+/// Returns false if the group does not exist.
+pub fn delete_subscription(conn: &Connection, topic_id: i64, consumer_group: &str) -> Result<bool> {
+    let deleted = conn.execute(
+        "DELETE FROM subscriptions WHERE topic_id = ?1 AND consumer_group = ?2",
+        params![topic_id, consumer_group],
+    )?;
+    conn.execute(
+        "DELETE FROM partition_cursors WHERE topic_id = ?1 AND consumer_group = ?2",
+        params![topic_id, consumer_group],
+    )?;
+    clear_group_progress(conn, topic_id, consumer_group)?;
+    Ok(deleted > 0)
+}
+
+/// Drop a consumer group's individual ACKs and failed delivery counts.
+fn clear_group_progress(conn: &Connection, topic_id: i64, consumer_group: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM subscription_acks WHERE topic_id = ?1 AND consumer_group = ?2",
+        params![topic_id, consumer_group],
+    )?;
+    conn.execute(
+        "DELETE FROM delivery_attempts WHERE topic_id = ?1 AND consumer_group = ?2",
+        params![topic_id, consumer_group],
+    )?;
+    Ok(())
+}
+
+/// Get the sequence of a topic's oldest stored message, if any.
+pub fn get_topic_min_seq(conn: &Connection, topic_id: i64) -> Result<Option<i64>> {
+    conn.query_row(
+        "SELECT MIN(global_seq) FROM messages WHERE topic_id = ?1",
+        params![topic_id],
+        |row| row.get(0),
+    )
+}
+
+/// Get the sequence of the first message created at or after `timestamp`.
+///
+/// Timestamps are assigned by the single writer, so they follow sequence
+/// order and the `(topic_id, created_at)` index finds the message directly.
+pub fn first_seq_at_or_after(
+    conn: &Connection,
+    topic_id: i64,
+    timestamp: i64,
+) -> Result<Option<i64>> {
+    conn.query_row(
+        "SELECT global_seq FROM messages WHERE topic_id = ?1 AND created_at >= ?2 ORDER BY created_at ASC, global_seq ASC LIMIT 1",
+        params![topic_id, timestamp],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Count a topic's stored messages above `after_seq`.
+pub fn count_messages_after(conn: &Connection, topic_id: i64, after_seq: i64) -> Result<i64> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM messages WHERE topic_id = ?1 AND global_seq > ?2",
+        params![topic_id, after_seq],
+        |row| row.get(0),
+    )
+}
+
+/// Delete a topic together with its messages, consumer groups and settings.
+pub fn delete_topic(conn: &Connection, topic_id: i64) -> Result<DeleteOutcome> {
+    let messages_deleted = conn.execute(
+        "DELETE FROM messages WHERE topic_id = ?1",
+        params![topic_id],
+    )? + delete_scheduled_messages(conn, topic_id)?;
+    let consumer_groups_deleted = conn.execute(
+        "DELETE FROM subscriptions WHERE topic_id = ?1",
+        params![topic_id],
+    )?;
+
+    for table in [
+        "partition_cursors",
+        "subscription_acks",
+        "delivery_attempts",
+        "topic_partitions",
+        "topic_config",
+        "idempotency_keys",
+    ] {
+        conn.execute(
+            &format!("DELETE FROM {table} WHERE topic_id = ?1"),
+            params![topic_id],
+        )?;
+    }
+
+    conn.execute("DELETE FROM topics WHERE id = ?1", params![topic_id])?;
+
+    Ok(DeleteOutcome {
+        messages_deleted,
+        consumer_groups_deleted,
+    })
+}
+
+/// Get the per-topic retention overrides.
+///
+/// Returns an unlimited policy if the topic has no overrides.
+pub fn get_topic_retention(conn: &Connection, topic_id: i64) -> Result<RetentionPolicy> {
+    conn.query_row(
+        "SELECT retention_max_age_ms, retention_max_messages, retention_max_bytes FROM topic_config WHERE topic_id = ?1",
+        params![topic_id],
+        |row| {
+            Ok(RetentionPolicy {
+                max_age_ms: row.get(0)?,
+                max_messages: row.get(1)?,
+                max_bytes: row.get(2)?,
+            })
+        },
+    )
+    .optional()
+    .map(Option::unwrap_or_default)
+}
+
+/// Store the per-topic retention overrides, replacing any previous values.
+pub fn set_topic_retention(
+    conn: &Connection,
+    topic_id: i64,
+    policy: &RetentionPolicy,
+    now: i64,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO topic_config (topic_id, retention_max_age_ms, retention_max_messages, retention_max_bytes, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(topic_id) DO UPDATE SET retention_max_age_ms = excluded.retention_max_age_ms, retention_max_messages = excluded.retention_max_messages, retention_max_bytes = excluded.retention_max_bytes, updated_at = excluded.updated_at",
+        params![topic_id, policy.max_age_ms, policy.max_messages, policy.max_bytes, now],
+    )?;
+    Ok(())
+}
+
+/// Get the configuration stored for a topic.
+///
+/// Returns the defaults if the topic has no configuration.
+pub fn get_topic_settings(conn: &Connection, topic_id: i64) -> Result<TopicSettings> {
+    conn.query_row(
+        "SELECT retention_max_age_ms, retention_max_messages, retention_max_bytes, max_message_bytes, description, dead_letter_expired, priority_delivery, auto_create FROM topic_config WHERE topic_id = ?1",
+        params![topic_id],
+        |row| {
+            Ok(TopicSettings {
+                retention: RetentionPolicy {
+                    max_age_ms: row.get(0)?,
+                    max_messages: row.get(1)?,
+                    max_bytes: row.get(2)?,
+                },
+                max_message_bytes: row.get(3)?,
+                description: row.get(4)?,
+                dead_letter_expired: row.get(5)?,
+                priority_delivery: row.get(6)?,
+                auto_create: row.get(7)?,
+            })
+        },
+    )
+    .optional()
+    .map(Option::unwrap_or_default)
+}
+
+/// Store the configuration of a topic, replacing any previous values.
+pub fn set_topic_settings(
+    conn: &Connection,
+    topic_id: i64,
+    settings: &TopicSettings,
+    now: i64,
+) -> Result<()> {
+    let retention = &settings.retention;
+    conn.execute(
+        "INSERT INTO topic_config (topic_id, retention_max_age_ms, retention_max_messages, retention_max_bytes, max_message_bytes, description, dead_letter_expired, priority_delivery, auto_create, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+         ON CONFLICT(topic_id) DO UPDATE SET retention_max_age_ms = excluded.retention_max_age_ms, retention_max_messages = excluded.retention_max_messages, retention_max_bytes = excluded.retention_max_bytes, max_message_bytes = excluded.max_message_bytes, description = excluded.description, dead_letter_expired = excluded.dead_letter_expired, priority_delivery = excluded.priority_delivery, auto_create = excluded.auto_create, updated_at = excluded.updated_at",
+        params![
+            topic_id,
+            retention.max_age_ms,
+            retention.max_messages,
+            retention.max_bytes,
+            settings.max_message_bytes,
+            settings.description,
+            settings.dead_letter_expired,
+            settings.priority_delivery,
+            settings.auto_create,
+            now
+        ],
+    )?;
+    Ok(())
+}
+
+/// Compute the highest sequence that falls outside the retention policy.
+///
+/// Every message with `global_seq <= cutoff` should be deleted.
+/// Returns `None` if all messages are within the policy.
+pub fn retention_cutoff_seq(
+    conn: &Connection,
+    topic_id: i64,
+    policy: &RetentionPolicy,
+    now: i64,
+) -> Result<Option<i64>> {
+    let mut cutoff: Option<i64> = None;
+
+    if let Some(max_age_ms) = policy.max_age_ms {
+        let seq: Option<i64> = conn.query_row(
+            "SELECT MAX(global_seq) FROM messages WHERE topic_id = ?1 AND created_at < ?2",
+            params![topic_id, now - max_age_ms],
+            |row| row.get(0),
+        )?;
+        cutoff = cutoff.max(seq);
+    }
+
+    if let Some(max_messages) = policy.max_messages {
+        // The newest message that is past the count limit
+        let seq: Option<i64> = conn
+            .query_row(
+                "SELECT global_seq FROM messages WHERE topic_id = ?1 ORDER BY global_seq DESC LIMIT 1 OFFSET ?2",
+                params![topic_id, max_messages.max(0)],
+                |row| row.get(0),
+            )
+            .optional()?;
+        cutoff = cutoff.max(seq);
+    }
+
+    if let Some(max_bytes) = policy.max_bytes {
+        // Walk from newest to oldest; the first message that overflows the
+        // budget and everything older than it must go.
+        let seq: Option<i64> = conn
+            .query_row(
+                "SELECT global_seq FROM (
+                    SELECT global_seq, SUM(COALESCE(LENGTH(payload), 0) + COALESCE(LENGTH(attributes), 0))
+                        OVER (ORDER BY global_seq DESC) AS retained_bytes
+                    FROM messages WHERE topic_id = ?1
+                 ) WHERE retained_bytes > ?2 ORDER BY global_seq DESC LIMIT 1",
+                params![topic_id, max_bytes],
+                |row| row.get(0),
+            )
+            .optional()?;
+        cutoff = cutoff.max(seq);
+    }
+
+    Ok(cutoff)
+}
+
+/// Delete up to `limit` of the oldest messages with `global_seq <= up_to_seq`.
+///
+/// Subscriptions whose cursor falls behind the deleted range are moved
+/// forward so they resume at the new earliest retained message.
+pub fn prune_messages(
+    conn: &Connection,
+    topic_id: i64,
+    up_to_seq: i64,
+    limit: i64,
+    now: i64,
+) -> Result<PruneOutcome> {
+    let through_seq: Option<i64> = conn.query_row(
+        "SELECT MAX(global_seq) FROM (SELECT global_seq FROM messages WHERE topic_id = ?1 AND global_seq <= ?2 ORDER BY global_seq ASC LIMIT ?3)",
+        params![topic_id, up_to_seq, limit],
+        |row| row.get(0),
+    )?;
+
+    let Some(through_seq) = through_seq else {
+        return Ok(PruneOutcome::default());
+    };
+
+    let deleted = conn.execute(
+        "DELETE FROM messages WHERE topic_id = ?1 AND global_seq <= ?2",
+        params![topic_id, through_seq],
+    )?;
+
+    let cursors_advanced = conn.execute(
+        "UPDATE subscriptions SET cursor_seq = ?1, updated_at = ?2 WHERE topic_id = ?3 AND cursor_seq < ?1",
+        params![through_seq, now, topic_id],
+    )?;
+
+    conn.execute(
+        "UPDATE partition_cursors SET cursor_seq = ?1 WHERE topic_id = ?2 AND cursor_seq < ?1",
+        params![through_seq, topic_id],
+    )?;
+
+    conn.execute(
+        "DELETE FROM subscription_acks WHERE topic_id = ?1 AND global_seq <= ?2",
+        params![topic_id, through_seq],
+    )?;
+
+    conn.execute(
+        "DELETE FROM delivery_attempts WHERE topic_id = ?1 AND global_seq <= ?2",
+        params![topic_id, through_seq],
+    )?;
+
+    Ok(PruneOutcome {
+        deleted,
+        through_seq,
+        cursors_advanced,
+    })
+}
+
+/// Remove and return up to `limit` messages that expired at `now`, in
+/// expiry order.
+///
+/// Individual ACKs and failed delivery attempts recorded for them are
+/// deleted with them.
+pub fn take_expired_messages(conn: &Connection, now: i64, limit: i64) -> Result<Vec<Message>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {MESSAGE_COLUMNS} FROM messages WHERE expires_at <= ?1 ORDER BY expires_at, global_seq LIMIT ?2"
+    ))?;
+    let expired = stmt
+        .query_map(params![now, limit], message_from_row)?
+        .collect::<Result<Vec<_>>>()?;
+
+    for msg in &expired {
+        conn.execute(
+            "DELETE FROM messages WHERE global_seq = ?1",
+            params![msg.global_seq],
+        )?;
+        conn.execute(
+            "DELETE FROM subscription_acks WHERE topic_id = ?1 AND global_seq = ?2",
+            params![msg.topic_id, msg.global_seq],
+        )?;
+        conn.execute(
+            "DELETE FROM delivery_attempts WHERE topic_id = ?1 AND global_seq = ?2",
+            params![msg.topic_id, msg.global_seq],
+        )?;
+    }
+    Ok(expired)
+}
+
+/// Earliest expiry time of any stored message.
+pub fn next_message_expiry(conn: &Connection) -> Result<Option<i64>> {
+    conn.query_row(
+        "SELECT MIN(expires_at) FROM messages WHERE expires_at IS NOT NULL",
+        [],
+        |row| row.get(0),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        apply_pragmas(&conn).unwrap();
+        initialize_schema(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_schema_initialization() {
+        let conn = setup_test_db();
+        // Verify tables exist by querying them
         let topic_count: i64 = conn
             .query_row("SELECT COUNT(*) FROM topics", [], |row| row.get(0))
             .unwrap();
@@ -297,27 +1547,208 @@ mod tests {
         assert_ne!(id1, id3);
     }
 
+    #[test]
+    fn test_schema_adds_missing_columns() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE topics (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE,
+                created_at INTEGER NOT NULL
+            );
+            INSERT INTO topics (name, created_at) VALUES ('orders', 0);
+            CREATE TABLE messages (
+                global_seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                topic_id INTEGER NOT NULL,
+                message_id TEXT NOT NULL,
+                payload BLOB,
+                attributes TEXT,
+                created_at INTEGER NOT NULL
+            );
+            INSERT INTO messages (topic_id, message_id, created_at) VALUES (1, 'old-1', 0), (1, 'old-2', 0);",
+        )
+        .unwrap();
+
+        // Running twice must not try to add the column again
+        initialize_schema(&conn).unwrap();
+        initialize_schema(&conn).unwrap();
+
+        // Existing messages keep their sequence within partition 0
+        let msg = get_message_by_seq(&conn, 1, 2).unwrap().unwrap();
+        assert_eq!((msg.partition, msg.partition_seq), (0, 2));
+        assert_eq!(get_topic_last_seq(&conn, 1).unwrap(), 2);
+
+        let topic_id = insert_or_get_topic(&conn, "orders", 0).unwrap();
+        let (seq, partition_seq) = insert_message(
+            &conn,
+            topic_id,
+            0,
+            "msg-001",
+            None,
+            None,
+            Some("k"),
+            0,
+            None,
+            0,
+        )
+        .unwrap();
+        assert_eq!(partition_seq, 3);
+        let msg = get_message_by_seq(&conn, topic_id, seq).unwrap().unwrap();
+        assert_eq!(msg.key.as_deref(), Some("k"));
+
+        // Deleting messages does not lower the last assigned sequence
+        conn.execute("DELETE FROM messages", []).unwrap();
+        assert_eq!(get_topic_max_seq(&conn, topic_id).unwrap(), 0);
+        assert_eq!(get_topic_last_seq(&conn, topic_id).unwrap(), seq);
+    }
+
+    #[test]
+    fn test_partition_sequences() {
+        let conn = setup_test_db();
+        let now = 1234567890000i64;
+
+        let topic = create_topic(&conn, "orders", 3, now).unwrap().unwrap();
+        assert_eq!(topic.partition_count, 3);
+        assert!(create_topic(&conn, "orders", 1, now).unwrap().is_none());
+        assert_eq!(
+            get_topic_by_name(&conn, "orders")
+                .unwrap()
+                .unwrap()
+                .partition_count,
+            3
+        );
+
+        // Each partition numbers its own messages
+        for (i, (partition, expected)) in [(0, 1), (2, 1), (0, 2), (2, 2), (2, 3)]
+            .into_iter()
+            .enumerate()
+        {
+            let (_, partition_seq) = insert_message(
+                &conn,
+                topic.id,
+                partition,
+                &format!("msg-{i}"),
+                None,
+                None,
+                None,
+                now,
+                None,
+                0,
+            )
+            .unwrap();
+            assert_eq!(partition_seq, expected);
+        }
+        assert_eq!(
+            get_partition_max_seqs(&conn, topic.id).unwrap(),
+            vec![2, 0, 3]
+        );
+
+        // Sequences keep counting after retention deletes the messages
+        prune_messages(&conn, topic.id, i64::MAX, 100, now).unwrap();
+        let (_, partition_seq) =
+            insert_message(&conn, topic.id, 0, "msg-5", None, None, None, now, None, 0).unwrap();
+        assert_eq!(partition_seq, 3);
+
+        let messages = fetch_messages_from_seq(&conn, topic.id, &[0], 0, 10).unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].partition, 0);
+        assert!(fetch_messages_from_seq(&conn, topic.id, &[1], 0, 10)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_partition_cursors() {
+        let conn = setup_test_db();
+        let now = 1234567890000i64;
+
+        let topic = create_topic(&conn, "orders", 2, now).unwrap().unwrap();
+        let unpartitioned = insert_or_get_topic(&conn, "events", now).unwrap();
+        get_or_create_subscription(&conn, topic.id, "workers", now).unwrap();
+        get_or_create_subscription(&conn, unpartitioned, "workers", now).unwrap();
+        assert_eq!(
+            get_partition_cursors(&conn, topic.id, "workers").unwrap(),
+            vec![(0, 0), (1, 0)]
+        );
+        assert!(get_partition_cursors(&conn, unpartitioned, "workers")
+            .unwrap()
+            .is_empty());
+
+        // The group cursor trails the slowest partition
+        assert_eq!(
+            update_cursor(&conn, topic.id, "workers", &[1], 8, now).unwrap(),
+            0
+        );
+        assert_eq!(
+            update_cursor(&conn, topic.id, "workers", &[0], 5, now).unwrap(),
+            1
+        );
+        let sub = get_or_create_subscription(&conn, topic.id, "workers", now).unwrap();
+        assert_eq!(sub.cursor_seq, 5);
+
+        // Moving every partition never moves one backwards
+        update_cursor(&conn, topic.id, "workers", &[], 6, now).unwrap();
+        assert_eq!(
+            get_partition_cursors(&conn, topic.id, "workers").unwrap(),
+            vec![(0, 6), (1, 8)]
+        );
+        let sub = get_or_create_subscription(&conn, topic.id, "workers", now).unwrap();
+        assert_eq!(sub.cursor_seq, 6);
+    }
+
     #[test]
     fn test_insert_message() {
         let conn = setup_test_db();
         let now = 1234567890000i64;
 
         let topic_id = insert_or_get_topic(&conn, "orders", now).unwrap();
-        let seq = insert_message(
+        let (seq, partition_seq) = insert_message(
             &conn,
             topic_id,
+            0,
             "msg-001",
             Some(b"hello"),
             Some(r#"{"key":"value"}"#),
+            Some("order-1"),
             now,
+            None,
+            0,
         )
         .unwrap();
 
-        assert_eq!(seq, 1);
+        assert_eq!((seq, partition_seq), (1, 1));
 
         // Second message gets next sequence
-        let seq2 = insert_message(&conn, topic_id, "msg-002", Some(b"world"), None, now).unwrap();
+        let (seq2, _) = insert_message(
+            &conn,
+            topic_id,
+            0,
+            "msg-002",
+            Some(b"world"),
+            None,
+            None,
+            now,
+            None,
+            0,
+        )
+        .unwrap();
         assert_eq!(seq2, 2);
+
+        let msg = get_message_by_seq(&conn, topic_id, seq).unwrap().unwrap();
+        assert_eq!(msg.key.as_deref(), Some("order-1"));
+        let msg = get_message_by_seq(&conn, topic_id, seq2).unwrap().unwrap();
+        assert_eq!(msg.key, None);
+
+        // Message IDs only resolve within their own topic
+        assert_eq!(
+            get_message_seq_by_id(&conn, topic_id, "msg-002").unwrap(),
+            Some(seq2)
+        );
+        let other = insert_or_get_topic(&conn, "payments", now).unwrap();
+        assert_eq!(
+            get_message_seq_by_id(&conn, other, "msg-002").unwrap(),
+            None
+        );
     }
 
     #[test]
@@ -331,7 +1762,7 @@ mod tests {
         assert_eq!(sub.cursor_seq, 0);
 
         // Update cursor
-        let updated = update_cursor(&conn, topic_id, "workers", 5, now + 1000).unwrap();
+        let updated = update_cursor(&conn, topic_id, "workers", &[], 5, now + 1000).unwrap();
         assert_eq!(updated, 1);
 
         // Fetch updated subscription
@@ -339,7 +1770,7 @@ mod tests {
         assert_eq!(sub2.cursor_seq, 5);
 
         // Cursor only advances (idempotent)
-        let updated = update_cursor(&conn, topic_id, "workers", 3, now + 3000).unwrap();
+        let updated = update_cursor(&conn, topic_id, "workers", &[], 3, now + 3000).unwrap();
         assert_eq!(updated, 0); // No rows updated
     }
 
@@ -355,23 +1786,558 @@ mod tests {
             insert_message(
                 &conn,
                 topic_id,
+                0,
                 &format!("msg-{i:03}"),
                 Some(format!("payload-{i}").as_bytes()),
                 None,
+                None,
                 now + i,
+                None,
+                0,
             )
             .unwrap();
         }
 
         // Fetch from beginning
-        let messages = fetch_messages_from_seq(&conn, topic_id, 0, 10).unwrap();
+        let messages = fetch_messages_from_seq(&conn, topic_id, &[], 0, 10).unwrap();
         assert_eq!(messages.len(), 5);
         assert_eq!(messages[0].global_seq, 1);
         assert_eq!(messages[4].global_seq, 5);
 
         // Fetch with cursor
-        let messages = fetch_messages_from_seq(&conn, topic_id, 2, 10).unwrap();
+        let messages = fetch_messages_from_seq(&conn, topic_id, &[], 2, 10).unwrap();
         assert_eq!(messages.len(), 3);
         assert_eq!(messages[0].global_seq, 3);
     }
+
+    #[test]
+    fn test_fetch_messages_by_priority() {
+        let conn = setup_test_db();
+        let now = 1234567890000i64;
+
+        let topic_id = insert_or_get_topic(&conn, "orders", now).unwrap();
+        for (i, priority) in [0, 5, 0, 9, 5].into_iter().enumerate() {
+            insert_message(
+                &conn,
+                topic_id,
+                0,
+                &format!("msg-{i}"),
+                None,
+                None,
+                None,
+                now,
+                None,
+                priority,
+            )
+            .unwrap();
+        }
+
+        let seqs = |messages: Vec<Message>| -> Vec<i64> {
+            messages.iter().map(|m| m.global_seq).collect()
+        };
+
+        // Highest priority first, sequence order within a priority
+        let messages = fetch_messages_by_priority(&conn, topic_id, &[], 0, None, 10).unwrap();
+        assert_eq!(messages[0].priority, 9);
+        assert_eq!(seqs(messages), vec![4, 2, 5, 1, 3]);
+
+        // Resuming a scan, and reading after a sequence
+        let messages =
+            fetch_messages_by_priority(&conn, topic_id, &[], 0, Some((5, 2)), 2).unwrap();
+        assert_eq!(seqs(messages), vec![5, 1]);
+        let messages = fetch_messages_by_priority(&conn, topic_id, &[], 2, None, 10).unwrap();
+        assert_eq!(seqs(messages), vec![4, 5, 3]);
+
+        assert_eq!(
+            fetch_seqs_from_seq(&conn, topic_id, &[], 1, 3).unwrap(),
+            vec![2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn test_retention_cutoff() {
+        let conn = setup_test_db();
+        let now = 1234567890000i64;
+
+        let topic_id = insert_or_get_topic(&conn, "orders", now).unwrap();
+        for i in 1..=10 {
+            insert_message(
+                &conn,
+                topic_id,
+                0,
+                &format!("msg-{i:03}"),
+                Some(b"0123456789"),
+                None,
+                None,
+                now + i * 1000,
+                None,
+                0,
+            )
+            .unwrap();
+        }
+
+        let unlimited = RetentionPolicy::default();
+        assert_eq!(
+            retention_cutoff_seq(&conn, topic_id, &unlimited, now).unwrap(),
+            None
+        );
+
+        // Keep the newest 3 messages
+        let by_count = RetentionPolicy {
+            max_messages: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            retention_cutoff_seq(&conn, topic_id, &by_count, now).unwrap(),
+            Some(7)
+        );
+
+        // Keep messages created within the last 5 seconds of "now + 10s"
+        let by_age = RetentionPolicy {
+            max_age_ms: Some(5000),
+            ..Default::default()
+        };
+        assert_eq!(
+            retention_cutoff_seq(&conn, topic_id, &by_age, now + 10_000).unwrap(),
+            Some(4)
+        );
+
+        // Keep at most 45 bytes (4 messages of 10 bytes)
+        let by_bytes = RetentionPolicy {
+            max_bytes: Some(45),
+            ..Default::default()
+        };
+        assert_eq!(
+            retention_cutoff_seq(&conn, topic_id, &by_bytes, now).unwrap(),
+            Some(6)
+        );
+
+        // Strictest limit wins
+        let combined = RetentionPolicy {
+            max_messages: Some(3),
+            max_bytes: Some(45),
+            ..Default::default()
+        };
+        assert_eq!(
+            retention_cutoff_seq(&conn, topic_id, &combined, now).unwrap(),
+            Some(7)
+        );
+    }
+
+    #[test]
+    fn test_prune_messages_advances_cursors() {
+        let conn = setup_test_db();
+        let now = 1234567890000i64;
+
+        let topic_id = insert_or_get_topic(&conn, "orders", now).unwrap();
+        for i in 1..=10 {
+            insert_message(
+                &conn,
+                topic_id,
+                0,
+                &format!("msg-{i:03}"),
+                None,
+                None,
+                None,
+                now,
+                None,
+                0,
+            )
+            .unwrap();
+        }
+        get_or_create_subscription(&conn, topic_id, "behind", now).unwrap();
+        get_or_create_subscription(&conn, topic_id, "ahead", now).unwrap();
+        update_cursor(&conn, topic_id, "ahead", &[], 9, now).unwrap();
+
+        // First chunk is bounded by the limit
+        let outcome = prune_messages(&conn, topic_id, 6, 4, now).unwrap();
+        assert_eq!(outcome.deleted, 4);
+        assert_eq!(outcome.through_seq, 4);
+        assert_eq!(outcome.cursors_advanced, 1);
+
+        let outcome = prune_messages(&conn, topic_id, 6, 4, now).unwrap();
+        assert_eq!(outcome.deleted, 2);
+        assert_eq!(outcome.through_seq, 6);
+
+        // Nothing left below the cutoff
+        let outcome = prune_messages(&conn, topic_id, 6, 4, now).unwrap();
+        assert_eq!(outcome.deleted, 0);
+
+        let behind = get_or_create_subscription(&conn, topic_id, "behind", now).unwrap();
+        assert_eq!(behind.cursor_seq, 6);
+        let ahead = get_or_create_subscription(&conn, topic_id, "ahead", now).unwrap();
+        assert_eq!(ahead.cursor_seq, 9);
+
+        let messages = fetch_messages_from_seq(&conn, topic_id, &[], 0, 100).unwrap();
+        assert_eq!(messages.first().map(|m| m.global_seq), Some(7));
+    }
+
+    #[test]
+    fn test_topic_stats_and_delete() {
+        let conn = setup_test_db();
+        let now = 1234567890000i64;
+
+        let orders = insert_or_get_topic(&conn, "orders", now).unwrap();
+        let other = insert_or_get_topic(&conn, "other", now).unwrap();
+        assert_eq!(
+            get_topic_stats(&conn, orders).unwrap(),
+            TopicStats::default()
+        );
+
+        for i in 0..3 {
+            insert_message(
+                &conn,
+                orders,
+                0,
+                &format!("msg-{i}"),
+                Some(b"abcd"),
+                Some("{}"),
+                None,
+                now + i,
+                None,
+                0,
+            )
+            .unwrap();
+        }
+        insert_message(&conn, other, 0, "other-msg", None, None, None, now, None, 0).unwrap();
+
+        let stats = get_topic_stats(&conn, orders).unwrap();
+        assert_eq!(stats.message_count, 3);
+        assert_eq!((stats.min_seq, stats.max_seq), (1, 3));
+        assert_eq!(stats.size_bytes, 18);
+        assert_eq!(stats.oldest_created_at, now);
+        assert_eq!(stats.newest_created_at, now + 2);
+
+        get_or_create_subscription(&conn, orders, "workers", now).unwrap();
+        get_or_create_subscription(&conn, orders, "audit", now).unwrap();
+        update_cursor(&conn, orders, "workers", &[], 2, now).unwrap();
+        let groups = list_subscriptions(&conn, orders).unwrap();
+        let names: Vec<&str> = groups.iter().map(|g| g.consumer_group.as_str()).collect();
+        assert_eq!(names, vec!["audit", "workers"]);
+        assert_eq!(count_messages_after(&conn, orders, 2).unwrap(), 1);
+
+        let outcome = delete_topic(&conn, orders).unwrap();
+        assert_eq!(outcome.messages_deleted, 3);
+        assert_eq!(outcome.consumer_groups_deleted, 2);
+        assert!(get_topic_by_name(&conn, "orders").unwrap().is_none());
+        assert!(list_subscriptions(&conn, orders).unwrap().is_empty());
+
+        // Other topics are untouched
+        assert_eq!(get_topic_stats(&conn, other).unwrap().message_count, 1);
+    }
+
+    #[test]
+    fn test_reset_and_delete_consumer_group() {
+        let conn = setup_test_db();
+        let now = 1234567890000i64;
+
+        let topic_id = insert_or_get_topic(&conn, "orders", now).unwrap();
+        for i in 1..=5 {
+            insert_message(
+                &conn,
+                topic_id,
+                0,
+                &format!("msg-{i}"),
+                None,
+                None,
+                None,
+                now + i * 1000,
+                None,
+                0,
+            )
+            .unwrap();
+        }
+        assert_eq!(get_topic_min_seq(&conn, topic_id).unwrap(), Some(1));
+        assert_eq!(
+            first_seq_at_or_after(&conn, topic_id, now + 2500).unwrap(),
+            Some(3)
+        );
+        assert_eq!(
+            first_seq_at_or_after(&conn, topic_id, now + 9000).unwrap(),
+            None
+        );
+
+        get_or_create_subscription(&conn, topic_id, "workers", now).unwrap();
+        update_cursor(&conn, topic_id, "workers", &[], 4, now).unwrap();
+        record_individual_ack(&conn, topic_id, "workers", &[], 5, 4, now).unwrap();
+        record_delivery_failure(&conn, topic_id, "workers", 5).unwrap();
+
+        // Resetting moves the cursor backwards and forgets progress above it
+        assert!(reset_cursor(&conn, topic_id, "workers", 1, now).unwrap());
+        let sub = get_subscription(&conn, topic_id, "workers")
+            .unwrap()
+            .unwrap();
+        assert_eq!(sub.cursor_seq, 1);
+        assert_eq!(sub.reset_generation, 1);
+        assert!(get_subscription_acks(&conn, topic_id, "workers")
+            .unwrap()
+            .is_empty());
+        assert!(get_delivery_failures(&conn, topic_id, "workers", 0)
+            .unwrap()
+            .is_empty());
+        assert!(!reset_cursor(&conn, topic_id, "missing", 1, now).unwrap());
+
+        assert!(delete_subscription(&conn, topic_id, "workers").unwrap());
+        assert!(get_subscription(&conn, topic_id, "workers")
+            .unwrap()
+            .is_none());
+        assert!(!delete_subscription(&conn, topic_id, "workers").unwrap());
+    }
+
+    #[test]
+    fn test_topic_retention_roundtrip() {
+        let conn = setup_test_db();
+        let now = 1234567890000i64;
+
+        let topic_id = insert_or_get_topic(&conn, "orders", now).unwrap();
+        assert!(get_topic_retention(&conn, topic_id).unwrap().is_unlimited());
+
+        let policy = RetentionPolicy {
+            max_age_ms: Some(60_000),
+            max_messages: None,
+            max_bytes: Some(1024),
+        };
+        set_topic_retention(&conn, topic_id, &policy, now).unwrap();
+        assert_eq!(get_topic_retention(&conn, topic_id).unwrap(), policy);
+
+        let defaults = RetentionPolicy {
+            max_messages: Some(10),
+            max_bytes: Some(1),
+            ..Default::default()
+        };
+        let effective = policy.with_defaults(&defaults);
+        assert_eq!(effective.max_messages, Some(10));
+        assert_eq!(effective.max_bytes, Some(1024));
+    }
+
+    #[test]
+    fn test_topic_settings_roundtrip() {
+        let conn = setup_test_db();
+        let now = 1234567890000i64;
+
+        let topic_id = insert_or_get_topic(&conn, "orders", now).unwrap();
+        assert_eq!(
+            get_topic_settings(&conn, topic_id).unwrap(),
+            TopicSettings::default()
+        );
+
+        let settings = TopicSettings {
+            retention: RetentionPolicy {
+                max_messages: Some(100),
+                ..Default::default()
+            },
+            max_message_bytes: Some(512),
+            description: Some("Order events".to_string()),
+            dead_letter_expired: true,
+            priority_delivery: true,
+            auto_create: Some(false),
+        };
+        set_topic_settings(&conn, topic_id, &settings, now).unwrap();
+        assert_eq!(get_topic_settings(&conn, topic_id).unwrap(), settings);
+        assert_eq!(
+            get_topic_retention(&conn, topic_id).unwrap(),
+            settings.retention
+        );
+
+        // Retention updates keep the other settings
+        let policy = RetentionPolicy {
+            max_age_ms: Some(60_000),
+            ..Default::default()
+        };
+        set_topic_retention(&conn, topic_id, &policy, now).unwrap();
+        let updated = get_topic_settings(&conn, topic_id).unwrap();
+        assert_eq!(updated.retention, policy);
+        assert_eq!(updated.max_message_bytes, Some(512));
+    }
+
+    #[test]
+    fn test_idempotency_keys() {
+        let conn = setup_test_db();
+        let now = 1234567890000i64;
+
+        let topic_id = insert_or_get_topic(&conn, "orders", now).unwrap();
+        let publish = IdempotentPublish {
+            message_id: "msg-001".to_string(),
+            global_seq: 7,
+            partition: 0,
+            partition_seq: 7,
+            created_at: now,
+        };
+        record_idempotency_key(&conn, topic_id, "order-42", &publish).unwrap();
+
+        assert_eq!(
+            get_idempotent_publish(&conn, topic_id, "order-42", now - 1000).unwrap(),
+            Some(publish)
+        );
+        // Outside the window, or on another topic, the key is unknown
+        assert!(get_idempotent_publish(&conn, topic_id, "order-42", now + 1)
+            .unwrap()
+            .is_none());
+        assert!(
+            get_idempotent_publish(&conn, topic_id + 1, "order-42", now - 1000)
+                .unwrap()
+                .is_none()
+        );
+
+        assert_eq!(prune_idempotency_keys(&conn, now).unwrap(), 0);
+        assert_eq!(prune_idempotency_keys(&conn, now + 1).unwrap(), 1);
+        assert!(get_idempotent_publish(&conn, topic_id, "order-42", 0)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_scheduled_messages() {
+        let conn = setup_test_db();
+        let now = 1234567890000i64;
+
+        let topic_id = insert_or_get_topic(&conn, "orders", now).unwrap();
+        let schedule = |message_id: &str, deliver_at: i64| {
+            insert_scheduled_message(
+                &conn,
+                &ScheduledMessage {
+                    id: 0,
+                    topic_id,
+                    message_id: message_id.to_string(),
+                    payload: Some(b"later".to_vec()),
+                    attributes: None,
+                    key: None,
+                    partition: 0,
+                    created_at: now,
+                    deliver_at,
+                    ttl_ms: None,
+                    priority: 0,
+                },
+            )
+            .unwrap()
+        };
+        schedule("msg-c", now + 3000);
+        schedule("msg-a", now + 1000);
+        schedule("msg-b", now + 1000);
+        assert_eq!(next_scheduled_delivery(&conn).unwrap(), Some(now + 1000));
+
+        // Scheduled messages are not part of the topic until released
+        let stats = get_topic_stats(&conn, topic_id).unwrap();
+        assert_eq!(stats.message_count, 0);
+        assert_eq!(stats.scheduled_count, 3);
+
+        assert!(take_due_scheduled_messages(&conn, now, 10)
+            .unwrap()
+            .is_empty());
+        let due = take_due_scheduled_messages(&conn, now + 2000, 1).unwrap();
+        assert_eq!(due[0].message_id, "msg-a");
+        let due = take_due_scheduled_messages(&conn, now + 2000, 10).unwrap();
+        let ids: Vec<&str> = due.iter().map(|m| m.message_id.as_str()).collect();
+        assert_eq!(ids, vec!["msg-b"]);
+        assert_eq!(next_scheduled_delivery(&conn).unwrap(), Some(now + 3000));
+
+        assert_eq!(delete_scheduled_messages(&conn, topic_id).unwrap(), 1);
+        assert_eq!(next_scheduled_delivery(&conn).unwrap(), None);
+    }
+
+    #[test]
+    fn test_take_expired_messages() {
+        let conn = setup_test_db();
+        let now = 1234567890000i64;
+
+        let topic_id = insert_or_get_topic(&conn, "ticks", now).unwrap();
+        let mut seqs = Vec::new();
+        for (message_id, expires_at) in [
+            ("msg-late", Some(now + 3000)),
+            ("msg-forever", None),
+            ("msg-early", Some(now + 1000)),
+        ] {
+            let (seq, _) = insert_message(
+                &conn, topic_id, 0, message_id, None, None, None, now, expires_at, 0,
+            )
+            .unwrap();
+            seqs.push(seq);
+        }
+        assert_eq!(next_message_expiry(&conn).unwrap(), Some(now + 1000));
+
+        record_delivery_failure(&conn, topic_id, "group-1", seqs[2]).unwrap();
+
+        assert!(take_expired_messages(&conn, now, 10).unwrap().is_empty());
+        let expired = take_expired_messages(&conn, now + 5000, 1).unwrap();
+        assert_eq!(expired[0].message_id, "msg-early");
+        assert_eq!(expired[0].expires_at, Some(now + 1000));
+        assert!(get_delivery_failures(&conn, topic_id, "group-1", 0)
+            .unwrap()
+            .is_empty());
+        assert_eq!(next_message_expiry(&conn).unwrap(), Some(now + 3000));
+
+        let expired = take_expired_messages(&conn, now + 5000, 10).unwrap();
+        let ids: Vec<&str> = expired.iter().map(|m| m.message_id.as_str()).collect();
+        assert_eq!(ids, vec!["msg-late"]);
+        assert_eq!(next_message_expiry(&conn).unwrap(), None);
+        assert!(get_message_by_seq(&conn, topic_id, seqs[1])
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_record_individual_ack() {
+        let conn = setup_test_db();
+        let now = 1234567890000i64;
+
+        let topic_id = insert_or_get_topic(&conn, "orders", now).unwrap();
+        get_or_create_subscription(&conn, topic_id, "workers", now).unwrap();
+
+        // Out-of-order ACKs are stored without moving the cursor
+        record_individual_ack(&conn, topic_id, "workers", &[], 3, 0, now).unwrap();
+        record_individual_ack(&conn, topic_id, "workers", &[], 4, 0, now).unwrap();
+        assert_eq!(
+            get_subscription_acks(&conn, topic_id, "workers").unwrap(),
+            vec![3, 4]
+        );
+
+        // Filling the gap moves the cursor and drops the stored ACKs it covers
+        record_individual_ack(&conn, topic_id, "workers", &[], 1, 3, now).unwrap();
+        assert_eq!(
+            get_subscription_acks(&conn, topic_id, "workers").unwrap(),
+            vec![4]
+        );
+        let sub = get_or_create_subscription(&conn, topic_id, "workers", now).unwrap();
+        assert_eq!(sub.cursor_seq, 3);
+    }
+
+    #[test]
+    fn test_record_delivery_failure() {
+        let conn = setup_test_db();
+        let now = 1234567890000i64;
+
+        let topic_id = insert_or_get_topic(&conn, "orders", now).unwrap();
+        get_or_create_subscription(&conn, topic_id, "workers", now).unwrap();
+
+        assert_eq!(
+            record_delivery_failure(&conn, topic_id, "workers", 2).unwrap(),
+            1
+        );
+        assert_eq!(
+            record_delivery_failure(&conn, topic_id, "workers", 2).unwrap(),
+            2
+        );
+        assert_eq!(
+            record_delivery_failure(&conn, topic_id, "workers", 5).unwrap(),
+            1
+        );
+
+        let mut failures = get_delivery_failures(&conn, topic_id, "workers", 0).unwrap();
+        failures.sort();
+        assert_eq!(failures, vec![(2, 2), (5, 1)]);
+
+        // Counts at or below the cursor are dropped on the next failure
+        update_cursor(&conn, topic_id, "workers", &[], 3, now).unwrap();
+        record_delivery_failure(&conn, topic_id, "workers", 5).unwrap();
+        assert_eq!(
+            get_delivery_failures(&conn, topic_id, "workers", 0).unwrap(),
+            vec![(5, 2)]
+        );
+
+        // An individual ACK clears the count for that message
+        record_individual_ack(&conn, topic_id, "workers", &[], 5, 3, now).unwrap();
+        assert!(get_delivery_failures(&conn, topic_id, "workers", 0)
+            .unwrap()
+            .is_empty());
+    }
 }
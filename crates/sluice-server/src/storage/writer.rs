@@ -4,20 +4,35 @@
 //! - Single std::thread owns the write connection
 //! - Communication via tokio::sync::mpsc channel
 //! - Enables group commit for high throughput
+//! - Partition writer threads, each with its own connection, take the
+//!   publishes to partitioned topics off the main writer
 
-use rusqlite::Connection;
+use rusqlite::{Connection, Transaction, TransactionBehavior};
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::{mpsc as std_mpsc, Arc};
 use std::thread::{self, JoinHandle};
 use thiserror::Error;
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{mpsc, oneshot, watch};
 
 use super::batch::{BatchAccumulator, BatchConfig};
+use super::dead_letter::{expired_attributes, DEFAULT_DLQ_SUFFIX};
+use super::partition::Partitioner;
 use super::schema::{
-    apply_pragmas, get_or_create_subscription, initialize_schema, insert_message,
-    insert_or_get_topic, update_cursor, Subscription,
+    apply_pragmas, count_messages_after, create_topic, delete_scheduled_messages,
+    delete_subscription, delete_topic, first_seq_at_or_after, get_idempotent_publish,
+    get_or_create_subscription, get_partition_max_seqs, get_subscription, get_topic_by_name,
+    get_topic_last_seq, get_topic_max_seq, get_topic_min_seq, get_topic_name,
+    get_topic_partition_count, get_topic_settings, get_topic_stats, initialize_schema,
+    insert_message, insert_or_get_topic, insert_scheduled_message, is_inbox, list_subscriptions,
+    next_message_expiry, next_scheduled_delivery, prune_idempotency_keys, prune_messages,
+    record_delivery_failure, record_idempotency_key, record_individual_ack, reset_cursor,
+    set_topic_retention, set_topic_settings, take_due_scheduled_messages, take_expired_messages,
+    update_cursor, DeleteOutcome, IdempotentPublish, PruneOutcome, RetentionPolicy,
+    ScheduledMessage, Subscription, Topic, TopicSettings, TopicStats,
 };
 use crate::flow::notify::NotificationBus;
+use crate::generate_message_id;
 use crate::now_millis;
 
 /// Error type for writer operations.
@@ -31,6 +46,26 @@ pub enum WriterError {
 
     #[error("Writer thread panicked")]
     ThreadPanic,
+
+    #[error("Topic '{0}' does not exist and auto-creation is disabled")]
+    TopicNotFound(String),
+
+    #[error("Payload too large for topic '{topic}': {size} bytes (max {max} bytes)")]
+    MessageTooLarge {
+        topic: String,
+        size: usize,
+        max: i64,
+    },
+
+    #[error("Last sequence of topic '{topic}' is {actual}, expected {expected}")]
+    SequenceMismatch {
+        topic: String,
+        expected: i64,
+        actual: i64,
+    },
+
+    #[error("Consumer group '{0}' was reset")]
+    ConsumerGroupReset(String),
 }
 
 /// Result of a publish operation.
@@ -39,14 +74,30 @@ pub struct PublishResult {
     pub message_id: String,
     pub sequence: i64,
     pub timestamp: i64,
+    pub partition: u32,
+    pub partition_sequence: i64,
+    /// True if an earlier publish with the same idempotency key was returned.
+    pub duplicate: bool,
+}
+
+impl From<IdempotentPublish> for PublishResult {
+    fn from(original: IdempotentPublish) -> Self {
+        Self {
+            message_id: original.message_id,
+            sequence: original.global_seq,
+            timestamp: original.created_at,
+            partition: original.partition,
+            partition_sequence: original.partition_seq,
+            duplicate: true,
+        }
+    }
 }
 
 /// Command sent to the writer thread.
 pub struct PublishCommand {
     pub topic: String,
-    pub message_id: String,
-    pub payload: Option<Vec<u8>>,
-    pub attributes: Option<String>,
+    pub message: MessageInput,
+    pub expected_last_sequence: Option<i64>,
     pub reply: oneshot::Sender<Result<PublishResult, WriterError>>,
 }
 
@@ -61,15 +112,55 @@ pub struct SubscriptionCommand {
 pub struct CursorUpdateCommand {
     pub topic_id: i64,
     pub consumer_group: String,
+    pub reset_generation: i64,
+    pub partitions: Vec<u32>,
+    pub cursor_seq: i64,
+    pub reply: oneshot::Sender<Result<(), WriterError>>,
+}
+
+/// Command to record an individual ACK (AckMode::INDIVIDUAL).
+pub struct IndividualAckCommand {
+    pub topic_id: i64,
+    pub consumer_group: String,
+    pub reset_generation: i64,
+    pub partitions: Vec<u32>,
+    pub seq: i64,
     pub cursor_seq: i64,
     pub reply: oneshot::Sender<Result<(), WriterError>>,
 }
 
-/// A single message in a batch publish.
-pub struct BatchMessageInput {
+/// Command to record a failed delivery attempt (NACK).
+pub struct DeliveryFailureCommand {
+    pub topic_id: i64,
+    pub consumer_group: String,
+    pub seq: i64,
+    pub reply: oneshot::Sender<Result<u32, WriterError>>,
+}
+
+/// Command to append scheduled messages that are due to their topics.
+pub struct ReleaseScheduledCommand {
+    pub reply: oneshot::Sender<Result<usize, WriterError>>,
+}
+
+/// Command to delete expired messages.
+pub struct ExpireMessagesCommand {
+    pub reply: oneshot::Sender<Result<usize, WriterError>>,
+}
+
+/// A message to publish.
+#[derive(Debug, Default)]
+pub struct MessageInput {
     pub message_id: String,
     pub payload: Option<Vec<u8>>,
     pub attributes: Option<String>,
+    pub key: Option<String>,
+    pub idempotency_key: Option<String>,
+    /// Delivery time (Unix ms); `None` or a time in the past delivers now.
+    pub deliver_at: Option<i64>,
+    /// Time to live from delivery, in ms; `None` never expires.
+    pub ttl_ms: Option<i64>,
+    /// Delivery priority, 0 (default) to 9.
+    pub priority: u32,
 }
 
 /// Result of a single message in a batch publish.
@@ -77,47 +168,210 @@ pub struct BatchMessageInput {
 pub struct BatchPublishResultItem {
     pub message_id: String,
     pub sequence: i64,
+    pub partition: u32,
+    pub partition_sequence: i64,
+    /// True if an earlier publish with the same idempotency key was returned.
+    pub duplicate: bool,
 }
 
 /// Command to batch publish multiple messages atomically.
 pub struct BatchPublishCommand {
     pub topic: String,
-    pub messages: Vec<BatchMessageInput>,
+    pub messages: Vec<MessageInput>,
+    pub expected_last_sequence: Option<i64>,
     pub reply: oneshot::Sender<Result<(Vec<BatchPublishResultItem>, i64), WriterError>>,
 }
 
+/// Messages for one topic of a transactional publish.
+#[derive(Debug, Default)]
+pub struct TopicBatch {
+    pub topic: String,
+    pub messages: Vec<MessageInput>,
+    pub expected_last_sequence: Option<i64>,
+}
+
+/// Results of each batch of a transactional publish, and their timestamp.
+pub type TransactionResults = (Vec<Vec<BatchPublishResultItem>>, i64);
+
+/// Acks of a consumer group committed together with a transactional publish.
+#[derive(Debug)]
+pub struct GroupAck {
+    pub topic_id: i64,
+    pub consumer_group: String,
+    /// Reset generation the acking connection loaded.
+    pub reset_generation: i64,
+    /// Partitions whose cursors move (empty = all partitions).
+    pub partitions: Vec<u32>,
+    /// The group's new cursor.
+    pub cursor_seq: i64,
+    /// Individually acked sequences; empty for a cumulative ack.
+    pub seqs: Vec<i64>,
+}
+
+/// Command to publish to several topics in one transaction.
+pub struct PublishTransactionCommand {
+    pub ack: Option<GroupAck>,
+    pub batches: Vec<TopicBatch>,
+    pub reply: oneshot::Sender<Result<TransactionResults, WriterError>>,
+}
+
+/// Command to create a topic with a fixed number of partitions.
+pub struct CreateTopicCommand {
+    pub name: String,
+    pub partition_count: u32,
+    pub settings: TopicSettings,
+    pub reply: oneshot::Sender<Result<Option<Topic>, WriterError>>,
+}
+
+/// Command to replace a topic's configuration.
+pub struct UpdateTopicConfigCommand {
+    pub name: String,
+    pub settings: TopicSettings,
+    pub reply: oneshot::Sender<Result<Option<Topic>, WriterError>>,
+}
+
+/// Command to delete a topic with its messages and consumer groups.
+pub struct DeleteTopicCommand {
+    pub name: String,
+    pub reply: oneshot::Sender<Result<Option<(Topic, DeleteOutcome)>, WriterError>>,
+}
+
+/// Command to delete every message of a topic.
+pub struct PurgeTopicCommand {
+    pub name: String,
+    pub reply: oneshot::Sender<Result<Option<(Topic, PruneOutcome)>, WriterError>>,
+}
+
+/// Command to gather a topic's statistics and consumer groups.
+pub struct DescribeTopicCommand {
+    pub name: String,
+    pub reply: oneshot::Sender<Result<Option<TopicDescription>, WriterError>>,
+}
+
+/// A topic's storage statistics and consumer groups.
+#[derive(Debug, Clone)]
+pub struct TopicDescription {
+    pub topic: Topic,
+    /// Highest sequence assigned in each partition, indexed by partition.
+    pub partition_max_seqs: Vec<i64>,
+    pub stats: TopicStats,
+    pub settings: TopicSettings,
+    /// Consumer groups, each with the number of stored messages above its cursor.
+    pub consumer_groups: Vec<(Subscription, i64)>,
+}
+
+/// Where a consumer group reset moves the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetTarget {
+    /// Before the oldest stored message.
+    Earliest,
+    /// After the newest stored message.
+    Latest,
+    /// To this sequence; delivery resumes with the next message after it.
+    Sequence(i64),
+    /// Before the first message created at or after this Unix time (ms).
+    Timestamp(i64),
+}
+
+/// Command to move a consumer group's cursor.
+pub struct ResetConsumerGroupCommand {
+    pub topic_id: i64,
+    pub consumer_group: String,
+    pub target: ResetTarget,
+    pub reply: oneshot::Sender<Result<Option<Subscription>, WriterError>>,
+}
+
+/// Command to delete a consumer group.
+pub struct DeleteConsumerGroupCommand {
+    pub topic_id: i64,
+    pub consumer_group: String,
+    pub reply: oneshot::Sender<Result<bool, WriterError>>,
+}
+
+/// Command to delete a chunk of messages that fall outside retention.
+pub struct PruneCommand {
+    pub topic_id: i64,
+    pub up_to_seq: i64,
+    pub limit: i64,
+    pub reply: oneshot::Sender<Result<PruneOutcome, WriterError>>,
+}
+
+/// Command to store per-topic retention overrides.
+pub struct SetRetentionCommand {
+    pub topic_id: i64,
+    pub policy: RetentionPolicy,
+    pub reply: oneshot::Sender<Result<(), WriterError>>,
+}
+
 /// Handle to the writer thread.
 ///
 /// Provides async interface to submit write operations.
 #[derive(Clone)]
 pub struct WriterHandle {
     sender: mpsc::Sender<WriterMessage>,
+    next_scheduled: watch::Receiver<Option<i64>>,
+    next_expiry: watch::Receiver<Option<i64>>,
 }
 
 enum WriterMessage {
     Publish(PublishCommand),
     BatchPublish(BatchPublishCommand),
+    PublishTransaction(PublishTransactionCommand),
+    CreateTopic(CreateTopicCommand),
+    UpdateTopicConfig(UpdateTopicConfigCommand),
+    DeleteTopic(DeleteTopicCommand),
+    PurgeTopic(PurgeTopicCommand),
+    DescribeTopic(DescribeTopicCommand),
+    ResetConsumerGroup(ResetConsumerGroupCommand),
+    DeleteConsumerGroup(DeleteConsumerGroupCommand),
     GetOrCreateSubscription(SubscriptionCommand),
     UpdateCursor(CursorUpdateCommand),
+    IndividualAck(IndividualAckCommand),
+    DeliveryFailure(DeliveryFailureCommand),
+    Prune(PruneCommand),
+    SetRetention(SetRetentionCommand),
+    ReleaseScheduled(ReleaseScheduledCommand),
+    ExpireMessages(ExpireMessagesCommand),
     Shutdown,
 }
 
 impl WriterHandle {
     /// Submit a publish command and wait for the result.
+    ///
+    /// With an `idempotency_key` already used on the topic within the dedup
+    /// window, nothing is written and the original publish is returned. With
+    /// `expected_last_sequence`, the message is only written if the topic's
+    /// last sequence still matches.
     pub async fn publish(
         &self,
         topic: String,
-        message_id: String,
-        payload: Option<Vec<u8>>,
-        attributes: Option<String>,
+        message: MessageInput,
+        expected_last_sequence: Option<i64>,
     ) -> Result<PublishResult, WriterError> {
+        self.enqueue_publish(topic, message, expected_last_sequence)
+            .await?
+            .await
+            .map_err(|_| WriterError::ChannelClosed)?
+    }
+
+    /// Queue a message for publishing without waiting for it to commit.
+    ///
+    /// Waits only for room in the writer channel, so a full channel pushes
+    /// back on the caller. The returned receiver resolves once the message's
+    /// batch is committed; messages are committed in the order they are
+    /// queued.
+    pub async fn enqueue_publish(
+        &self,
+        topic: String,
+        message: MessageInput,
+        expected_last_sequence: Option<i64>,
+    ) -> Result<oneshot::Receiver<Result<PublishResult, WriterError>>, WriterError> {
         let (reply_tx, reply_rx) = oneshot::channel();
 
         let cmd = PublishCommand {
             topic,
-            message_id,
-            payload,
-            attributes,
+            message,
+            expected_last_sequence,
             reply: reply_tx,
         };
 
@@ -126,6 +380,171 @@ impl WriterHandle {
             .await
             .map_err(|_| WriterError::ChannelClosed)?;
 
+        Ok(reply_rx)
+    }
+
+    /// Create a topic with `partition_count` partitions and its settings.
+    ///
+    /// Returns `None` if the topic already exists.
+    pub async fn create_topic(
+        &self,
+        name: String,
+        partition_count: u32,
+        settings: TopicSettings,
+    ) -> Result<Option<Topic>, WriterError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        let cmd = CreateTopicCommand {
+            name,
+            partition_count,
+            settings,
+            reply: reply_tx,
+        };
+
+        self.sender
+            .send(WriterMessage::CreateTopic(cmd))
+            .await
+            .map_err(|_| WriterError::ChannelClosed)?;
+
+        reply_rx.await.map_err(|_| WriterError::ChannelClosed)?
+    }
+
+    /// Replace a topic's settings.
+    ///
+    /// Returns `None` if the topic does not exist.
+    pub async fn update_topic_config(
+        &self,
+        name: String,
+        settings: TopicSettings,
+    ) -> Result<Option<Topic>, WriterError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        let cmd = UpdateTopicConfigCommand {
+            name,
+            settings,
+            reply: reply_tx,
+        };
+
+        self.sender
+            .send(WriterMessage::UpdateTopicConfig(cmd))
+            .await
+            .map_err(|_| WriterError::ChannelClosed)?;
+
+        reply_rx.await.map_err(|_| WriterError::ChannelClosed)?
+    }
+
+    /// Delete a topic with its messages and consumer groups.
+    ///
+    /// Returns the deleted topic, or `None` if it does not exist.
+    pub async fn delete_topic(
+        &self,
+        name: String,
+    ) -> Result<Option<(Topic, DeleteOutcome)>, WriterError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        let cmd = DeleteTopicCommand {
+            name,
+            reply: reply_tx,
+        };
+
+        self.sender
+            .send(WriterMessage::DeleteTopic(cmd))
+            .await
+            .map_err(|_| WriterError::ChannelClosed)?;
+
+        reply_rx.await.map_err(|_| WriterError::ChannelClosed)?
+    }
+
+    /// Delete every message of a topic, moving its cursors past them.
+    ///
+    /// Returns the purged topic, or `None` if it does not exist.
+    pub async fn purge_topic(
+        &self,
+        name: String,
+    ) -> Result<Option<(Topic, PruneOutcome)>, WriterError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        let cmd = PurgeTopicCommand {
+            name,
+            reply: reply_tx,
+        };
+
+        self.sender
+            .send(WriterMessage::PurgeTopic(cmd))
+            .await
+            .map_err(|_| WriterError::ChannelClosed)?;
+
+        reply_rx.await.map_err(|_| WriterError::ChannelClosed)?
+    }
+
+    /// Gather a topic's statistics and consumer groups.
+    ///
+    /// Runs on the writer so that it sees every publish acknowledged before
+    /// it. Returns `None` if the topic does not exist.
+    pub async fn describe_topic(
+        &self,
+        name: String,
+    ) -> Result<Option<TopicDescription>, WriterError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        let cmd = DescribeTopicCommand {
+            name,
+            reply: reply_tx,
+        };
+
+        self.sender
+            .send(WriterMessage::DescribeTopic(cmd))
+            .await
+            .map_err(|_| WriterError::ChannelClosed)?;
+
+        reply_rx.await.map_err(|_| WriterError::ChannelClosed)?
+    }
+
+    /// Move a consumer group's cursor to `target`.
+    ///
+    /// Returns the updated subscription, or `None` if the group does not exist.
+    pub async fn reset_consumer_group(
+        &self,
+        topic_id: i64,
+        consumer_group: String,
+        target: ResetTarget,
+    ) -> Result<Option<Subscription>, WriterError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        let cmd = ResetConsumerGroupCommand {
+            topic_id,
+            consumer_group,
+            target,
+            reply: reply_tx,
+        };
+
+        self.sender
+            .send(WriterMessage::ResetConsumerGroup(cmd))
+            .await
+            .map_err(|_| WriterError::ChannelClosed)?;
+
+        reply_rx.await.map_err(|_| WriterError::ChannelClosed)?
+    }
+
+    /// Delete a consumer group, returning false if it does not exist.
+    pub async fn delete_consumer_group(
+        &self,
+        topic_id: i64,
+        consumer_group: String,
+    ) -> Result<bool, WriterError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        let cmd = DeleteConsumerGroupCommand {
+            topic_id,
+            consumer_group,
+            reply: reply_tx,
+        };
+
+        self.sender
+            .send(WriterMessage::DeleteConsumerGroup(cmd))
+            .await
+            .map_err(|_| WriterError::ChannelClosed)?;
+
         reply_rx.await.map_err(|_| WriterError::ChannelClosed)?
     }
 
@@ -152,10 +571,16 @@ impl WriterHandle {
     }
 
     /// Update the cursor for a subscription.
+    ///
+    /// A non-empty `partitions` only moves those partitions' cursors. Fails
+    /// with `ConsumerGroupReset` if the group was reset since the caller
+    /// loaded `reset_generation`.
     pub async fn update_cursor(
         &self,
         topic_id: i64,
         consumer_group: String,
+        reset_generation: i64,
+        partitions: Vec<u32>,
         cursor_seq: i64,
     ) -> Result<(), WriterError> {
         let (reply_tx, reply_rx) = oneshot::channel();
@@ -163,6 +588,8 @@ impl WriterHandle {
         let cmd = CursorUpdateCommand {
             topic_id,
             consumer_group,
+            reset_generation,
+            partitions,
             cursor_seq,
             reply: reply_tx,
         };
@@ -175,6 +602,65 @@ impl WriterHandle {
         reply_rx.await.map_err(|_| WriterError::ChannelClosed)?
     }
 
+    /// Record an individual ACK and move the cursor to `cursor_seq`.
+    ///
+    /// `cursor_seq` is the contiguous ack point computed by the caller;
+    /// `seq` is stored separately if it lies above it. A non-empty
+    /// `partitions` only moves those partitions' cursors. Fails like
+    /// [`update_cursor`](Self::update_cursor) after a reset.
+    pub async fn record_individual_ack(
+        &self,
+        topic_id: i64,
+        consumer_group: String,
+        reset_generation: i64,
+        partitions: Vec<u32>,
+        seq: i64,
+        cursor_seq: i64,
+    ) -> Result<(), WriterError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        let cmd = IndividualAckCommand {
+            topic_id,
+            consumer_group,
+            reset_generation,
+            partitions,
+            seq,
+            cursor_seq,
+            reply: reply_tx,
+        };
+
+        self.sender
+            .send(WriterMessage::IndividualAck(cmd))
+            .await
+            .map_err(|_| WriterError::ChannelClosed)?;
+
+        reply_rx.await.map_err(|_| WriterError::ChannelClosed)?
+    }
+
+    /// Record a failed delivery attempt, returning the total failures so far.
+    pub async fn record_delivery_failure(
+        &self,
+        topic_id: i64,
+        consumer_group: String,
+        seq: i64,
+    ) -> Result<u32, WriterError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        let cmd = DeliveryFailureCommand {
+            topic_id,
+            consumer_group,
+            seq,
+            reply: reply_tx,
+        };
+
+        self.sender
+            .send(WriterMessage::DeliveryFailure(cmd))
+            .await
+            .map_err(|_| WriterError::ChannelClosed)?;
+
+        reply_rx.await.map_err(|_| WriterError::ChannelClosed)?
+    }
+
     /// Submit a batch publish command and wait for the result.
     ///
     /// Returns a tuple of (results, timestamp) where results contains
@@ -182,13 +668,15 @@ impl WriterHandle {
     pub async fn batch_publish(
         &self,
         topic: String,
-        messages: Vec<BatchMessageInput>,
+        messages: Vec<MessageInput>,
+        expected_last_sequence: Option<i64>,
     ) -> Result<(Vec<BatchPublishResultItem>, i64), WriterError> {
         let (reply_tx, reply_rx) = oneshot::channel();
 
         let cmd = BatchPublishCommand {
             topic,
             messages,
+            expected_last_sequence,
             reply: reply_tx,
         };
 
@@ -200,45 +688,230 @@ impl WriterHandle {
         reply_rx.await.map_err(|_| WriterError::ChannelClosed)?
     }
 
-    /// Request graceful shutdown of the writer thread.
-    pub async fn shutdown(&self) -> Result<(), WriterError> {
+    /// Publish to several topics atomically, all or nothing.
+    ///
+    /// Returns the results of each batch, in order, and the common
+    /// timestamp. Every `expected_last_sequence` must match for anything
+    /// to be written.
+    pub async fn publish_transaction(
+        &self,
+        batches: Vec<TopicBatch>,
+    ) -> Result<TransactionResults, WriterError> {
+        self.ack_and_publish(None, batches).await
+    }
+
+    /// Apply a consumer group's acks and publish batches atomically.
+    ///
+    /// The cursor update and the new messages are committed in one
+    /// transaction, so a pipeline reading one topic and writing another
+    /// neither loses nor duplicates messages when it crashes in between.
+    pub async fn ack_and_publish(
+        &self,
+        ack: Option<GroupAck>,
+        batches: Vec<TopicBatch>,
+    ) -> Result<TransactionResults, WriterError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        let cmd = PublishTransactionCommand {
+            ack,
+            batches,
+            reply: reply_tx,
+        };
+
         self.sender
-            .send(WriterMessage::Shutdown)
+            .send(WriterMessage::PublishTransaction(cmd))
             .await
-            .map_err(|_| WriterError::ChannelClosed)
-    }
-}
+            .map_err(|_| WriterError::ChannelClosed)?;
 
-/// Writer thread that owns the SQLite write connection.
-pub struct Writer {
-    handle: Option<JoinHandle<()>>,
-    sender: mpsc::Sender<WriterMessage>,
-}
+        reply_rx.await.map_err(|_| WriterError::ChannelClosed)?
+    }
 
-impl Writer {
-    /// Spawn a new writer thread.
-    ///
-    /// # Arguments
+    /// Delete up to `limit` messages with `global_seq <= up_to_seq` from a topic.
     ///
-    /// * `db_path` - Path to the SQLite database
-    /// * `notify_bus` - Notification bus for waking subscriptions
-    /// * `channel_size` - Size of the command channel (backpressure control)
-    /// * `batch_config` - Configuration for batch commits
-    /// * `wal_checkpoint_pages` - WAL checkpoint threshold in pages
-    pub fn spawn<P: AsRef<Path>>(
-        db_path: P,
-        notify_bus: NotificationBus,
-        channel_size: usize,
+    /// Each call runs in its own transaction so that large prunes are split
+    /// into bounded chunks and interleave with publishes.
+    pub async fn prune_messages(
+        &self,
+        topic_id: i64,
+        up_to_seq: i64,
+        limit: i64,
+    ) -> Result<PruneOutcome, WriterError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        let cmd = PruneCommand {
+            topic_id,
+            up_to_seq,
+            limit,
+            reply: reply_tx,
+        };
+
+        self.sender
+            .send(WriterMessage::Prune(cmd))
+            .await
+            .map_err(|_| WriterError::ChannelClosed)?;
+
+        reply_rx.await.map_err(|_| WriterError::ChannelClosed)?
+    }
+
+    /// Store per-topic retention overrides.
+    pub async fn set_topic_retention(
+        &self,
+        topic_id: i64,
+        policy: RetentionPolicy,
+    ) -> Result<(), WriterError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        let cmd = SetRetentionCommand {
+            topic_id,
+            policy,
+            reply: reply_tx,
+        };
+
+        self.sender
+            .send(WriterMessage::SetRetention(cmd))
+            .await
+            .map_err(|_| WriterError::ChannelClosed)?;
+
+        reply_rx.await.map_err(|_| WriterError::ChannelClosed)?
+    }
+
+    /// Append scheduled messages that are due to their topics.
+    ///
+    /// Returns the number of messages released. At most one chunk is
+    /// released per call; `next_scheduled` stays due while more remain.
+    pub async fn release_scheduled(&self) -> Result<usize, WriterError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        let cmd = ReleaseScheduledCommand { reply: reply_tx };
+
+        self.sender
+            .send(WriterMessage::ReleaseScheduled(cmd))
+            .await
+            .map_err(|_| WriterError::ChannelClosed)?;
+
+        reply_rx.await.map_err(|_| WriterError::ChannelClosed)?
+    }
+
+    /// Watch the earliest delivery time (Unix ms) of any scheduled message.
+    pub fn next_scheduled(&self) -> watch::Receiver<Option<i64>> {
+        self.next_scheduled.clone()
+    }
+
+    /// Delete messages whose time to live has passed.
+    ///
+    /// Messages of topics configured with `dead_letter_expired` are moved to
+    /// the topic's dead-letter topic instead. Returns the number of messages
+    /// expired. At most one chunk is expired per call; `next_expiry` stays
+    /// due while more remain.
+    pub async fn expire_messages(&self) -> Result<usize, WriterError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        let cmd = ExpireMessagesCommand { reply: reply_tx };
+
+        self.sender
+            .send(WriterMessage::ExpireMessages(cmd))
+            .await
+            .map_err(|_| WriterError::ChannelClosed)?;
+
+        reply_rx.await.map_err(|_| WriterError::ChannelClosed)?
+    }
+
+    /// Watch the earliest expiry time (Unix ms) of any stored message.
+    pub fn next_expiry(&self) -> watch::Receiver<Option<i64>> {
+        self.next_expiry.clone()
+    }
+
+    /// Request graceful shutdown of the writer thread.
+    pub async fn shutdown(&self) -> Result<(), WriterError> {
+        self.sender
+            .send(WriterMessage::Shutdown)
+            .await
+            .map_err(|_| WriterError::ChannelClosed)
+    }
+}
+
+/// Writer thread that owns the SQLite write connection.
+pub struct Writer {
+    handle: Option<JoinHandle<()>>,
+    sender: mpsc::Sender<WriterMessage>,
+    next_scheduled: watch::Receiver<Option<i64>>,
+    next_expiry: watch::Receiver<Option<i64>>,
+}
+
+impl Writer {
+    /// Spawn a new writer thread.
+    ///
+    /// The database and its schema are set up before this returns.
+    ///
+    /// # Arguments
+    ///
+    /// * `db_path` - Path to the SQLite database
+    /// * `notify_bus` - Notification bus for waking subscriptions
+    /// * `channel_size` - Size of the command channel (backpressure control)
+    /// * `batch_config` - Configuration for batch commits
+    /// * `wal_checkpoint_pages` - WAL checkpoint threshold in pages
+    /// * `auto_create_topics` - Whether publishing to an unknown topic creates it
+    /// * `dedup_window_ms` - How long idempotency keys are remembered (0 = off)
+    /// * `partition_writers` - Threads publishing to partitioned topics (0 = none)
+    pub fn spawn<P: AsRef<Path>>(
+        db_path: P,
+        notify_bus: NotificationBus,
+        channel_size: usize,
         batch_config: BatchConfig,
         wal_checkpoint_pages: i32,
+        auto_create_topics: bool,
+        dedup_window_ms: i64,
+        partition_writers: usize,
     ) -> Result<Self, WriterError> {
-        let db_path = db_path.as_ref().to_path_buf();
+        let db_path = db_path.as_ref();
+
+        // Set up the database before returning, so readers opened next find
+        // the schema
+        let conn = open_write_connection(db_path, wal_checkpoint_pages)?;
+        initialize_schema(&conn).map_err(|e| WriterError::Database(e.to_string()))?;
+
+        tracing::info!(
+            path = ?db_path,
+            batch_size = batch_config.max_batch_size,
+            batch_delay_ms = batch_config.max_batch_delay.as_millis(),
+            wal_checkpoint_pages,
+            auto_create_topics,
+            dedup_window_ms,
+            partition_writers,
+            "Writer thread starting"
+        );
+
         let (sender, receiver) = mpsc::channel(channel_size);
+        let (schedule_tx, next_scheduled) = watch::channel(None);
+        let (expiry_tx, next_expiry) = watch::channel(None);
+        let schedule = Arc::new(Schedule {
+            next: schedule_tx,
+            next_expiry: expiry_tx,
+        });
+        let partition_writers = PartitionWriters::spawn(
+            db_path,
+            partition_writers,
+            channel_size,
+            batch_config,
+            wal_checkpoint_pages,
+            dedup_window_ms,
+            &schedule,
+            &notify_bus,
+        )?;
 
         let handle = thread::Builder::new()
             .name("sluice-writer".into())
             .spawn(move || {
-                if let Err(e) = writer_thread_main(db_path, receiver, notify_bus, batch_config, wal_checkpoint_pages) {
+                if let Err(e) = writer_thread_main(
+                    conn,
+                    receiver,
+                    notify_bus,
+                    batch_config,
+                    auto_create_topics,
+                    dedup_window_ms,
+                    schedule,
+                    partition_writers,
+                ) {
                     tracing::error!(error = %e, "Writer thread error");
                 }
             })
@@ -247,6 +920,8 @@ impl Writer {
         Ok(Self {
             handle: Some(handle),
             sender,
+            next_scheduled,
+            next_expiry,
         })
     }
 
@@ -254,6 +929,8 @@ impl Writer {
     pub fn handle(&self) -> WriterHandle {
         WriterHandle {
             sender: self.sender.clone(),
+            next_scheduled: self.next_scheduled.clone(),
+            next_expiry: self.next_expiry.clone(),
         }
     }
 
@@ -266,35 +943,45 @@ impl Writer {
     }
 }
 
+/// Open a connection for writing to the database at `db_path`.
+fn open_write_connection(
+    db_path: &Path,
+    wal_checkpoint_pages: i32,
+) -> Result<Connection, WriterError> {
+    let conn = Connection::open(db_path).map_err(|e| WriterError::Database(e.to_string()))?;
+    apply_pragmas(&conn).map_err(|e| WriterError::Database(e.to_string()))?;
+
+    // Set WAL auto-checkpoint threshold
+    conn.execute_batch(&format!(
+        "PRAGMA wal_autocheckpoint = {wal_checkpoint_pages};"
+    ))
+    .map_err(|e| WriterError::Database(e.to_string()))?;
+    Ok(conn)
+}
+
 /// Main function for the writer thread.
 fn writer_thread_main(
-    db_path: std::path::PathBuf,
+    conn: Connection,
     mut receiver: mpsc::Receiver<WriterMessage>,
     notify_bus: NotificationBus,
     batch_config: BatchConfig,
-    wal_checkpoint_pages: i32,
+    auto_create_topics: bool,
+    dedup_window_ms: i64,
+    schedule: Arc<Schedule>,
+    partition_writers: PartitionWriters,
 ) -> Result<(), WriterError> {
-    // Open database connection
-    let conn = Connection::open(&db_path).map_err(|e| WriterError::Database(e.to_string()))?;
-
-    // Apply pragmas and initialize schema
-    apply_pragmas(&conn).map_err(|e| WriterError::Database(e.to_string()))?;
-    initialize_schema(&conn).map_err(|e| WriterError::Database(e.to_string()))?;
-
-    // Set WAL auto-checkpoint threshold
-    conn.execute_batch(&format!("PRAGMA wal_autocheckpoint = {wal_checkpoint_pages};"))
-        .map_err(|e| WriterError::Database(e.to_string()))?;
+    // Topic ID cache
+    let mut topic_cache = TopicCache {
+        auto_create: auto_create_topics,
+        ..Default::default()
+    };
 
-    tracing::info!(
-        path = ?db_path,
-        batch_size = batch_config.max_batch_size,
-        batch_delay_ms = batch_config.max_batch_delay.as_millis(),
-        wal_checkpoint_pages,
-        "Writer thread started"
-    );
+    // Idempotency keys of recent publishes
+    let mut dedup = Deduplicator::new(dedup_window_ms);
 
-    // Topic ID cache
-    let mut topic_cache: HashMap<String, i64> = HashMap::new();
+    // Scheduled and expiring messages left from before a restart
+    schedule.refresh(&conn)?;
+    schedule.refresh_expiry(&conn)?;
 
     // Batch accumulator
     let mut batch: BatchAccumulator<PublishCommand> = BatchAccumulator::new(batch_config);
@@ -327,24 +1014,204 @@ fn writer_thread_main(
 
         match msg {
             Some(WriterMessage::Publish(cmd)) => {
+                // Publishes to a resolved partitioned topic go to their
+                // partition's writer
+                let Some(cmd) = partition_writers.route(cmd, &mut topic_cache) else {
+                    continue;
+                };
                 let ready = batch.push(cmd);
                 if ready {
-                    flush_batch(&conn, &mut batch, &mut topic_cache, &notify_bus)?;
+                    flush_batch(
+                        &conn,
+                        &mut batch,
+                        &mut topic_cache,
+                        &mut dedup,
+                        &schedule,
+                        &notify_bus,
+                    )?;
                 }
             }
             Some(WriterMessage::BatchPublish(cmd)) => {
-                // Flush pending batch first to ensure consistency
+                // Flush pending batches first to ensure consistency
                 if !batch.is_empty() {
-                    flush_batch(&conn, &mut batch, &mut topic_cache, &notify_bus)?;
+                    flush_batch(
+                        &conn,
+                        &mut batch,
+                        &mut topic_cache,
+                        &mut dedup,
+                        &schedule,
+                        &notify_bus,
+                    )?;
                 }
+                partition_writers.sync();
                 // Execute batch publish atomically
-                let result = execute_batch_publish(&conn, cmd.topic, cmd.messages, &mut topic_cache, &notify_bus);
+                let result = execute_batch_publish(
+                    &conn,
+                    cmd.topic,
+                    cmd.messages,
+                    cmd.expected_last_sequence,
+                    &mut topic_cache,
+                    &mut dedup,
+                    &schedule,
+                    &notify_bus,
+                );
+                let _ = cmd.reply.send(result);
+            }
+            Some(WriterMessage::PublishTransaction(cmd)) => {
+                // Flush pending batches first to ensure consistency
+                if !batch.is_empty() {
+                    flush_batch(
+                        &conn,
+                        &mut batch,
+                        &mut topic_cache,
+                        &mut dedup,
+                        &schedule,
+                        &notify_bus,
+                    )?;
+                }
+                partition_writers.sync();
+                let result = execute_publish_transaction(
+                    &conn,
+                    cmd.ack.as_ref(),
+                    cmd.batches,
+                    &mut topic_cache,
+                    &mut dedup,
+                    &schedule,
+                    &notify_bus,
+                );
+                let _ = cmd.reply.send(result);
+            }
+            Some(WriterMessage::CreateTopic(cmd)) => {
+                // Flush pending batch first to ensure consistency
+                if !batch.is_empty() {
+                    flush_batch(
+                        &conn,
+                        &mut batch,
+                        &mut topic_cache,
+                        &mut dedup,
+                        &schedule,
+                        &notify_bus,
+                    )?;
+                }
+                let result = execute_create_topic(&conn, &cmd);
+                if let Ok(Some(topic)) = &result {
+                    topic_cache.insert(topic, &cmd.settings);
+                }
+                let _ = cmd.reply.send(result);
+            }
+            Some(WriterMessage::UpdateTopicConfig(cmd)) => {
+                // Flush pending batches first to ensure consistency
+                if !batch.is_empty() {
+                    flush_batch(
+                        &conn,
+                        &mut batch,
+                        &mut topic_cache,
+                        &mut dedup,
+                        &schedule,
+                        &notify_bus,
+                    )?;
+                }
+                partition_writers.sync();
+                let result = execute_update_topic_config(&conn, &cmd);
+                if let Ok(Some(topic)) = &result {
+                    topic_cache.insert(topic, &cmd.settings);
+                }
+                let _ = cmd.reply.send(result);
+            }
+            Some(WriterMessage::DeleteTopic(cmd)) => {
+                // Flush pending batches first to ensure consistency
+                if !batch.is_empty() {
+                    flush_batch(
+                        &conn,
+                        &mut batch,
+                        &mut topic_cache,
+                        &mut dedup,
+                        &schedule,
+                        &notify_bus,
+                    )?;
+                }
+                partition_writers.sync();
+                let result = execute_delete_topic(&conn, &cmd.name);
+                if let Ok(Some(_)) = &result {
+                    topic_cache.remove(&cmd.name);
+                }
+                let _ = cmd.reply.send(result);
+            }
+            Some(WriterMessage::PurgeTopic(cmd)) => {
+                // Flush pending batches first to ensure consistency
+                if !batch.is_empty() {
+                    flush_batch(
+                        &conn,
+                        &mut batch,
+                        &mut topic_cache,
+                        &mut dedup,
+                        &schedule,
+                        &notify_bus,
+                    )?;
+                }
+                partition_writers.sync();
+                let result = execute_purge_topic(&conn, &cmd.name);
+                let _ = cmd.reply.send(result);
+            }
+            Some(WriterMessage::DescribeTopic(cmd)) => {
+                // Flush pending batches first to ensure consistency
+                if !batch.is_empty() {
+                    flush_batch(
+                        &conn,
+                        &mut batch,
+                        &mut topic_cache,
+                        &mut dedup,
+                        &schedule,
+                        &notify_bus,
+                    )?;
+                }
+                partition_writers.sync();
+                let result = describe_topic(&conn, &cmd.name)
+                    .map_err(|e| WriterError::Database(e.to_string()));
+                let _ = cmd.reply.send(result);
+            }
+            Some(WriterMessage::ResetConsumerGroup(cmd)) => {
+                // Flush pending batches first to ensure consistency
+                if !batch.is_empty() {
+                    flush_batch(
+                        &conn,
+                        &mut batch,
+                        &mut topic_cache,
+                        &mut dedup,
+                        &schedule,
+                        &notify_bus,
+                    )?;
+                }
+                partition_writers.sync();
+                let result = execute_reset_consumer_group(&conn, &cmd);
+                let _ = cmd.reply.send(result);
+            }
+            Some(WriterMessage::DeleteConsumerGroup(cmd)) => {
+                // Flush pending batch first to ensure consistency
+                if !batch.is_empty() {
+                    flush_batch(
+                        &conn,
+                        &mut batch,
+                        &mut topic_cache,
+                        &mut dedup,
+                        &schedule,
+                        &notify_bus,
+                    )?;
+                }
+                let result = execute_delete_consumer_group(&conn, &cmd);
                 let _ = cmd.reply.send(result);
             }
             Some(WriterMessage::GetOrCreateSubscription(cmd)) => {
                 // Flush pending batch first to ensure consistency
                 if !batch.is_empty() {
-                    flush_batch(&conn, &mut batch, &mut topic_cache, &notify_bus)?;
+                    flush_batch(
+                        &conn,
+                        &mut batch,
+                        &mut topic_cache,
+                        &mut dedup,
+                        &schedule,
+                        &notify_bus,
+                    )?;
                 }
                 let result = get_or_create_subscription(
                     &conn,
@@ -358,31 +1225,136 @@ fn writer_thread_main(
             Some(WriterMessage::UpdateCursor(cmd)) => {
                 // Flush pending batch first to ensure consistency
                 if !batch.is_empty() {
-                    flush_batch(&conn, &mut batch, &mut topic_cache, &notify_bus)?;
+                    flush_batch(
+                        &conn,
+                        &mut batch,
+                        &mut topic_cache,
+                        &mut dedup,
+                        &schedule,
+                        &notify_bus,
+                    )?;
                 }
-                let result = update_cursor(
-                    &conn,
-                    cmd.topic_id,
-                    &cmd.consumer_group,
-                    cmd.cursor_seq,
-                    now_millis(),
-                )
-                .map(|_| ())
-                .map_err(|e| WriterError::Database(e.to_string()));
+                let result = execute_update_cursor(&conn, &cmd);
+                let _ = cmd.reply.send(result);
+            }
+            Some(WriterMessage::IndividualAck(cmd)) => {
+                // Flush pending batch first to ensure consistency
+                if !batch.is_empty() {
+                    flush_batch(
+                        &conn,
+                        &mut batch,
+                        &mut topic_cache,
+                        &mut dedup,
+                        &schedule,
+                        &notify_bus,
+                    )?;
+                }
+                let result = execute_individual_ack(&conn, &cmd);
+                let _ = cmd.reply.send(result);
+            }
+            Some(WriterMessage::DeliveryFailure(cmd)) => {
+                // Flush pending batch first to ensure consistency
+                if !batch.is_empty() {
+                    flush_batch(
+                        &conn,
+                        &mut batch,
+                        &mut topic_cache,
+                        &mut dedup,
+                        &schedule,
+                        &notify_bus,
+                    )?;
+                }
+                let result = execute_delivery_failure(&conn, &cmd);
+                let _ = cmd.reply.send(result);
+            }
+            Some(WriterMessage::Prune(cmd)) => {
+                // Flush pending batch first to ensure consistency
+                if !batch.is_empty() {
+                    flush_batch(
+                        &conn,
+                        &mut batch,
+                        &mut topic_cache,
+                        &mut dedup,
+                        &schedule,
+                        &notify_bus,
+                    )?;
+                }
+                let result = execute_prune(&conn, cmd.topic_id, cmd.up_to_seq, cmd.limit);
+                let _ = cmd.reply.send(result);
+            }
+            Some(WriterMessage::SetRetention(cmd)) => {
+                // Flush pending batch first to ensure consistency
+                if !batch.is_empty() {
+                    flush_batch(
+                        &conn,
+                        &mut batch,
+                        &mut topic_cache,
+                        &mut dedup,
+                        &schedule,
+                        &notify_bus,
+                    )?;
+                }
+                let result = set_topic_retention(&conn, cmd.topic_id, &cmd.policy, now_millis())
+                    .map_err(|e| WriterError::Database(e.to_string()));
+                let _ = cmd.reply.send(result);
+            }
+            Some(WriterMessage::ReleaseScheduled(cmd)) => {
+                // Flush pending batch first to ensure consistency
+                if !batch.is_empty() {
+                    flush_batch(
+                        &conn,
+                        &mut batch,
+                        &mut topic_cache,
+                        &mut dedup,
+                        &schedule,
+                        &notify_bus,
+                    )?;
+                }
+                let result = execute_release_scheduled(&conn, &schedule, &notify_bus);
+                let _ = cmd.reply.send(result);
+            }
+            Some(WriterMessage::ExpireMessages(cmd)) => {
+                // Flush pending batch first to ensure consistency
+                if !batch.is_empty() {
+                    flush_batch(
+                        &conn,
+                        &mut batch,
+                        &mut topic_cache,
+                        &mut dedup,
+                        &schedule,
+                        &notify_bus,
+                    )?;
+                }
+                let result =
+                    execute_expire_messages(&conn, &mut topic_cache, &schedule, &notify_bus);
                 let _ = cmd.reply.send(result);
             }
             Some(WriterMessage::Shutdown) => {
                 tracing::info!("Writer thread shutting down");
                 // Flush remaining batch
                 if !batch.is_empty() {
-                    flush_batch(&conn, &mut batch, &mut topic_cache, &notify_bus)?;
+                    flush_batch(
+                        &conn,
+                        &mut batch,
+                        &mut topic_cache,
+                        &mut dedup,
+                        &schedule,
+                        &notify_bus,
+                    )?;
                 }
                 break;
             }
             None => {
                 // Timeout or channel closed - check if batch needs flushing
                 if !batch.is_empty() {
-                    flush_batch(&conn, &mut batch, &mut topic_cache, &notify_bus)?;
+                    flush_batch(
+                        &conn,
+                        &mut batch,
+                        &mut topic_cache,
+                        &mut dedup,
+                        &schedule,
+                        &notify_bus,
+                    )?;
                 }
                 if receiver.is_closed() {
                     tracing::info!("Writer channel closed, exiting");
@@ -398,157 +1370,1826 @@ fn writer_thread_main(
 
 use std::time::Duration;
 
-/// Flush the accumulated batch in a single transaction.
-fn flush_batch(
-    conn: &Connection,
-    batch: &mut BatchAccumulator<PublishCommand>,
-    topic_cache: &mut HashMap<String, i64>,
-    notify_bus: &NotificationBus,
-) -> Result<(), WriterError> {
-    let commands = batch.drain();
-    if commands.is_empty() {
-        return Ok(());
+/// A topic the writer has resolved.
+#[derive(Debug, Clone, Copy)]
+struct CachedTopic {
+    id: i64,
+    partition_count: u32,
+    max_message_bytes: Option<i64>,
+}
+
+impl CachedTopic {
+    /// Reject a payload larger than the topic allows.
+    fn check_size(&self, name: &str, payload: Option<&[u8]>) -> Result<(), WriterError> {
+        let size = payload.map_or(0, <[u8]>::len);
+        match self.max_message_bytes {
+            Some(max) if size as i64 > max => Err(WriterError::MessageTooLarge {
+                topic: name.to_string(),
+                size,
+                max,
+            }),
+            _ => Ok(()),
+        }
     }
+}
 
-    let now = now_millis();
-    let batch_size = commands.len();
+/// Topics the writer has resolved, and how their messages are partitioned.
+#[derive(Default)]
+struct TopicCache {
+    /// Topic name -> resolved topic.
+    topics: HashMap<String, CachedTopic>,
+    partitioner: Partitioner,
+    /// Whether resolving an unknown topic creates it.
+    auto_create: bool,
+}
 
-    tracing::debug!(batch_size, "Flushing batch");
+impl TopicCache {
+    /// Remember a topic the writer just created or reconfigured.
+    fn insert(&mut self, topic: &Topic, settings: &TopicSettings) {
+        self.topics.insert(
+            topic.name.clone(),
+            CachedTopic {
+                id: topic.id,
+                partition_count: topic.partition_count,
+                max_message_bytes: settings.max_message_bytes,
+            },
+        );
+    }
 
-    // Track max sequence per topic for notifications
-    let mut topic_max_seq: HashMap<i64, i64> = HashMap::new();
+    /// Forget a topic the writer just deleted.
+    fn remove(&mut self, name: &str) {
+        self.topics.remove(name);
+    }
 
-    // Execute batch in a transaction
-    let tx = conn
-        .unchecked_transaction()
-        .map_err(|e| WriterError::Database(e.to_string()))?;
+    /// Get a topic, creating it if auto-creation is allowed.
+    ///
+    /// Request inboxes are never auto-created; a reply to a requester that
+    /// is gone fails instead of bringing its inbox back.
+    fn resolve(
+        &mut self,
+        conn: &Connection,
+        name: &str,
+        now: i64,
+    ) -> Result<CachedTopic, WriterError> {
+        if let Some(&entry) = self.topics.get(name) {
+            return Ok(entry);
+        }
 
-    for cmd in commands {
-        // Get or create topic
-        let topic_id = match topic_cache.get(&cmd.topic) {
-            Some(&id) => id,
-            None => {
-                let id = insert_or_get_topic(&tx, &cmd.topic, now)
-                    .map_err(|e| WriterError::Database(e.to_string()))?;
-                topic_cache.insert(cmd.topic.clone(), id);
-                id
+        let existing =
+            get_topic_by_name(conn, name).map_err(|e| WriterError::Database(e.to_string()))?;
+        let id = match existing {
+            Some(topic) => topic.id,
+            None if !is_inbox(name) && self.may_create(conn, name)? => {
+                insert_or_get_topic(conn, name, now)
+                    .map_err(|e| WriterError::Database(e.to_string()))?
             }
+            None => return Err(WriterError::TopicNotFound(name.to_string())),
         };
-
-        // Insert message
-        let seq = insert_message(
-            &tx,
-            topic_id,
-            &cmd.message_id,
-            cmd.payload.as_deref(),
-            cmd.attributes.as_deref(),
-            now,
-        )
-        .map_err(|e| WriterError::Database(e.to_string()))?;
-
-        // Track max sequence for topic
-        topic_max_seq
-            .entry(topic_id)
-            .and_modify(|max| *max = (*max).max(seq))
-            .or_insert(seq);
-
-        // Send reply
-        let result = PublishResult {
-            message_id: cmd.message_id,
-            sequence: seq,
-            timestamp: now,
+        let partition_count = get_topic_partition_count(conn, id)
+            .map_err(|e| WriterError::Database(e.to_string()))?;
+        let settings =
+            get_topic_settings(conn, id).map_err(|e| WriterError::Database(e.to_string()))?;
+        let entry = CachedTopic {
+            id,
+            partition_count,
+            max_message_bytes: settings.max_message_bytes,
         };
-        let _ = cmd.reply.send(Ok(result));
+        self.topics.insert(name.to_string(), entry);
+        Ok(entry)
     }
 
-    // Commit transaction (single fsync for entire batch)
-    tx.commit()
-        .map_err(|e| WriterError::Database(e.to_string()))?;
-
-    tracing::debug!(batch_size, "Batch committed");
-
-    // Notify subscribers
-    for (topic_id, max_seq) in topic_max_seq {
-        notify_bus.notify(topic_id, max_seq);
+    /// Whether an unknown topic may be auto-created.
+    ///
+    /// The nearest existing ancestor (`orders` for `orders.dlq`) that sets
+    /// `auto_create` decides; otherwise the server setting applies.
+    fn may_create(&self, conn: &Connection, name: &str) -> Result<bool, WriterError> {
+        for (end, _) in name.rmatch_indices('.') {
+            let Some(parent) = get_topic_by_name(conn, &name[..end])
+                .map_err(|e| WriterError::Database(e.to_string()))?
+            else {
+                continue;
+            };
+            let settings = get_topic_settings(conn, parent.id)
+                .map_err(|e| WriterError::Database(e.to_string()))?;
+            if let Some(allowed) = settings.auto_create {
+                return Ok(allowed);
+            }
+        }
+        Ok(self.auto_create)
     }
 
-    Ok(())
-}
-
-/// Execute a batch publish atomically in a single transaction.
-fn execute_batch_publish(
-    conn: &Connection,
-    topic: String,
-    messages: Vec<BatchMessageInput>,
-    topic_cache: &mut HashMap<String, i64>,
-    notify_bus: &NotificationBus,
-) -> Result<(Vec<BatchPublishResultItem>, i64), WriterError> {
-    if messages.is_empty() {
-        return Ok((Vec::new(), now_millis()));
+    /// Pick the partition for a message on a resolved topic.
+    fn partition(&mut self, topic: &CachedTopic, key: Option<&str>) -> u32 {
+        self.partitioner
+            .assign(topic.id, topic.partition_count, key)
     }
+}
 
-    let now = now_millis();
-    let batch_size = messages.len();
-
-    tracing::debug!(batch_size, topic = %topic, "Executing batch publish");
+/// Minimum time between two prunes of expired idempotency keys.
+const DEDUP_PRUNE_INTERVAL_MS: i64 = 1000;
 
-    // Execute in a transaction
-    let tx = conn
-        .unchecked_transaction()
-        .map_err(|e| WriterError::Database(e.to_string()))?;
+/// Idempotency keys of publishes within the dedup window.
+///
+/// Keys live in SQLite so that deduplication survives restarts; expired keys
+/// are pruned as publishes come in, which bounds the table by the window.
+struct Deduplicator {
+    /// How long a key is remembered (0 disables deduplication).
+    window_ms: i64,
+    last_pruned_at: i64,
+}
 
-    // Get or create topic
-    let topic_id = match topic_cache.get(&topic) {
-        Some(&id) => id,
-        None => {
-            let id = insert_or_get_topic(&tx, &topic, now)
-                .map_err(|e| WriterError::Database(e.to_string()))?;
-            topic_cache.insert(topic.clone(), id);
-            id
+impl Deduplicator {
+    fn new(window_ms: i64) -> Self {
+        Self {
+            window_ms,
+            last_pruned_at: 0,
         }
-    };
+    }
 
-    let mut results = Vec::with_capacity(batch_size);
-    let mut max_seq = 0i64;
+    /// Find an earlier publish with this key within the window.
+    fn lookup(
+        &self,
+        conn: &Connection,
+        topic_id: i64,
+        key: Option<&str>,
+        now: i64,
+    ) -> Result<Option<IdempotentPublish>, WriterError> {
+        match key {
+            Some(key) if self.window_ms > 0 => {
+                get_idempotent_publish(conn, topic_id, key, now - self.window_ms)
+                    .map_err(|e| WriterError::Database(e.to_string()))
+            }
+            _ => Ok(None),
+        }
+    }
 
-    for msg in messages {
-        let seq = insert_message(
+    /// Remember a publish under its key.
+    fn record(
+        &self,
+        conn: &Connection,
+        topic_id: i64,
+        key: Option<&str>,
+        publish: &IdempotentPublish,
+    ) -> Result<(), WriterError> {
+        match key {
+            Some(key) if self.window_ms > 0 => record_idempotency_key(conn, topic_id, key, publish)
+                .map_err(|e| WriterError::Database(e.to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Forget keys that left the window, at most once per prune interval.
+    fn maybe_prune(&mut self, conn: &Connection, now: i64) -> Result<(), WriterError> {
+        if self.window_ms <= 0 || now - self.last_pruned_at < DEDUP_PRUNE_INTERVAL_MS {
+            return Ok(());
+        }
+        self.last_pruned_at = now;
+        let pruned = prune_idempotency_keys(conn, now - self.window_ms)
+            .map_err(|e| WriterError::Database(e.to_string()))?;
+        if pruned > 0 {
+            tracing::debug!(pruned, "Pruned idempotency keys");
+        }
+        Ok(())
+    }
+}
+
+/// Maximum number of scheduled messages released per transaction.
+const RELEASE_CHUNK_SIZE: i64 = 1000;
+
+/// Maximum number of expired messages deleted per transaction.
+const EXPIRE_CHUNK_SIZE: i64 = 1000;
+
+/// Earliest delivery time of any scheduled message and earliest expiry
+/// time of any stored message, published to the scheduler and expiry tasks
+/// through `WriterHandle::next_scheduled` and `WriterHandle::next_expiry`.
+struct Schedule {
+    next: watch::Sender<Option<i64>>,
+    next_expiry: watch::Sender<Option<i64>>,
+}
+
+impl Schedule {
+    /// Account for a message committed with `deliver_at`.
+    fn add(&self, deliver_at: i64) {
+        lower(&self.next, deliver_at);
+    }
+
+    /// Account for a message committed with `expires_at`.
+    fn add_expiry(&self, expires_at: i64) {
+        lower(&self.next_expiry, expires_at);
+    }
+
+    /// Re-read the earliest delivery time from storage.
+    fn refresh(&self, conn: &Connection) -> Result<(), WriterError> {
+        let earliest =
+            next_scheduled_delivery(conn).map_err(|e| WriterError::Database(e.to_string()))?;
+        replace(&self.next, earliest);
+        Ok(())
+    }
+
+    /// Re-read the earliest expiry time from storage.
+    fn refresh_expiry(&self, conn: &Connection) -> Result<(), WriterError> {
+        let earliest =
+            next_message_expiry(conn).map_err(|e| WriterError::Database(e.to_string()))?;
+        replace(&self.next_expiry, earliest);
+        Ok(())
+    }
+}
+
+/// Move a watched time earlier to `at`, if it is later or unset.
+fn lower(time: &watch::Sender<Option<i64>>, at: i64) {
+    time.send_if_modified(|next| {
+        let earlier = next.map_or(true, |next| at < next);
+        if earlier {
+            *next = Some(at);
+        }
+        earlier
+    });
+}
+
+/// Set a watched time, notifying watchers only if it changed.
+fn replace(time: &watch::Sender<Option<i64>>, earliest: Option<i64>) {
+    time.send_if_modified(|next| {
+        let changed = *next != earliest;
+        *next = earliest;
+        changed
+    });
+}
+
+/// Expiry time of a message made visible at `now`.
+fn expires_at(now: i64, ttl_ms: Option<i64>) -> Option<i64> {
+    ttl_ms.map(|ttl| now.saturating_add(ttl))
+}
+
+/// Store a message for delivery at `deliver_at` instead of appending it.
+///
+/// It is sequenced when released, so the returned publish has no sequence.
+fn schedule_message(
+    conn: &Connection,
+    topic_id: i64,
+    partition: u32,
+    msg: MessageInput,
+    now: i64,
+    deliver_at: i64,
+) -> Result<IdempotentPublish, WriterError> {
+    let scheduled = ScheduledMessage {
+        id: 0,
+        topic_id,
+        message_id: msg.message_id,
+        payload: msg.payload,
+        attributes: msg.attributes,
+        key: msg.key,
+        partition,
+        created_at: now,
+        deliver_at,
+        ttl_ms: msg.ttl_ms,
+        priority: msg.priority,
+    };
+    insert_scheduled_message(conn, &scheduled).map_err(|e| WriterError::Database(e.to_string()))?;
+
+    Ok(IdempotentPublish {
+        message_id: scheduled.message_id,
+        global_seq: 0,
+        partition,
+        partition_seq: 0,
+        created_at: now,
+    })
+}
+
+/// Begin a write transaction, taking the database write lock up front.
+///
+/// Partition writers commit on connections of their own. A deferred
+/// transaction that read first could not upgrade to a write once another
+/// connection committed, so every write transaction locks immediately.
+fn begin_write(conn: &Connection) -> Result<Transaction<'_>, WriterError> {
+    Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+        .map_err(|e| WriterError::Database(e.to_string()))
+}
+
+/// Flush the accumulated batch in a single transaction.
+///
+/// Replies are sent only after the commit, so a publisher never sees a
+/// sequence that a failed commit would roll back. The cost is latency: each
+/// publish waits for its whole batch, which is bounded by the batch delay.
+fn flush_batch(
+    conn: &Connection,
+    batch: &mut BatchAccumulator<PublishCommand>,
+    topic_cache: &mut TopicCache,
+    dedup: &mut Deduplicator,
+    schedule: &Schedule,
+    notify_bus: &NotificationBus,
+) -> Result<(), WriterError> {
+    let commands = batch.drain();
+    if commands.is_empty() {
+        return Ok(());
+    }
+
+    let now = now_millis();
+    let batch_size = commands.len();
+
+    tracing::debug!(batch_size, "Flushing batch");
+
+    let topics: Vec<String> = commands.iter().map(|cmd| cmd.topic.clone()).collect();
+    let writes = match write_batch(conn, commands, now, topic_cache, dedup) {
+        Ok(writes) => writes,
+        Err(e) => {
+            // Topics auto-created by this transaction are rolled back
+            for topic in &topics {
+                topic_cache.remove(topic);
+            }
+            return Err(e);
+        }
+    };
+
+    tracing::debug!(batch_size, "Batch committed");
+
+    dedup.maybe_prune(conn, now)?;
+    writes.announce(schedule, notify_bus);
+
+    Ok(())
+}
+
+/// Reply channel of a single publish.
+type PublishReply = oneshot::Sender<Result<PublishResult, WriterError>>;
+
+/// What a committed publish batch leaves to be announced.
+struct BatchWrites {
+    /// Replies held until the commit, in publish order.
+    replies: Vec<(PublishReply, Result<PublishResult, WriterError>)>,
+    /// Highest sequence appended per topic, for notifications.
+    topic_max_seq: HashMap<i64, i64>,
+    /// Delivery times of the messages held back.
+    scheduled: Vec<i64>,
+    /// Expiry times of the messages appended.
+    expiring: Vec<i64>,
+}
+
+impl BatchWrites {
+    fn new(batch_size: usize) -> Self {
+        Self {
+            replies: Vec::with_capacity(batch_size),
+            topic_max_seq: HashMap::new(),
+            scheduled: Vec::new(),
+            expiring: Vec::new(),
+        }
+    }
+
+    /// Publish the schedule, replies and notifications of the committed batch.
+    fn announce(self, schedule: &Schedule, notify_bus: &NotificationBus) {
+        for deliver_at in self.scheduled {
+            schedule.add(deliver_at);
+        }
+        for expires_at in self.expiring {
+            schedule.add_expiry(expires_at);
+        }
+
+        // Send replies
+        for (reply, result) in self.replies {
+            let _ = reply.send(result);
+        }
+
+        // Notify subscribers
+        for (topic_id, max_seq) in self.topic_max_seq {
+            notify_bus.notify(topic_id, max_seq);
+        }
+    }
+}
+
+/// Write and commit a publish batch in one transaction.
+fn write_batch(
+    conn: &Connection,
+    commands: Vec<PublishCommand>,
+    now: i64,
+    topic_cache: &mut TopicCache,
+    dedup: &mut Deduplicator,
+) -> Result<BatchWrites, WriterError> {
+    let mut writes = BatchWrites::new(commands.len());
+
+    let tx = begin_write(conn)?;
+
+    for cmd in commands {
+        // Get or create topic, failing only this publish if it is rejected
+        let topic = match topic_cache.resolve(&tx, &cmd.topic, now) {
+            Ok(topic) => topic,
+            Err(e @ WriterError::TopicNotFound(_)) => {
+                writes.replies.push((cmd.reply, Err(e)));
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        write_publish(
+            &tx,
+            cmd,
+            topic,
+            |key| topic_cache.partition(&topic, key),
+            now,
+            dedup,
+            &mut writes,
+        )?;
+    }
+
+    // Commit transaction (single fsync for entire batch)
+    tx.commit()
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+
+    Ok(writes)
+}
+
+/// Write one publish of a batch on a resolved topic, recording its reply.
+///
+/// `partition` picks the partition from the message key, once the publish
+/// is known to be written.
+fn write_publish(
+    tx: &Connection,
+    cmd: PublishCommand,
+    topic: CachedTopic,
+    partition: impl FnOnce(Option<&str>) -> u32,
+    now: i64,
+    dedup: &Deduplicator,
+    writes: &mut BatchWrites,
+) -> Result<(), WriterError> {
+    let mut msg = cmd.message;
+
+    // A payload over the topic's limit fails only this publish
+    if let Err(e) = topic.check_size(&cmd.topic, msg.payload.as_deref()) {
+        writes.replies.push((cmd.reply, Err(e)));
+        return Ok(());
+    }
+    let topic_id = topic.id;
+
+    // A retried publish gets the original result
+    let idempotency_key = msg.idempotency_key.take();
+    let idempotency_key = idempotency_key.as_deref();
+    if let Some(original) = dedup.lookup(tx, topic_id, idempotency_key, now)? {
+        writes
+            .replies
+            .push((cmd.reply, Ok(PublishResult::from(original))));
+        return Ok(());
+    }
+
+    // Earlier publishes in this batch are visible, so checks stay serial
+    if let Err(e) = check_last_sequence(tx, &cmd.topic, topic_id, cmd.expected_last_sequence) {
+        writes.replies.push((cmd.reply, Err(e)));
+        return Ok(());
+    }
+
+    let partition = partition(msg.key.as_deref());
+
+    // Messages due later are held back and sequenced on release
+    if let Some(deliver_at) = msg.deliver_at.filter(|&at| at > now) {
+        let publish = schedule_message(tx, topic_id, partition, msg, now, deliver_at)?;
+        dedup.record(tx, topic_id, idempotency_key, &publish)?;
+        writes.scheduled.push(deliver_at);
+
+        let result = PublishResult {
+            duplicate: false,
+            ..PublishResult::from(publish)
+        };
+        writes.replies.push((cmd.reply, Ok(result)));
+        return Ok(());
+    }
+
+    // Insert message
+    let expires_at = expires_at(now, msg.ttl_ms);
+    let (seq, partition_seq) = insert_message(
+        tx,
+        topic_id,
+        partition,
+        &msg.message_id,
+        msg.payload.as_deref(),
+        msg.attributes.as_deref(),
+        msg.key.as_deref(),
+        now,
+        expires_at,
+        msg.priority,
+    )
+    .map_err(|e| WriterError::Database(e.to_string()))?;
+    writes.expiring.extend(expires_at);
+
+    // Track max sequence for topic
+    writes
+        .topic_max_seq
+        .entry(topic_id)
+        .and_modify(|max| *max = (*max).max(seq))
+        .or_insert(seq);
+
+    let publish = IdempotentPublish {
+        message_id: msg.message_id,
+        global_seq: seq,
+        partition,
+        partition_seq,
+        created_at: now,
+    };
+    dedup.record(tx, topic_id, idempotency_key, &publish)?;
+
+    let result = PublishResult {
+        duplicate: false,
+        ..PublishResult::from(publish)
+    };
+    writes.replies.push((cmd.reply, Ok(result)));
+    Ok(())
+}
+
+/// Publish handed to a partition writer, with its topic and partition
+/// already chosen.
+struct PartitionPublish {
+    cmd: PublishCommand,
+    topic: CachedTopic,
+    partition: u32,
+}
+
+/// Message from the main writer thread to a partition writer.
+// Nearly every message is a publish, so boxing it would only add allocations
+#[allow(clippy::large_enum_variant)]
+enum PartitionMessage {
+    Publish(PartitionPublish),
+    /// Commit everything received so far, then reply.
+    Sync(std_mpsc::Sender<()>),
+}
+
+/// A partition writer thread and the channel to it.
+struct PartitionWriter {
+    sender: std_mpsc::SyncSender<PartitionMessage>,
+    handle: JoinHandle<()>,
+}
+
+/// Writer threads that publish to the partitions of partitioned topics.
+///
+/// Partition `p` of every partitioned topic belongs to writer
+/// `p % writers.len()`, so each partition keeps its publish order. Every
+/// writer batches and commits on its own connection, in parallel with the
+/// main writer thread and the other partition writers. SQLite still admits
+/// one committing transaction at a time; global sequences are assigned
+/// under that lock, so they stay in commit order.
+struct PartitionWriters {
+    writers: Vec<PartitionWriter>,
+}
+
+impl PartitionWriters {
+    /// Start `count` partition writers on the database at `db_path`.
+    fn spawn(
+        db_path: &Path,
+        count: usize,
+        channel_size: usize,
+        batch_config: BatchConfig,
+        wal_checkpoint_pages: i32,
+        dedup_window_ms: i64,
+        schedule: &Arc<Schedule>,
+        notify_bus: &NotificationBus,
+    ) -> Result<Self, WriterError> {
+        let mut writers = Vec::with_capacity(count);
+        for index in 0..count {
+            let conn = open_write_connection(db_path, wal_checkpoint_pages)?;
+            let (sender, receiver) = std_mpsc::sync_channel(channel_size);
+            let schedule = Arc::clone(schedule);
+            let notify_bus = notify_bus.clone();
+
+            let handle = thread::Builder::new()
+                .name(format!("sluice-partition-writer-{index}"))
+                .spawn(move || {
+                    if let Err(e) = partition_writer_main(
+                        &conn,
+                        &receiver,
+                        batch_config,
+                        dedup_window_ms,
+                        &schedule,
+                        &notify_bus,
+                    ) {
+                        tracing::error!(error = %e, index, "Partition writer error");
+                    }
+                })
+                .map_err(|e| WriterError::Database(e.to_string()))?;
+            writers.push(PartitionWriter { sender, handle });
+        }
+        Ok(Self { writers })
+    }
+
+    /// Hand a publish to the writer of its partition.
+    ///
+    /// Returns the publish if the main writer has to take it: its topic is
+    /// not resolved yet or has a single partition, or the partition writer
+    /// is gone. Unkeyed publishes with an idempotency key stay too, since a
+    /// retry could otherwise reach another writer before the original is
+    /// committed.
+    fn route(&self, cmd: PublishCommand, topic_cache: &mut TopicCache) -> Option<PublishCommand> {
+        if self.writers.is_empty() {
+            return Some(cmd);
+        }
+        let topic = match topic_cache.topics.get(&cmd.topic) {
+            Some(&topic) if topic.partition_count > 1 => topic,
+            _ => return Some(cmd),
+        };
+        if cmd.message.key.is_none() && cmd.message.idempotency_key.is_some() {
+            return Some(cmd);
+        }
+
+        let partition = topic_cache.partition(&topic, cmd.message.key.as_deref());
+        let writer = &self.writers[partition as usize % self.writers.len()];
+        let publish = PartitionPublish {
+            cmd,
+            topic,
+            partition,
+        };
+        match writer.sender.send(PartitionMessage::Publish(publish)) {
+            Ok(()) => None,
+            Err(std_mpsc::SendError(PartitionMessage::Publish(publish))) => Some(publish.cmd),
+            Err(std_mpsc::SendError(PartitionMessage::Sync(_))) => None,
+        }
+    }
+
+    /// Wait until every partition writer has committed what it was handed.
+    fn sync(&self) {
+        let (done_tx, done_rx) = std_mpsc::channel();
+        let pending = self
+            .writers
+            .iter()
+            .filter(|writer| {
+                writer
+                    .sender
+                    .send(PartitionMessage::Sync(done_tx.clone()))
+                    .is_ok()
+            })
+            .count();
+        drop(done_tx);
+
+        for _ in 0..pending {
+            // A writer that failed dropped its reply
+            if done_rx.recv().is_err() {
+                break;
+            }
+        }
+    }
+}
+
+impl Drop for PartitionWriters {
+    fn drop(&mut self) {
+        // A closed channel makes a writer commit what it holds and exit
+        for writer in self.writers.drain(..) {
+            drop(writer.sender);
+            if writer.handle.join().is_err() {
+                tracing::error!("Partition writer thread panicked");
+            }
+        }
+    }
+}
+
+/// Main function for a partition writer thread.
+fn partition_writer_main(
+    conn: &Connection,
+    receiver: &std_mpsc::Receiver<PartitionMessage>,
+    batch_config: BatchConfig,
+    dedup_window_ms: i64,
+    schedule: &Schedule,
+    notify_bus: &NotificationBus,
+) -> Result<(), WriterError> {
+    let mut dedup = Deduplicator::new(dedup_window_ms);
+    let mut batch: BatchAccumulator<PartitionPublish> = BatchAccumulator::new(batch_config);
+
+    loop {
+        // Block for the first publish, then wait until the batch is ready
+        let received = match batch.time_until_ready() {
+            None => receiver
+                .recv()
+                .map_err(|_| std_mpsc::RecvTimeoutError::Disconnected),
+            Some(wait) => receiver.recv_timeout(wait),
+        };
+
+        match received {
+            Ok(PartitionMessage::Publish(publish)) => {
+                if batch.push(publish) {
+                    flush_partition_batch(conn, &mut batch, &mut dedup, schedule, notify_bus)?;
+                }
+            }
+            Ok(PartitionMessage::Sync(done)) => {
+                flush_partition_batch(conn, &mut batch, &mut dedup, schedule, notify_bus)?;
+                let _ = done.send(());
+            }
+            Err(std_mpsc::RecvTimeoutError::Timeout) => {
+                flush_partition_batch(conn, &mut batch, &mut dedup, schedule, notify_bus)?;
+            }
+            Err(std_mpsc::RecvTimeoutError::Disconnected) => {
+                // The main writer is shutting down
+                return flush_partition_batch(conn, &mut batch, &mut dedup, schedule, notify_bus);
+            }
+        }
+    }
+}
+
+/// Flush a partition writer's batch in a single transaction.
+fn flush_partition_batch(
+    conn: &Connection,
+    batch: &mut BatchAccumulator<PartitionPublish>,
+    dedup: &mut Deduplicator,
+    schedule: &Schedule,
+    notify_bus: &NotificationBus,
+) -> Result<(), WriterError> {
+    let publishes = batch.drain();
+    if publishes.is_empty() {
+        return Ok(());
+    }
+
+    let now = now_millis();
+    let mut writes = BatchWrites::new(publishes.len());
+
+    let tx = begin_write(conn)?;
+    for publish in publishes {
+        write_publish(
+            &tx,
+            publish.cmd,
+            publish.topic,
+            |_| publish.partition,
+            now,
+            dedup,
+            &mut writes,
+        )?;
+    }
+    tx.commit()
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+
+    dedup.maybe_prune(conn, now)?;
+    writes.announce(schedule, notify_bus);
+
+    Ok(())
+}
+
+/// Execute a batch publish atomically in a single transaction.
+fn execute_batch_publish(
+    conn: &Connection,
+    topic: String,
+    messages: Vec<MessageInput>,
+    expected_last_sequence: Option<i64>,
+    topic_cache: &mut TopicCache,
+    dedup: &mut Deduplicator,
+    schedule: &Schedule,
+    notify_bus: &NotificationBus,
+) -> Result<(Vec<BatchPublishResultItem>, i64), WriterError> {
+    if messages.is_empty() {
+        return Ok((Vec::new(), now_millis()));
+    }
+
+    let batch = TopicBatch {
+        topic,
+        messages,
+        expected_last_sequence,
+    };
+    let (mut results, now) = execute_publish_transaction(
+        conn,
+        None,
+        vec![batch],
+        topic_cache,
+        dedup,
+        schedule,
+        notify_bus,
+    )?;
+
+    Ok((results.pop().unwrap_or_default(), now))
+}
+
+/// Publish batches to one or more topics in a single transaction, together
+/// with a consumer group's acks.
+///
+/// Subscribers of every topic that got new messages are notified after the
+/// commit. If any batch fails, nothing is written and nothing is acked.
+fn execute_publish_transaction(
+    conn: &Connection,
+    ack: Option<&GroupAck>,
+    batches: Vec<TopicBatch>,
+    topic_cache: &mut TopicCache,
+    dedup: &mut Deduplicator,
+    schedule: &Schedule,
+    notify_bus: &NotificationBus,
+) -> Result<TransactionResults, WriterError> {
+    let now = now_millis();
+    let topics: Vec<String> = batches.iter().map(|batch| batch.topic.clone()).collect();
+
+    tracing::debug!(topics = ?topics, "Executing publish transaction");
+
+    // Execute in a transaction
+    let tx = begin_write(conn)?;
+
+    if let Some(ack) = ack {
+        check_reset_generation(&tx, ack.topic_id, &ack.consumer_group, ack.reset_generation)?;
+        apply_group_ack(&tx, ack, now).map_err(|e| WriterError::Database(e.to_string()))?;
+    }
+
+    let mut results = Vec::with_capacity(batches.len());
+    let mut appended = Vec::with_capacity(batches.len());
+    let mut scheduled = Vec::new();
+    let mut expiring = Vec::new();
+
+    for batch in batches {
+        match append_batch(
+            &tx,
+            batch,
+            now,
+            topic_cache,
+            dedup,
+            &mut scheduled,
+            &mut expiring,
+        ) {
+            Ok((batch_results, topic_id, max_seq)) => {
+                results.push(batch_results);
+                appended.push((topic_id, max_seq));
+            }
+            Err(e) => {
+                // Topics auto-created by this transaction are rolled back
+                for topic in &topics {
+                    topic_cache.remove(topic);
+                }
+                return Err(e);
+            }
+        }
+    }
+
+    // Commit transaction (single fsync for every topic)
+    if let Err(e) = tx.commit() {
+        for topic in &topics {
+            topic_cache.remove(topic);
+        }
+        return Err(WriterError::Database(e.to_string()));
+    }
+
+    tracing::debug!(topics = ?topics, "Publish transaction committed");
+
+    dedup.maybe_prune(conn, now)?;
+    for deliver_at in scheduled {
+        schedule.add(deliver_at);
+    }
+    for expires_at in expiring {
+        schedule.add_expiry(expires_at);
+    }
+
+    // Notify subscribers of each topic unless every message was a duplicate
+    for (topic_id, max_seq) in appended {
+        if max_seq > 0 {
+            notify_bus.notify(topic_id, max_seq);
+        }
+    }
+
+    Ok((results, now))
+}
+
+/// Move a consumer group's cursor and record its individual acks.
+fn apply_group_ack(conn: &Connection, ack: &GroupAck, now: i64) -> rusqlite::Result<()> {
+    if ack.seqs.is_empty() {
+        update_cursor(
+            conn,
+            ack.topic_id,
+            &ack.consumer_group,
+            &ack.partitions,
+            ack.cursor_seq,
+            now,
+        )?;
+        return Ok(());
+    }
+
+    for &seq in &ack.seqs {
+        record_individual_ack(
+            conn,
+            ack.topic_id,
+            &ack.consumer_group,
+            &ack.partitions,
+            seq,
+            ack.cursor_seq,
+            now,
+        )?;
+    }
+    Ok(())
+}
+
+/// Append one topic's batch within an open transaction.
+///
+/// Returns the per-message results, the topic ID and the highest sequence
+/// written (0 if nothing was). Delivery times of scheduled messages are
+/// added to `scheduled`, and expiry times of appended ones to `expiring`.
+fn append_batch(
+    tx: &Connection,
+    batch: TopicBatch,
+    now: i64,
+    topic_cache: &mut TopicCache,
+    dedup: &mut Deduplicator,
+    scheduled: &mut Vec<i64>,
+    expiring: &mut Vec<i64>,
+) -> Result<(Vec<BatchPublishResultItem>, i64, i64), WriterError> {
+    let TopicBatch {
+        topic,
+        messages,
+        mut expected_last_sequence,
+    } = batch;
+
+    // Get or create topic; the batch is rejected as a whole
+    let resolved = topic_cache.resolve(tx, &topic, now)?;
+    for msg in &messages {
+        resolved.check_size(&topic, msg.payload.as_deref())?;
+    }
+    let topic_id = resolved.id;
+
+    let mut results = Vec::with_capacity(messages.len());
+    let mut max_seq = 0i64;
+
+    for mut msg in messages {
+        // A retried message gets the original result
+        let idempotency_key = msg.idempotency_key.take();
+        let idempotency_key = idempotency_key.as_deref();
+        if let Some(original) = dedup.lookup(tx, topic_id, idempotency_key, now)? {
+            results.push(BatchPublishResultItem {
+                message_id: original.message_id,
+                sequence: original.global_seq,
+                partition: original.partition,
+                partition_sequence: original.partition_seq,
+                duplicate: true,
+            });
+            continue;
+        }
+
+        // Checked before the first new message, so a retried batch whose
+        // messages are all duplicates still gets its original results
+        check_last_sequence(tx, &topic, topic_id, expected_last_sequence.take())?;
+
+        let partition = topic_cache.partition(&resolved, msg.key.as_deref());
+
+        // Messages due later are held back and sequenced on release
+        if let Some(deliver_at) = msg.deliver_at.filter(|&at| at > now) {
+            let publish = schedule_message(tx, topic_id, partition, msg, now, deliver_at)?;
+            dedup.record(tx, topic_id, idempotency_key, &publish)?;
+            scheduled.push(deliver_at);
+
+            results.push(BatchPublishResultItem {
+                message_id: publish.message_id,
+                sequence: 0,
+                partition,
+                partition_sequence: 0,
+                duplicate: false,
+            });
+            continue;
+        }
+
+        let expires_at = expires_at(now, msg.ttl_ms);
+        let (seq, partition_seq) = insert_message(
+            tx,
+            topic_id,
+            partition,
+            &msg.message_id,
+            msg.payload.as_deref(),
+            msg.attributes.as_deref(),
+            msg.key.as_deref(),
+            now,
+            expires_at,
+            msg.priority,
+        )
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+        expiring.extend(expires_at);
+
+        max_seq = max_seq.max(seq);
+
+        let publish = IdempotentPublish {
+            message_id: msg.message_id,
+            global_seq: seq,
+            partition,
+            partition_seq,
+            created_at: now,
+        };
+        dedup.record(tx, topic_id, idempotency_key, &publish)?;
+
+        results.push(BatchPublishResultItem {
+            message_id: publish.message_id,
+            sequence: seq,
+            partition,
+            partition_sequence: partition_seq,
+            duplicate: false,
+        });
+    }
+
+    Ok((results, topic_id, max_seq))
+}
+
+/// Append one chunk of due scheduled messages to their topics.
+///
+/// Released messages get the release time as their timestamp, so timestamps
+/// keep increasing with the sequence as time-based positioning expects.
+fn execute_release_scheduled(
+    conn: &Connection,
+    schedule: &Schedule,
+    notify_bus: &NotificationBus,
+) -> Result<usize, WriterError> {
+    let now = now_millis();
+
+    let tx = begin_write(conn)?;
+
+    let due = take_due_scheduled_messages(&tx, now, RELEASE_CHUNK_SIZE)
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+
+    let mut topic_max_seq: HashMap<i64, i64> = HashMap::new();
+    let mut expiring = Vec::new();
+    for msg in &due {
+        // The time to live starts when the message becomes visible
+        let expires_at = expires_at(now, msg.ttl_ms);
+        let (seq, _) = insert_message(
             &tx,
-            topic_id,
+            msg.topic_id,
+            msg.partition,
             &msg.message_id,
             msg.payload.as_deref(),
             msg.attributes.as_deref(),
+            msg.key.as_deref(),
+            now,
+            expires_at,
+            msg.priority,
+        )
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+        topic_max_seq.insert(msg.topic_id, seq);
+        expiring.extend(expires_at);
+    }
+
+    tx.commit()
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+
+    schedule.refresh(conn)?;
+    for expires_at in expiring {
+        schedule.add_expiry(expires_at);
+    }
+
+    if !due.is_empty() {
+        tracing::debug!(released = due.len(), "Released scheduled messages");
+    }
+    for (topic_id, max_seq) in topic_max_seq {
+        notify_bus.notify(topic_id, max_seq);
+    }
+
+    Ok(due.len())
+}
+
+/// Delete one chunk of expired messages.
+///
+/// Messages of topics configured with `dead_letter_expired` are copied to
+/// `<topic>.dlq` in the same transaction, so an expired message is either
+/// still in its topic or in the dead-letter topic. If the dead-letter topic
+/// does not exist and cannot be auto-created, the message is dropped.
+fn execute_expire_messages(
+    conn: &Connection,
+    topic_cache: &mut TopicCache,
+    schedule: &Schedule,
+    notify_bus: &NotificationBus,
+) -> Result<usize, WriterError> {
+    let now = now_millis();
+
+    let tx = begin_write(conn)?;
+
+    let expired = take_expired_messages(&tx, now, EXPIRE_CHUNK_SIZE)
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+
+    // Name of each topic whose expired messages are dead-lettered
+    let mut dead_lettered: HashMap<i64, Option<String>> = HashMap::new();
+    let mut dlq_topics = Vec::new();
+    let mut topic_max_seq: HashMap<i64, i64> = HashMap::new();
+
+    for msg in &expired {
+        let topic_name = match dead_lettered.get(&msg.topic_id) {
+            Some(name) => name.clone(),
+            None => {
+                let settings = get_topic_settings(&tx, msg.topic_id)
+                    .map_err(|e| WriterError::Database(e.to_string()))?;
+                let name = if settings.dead_letter_expired {
+                    get_topic_name(&tx, msg.topic_id)
+                        .map_err(|e| WriterError::Database(e.to_string()))?
+                } else {
+                    None
+                };
+                dead_lettered.insert(msg.topic_id, name.clone());
+                name
+            }
+        };
+        let Some(topic_name) = topic_name else {
+            continue;
+        };
+
+        let dlq_name = format!("{topic_name}{DEFAULT_DLQ_SUFFIX}");
+        let dlq = match topic_cache.resolve(&tx, &dlq_name, now) {
+            Ok(dlq) => dlq,
+            Err(WriterError::TopicNotFound(_)) => {
+                tracing::warn!(
+                    topic = %topic_name,
+                    dead_letter_topic = %dlq_name,
+                    seq = msg.global_seq,
+                    "Dropping expired message without a dead-letter topic"
+                );
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        dlq_topics.push(dlq_name);
+
+        let attributes = serde_json::to_string(&expired_attributes(msg, &topic_name))
+            .map_err(|e| WriterError::Database(format!("invalid attributes: {e}")))?;
+        let partition = topic_cache.partition(&dlq, msg.key.as_deref());
+        let (seq, _) = insert_message(
+            &tx,
+            dlq.id,
+            partition,
+            &generate_message_id(),
+            msg.payload.as_deref(),
+            Some(&attributes),
+            msg.key.as_deref(),
             now,
+            None,
+            msg.priority,
+        )
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+        topic_max_seq.insert(dlq.id, seq);
+    }
+
+    if let Err(e) = tx.commit() {
+        // Dead-letter topics auto-created by this transaction are rolled back
+        for name in &dlq_topics {
+            topic_cache.remove(name);
+        }
+        return Err(WriterError::Database(e.to_string()));
+    }
+
+    schedule.refresh_expiry(conn)?;
+
+    if !expired.is_empty() {
+        tracing::debug!(expired = expired.len(), "Expired messages");
+    }
+    for (topic_id, max_seq) in topic_max_seq {
+        notify_bus.notify(topic_id, max_seq);
+    }
+
+    Ok(expired.len())
+}
+
+/// Fail with `SequenceMismatch` unless the last sequence assigned on the
+/// topic is `expected`.
+fn check_last_sequence(
+    conn: &Connection,
+    topic: &str,
+    topic_id: i64,
+    expected: Option<i64>,
+) -> Result<(), WriterError> {
+    let Some(expected) = expected else {
+        return Ok(());
+    };
+    let actual =
+        get_topic_last_seq(conn, topic_id).map_err(|e| WriterError::Database(e.to_string()))?;
+    if actual != expected {
+        return Err(WriterError::SequenceMismatch {
+            topic: topic.to_string(),
+            expected,
+            actual,
+        });
+    }
+    Ok(())
+}
+
+/// Fail if the consumer group was reset after the caller loaded
+/// `reset_generation`.
+///
+/// A connection that loaded the group before a reset may still ack; its
+/// cursor moves would undo a reset that rewound the group.
+fn check_reset_generation(
+    conn: &Connection,
+    topic_id: i64,
+    consumer_group: &str,
+    reset_generation: i64,
+) -> Result<(), WriterError> {
+    let current = get_subscription(conn, topic_id, consumer_group)
+        .map_err(|e| WriterError::Database(e.to_string()))?
+        .map(|subscription| subscription.reset_generation);
+    match current {
+        Some(current) if current != reset_generation => {
+            Err(WriterError::ConsumerGroupReset(consumer_group.to_string()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Move a consumer group's cursor unless the group was reset meanwhile.
+fn execute_update_cursor(conn: &Connection, cmd: &CursorUpdateCommand) -> Result<(), WriterError> {
+    check_reset_generation(
+        conn,
+        cmd.topic_id,
+        &cmd.consumer_group,
+        cmd.reset_generation,
+    )?;
+    update_cursor(
+        conn,
+        cmd.topic_id,
+        &cmd.consumer_group,
+        &cmd.partitions,
+        cmd.cursor_seq,
+        now_millis(),
+    )
+    .map(|_| ())
+    .map_err(|e| WriterError::Database(e.to_string()))
+}
+
+/// Record an individual ACK and cursor move atomically.
+fn execute_individual_ack(
+    conn: &Connection,
+    cmd: &IndividualAckCommand,
+) -> Result<(), WriterError> {
+    let tx = begin_write(conn)?;
+
+    check_reset_generation(&tx, cmd.topic_id, &cmd.consumer_group, cmd.reset_generation)?;
+
+    record_individual_ack(
+        &tx,
+        cmd.topic_id,
+        &cmd.consumer_group,
+        &cmd.partitions,
+        cmd.seq,
+        cmd.cursor_seq,
+        now_millis(),
+    )
+    .map_err(|e| WriterError::Database(e.to_string()))?;
+
+    tx.commit()
+        .map_err(|e| WriterError::Database(e.to_string()))
+}
+
+/// Record a failed delivery attempt in its own transaction.
+fn execute_delivery_failure(
+    conn: &Connection,
+    cmd: &DeliveryFailureCommand,
+) -> Result<u32, WriterError> {
+    let tx = begin_write(conn)?;
+
+    let failures = record_delivery_failure(&tx, cmd.topic_id, &cmd.consumer_group, cmd.seq)
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+
+    tx.commit()
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+
+    Ok(failures)
+}
+
+/// Delete one chunk of expired messages in its own transaction.
+fn execute_prune(
+    conn: &Connection,
+    topic_id: i64,
+    up_to_seq: i64,
+    limit: i64,
+) -> Result<PruneOutcome, WriterError> {
+    let tx = begin_write(conn)?;
+
+    let outcome = prune_messages(&tx, topic_id, up_to_seq, limit, now_millis())
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+
+    tx.commit()
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+
+    if outcome.deleted > 0 {
+        tracing::debug!(
+            topic_id,
+            deleted = outcome.deleted,
+            through_seq = outcome.through_seq,
+            cursors_advanced = outcome.cursors_advanced,
+            "Pruned messages"
+        );
+    }
+
+    Ok(outcome)
+}
+
+/// Create a topic and store its settings in one transaction.
+fn execute_create_topic(
+    conn: &Connection,
+    cmd: &CreateTopicCommand,
+) -> Result<Option<Topic>, WriterError> {
+    let tx = begin_write(conn)?;
+
+    let now = now_millis();
+    let Some(topic) = create_topic(&tx, &cmd.name, cmd.partition_count, now)
+        .map_err(|e| WriterError::Database(e.to_string()))?
+    else {
+        return Ok(None);
+    };
+    if cmd.settings != TopicSettings::default() {
+        set_topic_settings(&tx, topic.id, &cmd.settings, now)
+            .map_err(|e| WriterError::Database(e.to_string()))?;
+    }
+
+    tx.commit()
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+
+    Ok(Some(topic))
+}
+
+/// Replace a topic's settings in its own transaction.
+fn execute_update_topic_config(
+    conn: &Connection,
+    cmd: &UpdateTopicConfigCommand,
+) -> Result<Option<Topic>, WriterError> {
+    let tx = begin_write(conn)?;
+
+    let Some(topic) =
+        get_topic_by_name(&tx, &cmd.name).map_err(|e| WriterError::Database(e.to_string()))?
+    else {
+        return Ok(None);
+    };
+    set_topic_settings(&tx, topic.id, &cmd.settings, now_millis())
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+
+    tx.commit()
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+
+    Ok(Some(topic))
+}
+
+/// Delete a topic and everything stored for it in one transaction.
+fn execute_delete_topic(
+    conn: &Connection,
+    name: &str,
+) -> Result<Option<(Topic, DeleteOutcome)>, WriterError> {
+    let tx = begin_write(conn)?;
+
+    let Some(topic) =
+        get_topic_by_name(&tx, name).map_err(|e| WriterError::Database(e.to_string()))?
+    else {
+        return Ok(None);
+    };
+    let outcome = delete_topic(&tx, topic.id).map_err(|e| WriterError::Database(e.to_string()))?;
+
+    tx.commit()
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+
+    Ok(Some((topic, outcome)))
+}
+
+/// Delete every message of a topic in one transaction.
+///
+/// Cursors move past the deleted messages exactly as they do for retention.
+fn execute_purge_topic(
+    conn: &Connection,
+    name: &str,
+) -> Result<Option<(Topic, PruneOutcome)>, WriterError> {
+    let tx = begin_write(conn)?;
+
+    let Some(topic) =
+        get_topic_by_name(&tx, name).map_err(|e| WriterError::Database(e.to_string()))?
+    else {
+        return Ok(None);
+    };
+    let mut outcome = prune_messages(&tx, topic.id, i64::MAX, i64::MAX, now_millis())
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+    outcome.deleted += delete_scheduled_messages(&tx, topic.id)
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+
+    tx.commit()
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+
+    Ok(Some((topic, outcome)))
+}
+
+/// Read a topic's statistics and consumer groups.
+fn describe_topic(conn: &Connection, name: &str) -> rusqlite::Result<Option<TopicDescription>> {
+    let Some(topic) = get_topic_by_name(conn, name)? else {
+        return Ok(None);
+    };
+
+    let consumer_groups = list_subscriptions(conn, topic.id)?
+        .into_iter()
+        .map(|sub| {
+            let lag = count_messages_after(conn, topic.id, sub.cursor_seq)?;
+            Ok((sub, lag))
+        })
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(Some(TopicDescription {
+        partition_max_seqs: get_partition_max_seqs(conn, topic.id)?,
+        stats: get_topic_stats(conn, topic.id)?,
+        settings: get_topic_settings(conn, topic.id)?,
+        consumer_groups,
+        topic,
+    }))
+}
+
+/// Resolve a reset target and move the group's cursor in one transaction.
+fn execute_reset_consumer_group(
+    conn: &Connection,
+    cmd: &ResetConsumerGroupCommand,
+) -> Result<Option<Subscription>, WriterError> {
+    let tx = begin_write(conn)?;
+
+    let cursor_seq = reset_cursor_seq(&tx, cmd.topic_id, cmd.target)
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+    if !reset_cursor(
+        &tx,
+        cmd.topic_id,
+        &cmd.consumer_group,
+        cursor_seq,
+        now_millis(),
+    )
+    .map_err(|e| WriterError::Database(e.to_string()))?
+    {
+        return Ok(None);
+    }
+    let subscription = get_subscription(&tx, cmd.topic_id, &cmd.consumer_group)
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+
+    tx.commit()
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+
+    Ok(subscription)
+}
+
+/// The cursor a reset target corresponds to.
+fn reset_cursor_seq(
+    conn: &Connection,
+    topic_id: i64,
+    target: ResetTarget,
+) -> rusqlite::Result<i64> {
+    Ok(match target {
+        ResetTarget::Earliest => get_topic_min_seq(conn, topic_id)?.map_or(0, |seq| seq - 1),
+        ResetTarget::Latest => get_topic_max_seq(conn, topic_id)?,
+        ResetTarget::Sequence(seq) => seq,
+        ResetTarget::Timestamp(timestamp) => {
+            match first_seq_at_or_after(conn, topic_id, timestamp)? {
+                Some(seq) => seq - 1,
+                None => get_topic_max_seq(conn, topic_id)?,
+            }
+        }
+    })
+}
+
+/// Delete a consumer group in its own transaction.
+fn execute_delete_consumer_group(
+    conn: &Connection,
+    cmd: &DeleteConsumerGroupCommand,
+) -> Result<bool, WriterError> {
+    let tx = begin_write(conn)?;
+
+    let deleted = delete_subscription(&tx, cmd.topic_id, &cmd.consumer_group)
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+
+    tx.commit()
+        .map_err(|e| WriterError::Database(e.to_string()))?;
+
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::partition::partition_for_key;
+    use crate::storage::schema::{fetch_messages_from_seq, get_message_by_seq};
+    use tempfile::TempDir;
+
+    fn message(message_id: &str, idempotency_key: Option<&str>) -> MessageInput {
+        MessageInput {
+            message_id: message_id.to_string(),
+            idempotency_key: idempotency_key.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_writer_publish() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let notify_bus = NotificationBus::new(16);
+
+        let writer = Writer::spawn(
+            &db_path,
+            notify_bus.clone(),
+            100,
+            BatchConfig::test_config(),
+            100,
+            true,
+            0,
+            0,
+        )
+        .unwrap();
+        let handle = writer.handle();
+
+        // Publish a message
+        let result = handle
+            .publish(
+                "orders".into(),
+                MessageInput {
+                    message_id: "msg-001".into(),
+                    payload: Some(b"hello".to_vec()),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.message_id, "msg-001");
+        assert_eq!(result.sequence, 1);
+        assert!(result.timestamp > 0);
+
+        // Publish another message
+        let result2 = handle
+            .publish(
+                "orders".into(),
+                MessageInput {
+                    message_id: "msg-002".into(),
+                    payload: Some(b"world".to_vec()),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result2.sequence, 2);
+
+        // Shutdown
+        handle.shutdown().await.unwrap();
+        writer.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_writer_assigns_partitions() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let notify_bus = NotificationBus::new(16);
+
+        let writer = Writer::spawn(
+            &db_path,
+            notify_bus.clone(),
+            100,
+            BatchConfig::test_config(),
+            100,
+            true,
+            0,
+            2,
         )
-        .map_err(|e| WriterError::Database(e.to_string()))?;
+        .unwrap();
+        let handle = writer.handle();
 
-        max_seq = max_seq.max(seq);
+        let topic = handle
+            .create_topic("orders".into(), 2, TopicSettings::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(topic.partition_count, 2);
+        assert!(handle
+            .create_topic("orders".into(), 4, TopicSettings::default())
+            .await
+            .unwrap()
+            .is_none());
+
+        // Messages without a key alternate between partitions
+        let mut assigned = Vec::new();
+        for i in 0..4 {
+            let result = handle
+                .publish("orders".into(), message(&format!("msg-{i}"), None), None)
+                .await
+                .unwrap();
+            assigned.push((result.partition, result.partition_sequence));
+        }
+        assert_eq!(assigned, vec![(0, 1), (1, 1), (0, 2), (1, 2)]);
+
+        // Messages with the same key share a partition
+        let messages = (0..3)
+            .map(|i| MessageInput {
+                message_id: format!("keyed-{i}"),
+                key: Some("order-1".to_string()),
+                ..Default::default()
+            })
+            .collect();
+        let (results, _) = handle
+            .batch_publish("orders".into(), messages, None)
+            .await
+            .unwrap();
+        assert!(results.iter().all(|r| r.partition == results[0].partition));
+        let sequences: Vec<i64> = results.iter().map(|r| r.partition_sequence).collect();
+        assert_eq!(sequences, vec![3, 4, 5]);
 
-        results.push(BatchPublishResultItem {
-            message_id: msg.message_id,
-            sequence: seq,
-        });
+        handle.shutdown().await.unwrap();
+        writer.join().unwrap();
     }
 
-    // Commit transaction (single fsync for entire batch)
-    tx.commit()
-        .map_err(|e| WriterError::Database(e.to_string()))?;
+    #[tokio::test]
+    async fn test_partition_writers_keep_partition_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let writer = Writer::spawn(
+            &db_path,
+            NotificationBus::new(16),
+            100,
+            BatchConfig {
+                max_batch_size: 50,
+                max_batch_delay: Duration::from_millis(5),
+            },
+            100,
+            true,
+            0,
+            2,
+        )
+        .unwrap();
+        let handle = writer.handle();
+        handle
+            .create_topic("orders".into(), 4, TopicSettings::default())
+            .await
+            .unwrap()
+            .unwrap();
 
-    tracing::debug!(batch_size, topic = %topic, "Batch publish committed");
+        let keyed = |i: usize| MessageInput {
+            message_id: format!("msg-{i}"),
+            key: Some(format!("order-{}", i % 8)),
+            ..Default::default()
+        };
+        let mut replies = Vec::new();
+        for i in 0..200 {
+            let reply = handle
+                .enqueue_publish("orders".into(), keyed(i), None)
+                .await
+                .unwrap();
+            replies.push(reply);
+        }
+        let mut results = Vec::new();
+        for reply in replies {
+            results.push(reply.await.unwrap().unwrap());
+        }
 
-    // Notify subscribers
-    notify_bus.notify(topic_id, max_seq);
+        // A key's messages keep their publish order within their partition
+        for key in 0..8 {
+            let of_key: Vec<&PublishResult> = results.iter().skip(key).step_by(8).collect();
+            assert!(of_key.iter().all(|r| r.partition == of_key[0].partition));
+            assert!(of_key.windows(2).all(|w| w[0].sequence < w[1].sequence
+                && w[0].partition_sequence < w[1].partition_sequence));
+        }
 
-    Ok((results, now))
-}
+        // Every partition counts up from 1 in global sequence order
+        let mut by_seq: Vec<&PublishResult> = results.iter().collect();
+        by_seq.sort_by_key(|r| r.sequence);
+        by_seq.dedup_by_key(|r| r.sequence);
+        assert_eq!(by_seq.len(), 200);
+        for partition in 0..4 {
+            let sequences: Vec<i64> = by_seq
+                .iter()
+                .filter(|r| r.partition == partition)
+                .map(|r| r.partition_sequence)
+                .collect();
+            assert_eq!(sequences, (1..=sequences.len() as i64).collect::<Vec<_>>());
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::TempDir;
+        // Topic operations wait for the partition writers' pending batches
+        let mut pending = Vec::new();
+        for i in 200..210 {
+            let reply = handle
+                .enqueue_publish("orders".into(), keyed(i), None)
+                .await
+                .unwrap();
+            pending.push(reply);
+        }
+        let description = handle
+            .describe_topic("orders".into())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(description.stats.message_count, 210);
+        for reply in pending {
+            reply.await.unwrap().unwrap();
+        }
+
+        handle.shutdown().await.unwrap();
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn test_partition_writers_take_keyed_publishes() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let conn = Connection::open(&db_path).unwrap();
+        initialize_schema(&conn).unwrap();
+
+        let mut topic_cache = TopicCache::default();
+        for (name, partitions) in [("orders", 4), ("events", 1)] {
+            let topic = create_topic(&conn, name, partitions, now_millis())
+                .unwrap()
+                .unwrap();
+            topic_cache.insert(&topic, &TopicSettings::default());
+        }
+        let (next, _) = watch::channel(None);
+        let (next_expiry, _) = watch::channel(None);
+        let schedule = Arc::new(Schedule { next, next_expiry });
+        let writers = PartitionWriters::spawn(
+            &db_path,
+            2,
+            16,
+            BatchConfig::test_config(),
+            100,
+            60_000,
+            &schedule,
+            &NotificationBus::new(16),
+        )
+        .unwrap();
+
+        let publish = |topic: &str, key: Option<&str>, idempotency_key: Option<&str>| {
+            let (reply, rx) = oneshot::channel();
+            let cmd = PublishCommand {
+                topic: topic.into(),
+                message: MessageInput {
+                    message_id: generate_message_id(),
+                    key: key.map(str::to_string),
+                    idempotency_key: idempotency_key.map(str::to_string),
+                    ..Default::default()
+                },
+                expected_last_sequence: None,
+                reply,
+            };
+            (cmd, rx)
+        };
+
+        // A keyed publish to a partitioned topic is written by a partition writer
+        let (cmd, mut rx) = publish("orders", Some("order-1"), None);
+        assert!(writers.route(cmd, &mut topic_cache).is_none());
+        writers.sync();
+        let result = rx.try_recv().unwrap().unwrap();
+        assert_eq!(result.partition, partition_for_key("order-1", 4));
+        assert_eq!(result.partition_sequence, 1);
+
+        // The main writer keeps everything else
+        for (topic, key, idempotency_key) in [
+            ("events", Some("order-1"), None),
+            ("unknown", Some("order-1"), None),
+            ("orders", None, Some("retry-1")),
+        ] {
+            let (cmd, _rx) = publish(topic, key, idempotency_key);
+            assert!(writers.route(cmd, &mut topic_cache).is_some());
+        }
+    }
 
     #[tokio::test]
-    async fn test_writer_publish() {
+    async fn test_writer_idempotency_keys() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let notify_bus = NotificationBus::new(16);
+
+        let spawn = || {
+            Writer::spawn(
+                &db_path,
+                notify_bus.clone(),
+                100,
+                BatchConfig::test_config(),
+                100,
+                true,
+                60_000,
+                0,
+            )
+            .unwrap()
+        };
+        let writer = spawn();
+        let handle = writer.handle();
+
+        let first = handle
+            .publish("orders".into(), message("msg-001", Some("order-1")), None)
+            .await
+            .unwrap();
+        assert!(!first.duplicate);
+
+        // A retry returns the original message instead of storing a new one
+        let retry = handle
+            .publish("orders".into(), message("msg-002", Some("order-1")), None)
+            .await
+            .unwrap();
+        assert!(retry.duplicate);
+        assert_eq!(retry.message_id, "msg-001");
+        assert_eq!(retry.sequence, first.sequence);
+
+        // Duplicates inside a single batch collapse onto the first message
+        let messages = ["order-2", "order-2", "order-1"]
+            .iter()
+            .enumerate()
+            .map(|(i, key)| MessageInput {
+                message_id: format!("batch-{i}"),
+                idempotency_key: Some(key.to_string()),
+                ..Default::default()
+            })
+            .collect();
+        let (results, _) = handle
+            .batch_publish("orders".into(), messages, None)
+            .await
+            .unwrap();
+        let duplicates: Vec<bool> = results.iter().map(|r| r.duplicate).collect();
+        assert_eq!(duplicates, vec![false, true, true]);
+        assert_eq!(results[1].message_id, "batch-0");
+        assert_eq!(results[2].sequence, first.sequence);
+
+        handle.shutdown().await.unwrap();
+        writer.join().unwrap();
+
+        // Keys are persisted, so deduplication survives a restart
+        let writer = spawn();
+        let handle = writer.handle();
+        let retry = handle
+            .publish("orders".into(), message("msg-003", Some("order-1")), None)
+            .await
+            .unwrap();
+        assert!(retry.duplicate);
+        assert_eq!(retry.message_id, "msg-001");
+
+        handle.shutdown().await.unwrap();
+        writer.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_writer_expected_last_sequence() {
         let temp_dir = TempDir::new().unwrap();
         let db_path = temp_dir.path().join("test.db");
         let notify_bus = NotificationBus::new(16);
@@ -559,39 +3200,467 @@ mod tests {
             100,
             BatchConfig::test_config(),
             100,
+            true,
+            0,
+            0,
         )
         .unwrap();
         let handle = writer.handle();
 
-        // Publish a message
-        let result = handle
+        // 0 expects an empty topic
+        let first = handle
+            .publish("account-1".into(), message("evt-0", None), Some(0))
+            .await
+            .unwrap();
+        assert_eq!(first.sequence, 1);
+
+        let err = handle
+            .publish("account-1".into(), message("evt-1", None), Some(0))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            WriterError::SequenceMismatch {
+                expected: 0,
+                actual: 1,
+                ..
+            }
+        ));
+
+        let messages = vec![message("evt-1", None), message("evt-2", None)];
+        let (results, _) = handle
+            .batch_publish("account-1".into(), messages, Some(first.sequence))
+            .await
+            .unwrap();
+        assert_eq!(results[1].sequence, 3);
+
+        // A stale batch is rejected as a whole
+        let messages = vec![message("evt-3", None), message("evt-4", None)];
+        let err = handle
+            .batch_publish("account-1".into(), messages, Some(first.sequence))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            WriterError::SequenceMismatch { actual: 3, .. }
+        ));
+
+        let next = handle
+            .publish("account-1".into(), message("evt-3", None), Some(3))
+            .await
+            .unwrap();
+        assert_eq!(next.sequence, 4);
+
+        handle.shutdown().await.unwrap();
+        writer.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_writer_publish_transaction() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let notify_bus = NotificationBus::new(16);
+        let mut notifications = notify_bus.subscribe();
+
+        let writer = Writer::spawn(
+            &db_path,
+            notify_bus.clone(),
+            100,
+            BatchConfig::test_config(),
+            100,
+            true,
+            0,
+            0,
+        )
+        .unwrap();
+        let handle = writer.handle();
+
+        let (results, _) = handle
+            .publish_transaction(vec![
+                TopicBatch {
+                    topic: "orders".into(),
+                    messages: vec![message("order-1", None), message("order-2", None)],
+                    expected_last_sequence: None,
+                },
+                TopicBatch {
+                    topic: "audit".into(),
+                    messages: vec![message("audit-1", None)],
+                    expected_last_sequence: None,
+                },
+            ])
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0][1].sequence, 2);
+        assert_eq!(results[1][0].sequence, 3);
+
+        // Every affected topic is notified
+        let first = notifications.recv().await.unwrap();
+        let second = notifications.recv().await.unwrap();
+        assert_ne!(first.topic_id, second.topic_id);
+        assert_eq!((first.max_seq, second.max_seq), (2, 3));
+
+        // One stale batch rolls back the others, including a new topic
+        let err = handle
+            .publish_transaction(vec![
+                TopicBatch {
+                    topic: "shipments".into(),
+                    messages: vec![message("shipment-1", None)],
+                    expected_last_sequence: None,
+                },
+                TopicBatch {
+                    topic: "orders".into(),
+                    messages: vec![message("order-3", None)],
+                    expected_last_sequence: Some(0),
+                },
+            ])
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            WriterError::SequenceMismatch { actual: 2, .. }
+        ));
+
+        let conn = Connection::open(&db_path).unwrap();
+        assert!(get_topic_by_name(&conn, "shipments").unwrap().is_none());
+
+        // The rolled back topic is created again on its next publish
+        let shipment = handle
+            .publish("shipments".into(), message("shipment-1", None), None)
+            .await
+            .unwrap();
+        assert_eq!(shipment.sequence, 4);
+        assert!(get_topic_by_name(&conn, "shipments").unwrap().is_some());
+
+        handle.shutdown().await.unwrap();
+        writer.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_writer_scheduled_messages() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let notify_bus = NotificationBus::new(16);
+
+        let spawn = || {
+            Writer::spawn(
+                &db_path,
+                notify_bus.clone(),
+                100,
+                BatchConfig::test_config(),
+                100,
+                true,
+                0,
+                0,
+            )
+            .unwrap()
+        };
+        let writer = spawn();
+        let handle = writer.handle();
+
+        let deliver_at = now_millis() + 200;
+        let scheduled = handle
             .publish(
                 "orders".into(),
-                "msg-001".into(),
-                Some(b"hello".to_vec()),
+                MessageInput {
+                    deliver_at: Some(deliver_at),
+                    ..message("later", None)
+                },
                 None,
             )
             .await
             .unwrap();
+        assert_eq!(scheduled.sequence, 0);
+        assert_eq!(*handle.next_scheduled().borrow(), Some(deliver_at));
 
-        assert_eq!(result.message_id, "msg-001");
-        assert_eq!(result.sequence, 1);
-        assert!(result.timestamp > 0);
-
-        // Publish another message
-        let result2 = handle
+        // A delivery time in the past publishes immediately
+        let immediate = handle
             .publish(
                 "orders".into(),
-                "msg-002".into(),
-                Some(b"world".to_vec()),
+                MessageInput {
+                    deliver_at: Some(1),
+                    ..message("now", None)
+                },
                 None,
             )
             .await
             .unwrap();
+        assert_eq!(immediate.sequence, 1);
+        assert_eq!(handle.release_scheduled().await.unwrap(), 0);
 
-        assert_eq!(result2.sequence, 2);
+        handle.shutdown().await.unwrap();
+        writer.join().unwrap();
+
+        // Pending messages are picked up again after a restart
+        let writer = spawn();
+        let handle = writer.handle();
+        let mut next_scheduled = handle.next_scheduled();
+        next_scheduled
+            .wait_for(|next| *next == Some(deliver_at))
+            .await
+            .unwrap();
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert_eq!(handle.release_scheduled().await.unwrap(), 1);
+        assert_eq!(*handle.next_scheduled().borrow(), None);
+
+        let next = handle
+            .publish("orders".into(), message("next", None), Some(2))
+            .await
+            .unwrap();
+        assert_eq!(
+            next.sequence, 3,
+            "released message was appended as sequence 2"
+        );
+
+        handle.shutdown().await.unwrap();
+        writer.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_writer_expired_messages() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let notify_bus = NotificationBus::new(16);
+
+        let writer = Writer::spawn(
+            &db_path,
+            notify_bus.clone(),
+            100,
+            BatchConfig::test_config(),
+            100,
+            true,
+            0,
+            0,
+        )
+        .unwrap();
+        let handle = writer.handle();
+
+        let settings = TopicSettings {
+            dead_letter_expired: true,
+            ..Default::default()
+        };
+        handle
+            .create_topic("ticks".into(), 1, settings)
+            .await
+            .unwrap();
+
+        let expiring = |topic: &str, message_id: &str, ttl_ms: i64| {
+            handle.publish(
+                topic.to_string(),
+                MessageInput {
+                    payload: Some(message_id.as_bytes().to_vec()),
+                    ttl_ms: Some(ttl_ms),
+                    ..message(message_id, None)
+                },
+                None,
+            )
+        };
+        let start = now_millis();
+        let tick = expiring("ticks", "tick", 100).await.unwrap();
+        expiring("presence", "presence", 100).await.unwrap();
+        expiring("presence", "forever", 60_000).await.unwrap();
+        let next = (*handle.next_expiry().borrow()).unwrap();
+        assert!(next >= start + 100 && next <= now_millis() + 100);
+
+        assert_eq!(handle.expire_messages().await.unwrap(), 0);
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert_eq!(handle.expire_messages().await.unwrap(), 2);
+        assert!((*handle.next_expiry().borrow()).unwrap() > now_millis());
+
+        let conn = Connection::open(&db_path).unwrap();
+        let ticks = get_topic_by_name(&conn, "ticks").unwrap().unwrap();
+        assert!(get_message_by_seq(&conn, ticks.id, tick.sequence)
+            .unwrap()
+            .is_none());
+
+        // Only the topic configured for it dead-letters its expired messages
+        let dlq = get_topic_by_name(&conn, "ticks.dlq").unwrap().unwrap();
+        let copies = fetch_messages_from_seq(&conn, dlq.id, &[], 0, 10).unwrap();
+        assert_eq!(copies.len(), 1);
+        assert_eq!(copies[0].payload.as_deref(), Some(&b"tick"[..]));
+        assert_eq!(copies[0].expires_at, None);
+        assert!(get_topic_by_name(&conn, "presence.dlq").unwrap().is_none());
+
+        handle.shutdown().await.unwrap();
+        writer.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_writer_replies_after_commit() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let writer = Writer::spawn(
+            &db_path,
+            NotificationBus::new(16),
+            1000,
+            BatchConfig {
+                max_batch_size: 200,
+                max_batch_delay: Duration::from_millis(50),
+            },
+            100,
+            true,
+            0,
+            0,
+        )
+        .unwrap();
+        let handle = writer.handle();
+
+        handle
+            .publish("orders".into(), message("msg-0", None), None)
+            .await
+            .unwrap();
+        let conn = Connection::open(&db_path).unwrap();
+        let topic_id = get_topic_by_name(&conn, "orders").unwrap().unwrap().id;
+
+        // Every reply of a batch comes after the batch is visible to readers
+        let mut replies = Vec::new();
+        for i in 1..=200 {
+            let reply = handle
+                .enqueue_publish("orders".into(), message(&format!("msg-{i}"), None), None)
+                .await
+                .unwrap();
+            replies.push(reply);
+        }
+        for reply in replies {
+            let result = reply.await.unwrap().unwrap();
+            assert!(
+                get_message_by_seq(&conn, topic_id, result.sequence)
+                    .unwrap()
+                    .is_some(),
+                "reply for sequence {} sent before commit",
+                result.sequence
+            );
+        }
+
+        handle.shutdown().await.unwrap();
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn test_failed_batch_forgets_auto_created_topics() {
+        let temp_dir = TempDir::new().unwrap();
+        let conn = Connection::open(temp_dir.path().join("test.db")).unwrap();
+        initialize_schema(&conn).unwrap();
+        conn.execute_batch(
+            "CREATE TRIGGER poison BEFORE INSERT ON messages
+             BEGIN SELECT RAISE(ABORT, 'poisoned'); END;",
+        )
+        .unwrap();
+
+        let mut batch = BatchAccumulator::new(BatchConfig::test_config());
+        let (reply, _rx) = oneshot::channel();
+        batch.push(PublishCommand {
+            topic: "orders".into(),
+            message: message("msg-1", None),
+            expected_last_sequence: None,
+            reply,
+        });
+        let mut topic_cache = TopicCache {
+            auto_create: true,
+            ..Default::default()
+        };
+        let (next, _) = watch::channel(None);
+        let (next_expiry, _) = watch::channel(None);
+        let schedule = Schedule { next, next_expiry };
+
+        let result = flush_batch(
+            &conn,
+            &mut batch,
+            &mut topic_cache,
+            &mut Deduplicator::new(0),
+            &schedule,
+            &NotificationBus::new(16),
+        );
+        assert!(matches!(result, Err(WriterError::Database(_))));
+
+        // The topic creation was rolled back with the batch
+        assert!(get_topic_by_name(&conn, "orders").unwrap().is_none());
+        assert!(!topic_cache.topics.contains_key("orders"));
+    }
+
+    #[tokio::test]
+    async fn test_writer_rejects_acks_from_before_reset() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let writer = Writer::spawn(
+            &db_path,
+            NotificationBus::new(16),
+            100,
+            BatchConfig::test_config(),
+            100,
+            true,
+            0,
+            0,
+        )
+        .unwrap();
+        let handle = writer.handle();
+
+        for i in 1..=3 {
+            handle
+                .publish("orders".into(), message(&format!("msg-{i}"), None), None)
+                .await
+                .unwrap();
+        }
+        let conn = Connection::open(&db_path).unwrap();
+        let topic_id = get_topic_by_name(&conn, "orders").unwrap().unwrap().id;
+        let before = handle
+            .get_or_create_subscription(topic_id, "workers".into())
+            .await
+            .unwrap();
+
+        let after = handle
+            .reset_consumer_group(topic_id, "workers".into(), ResetTarget::Earliest)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(after.reset_generation, before.reset_generation + 1);
+
+        // A late ack of a connection that loaded the group before the reset
+        let result = handle
+            .update_cursor(
+                topic_id,
+                "workers".into(),
+                before.reset_generation,
+                Vec::new(),
+                3,
+            )
+            .await;
+        assert!(matches!(result, Err(WriterError::ConsumerGroupReset(_))));
+        let result = handle
+            .record_individual_ack(
+                topic_id,
+                "workers".into(),
+                before.reset_generation,
+                Vec::new(),
+                3,
+                0,
+            )
+            .await;
+        assert!(matches!(result, Err(WriterError::ConsumerGroupReset(_))));
+        let cursor = get_subscription(&conn, topic_id, "workers")
+            .unwrap()
+            .unwrap()
+            .cursor_seq;
+        assert_eq!(cursor, 0);
+
+        // Connections that loaded the reset group ack as usual
+        handle
+            .update_cursor(
+                topic_id,
+                "workers".into(),
+                after.reset_generation,
+                Vec::new(),
+                2,
+            )
+            .await
+            .unwrap();
+        let cursor = get_subscription(&conn, topic_id, "workers")
+            .unwrap()
+            .unwrap()
+            .cursor_seq;
+        assert_eq!(cursor, 2);
 
-        // Shutdown
         handle.shutdown().await.unwrap();
         writer.join().unwrap();
     }
@@ -0,0 +1,170 @@
+//! Token authentication and ACL tests.
+//!
+//! Tests:
+//! - T056: Requests need a valid static token or signed JWT
+//! - T057: The ACL limits what each principal may do per topic
+
+mod common;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ring::hmac;
+use sluice_client::{ConnectConfig, InitialPosition, SluiceClient};
+use sluice_server::config::Config;
+use std::path::Path;
+use tempfile::TempDir;
+
+const JWT_SECRET: &[u8] = b"test-jwt-secret";
+
+/// Write token, JWT secret and ACL files, and return a config using them.
+fn auth_config(dir: &Path, acl: Option<&str>) -> Config {
+    let tokens = dir.join("tokens.json");
+    std::fs::write(
+        &tokens,
+        r#"{"tokens": [
+            {"token": "producer-token", "principal": "producer"},
+            {"token": "reader-token", "principal": "reader"}
+        ]}"#,
+    )
+    .unwrap();
+
+    let secret = dir.join("jwt.secret");
+    std::fs::write(&secret, JWT_SECRET).unwrap();
+
+    let acl = acl.map(|rules| {
+        let path = dir.join("acl.json");
+        std::fs::write(&path, rules).unwrap();
+        path
+    });
+
+    Config {
+        auth_token_file: Some(tokens),
+        auth_jwt_secret_file: Some(secret),
+        auth_acl_file: acl,
+        ..Config::default()
+    }
+}
+
+fn sign_jwt(subject: &str) -> String {
+    let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"HS256","typ":"JWT"}"#);
+    let claims = URL_SAFE_NO_PAD.encode(format!(r#"{{"sub":"{subject}"}}"#));
+    let signing_input = format!("{header}.{claims}");
+    let key = hmac::Key::new(hmac::HMAC_SHA256, JWT_SECRET);
+    let signature = URL_SAFE_NO_PAD.encode(hmac::sign(&key, signing_input.as_bytes()));
+    format!("{signing_input}.{signature}")
+}
+
+async fn connect(server: &common::TestServer, token: Option<&str>) -> SluiceClient {
+    let mut config = ConnectConfig::plaintext(format!("http://{}", server.addr)).without_retry();
+    if let Some(token) = token {
+        config = config.with_token(token);
+    }
+    SluiceClient::connect(config)
+        .await
+        .expect("failed to connect")
+}
+
+fn status_code(err: &anyhow::Error) -> tonic::Code {
+    err.downcast_ref::<tonic::Status>()
+        .map(tonic::Status::code)
+        .unwrap_or_else(|| panic!("not a gRPC status: {err:#}"))
+}
+
+/// T056: Requests need a valid static token or signed JWT.
+#[tokio::test]
+async fn test_requests_require_valid_token() {
+    let dir = TempDir::new().unwrap();
+    let server = common::TestServer::start_with_config(auth_config(dir.path(), None)).await;
+
+    let mut anonymous = connect(&server, None).await;
+    let err = anonymous.list_topics().await.unwrap_err();
+    assert_eq!(status_code(&err), tonic::Code::Unauthenticated);
+
+    let mut wrong = connect(&server, Some("not-a-token")).await;
+    let err = wrong.publish("orders", b"x".to_vec()).await.unwrap_err();
+    assert_eq!(status_code(&err), tonic::Code::Unauthenticated);
+
+    let mut producer = connect(&server, Some("producer-token")).await;
+    producer
+        .publish("orders", b"from static token".to_vec())
+        .await
+        .expect("static token rejected");
+
+    let mut jwt = connect(&server, Some(&sign_jwt("svc-jwt"))).await;
+    jwt.publish("orders", b"from jwt".to_vec())
+        .await
+        .expect("JWT rejected");
+
+    server.shutdown().await;
+}
+
+/// T057: The ACL limits what each principal may do per topic.
+#[tokio::test]
+async fn test_acl_limits_actions_per_topic() {
+    let dir = TempDir::new().unwrap();
+    let acl = r#"{"rules": [
+        {"principal": "producer", "topics": ["orders.*"], "actions": ["publish"]},
+        {"principal": "reader", "topics": ["orders.*"], "actions": ["subscribe"]},
+        {"principal": "ops", "topics": ["*"], "actions": ["admin"]}
+    ]}"#;
+    let server = common::TestServer::start_with_config(auth_config(dir.path(), Some(acl))).await;
+
+    let mut producer = connect(&server, Some("producer-token")).await;
+    let mut reader = connect(&server, Some("reader-token")).await;
+    let mut ops = connect(&server, Some(&sign_jwt("ops"))).await;
+
+    producer
+        .publish("orders.eu", b"order".to_vec())
+        .await
+        .expect("publish within grant failed");
+    let err = producer
+        .publish("billing", b"invoice".to_vec())
+        .await
+        .unwrap_err();
+    assert_eq!(status_code(&err), tonic::Code::PermissionDenied);
+    let Err(err) = producer
+        .subscribe("orders.eu", Some("g"), None, InitialPosition::Earliest, 10)
+        .await
+    else {
+        panic!("subscribe outside grant succeeded");
+    };
+    assert_eq!(status_code(&err), tonic::Code::PermissionDenied);
+
+    let err = reader
+        .publish("orders.eu", b"order".to_vec())
+        .await
+        .unwrap_err();
+    assert_eq!(status_code(&err), tonic::Code::PermissionDenied);
+    let mut subscription = reader
+        .subscribe("orders.eu", Some("g"), None, InitialPosition::Earliest, 10)
+        .await
+        .expect("subscribe within grant failed");
+    let msg = subscription
+        .next_message()
+        .await
+        .expect("receive failed")
+        .expect("stream ended");
+    assert_eq!(msg.payload, b"order");
+
+    // Only admins create topics; listings only show accessible topics
+    let err = producer.create_topic("orders.us", 1).await.unwrap_err();
+    assert_eq!(status_code(&err), tonic::Code::PermissionDenied);
+    ops.create_topic("billing", 1)
+        .await
+        .expect("admin create failed");
+
+    let names = |topics: Vec<sluice_client::Topic>| -> Vec<String> {
+        topics.into_iter().map(|t| t.name).collect()
+    };
+    assert_eq!(
+        names(reader.list_topics().await.unwrap()),
+        vec!["orders.eu"]
+    );
+    assert_eq!(
+        names(ops.list_topics().await.unwrap()),
+        vec!["billing", "orders.eu"]
+    );
+
+    drop(subscription);
+    server.shutdown().await;
+}
@@ -16,6 +16,7 @@ fn make_publish_request(topic: &str, payload: &[u8]) -> PublishRequest {
         topic: topic.to_string(),
         payload: payload.to_vec(),
         attributes: HashMap::new(),
+        ..Default::default()
     }
 }
 
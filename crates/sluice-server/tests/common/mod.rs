@@ -12,13 +12,17 @@ use std::time::Duration;
 use tempfile::TempDir;
 use tokio::sync::watch;
 
-use sluice_server::config::Config;
-use sluice_server::flow::notify::NotificationBus;
 use sluice_proto::sluice::v1::sluice_client::SluiceClient;
 use sluice_proto::sluice::v1::sluice_server::SluiceServer;
+use sluice_server::auth::{Auth, AuthInterceptor};
+use sluice_server::config::Config;
+use sluice_server::flow::notify::NotificationBus;
 use sluice_server::server::ServerState;
 use sluice_server::service::{ConnectionRegistry, SluiceService};
+use sluice_server::storage::expiry::run_expiry_task;
 use sluice_server::storage::reader::ReaderPool;
+use sluice_server::storage::retention::{run_retention_task, RetentionConfig};
+use sluice_server::storage::scheduler::run_scheduler_task;
 use sluice_server::storage::writer::Writer;
 use tonic::transport::{Channel, Server};
 
@@ -66,6 +70,7 @@ pub struct TestServer {
 
 impl TestServer {
     /// Start a new test server on a random available port.
+    #[allow(dead_code)]
     pub async fn start() -> Self {
         Self::start_with_config(Config::default()).await
     }
@@ -108,21 +113,50 @@ impl TestServer {
             config.write_channel_size,
             sluice_server::storage::batch::BatchConfig::from_config(1, 1),
             100, // WAL checkpoint pages
+            !config.disable_auto_create,
+            config.dedup_window_ms(),
+            config.partition_writers,
         )
         .expect("failed to spawn writer");
         let writer_handle = writer.handle();
 
+        // Release scheduled messages as they become due
+        tokio::spawn(run_scheduler_task(
+            writer_handle.clone(),
+            shutdown_rx.clone(),
+        ));
+
+        // Delete messages as they expire
+        tokio::spawn(run_expiry_task(writer_handle.clone(), shutdown_rx.clone()));
+
         // Create reader pool
         let reader_pool =
             ReaderPool::new(config.data_dir.join("sluice.db"), config.reader_pool_size)
                 .expect("failed to create reader pool");
 
+        // Enforce retention limits
+        tokio::spawn(run_retention_task(
+            writer_handle.clone(),
+            reader_pool.clone(),
+            RetentionConfig::from_config(
+                config.retention_max_age_secs,
+                config.retention_max_messages,
+                config.retention_max_bytes,
+                config.retention_interval_secs,
+                config.retention_chunk_size,
+            ),
+            shutdown_rx.clone(),
+        ));
+
+        let auth = Arc::new(Auth::from_config(&config).expect("invalid auth config"));
+
         // Create shared state
         let state = Arc::new(ServerState {
             writer: writer_handle.clone(),
             reader_pool,
             notify_bus,
             connection_registry: ConnectionRegistry::new(),
+            auth: auth.clone(),
         });
 
         // Create service
@@ -133,7 +167,10 @@ impl TestServer {
         // Spawn server task
         let server_task = tokio::spawn(async move {
             Server::builder()
-                .add_service(SluiceServer::new(service))
+                .add_service(SluiceServer::with_interceptor(
+                    service,
+                    AuthInterceptor::new(auth),
+                ))
                 .serve_with_shutdown(addr, async move {
                     let _ = shutdown_rx_clone.changed().await;
                 })
@@ -160,6 +197,7 @@ impl TestServer {
     }
 
     /// Get a client connected to this test server.
+    #[allow(dead_code)]
     pub async fn client(&self) -> SluiceClient<Channel> {
         let endpoint = format!("http://{}", self.addr);
         SluiceClient::connect(endpoint)
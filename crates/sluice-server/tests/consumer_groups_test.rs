@@ -0,0 +1,470 @@
+//! Contract tests for the consumer group admin RPCs.
+//!
+//! Tests:
+//! - T060: ListConsumerGroups and DescribeConsumerGroup report cursor, lag
+//!   and connection state
+//! - T061: ResetConsumerGroup disconnects live consumers and moves the cursor;
+//!   DeleteConsumerGroup forgets the group
+//! - T083: Acks racing a ResetConsumerGroup cannot move the cursor past the
+//!   reset
+//! - T085: ResetConsumerGroup disconnects a consumer that took over the group
+
+mod common;
+
+use futures::StreamExt;
+use sluice_server::proto::sluice::v1::{
+    sluice_client::SluiceClient, subscribe_downstream::Response as DownstreamResponse,
+    subscribe_upstream::Request as UpstreamRequest, Ack, ConsumerGroupInfo, CreditGrant,
+    DeleteConsumerGroupRequest, DescribeConsumerGroupRequest, InitialPosition,
+    ListConsumerGroupsRequest, MessageDelivery, PublishRequest, ResetConsumerGroupRequest,
+    ResetPosition, SubscribeDownstream, SubscribeUpstream, SubscriptionInit,
+};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use tonic::transport::Channel;
+use tonic::Streaming;
+
+fn make_publish(topic: &str, payload: &[u8]) -> PublishRequest {
+    PublishRequest {
+        topic: topic.to_string(),
+        payload: payload.to_vec(),
+        ..Default::default()
+    }
+}
+
+fn make_init(topic: &str, consumer_group: &str) -> SubscribeUpstream {
+    SubscribeUpstream {
+        request: Some(UpstreamRequest::Init(SubscriptionInit {
+            topic: topic.to_string(),
+            consumer_group: consumer_group.to_string(),
+            initial_position: InitialPosition::Earliest as i32,
+            ..Default::default()
+        })),
+    }
+}
+
+fn make_credit(credits: u32) -> SubscribeUpstream {
+    SubscribeUpstream {
+        request: Some(UpstreamRequest::Credit(CreditGrant { credits })),
+    }
+}
+
+fn make_ack(message_id: &str) -> SubscribeUpstream {
+    SubscribeUpstream {
+        request: Some(UpstreamRequest::Ack(Ack {
+            message_id: message_id.to_string(),
+        })),
+    }
+}
+
+/// Subscribe to `topic` as `consumer_group`, granting `credits` credits.
+async fn subscribe(
+    client: &mut SluiceClient<Channel>,
+    topic: &str,
+    consumer_group: &str,
+    credits: u32,
+) -> (
+    mpsc::Sender<SubscribeUpstream>,
+    Streaming<SubscribeDownstream>,
+) {
+    let (tx, rx) = mpsc::channel(10);
+    tx.send(make_init(topic, consumer_group)).await.unwrap();
+    tx.send(make_credit(credits)).await.unwrap();
+    let stream = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect("subscribe failed")
+        .into_inner();
+    (tx, stream)
+}
+
+async fn next_delivery(stream: &mut Streaming<SubscribeDownstream>) -> MessageDelivery {
+    loop {
+        let msg = timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("timeout waiting for delivery")
+            .expect("stream ended")
+            .expect("stream error");
+        if let Some(DownstreamResponse::Delivery(delivery)) = msg.response {
+            return delivery;
+        }
+    }
+}
+
+async fn next_error(stream: &mut Streaming<SubscribeDownstream>) -> tonic::Status {
+    loop {
+        match timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("timeout waiting for termination")
+        {
+            Some(Ok(_)) => continue,
+            Some(Err(status)) => return status,
+            None => panic!("stream ended without a status"),
+        }
+    }
+}
+
+async fn describe(
+    client: &mut SluiceClient<Channel>,
+    topic: &str,
+    consumer_group: &str,
+) -> Result<ConsumerGroupInfo, tonic::Status> {
+    let request = DescribeConsumerGroupRequest {
+        topic: topic.to_string(),
+        consumer_group: consumer_group.to_string(),
+    };
+    Ok(client
+        .describe_consumer_group(request)
+        .await?
+        .into_inner()
+        .consumer_group
+        .expect("response missing group"))
+}
+
+/// Describe a group until `condition` holds, or panic after five seconds.
+async fn describe_until(
+    client: &mut SluiceClient<Channel>,
+    topic: &str,
+    consumer_group: &str,
+    condition: impl Fn(&ConsumerGroupInfo) -> bool,
+) -> ConsumerGroupInfo {
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    loop {
+        let group = describe(client, topic, consumer_group).await;
+        if let Ok(group) = &group {
+            if condition(group) {
+                return group.clone();
+            }
+        }
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "timed out waiting for consumer group state: {group:?}"
+        );
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+/// T060: ListConsumerGroups and DescribeConsumerGroup report group state.
+#[tokio::test]
+async fn test_list_and_describe_consumer_groups() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    let mut sequences = Vec::new();
+    for payload in [b"one".as_slice(), b"two", b"three"] {
+        let resp = client
+            .publish(make_publish("orders", payload))
+            .await
+            .expect("publish failed")
+            .into_inner();
+        sequences.push(resp.sequence);
+    }
+    client
+        .publish(make_publish("billing", b"invoice"))
+        .await
+        .expect("publish failed");
+
+    // One connected group that acked the first message
+    let (tx, mut stream) = subscribe(&mut client, "orders", "workers", 1).await;
+    let delivery = next_delivery(&mut stream).await;
+    tx.send(make_ack(&delivery.message_id)).await.unwrap();
+
+    let mut admin = server.client().await;
+    let group = describe_until(&mut admin, "orders", "workers", |group| {
+        group.cursor_sequence == sequences[0]
+    })
+    .await;
+    assert_eq!(group.topic, "orders");
+    assert_eq!(group.max_sequence, sequences[2]);
+    assert_eq!(group.lag, 2);
+    assert!(group.connected);
+    assert!(group.updated_at > 0);
+
+    // One disconnected group on another topic
+    let mut other = server.client().await;
+    let (billing_tx, billing_stream) = subscribe(&mut other, "billing", "audit", 0).await;
+    describe_until(&mut admin, "billing", "audit", |group| group.connected).await;
+    drop(billing_tx);
+    drop(billing_stream);
+    let audit = describe_until(&mut admin, "billing", "audit", |group| !group.connected).await;
+    assert_eq!(audit.lag, 1);
+
+    let all = admin
+        .list_consumer_groups(ListConsumerGroupsRequest::default())
+        .await
+        .expect("list failed")
+        .into_inner()
+        .consumer_groups;
+    let names: Vec<(&str, &str)> = all
+        .iter()
+        .map(|group| (group.topic.as_str(), group.name.as_str()))
+        .collect();
+    assert_eq!(names, vec![("billing", "audit"), ("orders", "workers")]);
+
+    let orders = admin
+        .list_consumer_groups(ListConsumerGroupsRequest {
+            topic: "orders".to_string(),
+        })
+        .await
+        .expect("list failed")
+        .into_inner()
+        .consumer_groups;
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].name, "workers");
+
+    let status = describe(&mut admin, "orders", "missing").await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+    let status = describe(&mut admin, "missing", "workers")
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+
+    drop(tx);
+    drop(stream);
+    server.shutdown().await;
+}
+
+/// T061: ResetConsumerGroup disconnects consumers; DeleteConsumerGroup
+/// forgets the group.
+#[tokio::test]
+async fn test_reset_and_delete_consumer_group() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    let mut sequences = Vec::new();
+    for payload in [b"one".as_slice(), b"two", b"three"] {
+        let resp = client
+            .publish(make_publish("orders", payload))
+            .await
+            .expect("publish failed")
+            .into_inner();
+        sequences.push(resp.sequence);
+    }
+
+    // Consume and ack everything
+    let (tx, mut stream) = subscribe(&mut client, "orders", "workers", 10).await;
+    for _ in 0..3 {
+        let delivery = next_delivery(&mut stream).await;
+        tx.send(make_ack(&delivery.message_id)).await.unwrap();
+    }
+    let mut admin = server.client().await;
+    describe_until(&mut admin, "orders", "workers", |group| {
+        group.cursor_sequence == sequences[2] && group.connected
+    })
+    .await;
+
+    // Resetting to earliest ends the live subscription
+    let group = admin
+        .reset_consumer_group(ResetConsumerGroupRequest {
+            topic: "orders".to_string(),
+            consumer_group: "workers".to_string(),
+            position: ResetPosition::ResetEarliest as i32,
+            ..Default::default()
+        })
+        .await
+        .expect("reset failed")
+        .into_inner()
+        .consumer_group
+        .unwrap();
+    assert_eq!(group.lag, 3);
+    assert!(!group.connected);
+    assert_eq!(next_error(&mut stream).await.code(), tonic::Code::Aborted);
+    drop(tx);
+    drop(stream);
+
+    // Reconnecting replays from the start
+    let (tx, mut stream) = subscribe(&mut client, "orders", "workers", 10).await;
+    let delivery = next_delivery(&mut stream).await;
+    assert_eq!(delivery.sequence, sequences[0]);
+    assert_eq!(delivery.payload, b"one");
+    drop(tx);
+    drop(stream);
+    describe_until(&mut admin, "orders", "workers", |group| !group.connected).await;
+
+    // Resetting to a sequence resumes after it
+    let group = admin
+        .reset_consumer_group(ResetConsumerGroupRequest {
+            topic: "orders".to_string(),
+            consumer_group: "workers".to_string(),
+            position: ResetPosition::ResetSequence as i32,
+            sequence: sequences[1],
+            ..Default::default()
+        })
+        .await
+        .expect("reset failed")
+        .into_inner()
+        .consumer_group
+        .unwrap();
+    assert_eq!(group.cursor_sequence, sequences[1]);
+    assert_eq!(group.lag, 1);
+
+    // Resetting to a timestamp after every message skips them all
+    let group = admin
+        .reset_consumer_group(ResetConsumerGroupRequest {
+            topic: "orders".to_string(),
+            consumer_group: "workers".to_string(),
+            position: ResetPosition::ResetTimestamp as i32,
+            timestamp: i64::MAX,
+            ..Default::default()
+        })
+        .await
+        .expect("reset failed")
+        .into_inner()
+        .consumer_group
+        .unwrap();
+    assert_eq!(group.cursor_sequence, sequences[2]);
+    assert_eq!(group.lag, 0);
+
+    let status = admin
+        .reset_consumer_group(ResetConsumerGroupRequest {
+            topic: "orders".to_string(),
+            consumer_group: "missing".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+
+    // Deleting ends the live subscription and forgets the group
+    let (tx, mut stream) = subscribe(&mut client, "orders", "workers", 10).await;
+    describe_until(&mut admin, "orders", "workers", |group| group.connected).await;
+    let resp = admin
+        .delete_consumer_group(DeleteConsumerGroupRequest {
+            topic: "orders".to_string(),
+            consumer_group: "workers".to_string(),
+        })
+        .await
+        .expect("delete failed")
+        .into_inner();
+    assert!(resp.disconnected);
+    assert_eq!(next_error(&mut stream).await.code(), tonic::Code::Aborted);
+
+    let status = describe(&mut admin, "orders", "workers").await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+    let status = admin
+        .delete_consumer_group(DeleteConsumerGroupRequest {
+            topic: "orders".to_string(),
+            consumer_group: "workers".to_string(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+
+    drop(tx);
+    drop(stream);
+    server.shutdown().await;
+}
+
+/// T083: Acks racing a ResetConsumerGroup cannot move the cursor past the
+/// reset.
+#[tokio::test]
+async fn test_reset_with_concurrent_acks() {
+    const MESSAGES: usize = 200;
+
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+    for i in 0..MESSAGES {
+        client
+            .publish(make_publish("orders", format!("msg-{i}").as_bytes()))
+            .await
+            .expect("publish failed");
+    }
+
+    let (tx, mut stream) = subscribe(&mut client, "orders", "workers", MESSAGES as u32).await;
+    let mut message_ids = Vec::with_capacity(MESSAGES);
+    for _ in 0..MESSAGES {
+        message_ids.push(next_delivery(&mut stream).await.message_id);
+    }
+
+    // Ack everything while the group is reset to the start
+    let acks = tokio::spawn(async move {
+        for message_id in message_ids {
+            if tx.send(make_ack(&message_id)).await.is_err() {
+                break;
+            }
+        }
+        tx
+    });
+    let mut admin = server.client().await;
+    describe_until(&mut admin, "orders", "workers", |group| {
+        group.cursor_sequence > 0
+    })
+    .await;
+    let group = admin
+        .reset_consumer_group(ResetConsumerGroupRequest {
+            topic: "orders".to_string(),
+            consumer_group: "workers".to_string(),
+            position: ResetPosition::ResetEarliest as i32,
+            ..Default::default()
+        })
+        .await
+        .expect("reset failed")
+        .into_inner()
+        .consumer_group
+        .unwrap();
+    assert_eq!(group.cursor_sequence, 0);
+
+    // Let the old connection finish with every ack it was sent
+    let tx = acks.await.unwrap();
+    while let Ok(Some(Ok(_))) = timeout(Duration::from_secs(5), stream.next()).await {}
+    drop(tx);
+    describe_until(&mut admin, "orders", "workers", |group| !group.connected).await;
+
+    let group = describe(&mut admin, "orders", "workers")
+        .await
+        .expect("describe failed");
+    assert_eq!(group.cursor_sequence, 0);
+    assert_eq!(group.lag, MESSAGES as u64);
+
+    server.shutdown().await;
+}
+
+/// T085: ResetConsumerGroup disconnects a consumer that took over the group.
+#[tokio::test]
+async fn test_reset_after_takeover() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+    client
+        .publish(make_publish("orders", b"one"))
+        .await
+        .expect("publish failed");
+
+    let (old_tx, mut old_stream) = subscribe(&mut client, "orders", "workers", 10).await;
+    next_delivery(&mut old_stream).await;
+    let (tx, mut stream) = subscribe(&mut client, "orders", "workers", 10).await;
+    next_delivery(&mut stream).await;
+    assert_eq!(
+        next_error(&mut old_stream).await.code(),
+        tonic::Code::Aborted
+    );
+
+    // The replaced connection cleaning up does not unregister the new one
+    drop(old_tx);
+    drop(old_stream);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let mut admin = server.client().await;
+    let group = describe(&mut admin, "orders", "workers")
+        .await
+        .expect("describe failed");
+    assert!(group.connected);
+
+    let group = admin
+        .reset_consumer_group(ResetConsumerGroupRequest {
+            topic: "orders".to_string(),
+            consumer_group: "workers".to_string(),
+            position: ResetPosition::ResetEarliest as i32,
+            ..Default::default()
+        })
+        .await
+        .expect("reset failed")
+        .into_inner()
+        .consumer_group
+        .unwrap();
+    assert!(!group.connected);
+    assert_eq!(next_error(&mut stream).await.code(), tonic::Code::Aborted);
+
+    drop(tx);
+    drop(stream);
+    server.shutdown().await;
+}
@@ -21,6 +21,7 @@ fn make_publish(topic: &str, payload: &[u8]) -> PublishRequest {
         topic: topic.to_string(),
         payload: payload.to_vec(),
         attributes: HashMap::new(),
+        ..Default::default()
     }
 }
 
@@ -32,6 +33,7 @@ fn make_init(topic: &str, consumer_group: &str, position: InitialPosition) -> Su
             consumer_id: "test".to_string(),
             initial_position: position as i32,
             offset: 0,
+            ..Default::default()
         })),
     }
 }
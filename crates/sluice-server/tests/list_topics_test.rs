@@ -2,10 +2,11 @@
 //!
 //! Tests:
 //! - T011: ListTopics returns lexicographically sorted known topics
+//! - T052: CreateTopic creates a partitioned topic visible in ListTopics
 
 mod common;
 
-use sluice_server::proto::sluice::v1::{ListTopicsRequest, PublishRequest};
+use sluice_server::proto::sluice::v1::{CreateTopicRequest, ListTopicsRequest, PublishRequest};
 use std::collections::HashMap;
 
 fn make_publish(topic: &str, payload: &[u8]) -> PublishRequest {
@@ -13,6 +14,7 @@ fn make_publish(topic: &str, payload: &[u8]) -> PublishRequest {
         topic: topic.to_string(),
         payload: payload.to_vec(),
         attributes: HashMap::new(),
+        ..Default::default()
     }
 }
 
@@ -49,3 +51,61 @@ async fn test_list_topics_returns_sorted_topics() {
 
     server.shutdown().await;
 }
+
+/// T052: CreateTopic creates a partitioned topic visible in ListTopics.
+#[tokio::test]
+async fn test_create_topic_with_partitions() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    let topic = client
+        .create_topic(CreateTopicRequest {
+            name: "orders".to_string(),
+            partitions: 3,
+            ..Default::default()
+        })
+        .await
+        .expect("create_topic failed")
+        .into_inner()
+        .topic
+        .expect("missing topic");
+    assert_eq!(topic.partition_count, 3);
+
+    // Creating it again is rejected
+    let err = client
+        .create_topic(CreateTopicRequest {
+            name: "orders".to_string(),
+            partitions: 3,
+            ..Default::default()
+        })
+        .await
+        .expect_err("duplicate create should fail");
+    assert_eq!(err.code(), tonic::Code::AlreadyExists);
+
+    // Unkeyed messages are spread round-robin over the partitions
+    for i in 0..4 {
+        let resp = client
+            .publish(make_publish("orders", format!("order {i}").as_bytes()))
+            .await
+            .expect("publish failed")
+            .into_inner();
+        assert_eq!(resp.partition, i % 3);
+        assert_eq!(resp.partition_sequence, u64::from(i / 3 + 1));
+    }
+
+    let resp = client
+        .list_topics(ListTopicsRequest {})
+        .await
+        .expect("list_topics failed")
+        .into_inner();
+    let orders = resp
+        .topics
+        .iter()
+        .find(|t| t.name == "orders")
+        .expect("orders not listed");
+    assert_eq!(orders.partition_count, 3);
+    let max_sequences: Vec<u64> = orders.partitions.iter().map(|p| p.max_sequence).collect();
+    assert_eq!(max_sequences, vec![2, 1, 1]);
+
+    server.shutdown().await;
+}
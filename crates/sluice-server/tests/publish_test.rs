@@ -3,10 +3,18 @@
 //! Tests:
 //! - T016: Valid publish returns message_id, sequence, timestamp
 //! - T017: Publish to new topic creates it automatically
+//! - T066: expected_last_sequence rejects stale publishes with FAILED_PRECONDITION
+//! - T070: PublishStream acknowledges every message in order, rejections included
+//! - T071: PublishTransaction writes to several topics all-or-nothing
 
 mod common;
 
-use sluice_server::proto::sluice::v1::PublishRequest;
+use futures::StreamExt;
+use sluice_server::proto::sluice::v1::{
+    publish_stream_response::Result as StreamResult, BatchMessage, DescribeTopicRequest,
+    PublishRequest, PublishStreamRequest, PublishTransactionRequest, PurgeTopicRequest,
+    TopicMessages,
+};
 use std::collections::HashMap;
 
 /// Helper to create a publish request.
@@ -15,6 +23,7 @@ fn make_publish_request(topic: &str, payload: &[u8]) -> PublishRequest {
         topic: topic.to_string(),
         payload: payload.to_vec(),
         attributes: HashMap::new(),
+        ..Default::default()
     }
 }
 
@@ -161,6 +170,7 @@ async fn test_publish_with_attributes() {
         topic: "attributed-topic".to_string(),
         payload: b"message with attributes".to_vec(),
         attributes,
+        ..Default::default()
     };
 
     let response = client.publish(request).await.expect("publish failed");
@@ -269,3 +279,217 @@ async fn test_publish_topic_valid_chars_accepted() {
 
     server.shutdown().await;
 }
+
+/// T066: expected_last_sequence rejects stale publishes with FAILED_PRECONDITION.
+#[tokio::test]
+async fn test_publish_expected_last_sequence() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    let append = |expected: u64| PublishRequest {
+        expected_last_sequence: Some(expected),
+        ..make_publish_request("account-42", b"event")
+    };
+
+    let first = client
+        .publish(append(0))
+        .await
+        .expect("publish to empty topic failed")
+        .into_inner();
+    assert_eq!(first.sequence, 1);
+
+    // A concurrent writer that also saw an empty topic loses
+    let status = client
+        .publish(append(0))
+        .await
+        .expect_err("stale publish should fail");
+    assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+    let actual = status
+        .metadata()
+        .get("sluice-last-sequence")
+        .expect("actual last sequence should be returned");
+    assert_eq!(actual.to_str().unwrap(), "1");
+
+    let second = client
+        .publish(append(first.sequence))
+        .await
+        .expect("publish after last sequence failed")
+        .into_inner();
+    assert_eq!(second.sequence, 2);
+
+    // Purging the topic does not make an old expected value current again
+    client
+        .purge_topic(PurgeTopicRequest {
+            name: "account-42".to_string(),
+        })
+        .await
+        .expect("purge failed");
+    let status = client
+        .publish(append(first.sequence))
+        .await
+        .expect_err("stale publish after purge should fail");
+    assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+    let actual = status
+        .metadata()
+        .get("sluice-last-sequence")
+        .expect("actual last sequence should be returned");
+    assert_eq!(actual.to_str().unwrap(), "2");
+
+    let third = client
+        .publish(append(second.sequence))
+        .await
+        .expect("publish after purge failed")
+        .into_inner();
+    assert_eq!(third.sequence, 3);
+
+    server.shutdown().await;
+}
+
+/// T070: PublishStream acknowledges every message in order, rejections included.
+#[tokio::test]
+async fn test_publish_stream_acks_in_order() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<PublishStreamRequest>(10);
+    let response = client
+        .publish_stream(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect("publish_stream failed");
+    let mut acks = response.into_inner();
+
+    let mut requests = Vec::new();
+    for i in 1..=50 {
+        requests.push(make_publish_request(
+            "stream-topic",
+            format!("msg-{i}").as_bytes(),
+        ));
+    }
+    // An invalid topic and a stale conditional publish are rejected
+    // without ending the stream
+    requests.insert(10, make_publish_request("bad topic!", b"rejected"));
+    requests.insert(
+        20,
+        PublishRequest {
+            expected_last_sequence: Some(0),
+            ..make_publish_request("stream-topic", b"stale")
+        },
+    );
+
+    // Send everything before reading any acknowledgement
+    let sender = tokio::spawn(async move {
+        for (request_id, message) in requests.into_iter().enumerate() {
+            tx.send(PublishStreamRequest {
+                request_id: request_id as u64,
+                message: Some(message),
+            })
+            .await
+            .expect("send failed");
+        }
+        // Dropping tx closes the client side
+    });
+
+    let mut sequences = Vec::new();
+    let mut request_ids = Vec::new();
+    while let Some(response) = acks.next().await {
+        let response = response.expect("stream error");
+        request_ids.push(response.request_id);
+        match response.result.expect("result missing") {
+            StreamResult::Ack(ack) => sequences.push(ack.sequence),
+            StreamResult::Error(error) if response.request_id == 10 => {
+                assert_eq!(error.code, tonic::Code::InvalidArgument as i32);
+            }
+            StreamResult::Error(error) => {
+                assert_eq!(response.request_id, 20);
+                assert_eq!(error.code, tonic::Code::FailedPrecondition as i32);
+                assert_eq!(error.last_sequence, Some(19));
+            }
+        }
+    }
+    sender.await.unwrap();
+
+    // The stream ends once every message is acknowledged
+    assert_eq!(request_ids, (0..52).collect::<Vec<u64>>());
+    assert_eq!(sequences, (1..=50).collect::<Vec<u64>>());
+
+    server.shutdown().await;
+}
+
+/// T071: PublishTransaction writes to several topics all-or-nothing.
+#[tokio::test]
+async fn test_publish_transaction_all_or_nothing() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    let topic_messages = |topic: &str, payloads: &[&str]| TopicMessages {
+        topic: topic.to_string(),
+        messages: payloads
+            .iter()
+            .map(|payload| BatchMessage {
+                payload: payload.as_bytes().to_vec(),
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    let response = client
+        .publish_transaction(PublishTransactionRequest {
+            topics: vec![
+                topic_messages("orders", &["created", "paid"]),
+                topic_messages("audit", &["order paid"]),
+            ],
+        })
+        .await
+        .expect("transaction failed")
+        .into_inner();
+
+    assert_eq!(response.topics.len(), 2);
+    assert_eq!(response.topics[0].topic, "orders");
+    let order_sequences: Vec<u64> = response.topics[0]
+        .results
+        .iter()
+        .map(|r| r.sequence)
+        .collect();
+    assert_eq!(order_sequences, vec![1, 2]);
+    assert_eq!(response.topics[1].topic, "audit");
+    assert_eq!(response.topics[1].results[0].sequence, 3);
+
+    // A stale check on one topic stores nothing on the other
+    let status = client
+        .publish_transaction(PublishTransactionRequest {
+            topics: vec![
+                topic_messages("audit", &["order shipped"]),
+                TopicMessages {
+                    expected_last_sequence: Some(1),
+                    ..topic_messages("orders", &["shipped"])
+                },
+            ],
+        })
+        .await
+        .expect_err("stale transaction should fail");
+    assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+
+    let audit = client
+        .describe_topic(DescribeTopicRequest {
+            name: "audit".to_string(),
+        })
+        .await
+        .expect("describe failed")
+        .into_inner();
+    assert_eq!(audit.message_count, 1, "audit message was rolled back");
+
+    // A topic may only appear once
+    let status = client
+        .publish_transaction(PublishTransactionRequest {
+            topics: vec![
+                topic_messages("orders", &["a"]),
+                topic_messages("orders", &["b"]),
+            ],
+        })
+        .await
+        .expect_err("duplicate topic should fail");
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+    server.shutdown().await;
+}
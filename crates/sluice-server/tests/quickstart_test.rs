@@ -27,6 +27,7 @@ fn make_publish(topic: &str, payload: &[u8]) -> PublishRequest {
         topic: topic.to_string(),
         payload: payload.to_vec(),
         attributes: HashMap::new(),
+        ..Default::default()
     }
 }
 
@@ -38,6 +39,7 @@ fn make_init(topic: &str, group: &str) -> SubscribeUpstream {
             consumer_id: "quickstart-test".to_string(),
             initial_position: InitialPosition::Earliest as i32,
             offset: 0,
+            ..Default::default()
         })),
     }
 }
@@ -0,0 +1,261 @@
+//! Contract tests for the Request RPC.
+//!
+//! Tests:
+//! - T075: A request is answered by the reply carrying its correlation_id
+//! - T076: A request without a reply fails with DEADLINE_EXCEEDED
+//! - T077: The inbox is deleted when the requester disconnects
+//! - T081: The inbox prefix is reserved and inboxes are never auto-created
+
+mod common;
+
+use futures::StreamExt;
+use sluice_server::proto::sluice::v1::{
+    request_downstream::Result as RequestResult,
+    subscribe_downstream::Response as DownstreamResponse,
+    subscribe_upstream::Request as UpstreamRequest, CreateTopicRequest, CreditGrant,
+    InitialPosition, ListTopicsRequest, MessageDelivery, PublishRequest, RequestDownstream,
+    RequestUpstream, SubscribeDownstream, SubscribeUpstream, SubscriptionInit,
+};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time::timeout;
+
+/// Helper to create a request.
+fn make_request(request_id: u64, topic: &str, payload: &[u8], timeout_ms: u32) -> RequestUpstream {
+    RequestUpstream {
+        request_id,
+        message: Some(PublishRequest {
+            topic: topic.to_string(),
+            payload: payload.to_vec(),
+            ..Default::default()
+        }),
+        timeout_ms,
+    }
+}
+
+/// Helper to wait for the next response on a request stream.
+async fn next_response(stream: &mut tonic::Streaming<RequestDownstream>) -> RequestDownstream {
+    timeout(Duration::from_secs(2), stream.next())
+        .await
+        .expect("timeout")
+        .expect("stream ended")
+        .expect("stream error")
+}
+
+/// Helper to wait for the next delivery, skipping heartbeats.
+async fn next_delivery(stream: &mut tonic::Streaming<SubscribeDownstream>) -> MessageDelivery {
+    loop {
+        let downstream = timeout(Duration::from_secs(2), stream.next())
+            .await
+            .expect("timeout")
+            .expect("stream ended")
+            .expect("stream error");
+        if let Some(DownstreamResponse::Delivery(d)) = downstream.response {
+            return d;
+        }
+    }
+}
+
+/// Helper to list the inbox topics on the server.
+async fn inboxes(
+    client: &mut sluice_server::proto::sluice::v1::sluice_client::SluiceClient<
+        tonic::transport::Channel,
+    >,
+) -> Vec<String> {
+    client
+        .list_topics(ListTopicsRequest {})
+        .await
+        .expect("list_topics failed")
+        .into_inner()
+        .topics
+        .into_iter()
+        .map(|topic| topic.name)
+        .filter(|name| name.starts_with("_inbox."))
+        .collect()
+}
+
+/// T075: A request is answered by the reply carrying its correlation_id.
+#[tokio::test]
+async fn test_request_receives_reply() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    client
+        .create_topic(CreateTopicRequest {
+            name: "rpc-topic".to_string(),
+            ..Default::default()
+        })
+        .await
+        .expect("create_topic failed");
+
+    // Responder
+    let (sub_tx, sub_rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    sub_tx
+        .send(SubscribeUpstream {
+            request: Some(UpstreamRequest::Init(SubscriptionInit {
+                topic: "rpc-topic".to_string(),
+                consumer_group: "responders".to_string(),
+                initial_position: InitialPosition::Earliest as i32,
+                ..Default::default()
+            })),
+        })
+        .await
+        .unwrap();
+    sub_tx
+        .send(SubscribeUpstream {
+            request: Some(UpstreamRequest::Credit(CreditGrant { credits: 10 })),
+        })
+        .await
+        .unwrap();
+    let mut requests = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(sub_rx))
+        .await
+        .expect("subscribe failed")
+        .into_inner();
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<RequestUpstream>(10);
+    let mut replies = client
+        .request(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect("request failed")
+        .into_inner();
+    tx.send(make_request(7, "rpc-topic", b"ping", 2000))
+        .await
+        .unwrap();
+
+    let request = next_delivery(&mut requests).await;
+    assert_eq!(request.payload, b"ping");
+    let reply_to = request.attributes["reply_to"].clone();
+    let correlation_id = request.attributes["correlation_id"].clone();
+    assert!(reply_to.starts_with("_inbox."));
+
+    // A reply for another request is not delivered
+    for (id, payload) in [("someone-else", "wrong"), (correlation_id.as_str(), "pong")] {
+        client
+            .publish(PublishRequest {
+                topic: reply_to.clone(),
+                payload: payload.as_bytes().to_vec(),
+                attributes: HashMap::from([("correlation_id".to_string(), id.to_string())]),
+                ..Default::default()
+            })
+            .await
+            .expect("publish failed");
+    }
+
+    let response = next_response(&mut replies).await;
+    assert_eq!(response.request_id, 7);
+    match response.result.expect("result missing") {
+        RequestResult::Reply(reply) => {
+            assert_eq!(reply.payload, b"pong");
+            assert_eq!(reply.topic, reply_to);
+        }
+        RequestResult::Error(error) => panic!("request failed: {}", error.message),
+    }
+
+    drop(tx);
+    drop(sub_tx);
+    server.shutdown().await;
+}
+
+/// T076: A request without a reply fails with DEADLINE_EXCEEDED.
+#[tokio::test]
+async fn test_request_times_out() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<RequestUpstream>(10);
+    let mut replies = client
+        .request(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect("request failed")
+        .into_inner();
+
+    // An invalid request fails on its own; the stream stays open
+    tx.send(make_request(1, "bad topic!", b"ping", 100))
+        .await
+        .unwrap();
+    tx.send(make_request(2, "nobody-home", b"ping", 100))
+        .await
+        .unwrap();
+
+    let response = next_response(&mut replies).await;
+    assert_eq!(response.request_id, 1);
+    match response.result.expect("result missing") {
+        RequestResult::Error(error) => {
+            assert_eq!(error.code, tonic::Code::InvalidArgument as i32)
+        }
+        RequestResult::Reply(_) => panic!("invalid request was answered"),
+    }
+
+    let response = next_response(&mut replies).await;
+    assert_eq!(response.request_id, 2);
+    match response.result.expect("result missing") {
+        RequestResult::Error(error) => {
+            assert_eq!(error.code, tonic::Code::DeadlineExceeded as i32)
+        }
+        RequestResult::Reply(_) => panic!("request without a responder was answered"),
+    }
+
+    drop(tx);
+    server.shutdown().await;
+}
+
+/// T077: The inbox is deleted when the requester disconnects.
+#[tokio::test]
+async fn test_request_inbox_deleted_on_disconnect() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<RequestUpstream>(10);
+    let replies = client
+        .request(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect("request failed")
+        .into_inner();
+    assert_eq!(inboxes(&mut client).await.len(), 1);
+
+    drop(tx);
+    drop(replies);
+
+    let mut deleted = false;
+    for _ in 0..50 {
+        if inboxes(&mut client).await.is_empty() {
+            deleted = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert!(deleted, "inbox outlived its requester");
+
+    server.shutdown().await;
+}
+
+/// T081: The inbox prefix is reserved and inboxes are never auto-created.
+#[tokio::test]
+async fn test_inbox_prefix_reserved() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    let status = client
+        .create_topic(CreateTopicRequest {
+            name: "_inbox.mine".to_string(),
+            ..Default::default()
+        })
+        .await
+        .expect_err("reserved topic name accepted");
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+    // A reply to an inbox that is gone does not bring it back
+    let status = client
+        .publish(PublishRequest {
+            topic: "_inbox.gone".to_string(),
+            payload: b"late reply".to_vec(),
+            ..Default::default()
+        })
+        .await
+        .expect_err("publish auto-created an inbox");
+    assert_eq!(status.code(), tonic::Code::NotFound);
+    assert!(inboxes(&mut client).await.is_empty());
+
+    server.shutdown().await;
+}
@@ -6,16 +6,38 @@
 //! - T029: EARLIEST/LATEST initial positions
 //! - T041: Ack updates cursor
 //! - T043: Duplicate ACK is idempotent
+//! - T044: Individual ack mode redelivers unacked messages
+//! - T045: Nack redelivers after the requeue delay with an attempt counter
+//! - T046: Messages are dead-lettered after max_delivery_attempts
+//! - T047: Unacked messages are redelivered after the ack deadline
+//! - T048: ModifyAckDeadline extends the lease
+//! - T049: Shared consumers receive disjoint messages
+//! - T050: A departed shared consumer's unacked messages go to the others
+//! - T051: Keyed messages stay on one shared consumer, in order
+//! - T053: Exclusive consumers on disjoint partitions run side by side
+//! - T062: TIMESTAMP position starts from the first message at or after a time
+//! - T069: Scheduled messages are delivered once their deliver_at has passed
+//! - T072: AckAndPublish commits acks and publishes together, or neither
+//! - T073: A filter skips non-matching messages without credits or lag
+//! - T074: A topic pattern subscribes to every matching topic, new ones included
+//! - T078: Expired messages are never delivered and can go to the dead-letter topic
+//! - T079: Priority delivery sends higher-priority messages first with individual acks
+//! - T080: An ACK carrying another topic's message ID does not move the cursor
+//! - T084: In-flight messages deleted by expiry or retention do not hold the cursor back
 
 mod common;
 
 use futures::StreamExt;
+use sluice_server::config::Config;
 use sluice_server::proto::sluice::v1::{
     subscribe_downstream::Response as DownstreamResponse,
-    subscribe_upstream::Request as UpstreamRequest, Ack, CreditGrant, InitialPosition,
-    PublishRequest, SubscribeUpstream, SubscriptionInit,
+    subscribe_upstream::Request as UpstreamRequest, Ack, AckAndPublish, AckAndPublishResult,
+    AckMode, BatchMessage, BatchPublishRequest, CreateTopicRequest, CreditGrant,
+    DescribeConsumerGroupRequest, DescribeTopicRequest, InitialPosition, MessageDelivery,
+    ModifyAckDeadline, Nack, PublishRequest, SubscribeDownstream, SubscribeUpstream,
+    SubscriptionInit, SubscriptionMode, TopicConfig, TopicMessages, UpdateTopicConfigRequest,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 use tokio::time::timeout;
 
@@ -28,6 +50,7 @@ fn make_init(topic: &str, consumer_group: &str, position: InitialPosition) -> Su
             consumer_id: "test-consumer".to_string(),
             initial_position: position as i32,
             offset: 0,
+            ..Default::default()
         })),
     }
 }
@@ -48,12 +71,70 @@ fn make_ack(message_id: &str) -> SubscribeUpstream {
     }
 }
 
+/// Helper to create a NACK message.
+fn make_nack(message_id: &str, requeue_delay_ms: u32) -> SubscribeUpstream {
+    SubscribeUpstream {
+        request: Some(UpstreamRequest::Nack(Nack {
+            message_id: message_id.to_string(),
+            requeue_delay_ms,
+            ..Default::default()
+        })),
+    }
+}
+
+/// Helper to create a subscription init message with an ack deadline.
+fn make_init_with_deadline(
+    topic: &str,
+    consumer_group: &str,
+    ack_deadline_ms: u32,
+) -> SubscribeUpstream {
+    SubscribeUpstream {
+        request: Some(UpstreamRequest::Init(SubscriptionInit {
+            topic: topic.to_string(),
+            consumer_group: consumer_group.to_string(),
+            consumer_id: "test-consumer".to_string(),
+            initial_position: InitialPosition::Earliest as i32,
+            ack_deadline_ms,
+            ..Default::default()
+        })),
+    }
+}
+
+/// Helper to join a consumer group in shared mode.
+fn make_init_shared(topic: &str, consumer_group: &str, consumer_id: &str) -> SubscribeUpstream {
+    SubscribeUpstream {
+        request: Some(UpstreamRequest::Init(SubscriptionInit {
+            topic: topic.to_string(),
+            consumer_group: consumer_group.to_string(),
+            consumer_id: consumer_id.to_string(),
+            initial_position: InitialPosition::Earliest as i32,
+            mode: SubscriptionMode::Shared as i32,
+            ..Default::default()
+        })),
+    }
+}
+
+/// Helper to wait for the next delivery, skipping heartbeats.
+async fn next_delivery(stream: &mut tonic::Streaming<SubscribeDownstream>) -> MessageDelivery {
+    loop {
+        let downstream = timeout(Duration::from_secs(2), stream.next())
+            .await
+            .expect("timeout")
+            .expect("stream ended")
+            .expect("stream error");
+        if let Some(DownstreamResponse::Delivery(d)) = downstream.response {
+            return d;
+        }
+    }
+}
+
 /// Helper to create a publish request.
 fn make_publish(topic: &str, payload: &[u8]) -> PublishRequest {
     PublishRequest {
         topic: topic.to_string(),
         payload: payload.to_vec(),
         attributes: HashMap::new(),
+        ..Default::default()
     }
 }
 
@@ -430,6 +511,1649 @@ async fn test_subscribe_duplicate_ack_is_idempotent() {
     server.shutdown().await;
 }
 
+/// T044: Individual ack mode redelivers unacked messages.
+#[tokio::test]
+async fn test_subscribe_individual_ack_redelivers_unacked() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    for payload in [b"message 1", b"message 2", b"message 3"] {
+        client
+            .publish(make_publish("individual-topic", payload))
+            .await
+            .expect("publish failed");
+    }
+
+    let make_individual_init = || SubscribeUpstream {
+        request: Some(UpstreamRequest::Init(SubscriptionInit {
+            topic: "individual-topic".to_string(),
+            consumer_group: "individual-group".to_string(),
+            consumer_id: "test-consumer".to_string(),
+            initial_position: InitialPosition::Earliest as i32,
+            ack_mode: AckMode::Individual as i32,
+            ..Default::default()
+        })),
+    };
+
+    // First subscription - receive all three, ACK 3 then 1, leave 2 unacked
+    {
+        let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+        let stream = tokio_stream::wrappers::ReceiverStream::new(rx);
+
+        tx.send(make_individual_init()).await.unwrap();
+        tx.send(make_credit(3)).await.unwrap();
+
+        let response = client.subscribe(stream).await.expect("subscribe failed");
+        let mut stream = response.into_inner();
+
+        let mut ids = Vec::new();
+        while ids.len() < 3 {
+            let downstream = timeout(Duration::from_secs(2), stream.next())
+                .await
+                .expect("timeout")
+                .expect("stream ended")
+                .expect("stream error");
+            if let Some(DownstreamResponse::Delivery(d)) = downstream.response {
+                ids.push(d.message_id);
+            }
+        }
+
+        tx.send(make_ack(&ids[2])).await.unwrap();
+        tx.send(make_ack(&ids[0])).await.unwrap();
+
+        // Small delay to ensure ACKs are processed
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        drop(tx);
+    }
+
+    // Second subscription - only message 2 is redelivered
+    {
+        let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+        let stream = tokio_stream::wrappers::ReceiverStream::new(rx);
+
+        tx.send(make_individual_init()).await.unwrap();
+        tx.send(make_credit(10)).await.unwrap();
+
+        let response = client.subscribe(stream).await.expect("subscribe failed");
+        let mut stream = response.into_inner();
+
+        let delivery = timeout(Duration::from_secs(2), stream.next())
+            .await
+            .expect("timeout")
+            .expect("stream ended")
+            .expect("stream error");
+
+        if let Some(DownstreamResponse::Delivery(d)) = delivery.response {
+            assert_eq!(
+                d.payload, b"message 2",
+                "only the unacked message is redelivered"
+            );
+        } else {
+            panic!("expected delivery");
+        }
+
+        // Message 3 was acked and must not come back
+        let result = timeout(Duration::from_millis(200), stream.next()).await;
+        assert!(result.is_err(), "no further deliveries expected");
+
+        drop(tx);
+    }
+
+    server.shutdown().await;
+}
+
+/// T045: Nack redelivers after the requeue delay with an attempt counter.
+#[tokio::test]
+async fn test_subscribe_nack_redelivers_after_delay() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    let msg = client
+        .publish(make_publish("nack-topic", b"retry me"))
+        .await
+        .expect("publish failed")
+        .into_inner();
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    let stream = tokio_stream::wrappers::ReceiverStream::new(rx);
+
+    tx.send(make_init(
+        "nack-topic",
+        "nack-group",
+        InitialPosition::Earliest,
+    ))
+    .await
+    .unwrap();
+    tx.send(make_credit(10)).await.unwrap();
+
+    let response = client.subscribe(stream).await.expect("subscribe failed");
+    let mut stream = response.into_inner();
+
+    let first = match timeout(Duration::from_secs(2), stream.next())
+        .await
+        .expect("timeout")
+        .expect("stream ended")
+        .expect("stream error")
+        .response
+    {
+        Some(DownstreamResponse::Delivery(d)) => d,
+        other => panic!("expected delivery, got {other:?}"),
+    };
+    assert_eq!(first.message_id, msg.message_id);
+    assert_eq!(first.delivery_attempt, 1);
+
+    tx.send(make_nack(&first.message_id, 300)).await.unwrap();
+
+    // Not redelivered before the delay elapses
+    let early = timeout(Duration::from_millis(150), stream.next()).await;
+    assert!(early.is_err(), "redelivered before requeue delay");
+
+    let retry = match timeout(Duration::from_secs(2), stream.next())
+        .await
+        .expect("timeout")
+        .expect("stream ended")
+        .expect("stream error")
+        .response
+    {
+        Some(DownstreamResponse::Delivery(d)) => d,
+        other => panic!("expected delivery, got {other:?}"),
+    };
+    assert_eq!(retry.message_id, msg.message_id);
+    assert_eq!(retry.payload, b"retry me");
+    assert_eq!(retry.delivery_attempt, 2);
+
+    // Once acked it is not redelivered again, even when NACKed
+    tx.send(make_ack(&retry.message_id)).await.unwrap();
+    tx.send(make_nack(&retry.message_id, 0)).await.unwrap();
+    let result = timeout(Duration::from_millis(200), stream.next()).await;
+    assert!(result.is_err(), "no further deliveries expected");
+
+    drop(tx);
+    server.shutdown().await;
+}
+
+/// T046: Messages are dead-lettered after max_delivery_attempts.
+#[tokio::test]
+async fn test_subscribe_dead_letters_after_max_attempts() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    let mut publish = make_publish("poison-topic", b"poison");
+    publish
+        .attributes
+        .insert("trace".to_string(), "abc".to_string());
+    let original = client
+        .publish(publish)
+        .await
+        .expect("publish failed")
+        .into_inner();
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    let stream = tokio_stream::wrappers::ReceiverStream::new(rx);
+
+    tx.send(SubscribeUpstream {
+        request: Some(UpstreamRequest::Init(SubscriptionInit {
+            topic: "poison-topic".to_string(),
+            consumer_group: "poison-group".to_string(),
+            initial_position: InitialPosition::Earliest as i32,
+            max_delivery_attempts: 2,
+            ..Default::default()
+        })),
+    })
+    .await
+    .unwrap();
+    tx.send(make_credit(10)).await.unwrap();
+
+    let response = client.subscribe(stream).await.expect("subscribe failed");
+    let mut stream = response.into_inner();
+
+    for attempt in 1..=2 {
+        let delivery = match timeout(Duration::from_secs(2), stream.next())
+            .await
+            .expect("timeout")
+            .expect("stream ended")
+            .expect("stream error")
+            .response
+        {
+            Some(DownstreamResponse::Delivery(d)) => d,
+            other => panic!("expected delivery, got {other:?}"),
+        };
+        assert_eq!(delivery.delivery_attempt, attempt);
+
+        tx.send(SubscribeUpstream {
+            request: Some(UpstreamRequest::Nack(Nack {
+                message_id: delivery.message_id,
+                requeue_delay_ms: 0,
+                reason: "cannot parse".to_string(),
+            })),
+        })
+        .await
+        .unwrap();
+    }
+
+    // The second failure exhausts the attempts; no third delivery
+    let result = timeout(Duration::from_millis(300), stream.next()).await;
+    assert!(result.is_err(), "dead-lettered message was redelivered");
+
+    // The copy is an ordinary message on the default dead-letter topic
+    let (dlq_tx, dlq_rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    let dlq_stream = tokio_stream::wrappers::ReceiverStream::new(dlq_rx);
+    dlq_tx
+        .send(make_init(
+            "poison-topic.dlq",
+            "dlq-reader",
+            InitialPosition::Earliest,
+        ))
+        .await
+        .unwrap();
+    dlq_tx.send(make_credit(10)).await.unwrap();
+
+    let response = client
+        .subscribe(dlq_stream)
+        .await
+        .expect("subscribe to dead-letter topic failed");
+    let mut dlq_stream = response.into_inner();
+
+    let dead = match timeout(Duration::from_secs(2), dlq_stream.next())
+        .await
+        .expect("timeout")
+        .expect("stream ended")
+        .expect("stream error")
+        .response
+    {
+        Some(DownstreamResponse::Delivery(d)) => d,
+        other => panic!("expected delivery, got {other:?}"),
+    };
+    assert_eq!(dead.payload, b"poison");
+    assert_eq!(dead.attributes["trace"], "abc");
+    assert_eq!(dead.attributes["sluice.dlq.original_topic"], "poison-topic");
+    assert_eq!(
+        dead.attributes["sluice.dlq.original_sequence"],
+        original.sequence.to_string()
+    );
+    assert_eq!(dead.attributes["sluice.dlq.consumer_group"], "poison-group");
+    assert_eq!(dead.attributes["sluice.dlq.delivery_attempts"], "2");
+    assert_eq!(dead.attributes["sluice.dlq.failure_reason"], "cannot parse");
+
+    drop(tx);
+    drop(dlq_tx);
+    server.shutdown().await;
+}
+
+/// T047: Unacked messages are redelivered after the ack deadline.
+#[tokio::test]
+async fn test_subscribe_ack_deadline_redelivers() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    client
+        .publish(make_publish("deadline-topic", b"slow job"))
+        .await
+        .expect("publish failed");
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    let stream = tokio_stream::wrappers::ReceiverStream::new(rx);
+
+    tx.send(make_init_with_deadline(
+        "deadline-topic",
+        "deadline-group",
+        200,
+    ))
+    .await
+    .unwrap();
+    tx.send(make_credit(10)).await.unwrap();
+
+    let response = client.subscribe(stream).await.expect("subscribe failed");
+    let mut stream = response.into_inner();
+
+    let first = next_delivery(&mut stream).await;
+    assert_eq!(first.delivery_attempt, 1);
+
+    // Not acked - comes back once the deadline passes
+    let retry = next_delivery(&mut stream).await;
+    assert_eq!(retry.message_id, first.message_id);
+    assert_eq!(retry.delivery_attempt, 2);
+
+    tx.send(make_ack(&retry.message_id)).await.unwrap();
+    let result = timeout(Duration::from_millis(400), stream.next()).await;
+    assert!(result.is_err(), "acked message was redelivered");
+
+    drop(tx);
+    server.shutdown().await;
+}
+
+/// T048: ModifyAckDeadline extends the lease.
+#[tokio::test]
+async fn test_subscribe_modify_ack_deadline_extends_lease() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    client
+        .publish(make_publish("lease-topic", b"long job"))
+        .await
+        .expect("publish failed");
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    let stream = tokio_stream::wrappers::ReceiverStream::new(rx);
+
+    tx.send(make_init_with_deadline("lease-topic", "lease-group", 200))
+        .await
+        .unwrap();
+    tx.send(make_credit(10)).await.unwrap();
+
+    let response = client.subscribe(stream).await.expect("subscribe failed");
+    let mut stream = response.into_inner();
+
+    let first = next_delivery(&mut stream).await;
+    tx.send(SubscribeUpstream {
+        request: Some(UpstreamRequest::ModifyAckDeadline(ModifyAckDeadline {
+            message_id: first.message_id.clone(),
+            ack_deadline_ms: 1000,
+        })),
+    })
+    .await
+    .unwrap();
+
+    // The original 200ms deadline no longer applies
+    let early = timeout(Duration::from_millis(600), stream.next()).await;
+    assert!(early.is_err(), "redelivered before the extended deadline");
+
+    let retry = next_delivery(&mut stream).await;
+    assert_eq!(retry.message_id, first.message_id);
+    assert_eq!(retry.delivery_attempt, 2);
+
+    drop(tx);
+    server.shutdown().await;
+}
+
+/// T049: Shared consumers receive disjoint messages.
+#[tokio::test]
+async fn test_subscribe_shared_consumers_split_messages() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    for i in 0..10 {
+        client
+            .publish(make_publish("shared-topic", format!("job {i}").as_bytes()))
+            .await
+            .expect("publish failed");
+    }
+
+    // Each member can hold at most 5 messages, so both must take part
+    let mut streams = Vec::new();
+    let mut senders = Vec::new();
+    for consumer_id in ["worker-a", "worker-b"] {
+        let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+        tx.send(make_init_shared("shared-topic", "workers", consumer_id))
+            .await
+            .unwrap();
+        tx.send(make_credit(5)).await.unwrap();
+
+        let mut client = server.client().await;
+        let response = client
+            .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+            .await
+            .expect("subscribe failed");
+        streams.push(response.into_inner());
+        senders.push(tx);
+    }
+
+    let mut seen = HashSet::new();
+    for (stream, tx) in streams.iter_mut().zip(&senders) {
+        for _ in 0..5 {
+            let delivery = next_delivery(stream).await;
+            assert_eq!(delivery.delivery_attempt, 1);
+            assert!(
+                seen.insert(delivery.sequence),
+                "message delivered to two members"
+            );
+            tx.send(make_ack(&delivery.message_id)).await.unwrap();
+        }
+    }
+    assert_eq!(seen.len(), 10);
+
+    // Nothing is left over for either member
+    for stream in &mut streams {
+        let result = timeout(Duration::from_millis(200), stream.next()).await;
+        assert!(result.is_err(), "unexpected extra delivery");
+    }
+
+    drop(senders);
+    server.shutdown().await;
+}
+
+/// T050: A departed shared consumer's unacked messages go to the others.
+#[tokio::test]
+async fn test_subscribe_shared_member_leave_requeues() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    for i in 0..5 {
+        client
+            .publish(make_publish(
+                "shared-leave-topic",
+                format!("job {i}").as_bytes(),
+            ))
+            .await
+            .expect("publish failed");
+    }
+
+    // First member takes three messages and never acks them
+    let (tx_a, rx_a) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    tx_a.send(make_init_shared(
+        "shared-leave-topic",
+        "workers",
+        "worker-a",
+    ))
+    .await
+    .unwrap();
+    tx_a.send(make_credit(3)).await.unwrap();
+    let mut stream_a = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx_a))
+        .await
+        .expect("subscribe failed")
+        .into_inner();
+
+    let mut held = HashSet::new();
+    for _ in 0..3 {
+        held.insert(next_delivery(&mut stream_a).await.sequence);
+    }
+
+    // Second member gets the rest
+    let (tx_b, rx_b) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    tx_b.send(make_init_shared(
+        "shared-leave-topic",
+        "workers",
+        "worker-b",
+    ))
+    .await
+    .unwrap();
+    tx_b.send(make_credit(10)).await.unwrap();
+    let mut client_b = server.client().await;
+    let mut stream_b = client_b
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx_b))
+        .await
+        .expect("subscribe failed")
+        .into_inner();
+
+    for _ in 0..2 {
+        let delivery = next_delivery(&mut stream_b).await;
+        assert!(!held.contains(&delivery.sequence));
+        tx_b.send(make_ack(&delivery.message_id)).await.unwrap();
+    }
+
+    // First member disconnects; its messages move to the second
+    drop(tx_a);
+    drop(stream_a);
+
+    let mut requeued = HashSet::new();
+    for _ in 0..3 {
+        let delivery = next_delivery(&mut stream_b).await;
+        assert_eq!(delivery.delivery_attempt, 1);
+        requeued.insert(delivery.sequence);
+    }
+    assert_eq!(requeued, held);
+
+    drop(tx_b);
+    server.shutdown().await;
+}
+
+/// T051: Keyed messages stay on one shared consumer, in order.
+#[tokio::test]
+async fn test_subscribe_shared_keys_stick_to_one_consumer() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    // Create the topic so both members can join before anything is keyed
+    client
+        .publish(make_publish("keyed-topic", b"warmup"))
+        .await
+        .expect("publish failed");
+
+    let mut streams = Vec::new();
+    let mut senders = Vec::new();
+    for consumer_id in ["worker-a", "worker-b"] {
+        let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+        tx.send(make_init_shared("keyed-topic", "workers", consumer_id))
+            .await
+            .unwrap();
+
+        let mut client = server.client().await;
+        let response = client
+            .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+            .await
+            .expect("subscribe failed");
+        streams.push(response.into_inner());
+        senders.push(tx);
+    }
+
+    for round in 0..3 {
+        for key in ["order-1", "order-2", "order-3", "order-4"] {
+            let mut request = make_publish("keyed-topic", format!("{key}/{round}").as_bytes());
+            request.key = key.to_string();
+            client.publish(request).await.expect("publish failed");
+        }
+    }
+
+    // key -> (member index, payloads in delivery order)
+    let mut by_key: HashMap<String, (usize, Vec<String>)> = HashMap::new();
+    let mut receive = |member: usize, delivery: MessageDelivery| {
+        let payload = String::from_utf8(delivery.payload).unwrap();
+        if delivery.key.is_empty() {
+            return;
+        }
+        let (owner, payloads) = by_key
+            .entry(delivery.key.clone())
+            .or_insert((member, Vec::new()));
+        assert_eq!(
+            *owner, member,
+            "key {} split across consumers",
+            delivery.key
+        );
+        payloads.push(payload);
+    };
+
+    // A takes the warmup message and claims order-1 and order-2
+    senders[0].send(make_credit(3)).await.unwrap();
+    for _ in 0..3 {
+        let delivery = next_delivery(&mut streams[0]).await;
+        receive(0, delivery);
+    }
+
+    // B claims order-3 and order-4, setting A's keys aside for it
+    senders[1].send(make_credit(20)).await.unwrap();
+    for _ in 0..6 {
+        let delivery = next_delivery(&mut streams[1]).await;
+        receive(1, delivery);
+    }
+
+    // A picks up its set-aside messages once it has credits
+    senders[0].send(make_credit(20)).await.unwrap();
+    for _ in 0..4 {
+        let delivery = next_delivery(&mut streams[0]).await;
+        receive(0, delivery);
+    }
+
+    for stream in &mut streams {
+        let result = timeout(Duration::from_millis(200), stream.next()).await;
+        assert!(result.is_err(), "unexpected extra delivery");
+    }
+
+    assert_eq!(by_key.len(), 4);
+    assert_eq!(by_key["order-1"].0, 0);
+    assert_eq!(by_key["order-3"].0, 1);
+    for (key, (_, payloads)) in &by_key {
+        let expected: Vec<String> = (0..3).map(|round| format!("{key}/{round}")).collect();
+        assert_eq!(payloads, &expected);
+    }
+
+    drop(senders);
+    server.shutdown().await;
+}
+
+/// T053: Exclusive consumers on disjoint partitions run side by side.
+#[tokio::test]
+async fn test_subscribe_disjoint_partitions() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    client
+        .create_topic(CreateTopicRequest {
+            name: "partitioned-topic".to_string(),
+            partitions: 2,
+            ..Default::default()
+        })
+        .await
+        .expect("create_topic failed");
+    for i in 0..4 {
+        client
+            .publish(make_publish(
+                "partitioned-topic",
+                format!("msg {i}").as_bytes(),
+            ))
+            .await
+            .expect("publish failed");
+    }
+
+    let mut streams = Vec::new();
+    let mut senders = Vec::new();
+    for partition in [0, 1] {
+        let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+        tx.send(SubscribeUpstream {
+            request: Some(UpstreamRequest::Init(SubscriptionInit {
+                topic: "partitioned-topic".to_string(),
+                consumer_group: "workers".to_string(),
+                consumer_id: format!("worker-{partition}"),
+                initial_position: InitialPosition::Earliest as i32,
+                partitions: vec![partition],
+                ..Default::default()
+            })),
+        })
+        .await
+        .unwrap();
+        tx.send(make_credit(10)).await.unwrap();
+
+        let mut client = server.client().await;
+        let response = client
+            .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+            .await
+            .expect("subscribe failed");
+        streams.push(response.into_inner());
+        senders.push(tx);
+    }
+
+    // Each consumer sees only its own partition, in partition order
+    for (partition, stream) in streams.iter_mut().enumerate() {
+        let mut last = None;
+        for expected_seq in 1..=2 {
+            let delivery = next_delivery(stream).await;
+            assert_eq!(delivery.partition, partition as u32);
+            assert_eq!(delivery.partition_sequence, expected_seq);
+            last = Some(delivery.message_id);
+        }
+        senders[partition]
+            .send(make_ack(&last.unwrap()))
+            .await
+            .unwrap();
+    }
+
+    for stream in &mut streams {
+        let result = timeout(Duration::from_millis(200), stream.next()).await;
+        assert!(result.is_err(), "unexpected extra delivery or takeover");
+    }
+
+    // Partitions that do not exist are rejected
+    let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    tx.send(SubscribeUpstream {
+        request: Some(UpstreamRequest::Init(SubscriptionInit {
+            topic: "partitioned-topic".to_string(),
+            consumer_group: "workers".to_string(),
+            partitions: vec![2],
+            ..Default::default()
+        })),
+    })
+    .await
+    .unwrap();
+    let status = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect_err("subscribe to missing partition should fail");
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+    drop(senders);
+    server.shutdown().await;
+}
+
+/// Helper to create a subscription init message starting at a timestamp.
+fn make_init_at(topic: &str, consumer_group: &str, start_timestamp: i64) -> SubscribeUpstream {
+    SubscribeUpstream {
+        request: Some(UpstreamRequest::Init(SubscriptionInit {
+            topic: topic.to_string(),
+            consumer_group: consumer_group.to_string(),
+            initial_position: InitialPosition::Timestamp as i32,
+            start_timestamp,
+            ..Default::default()
+        })),
+    }
+}
+
+/// T062: TIMESTAMP position starts from the first message at or after a time.
+#[tokio::test]
+async fn test_subscribe_timestamp_position() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    let mut published = Vec::new();
+    for payload in [b"before".as_slice(), b"at", b"after"] {
+        let resp = client
+            .publish(make_publish("timestamp-topic", payload))
+            .await
+            .expect("publish failed")
+            .into_inner();
+        published.push(resp);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    // Starts with the message published at the timestamp, even though the
+    // group has already consumed further
+    let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    tx.send(make_init_at(
+        "timestamp-topic",
+        "replay",
+        published[1].timestamp,
+    ))
+    .await
+    .unwrap();
+    tx.send(make_credit(10)).await.unwrap();
+    let mut stream = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect("subscribe failed")
+        .into_inner();
+    let first = next_delivery(&mut stream).await;
+    assert_eq!(first.payload, b"at");
+    assert_eq!(first.sequence, published[1].sequence);
+    let second = next_delivery(&mut stream).await;
+    assert_eq!(second.payload, b"after");
+    drop(tx);
+    drop(stream);
+
+    // A timestamp after every message waits for new ones
+    let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    tx.send(make_init_at(
+        "timestamp-topic",
+        "future",
+        published[2].timestamp + 60_000,
+    ))
+    .await
+    .unwrap();
+    tx.send(make_credit(10)).await.unwrap();
+    let mut stream = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect("subscribe failed")
+        .into_inner();
+    let result = timeout(Duration::from_millis(200), stream.next()).await;
+    assert!(
+        !matches!(
+            result,
+            Ok(Some(Ok(SubscribeDownstream {
+                response: Some(DownstreamResponse::Delivery(_))
+            })))
+        ),
+        "unexpected delivery of an older message"
+    );
+    client
+        .publish(make_publish("timestamp-topic", b"new"))
+        .await
+        .expect("publish failed");
+    assert_eq!(next_delivery(&mut stream).await.payload, b"new");
+    drop(tx);
+    drop(stream);
+
+    // The timestamp is required
+    let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    tx.send(make_init_at("timestamp-topic", "missing", 0))
+        .await
+        .unwrap();
+    let status = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect_err("subscribe without a start timestamp should fail");
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+    server.shutdown().await;
+}
+
+/// T069: Scheduled messages are delivered once their deliver_at has passed.
+#[tokio::test]
+async fn test_subscribe_scheduled_delivery() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64;
+    let scheduled = client
+        .publish(PublishRequest {
+            deliver_at: now + 500,
+            ..make_publish("scheduled-topic", b"later")
+        })
+        .await
+        .expect("scheduled publish failed")
+        .into_inner();
+    assert_eq!(scheduled.sequence, 0, "sequenced only when delivered");
+    let immediate = client
+        .publish(make_publish("scheduled-topic", b"now"))
+        .await
+        .expect("publish failed")
+        .into_inner();
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    tx.send(make_init(
+        "scheduled-topic",
+        "scheduled-group",
+        InitialPosition::Earliest,
+    ))
+    .await
+    .unwrap();
+    tx.send(make_credit(10)).await.unwrap();
+    let mut stream = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect("subscribe failed")
+        .into_inner();
+
+    // The later publish overtakes the scheduled message
+    let first = next_delivery(&mut stream).await;
+    assert_eq!(first.payload, b"now");
+    tx.send(make_ack(&first.message_id)).await.unwrap();
+    let early = timeout(Duration::from_millis(200), stream.next()).await;
+    assert!(early.is_err(), "delivered before deliver_at");
+
+    // Acking past it did not lose it: it is appended when due
+    let later = next_delivery(&mut stream).await;
+    assert_eq!(later.message_id, scheduled.message_id);
+    assert_eq!(later.payload, b"later");
+    assert!(later.sequence > immediate.sequence);
+    assert!(later.timestamp >= now + 500);
+
+    drop(tx);
+    drop(stream);
+    server.shutdown().await;
+}
+
+/// Helper to create an ack-and-publish request for a single output message.
+fn make_ack_and_publish(
+    transaction_id: u64,
+    message_id: &str,
+    topic: &str,
+    payload: &[u8],
+    expected_last_sequence: Option<u64>,
+) -> SubscribeUpstream {
+    SubscribeUpstream {
+        request: Some(UpstreamRequest::AckAndPublish(AckAndPublish {
+            transaction_id,
+            ack_message_ids: vec![message_id.to_string()],
+            publish: vec![TopicMessages {
+                topic: topic.to_string(),
+                messages: vec![BatchMessage {
+                    payload: payload.to_vec(),
+                    ..Default::default()
+                }],
+                expected_last_sequence,
+            }],
+        })),
+    }
+}
+
+/// Helper to wait for the result of an ack-and-publish, skipping other responses.
+async fn next_ack_and_publish_result(
+    stream: &mut tonic::Streaming<SubscribeDownstream>,
+) -> AckAndPublishResult {
+    loop {
+        let downstream = timeout(Duration::from_secs(2), stream.next())
+            .await
+            .expect("timeout")
+            .expect("stream ended")
+            .expect("stream error");
+        if let Some(DownstreamResponse::AckAndPublishResult(r)) = downstream.response {
+            return r;
+        }
+    }
+}
+
+/// T072: AckAndPublish commits acks and publishes together, or neither.
+#[tokio::test]
+async fn test_subscribe_ack_and_publish() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    for payload in [b"in 1", b"in 2"] {
+        client
+            .publish(make_publish("pipeline-in", payload))
+            .await
+            .expect("publish failed");
+    }
+
+    {
+        let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+        tx.send(make_init(
+            "pipeline-in",
+            "pipeline",
+            InitialPosition::Earliest,
+        ))
+        .await
+        .unwrap();
+        tx.send(make_credit(1)).await.unwrap();
+        let mut stream = client
+            .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+            .await
+            .expect("subscribe failed")
+            .into_inner();
+
+        // Committed: the input is acked and the output published
+        let first = next_delivery(&mut stream).await;
+        assert_eq!(first.payload, b"in 1");
+        tx.send(make_ack_and_publish(
+            1,
+            &first.message_id,
+            "pipeline-out",
+            b"out 1",
+            None,
+        ))
+        .await
+        .unwrap();
+        let result = next_ack_and_publish_result(&mut stream).await;
+        assert_eq!(result.transaction_id, 1);
+        assert!(
+            result.error.is_none(),
+            "unexpected error: {:?}",
+            result.error
+        );
+        assert_eq!(result.topics.len(), 1);
+        assert_eq!(result.topics[0].topic, "pipeline-out");
+        let out_sequence = result.topics[0].results[0].sequence;
+
+        // Rejected: a stale expected_last_sequence also keeps the input unacked
+        tx.send(make_credit(1)).await.unwrap();
+        let second = next_delivery(&mut stream).await;
+        assert_eq!(second.payload, b"in 2");
+        tx.send(make_ack_and_publish(
+            2,
+            &second.message_id,
+            "pipeline-out",
+            b"out 2",
+            Some(0),
+        ))
+        .await
+        .unwrap();
+        let result = next_ack_and_publish_result(&mut stream).await;
+        assert_eq!(result.transaction_id, 2);
+        let error = result.error.expect("expected a sequence conflict");
+        assert_eq!(error.code, tonic::Code::FailedPrecondition as i32);
+        assert_eq!(error.last_sequence, Some(out_sequence));
+        assert!(result.topics.is_empty());
+
+        drop(tx);
+    }
+
+    // The failed transaction's input is redelivered
+    let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    tx.send(make_init(
+        "pipeline-in",
+        "pipeline",
+        InitialPosition::Earliest,
+    ))
+    .await
+    .unwrap();
+    tx.send(make_credit(10)).await.unwrap();
+    let mut stream = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect("subscribe failed")
+        .into_inner();
+    let redelivered = next_delivery(&mut stream).await;
+    assert_eq!(redelivered.payload, b"in 2");
+    drop(tx);
+    drop(stream);
+
+    // Only the committed output was published
+    let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    tx.send(make_init(
+        "pipeline-out",
+        "pipeline-check",
+        InitialPosition::Earliest,
+    ))
+    .await
+    .unwrap();
+    tx.send(make_credit(10)).await.unwrap();
+    let mut stream = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect("subscribe failed")
+        .into_inner();
+    let output = next_delivery(&mut stream).await;
+    assert_eq!(output.payload, b"out 1");
+    let extra = timeout(Duration::from_millis(200), stream.next()).await;
+    assert!(extra.is_err(), "rolled-back output was published");
+
+    drop(tx);
+    drop(stream);
+    server.shutdown().await;
+}
+
+/// Helper to create a subscription init message with an attribute filter.
+fn make_init_filtered(topic: &str, consumer_group: &str, filter: &str) -> SubscribeUpstream {
+    SubscribeUpstream {
+        request: Some(UpstreamRequest::Init(SubscriptionInit {
+            topic: topic.to_string(),
+            consumer_group: consumer_group.to_string(),
+            initial_position: InitialPosition::Earliest as i32,
+            filter: filter.to_string(),
+            ..Default::default()
+        })),
+    }
+}
+
+/// T073: A filter skips non-matching messages without credits or lag.
+#[tokio::test]
+async fn test_subscribe_filter() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    // (payload, region, test)
+    let messages = [
+        ("eu", Some("eu"), false),
+        ("ap", Some("ap"), false),
+        ("us", Some("us"), false),
+        ("eu test", Some("eu"), true),
+        ("none", None, false),
+    ];
+    for (payload, region, test) in messages {
+        let mut attributes = HashMap::new();
+        if let Some(region) = region {
+            attributes.insert("region".to_string(), region.to_string());
+        }
+        if test {
+            attributes.insert("test".to_string(), "1".to_string());
+        }
+        client
+            .publish(PublishRequest {
+                attributes,
+                ..make_publish("filter-topic", payload.as_bytes())
+            })
+            .await
+            .expect("publish failed");
+    }
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    tx.send(make_init_filtered(
+        "filter-topic",
+        "filter-group",
+        "region IN ('eu', 'us') AND NOT EXISTS test",
+    ))
+    .await
+    .unwrap();
+    tx.send(make_credit(2)).await.unwrap();
+    let mut stream = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect("subscribe failed")
+        .into_inner();
+
+    // Two credits are enough for both matches
+    let first = next_delivery(&mut stream).await;
+    assert_eq!(first.payload, b"eu");
+    let second = next_delivery(&mut stream).await;
+    assert_eq!(second.payload, b"us");
+    tx.send(make_credit(10)).await.unwrap();
+    let extra = timeout(Duration::from_millis(200), stream.next()).await;
+    assert!(extra.is_err(), "delivered a filtered message");
+
+    // Acking the last match moves the cursor past the filtered tail
+    tx.send(make_ack(&second.message_id)).await.unwrap();
+    let mut group = None;
+    for _ in 0..50 {
+        let info = client
+            .describe_consumer_group(DescribeConsumerGroupRequest {
+                topic: "filter-topic".to_string(),
+                consumer_group: "filter-group".to_string(),
+            })
+            .await
+            .expect("describe failed")
+            .into_inner()
+            .consumer_group
+            .expect("response missing group");
+        if info.lag == 0 {
+            group = Some(info);
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    let group = group.expect("filtered messages still count as lag");
+    assert_eq!(group.cursor_sequence, group.max_sequence);
+
+    drop(tx);
+    drop(stream);
+    server.shutdown().await;
+}
+
+/// Test subscribe validation - a malformed filter should fail.
+#[tokio::test]
+async fn test_subscribe_invalid_filter_fails() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    client
+        .publish(make_publish("bad-filter-topic", b"message"))
+        .await
+        .expect("publish failed");
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    tx.send(make_init_filtered(
+        "bad-filter-topic",
+        "test-group",
+        "region = eu",
+    ))
+    .await
+    .unwrap();
+
+    let status = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect_err("malformed filter accepted");
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    assert!(status.message().contains("position 9"));
+
+    server.shutdown().await;
+}
+
+/// T074: A topic pattern subscribes to every matching topic, new ones included.
+#[tokio::test]
+async fn test_subscribe_topic_pattern() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    for (topic, payload) in [
+        ("orders.eu", "eu"),
+        ("orders.us", "us"),
+        ("audit.eu", "audit"),
+    ] {
+        client
+            .publish(make_publish(topic, payload.as_bytes()))
+            .await
+            .expect("publish failed");
+    }
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    tx.send(make_init(
+        "orders.*",
+        "pattern-group",
+        InitialPosition::Earliest,
+    ))
+    .await
+    .unwrap();
+    tx.send(make_credit(10)).await.unwrap();
+    let mut stream = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect("subscribe failed")
+        .into_inner();
+
+    let mut received = HashMap::new();
+    for _ in 0..2 {
+        let delivery = next_delivery(&mut stream).await;
+        received.insert(delivery.topic.clone(), delivery);
+    }
+    assert_eq!(received["orders.eu"].payload, b"eu");
+    assert_eq!(received["orders.us"].payload, b"us");
+
+    // A topic created after subscribing is picked up from its first message
+    client
+        .publish(make_publish("orders.ap", b"ap"))
+        .await
+        .expect("publish failed");
+    let delivery = next_delivery(&mut stream).await;
+    assert_eq!(delivery.topic, "orders.ap");
+    assert_eq!(delivery.payload, b"ap");
+
+    // Acks move the cursor of the message's own topic only
+    tx.send(make_ack(&received["orders.eu"].message_id))
+        .await
+        .unwrap();
+    let mut acked = false;
+    for _ in 0..50 {
+        let info = client
+            .describe_consumer_group(DescribeConsumerGroupRequest {
+                topic: "orders.eu".to_string(),
+                consumer_group: "pattern-group".to_string(),
+            })
+            .await
+            .expect("describe failed")
+            .into_inner()
+            .consumer_group
+            .expect("response missing group");
+        if info.lag == 0 {
+            acked = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert!(acked, "ack did not reach orders.eu");
+    let us = client
+        .describe_consumer_group(DescribeConsumerGroupRequest {
+            topic: "orders.us".to_string(),
+            consumer_group: "pattern-group".to_string(),
+        })
+        .await
+        .expect("describe failed")
+        .into_inner()
+        .consumer_group
+        .expect("response missing group");
+    assert_eq!(us.lag, 1);
+
+    drop(tx);
+    drop(stream);
+    server.shutdown().await;
+}
+
+/// Test subscribe validation - a malformed topic pattern should fail.
+#[tokio::test]
+async fn test_subscribe_invalid_pattern_fails() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    tx.send(make_init(
+        "orders.>.eu",
+        "test-group",
+        InitialPosition::Earliest,
+    ))
+    .await
+    .unwrap();
+
+    let status = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect_err("malformed pattern accepted");
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+    server.shutdown().await;
+}
+
+/// T078: Expired messages are never delivered and can go to the dead-letter topic.
+#[tokio::test]
+async fn test_subscribe_skips_expired_messages() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    client
+        .create_topic(CreateTopicRequest {
+            name: "ttl-topic".to_string(),
+            config: Some(TopicConfig {
+                dead_letter_expired: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await
+        .expect("create_topic failed");
+
+    client
+        .publish(PublishRequest {
+            ttl_ms: 100,
+            ..make_publish("ttl-topic", b"stale")
+        })
+        .await
+        .expect("publish failed");
+    client
+        .batch_publish(BatchPublishRequest {
+            topic: "ttl-topic".to_string(),
+            messages: vec![
+                BatchMessage {
+                    payload: b"stale-batch".to_vec(),
+                    ttl_ms: 100,
+                    ..Default::default()
+                },
+                BatchMessage {
+                    payload: b"fresh".to_vec(),
+                    ttl_ms: 60_000,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        })
+        .await
+        .expect("batch publish failed");
+
+    let status = client
+        .publish(PublishRequest {
+            ttl_ms: -1,
+            ..make_publish("ttl-topic", b"invalid")
+        })
+        .await
+        .expect_err("negative ttl_ms accepted");
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    tx.send(make_init(
+        "ttl-topic",
+        "ttl-group",
+        InitialPosition::Earliest,
+    ))
+    .await
+    .unwrap();
+    tx.send(make_credit(10)).await.unwrap();
+    let mut stream = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect("subscribe failed")
+        .into_inner();
+
+    let delivery = next_delivery(&mut stream).await;
+    assert_eq!(delivery.payload, b"fresh");
+    drop(tx);
+    drop(stream);
+
+    // The expired messages were moved to the dead-letter topic
+    let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    tx.send(make_init(
+        "ttl-topic.dlq",
+        "dlq-group",
+        InitialPosition::Earliest,
+    ))
+    .await
+    .unwrap();
+    tx.send(make_credit(10)).await.unwrap();
+    let mut stream = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect("subscribe to dead-letter topic failed")
+        .into_inner();
+
+    for payload in [&b"stale"[..], b"stale-batch"] {
+        let dead = next_delivery(&mut stream).await;
+        assert_eq!(dead.payload, payload);
+        assert_eq!(dead.attributes["sluice.dlq.original_topic"], "ttl-topic");
+        assert_eq!(dead.attributes["sluice.dlq.failure_reason"], "expired");
+    }
+
+    drop(tx);
+    drop(stream);
+    server.shutdown().await;
+}
+
+/// T079: Priority delivery sends higher-priority messages first with individual acks.
+#[tokio::test]
+async fn test_subscribe_priority_delivery() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    client
+        .create_topic(CreateTopicRequest {
+            name: "priority-topic".to_string(),
+            config: Some(TopicConfig {
+                priority_delivery: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await
+        .expect("create_topic failed");
+
+    let publish = |payload: &'static [u8], priority: u32| PublishRequest {
+        priority,
+        ..make_publish("priority-topic", payload)
+    };
+    for (payload, priority) in [(&b"low-1"[..], 0), (b"mid", 5), (b"low-2", 0), (b"high", 9)] {
+        client
+            .publish(publish(payload, priority))
+            .await
+            .expect("publish failed");
+    }
+
+    let status = client
+        .publish(publish(b"invalid", 10))
+        .await
+        .expect_err("priority above 9 accepted");
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+    let make_priority_init = || SubscribeUpstream {
+        request: Some(UpstreamRequest::Init(SubscriptionInit {
+            topic: "priority-topic".to_string(),
+            consumer_group: "priority-group".to_string(),
+            consumer_id: "test-consumer".to_string(),
+            initial_position: InitialPosition::Earliest as i32,
+            ack_mode: AckMode::Individual as i32,
+            ..Default::default()
+        })),
+    };
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    tx.send(make_priority_init()).await.unwrap();
+    tx.send(make_credit(2)).await.unwrap();
+    let mut stream = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect("subscribe failed")
+        .into_inner();
+
+    let high = next_delivery(&mut stream).await;
+    assert_eq!(high.payload, b"high");
+    assert_eq!(high.priority, 9);
+    let mid = next_delivery(&mut stream).await;
+    assert_eq!(mid.payload, b"mid");
+    tx.send(make_ack(&high.message_id)).await.unwrap();
+
+    // A new urgent message overtakes the waiting low-priority ones
+    client
+        .publish(publish(b"urgent", 9))
+        .await
+        .expect("publish failed");
+    tx.send(make_credit(3)).await.unwrap();
+    let mut payloads = Vec::new();
+    let mut urgent_id = String::new();
+    for _ in 0..3 {
+        let delivery = next_delivery(&mut stream).await;
+        if delivery.payload == b"urgent" {
+            urgent_id = delivery.message_id.clone();
+        }
+        payloads.push(delivery.payload);
+    }
+    assert_eq!(
+        payloads,
+        vec![b"urgent".to_vec(), b"low-1".to_vec(), b"low-2".to_vec()]
+    );
+
+    // Acked out of order; the cursor must not pass the unacked messages
+    tx.send(make_ack(&urgent_id)).await.unwrap();
+    tx.send(make_ack(&mid.message_id)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    drop(tx);
+    drop(stream);
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    tx.send(make_priority_init()).await.unwrap();
+    tx.send(make_credit(10)).await.unwrap();
+    let mut stream = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect("resubscribe failed")
+        .into_inner();
+
+    for payload in [&b"low-1"[..], b"low-2"] {
+        let delivery = next_delivery(&mut stream).await;
+        assert_eq!(delivery.payload, payload);
+    }
+    let result = timeout(Duration::from_millis(200), stream.next()).await;
+    assert!(result.is_err(), "acked messages must not come back");
+
+    drop(tx);
+    server.shutdown().await;
+}
+
+/// T080: An ACK carrying another topic's message ID does not move the cursor.
+#[tokio::test]
+async fn test_subscribe_ack_ignores_other_topic_message() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    client
+        .publish(make_publish("ack-topic-a", b"a-1"))
+        .await
+        .expect("publish failed");
+    // Published later, so its sequence is above every message of topic A
+    let mut foreign = String::new();
+    for payload in [b"b-1", b"b-2", b"b-3"] {
+        foreign = client
+            .publish(make_publish("ack-topic-b", payload))
+            .await
+            .expect("publish failed")
+            .into_inner()
+            .message_id;
+    }
+    client
+        .publish(make_publish("ack-topic-a", b"a-2"))
+        .await
+        .expect("publish failed");
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    tx.send(make_init(
+        "ack-topic-a",
+        "ack-group",
+        InitialPosition::Earliest,
+    ))
+    .await
+    .unwrap();
+    tx.send(make_credit(1)).await.unwrap();
+    let mut stream = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect("subscribe failed")
+        .into_inner();
+
+    let delivery = next_delivery(&mut stream).await;
+    assert_eq!(delivery.payload, b"a-1");
+    tx.send(make_ack(&foreign)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    drop(tx);
+    drop(stream);
+
+    // Nothing was acked, so both messages of topic A come again
+    let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+    tx.send(make_init(
+        "ack-topic-a",
+        "ack-group",
+        InitialPosition::Earliest,
+    ))
+    .await
+    .unwrap();
+    tx.send(make_credit(10)).await.unwrap();
+    let mut stream = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect("resubscribe failed")
+        .into_inner();
+
+    for payload in [&b"a-1"[..], b"a-2"] {
+        let delivery = next_delivery(&mut stream).await;
+        assert_eq!(delivery.payload, payload);
+    }
+
+    drop(tx);
+    server.shutdown().await;
+}
+
+/// T084: In-flight messages deleted by expiry or retention do not hold the cursor back.
+#[tokio::test]
+async fn test_subscribe_deleted_in_flight_messages_are_settled() {
+    let server = common::TestServer::start_with_config(Config {
+        retention_interval_secs: 1,
+        ..Default::default()
+    })
+    .await;
+    let mut client = server.client().await;
+
+    for topic in ["settle-ttl", "settle-retention"] {
+        client
+            .create_topic(CreateTopicRequest {
+                name: topic.to_string(),
+                ..Default::default()
+            })
+            .await
+            .expect("create_topic failed");
+    }
+    client
+        .publish(PublishRequest {
+            ttl_ms: 300,
+            ..make_publish("settle-ttl", b"short-lived")
+        })
+        .await
+        .expect("publish failed");
+    client
+        .publish(make_publish("settle-retention", b"pruned"))
+        .await
+        .expect("publish failed");
+    for topic in ["settle-ttl", "settle-retention"] {
+        client
+            .publish(make_publish(topic, b"kept"))
+            .await
+            .expect("publish failed");
+    }
+
+    let mut subscriptions = Vec::new();
+    for topic in ["settle-ttl", "settle-retention"] {
+        let (tx, rx) = tokio::sync::mpsc::channel::<SubscribeUpstream>(10);
+        tx.send(SubscribeUpstream {
+            request: Some(UpstreamRequest::Init(SubscriptionInit {
+                topic: topic.to_string(),
+                consumer_group: "settle-group".to_string(),
+                consumer_id: "test-consumer".to_string(),
+                initial_position: InitialPosition::Earliest as i32,
+                ack_mode: AckMode::Individual as i32,
+                ..Default::default()
+            })),
+        })
+        .await
+        .unwrap();
+        tx.send(make_credit(10)).await.unwrap();
+        let mut stream = client
+            .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+            .await
+            .expect("subscribe failed")
+            .into_inner();
+
+        // The first message is left unacked
+        next_delivery(&mut stream).await;
+        let kept = next_delivery(&mut stream).await;
+        assert_eq!(kept.payload, b"kept");
+        subscriptions.push((topic, tx, stream, kept.message_id));
+    }
+
+    // Expire the first message of one topic and prune it from the other
+    client
+        .update_topic_config(UpdateTopicConfigRequest {
+            name: "settle-retention".to_string(),
+            config: Some(TopicConfig {
+                retention_max_messages: 1,
+                ..Default::default()
+            }),
+        })
+        .await
+        .expect("update failed");
+    for topic in ["settle-ttl", "settle-retention"] {
+        let mut count = 0;
+        for _ in 0..50 {
+            count = client
+                .describe_topic(DescribeTopicRequest {
+                    name: topic.to_string(),
+                })
+                .await
+                .expect("describe_topic failed")
+                .into_inner()
+                .message_count;
+            if count == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(count, 1, "{topic}: unacked message was not deleted");
+    }
+
+    // Acking the later message moves the cursor over the deleted one
+    for (topic, tx, _stream, message_id) in &subscriptions {
+        tx.send(make_ack(message_id)).await.unwrap();
+
+        let mut group = None;
+        for _ in 0..50 {
+            let info = client
+                .describe_consumer_group(DescribeConsumerGroupRequest {
+                    topic: topic.to_string(),
+                    consumer_group: "settle-group".to_string(),
+                })
+                .await
+                .expect("describe failed")
+                .into_inner()
+                .consumer_group
+                .expect("response missing group");
+            if info.lag == 0 {
+                group = Some(info);
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        let group = group.unwrap_or_else(|| panic!("{topic}: deleted message holds the cursor"));
+        assert_eq!(group.cursor_sequence, group.max_sequence);
+    }
+
+    drop(subscriptions);
+    server.shutdown().await;
+}
+
 /// Test subscribe validation - empty topic should fail.
 #[tokio::test]
 async fn test_subscribe_empty_topic_fails() {
@@ -477,6 +2201,7 @@ async fn test_subscribe_empty_consumer_group_uses_default() {
             consumer_id: "test".to_string(),
             initial_position: InitialPosition::Earliest as i32,
             offset: 0,
+            ..Default::default()
         })),
     })
     .await
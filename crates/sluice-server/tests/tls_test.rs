@@ -0,0 +1,125 @@
+//! TLS and mutual TLS tests for the gRPC listener.
+//!
+//! Tests:
+//! - T054: Server serves TLS to clients that trust its CA
+//! - T055: Mutual TLS rejects clients without a certificate
+//!
+//! Certificates come from `tests/fixtures/tls`.
+
+use sluice_client::{ConnectConfig, SluiceClient};
+use sluice_server::config::Config;
+use sluice_server::server::run_server;
+use std::path::PathBuf;
+use tempfile::TempDir;
+use tokio::sync::watch;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/tls")
+        .join(name)
+}
+
+fn fixture_str(name: &str) -> String {
+    fixture(name).to_string_lossy().to_string()
+}
+
+/// A TLS server running `run_server` on a random port.
+struct TlsServer {
+    endpoint: String,
+    shutdown_tx: watch::Sender<bool>,
+    task: tokio::task::JoinHandle<()>,
+    _data_dir: TempDir,
+}
+
+impl TlsServer {
+    async fn start(client_auth: bool) -> Self {
+        let data_dir = TempDir::new().expect("failed to create temp dir");
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind");
+        let port = listener.local_addr().expect("failed to get addr").port();
+        drop(listener);
+
+        let config = Config {
+            host: "127.0.0.1".to_string(),
+            port,
+            data_dir: data_dir.path().to_path_buf(),
+            metrics_enabled: false,
+            tls_cert: Some(fixture("server.pem")),
+            tls_key: Some(fixture("server.key")),
+            tls_client_ca: client_auth.then(|| fixture("ca.pem")),
+            ..Config::default()
+        };
+
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let task = tokio::spawn(async move {
+            run_server(config, shutdown_rx).await.expect("server error");
+        });
+
+        Self {
+            endpoint: format!("https://localhost:{port}"),
+            shutdown_tx,
+            task,
+            _data_dir: data_dir,
+        }
+    }
+
+    fn client_config(&self) -> ConnectConfig {
+        ConnectConfig::tls(&self.endpoint, fixture_str("ca.pem"))
+    }
+
+    async fn shutdown(self) {
+        let _ = self.shutdown_tx.send(true);
+        let _ = self.task.await;
+    }
+}
+
+/// T054: Server serves TLS to clients that trust its CA.
+#[tokio::test]
+async fn test_tls_server_accepts_trusted_client() {
+    let server = TlsServer::start(false).await;
+
+    let mut client = SluiceClient::connect(server.client_config())
+        .await
+        .expect("TLS connect failed");
+    let response = client
+        .publish("tls-topic", b"over tls".to_vec())
+        .await
+        .expect("publish failed");
+    assert_eq!(response.sequence, 1);
+
+    // A plaintext client cannot talk to the TLS listener
+    let plaintext =
+        ConnectConfig::plaintext(server.endpoint.replace("https", "http")).without_retry();
+    let result = match SluiceClient::connect(plaintext).await {
+        Ok(mut client) => client.list_topics().await.map(|_| ()),
+        Err(e) => Err(e),
+    };
+    assert!(result.is_err(), "plaintext request should fail");
+
+    server.shutdown().await;
+}
+
+/// T055: Mutual TLS rejects clients without a certificate.
+#[tokio::test]
+async fn test_mutual_tls_requires_client_certificate() {
+    let server = TlsServer::start(true).await;
+
+    let mut client = SluiceClient::connect(
+        server
+            .client_config()
+            .with_client_cert(fixture_str("client.pem"), fixture_str("client.key")),
+    )
+    .await
+    .expect("mTLS connect failed");
+    client.list_topics().await.expect("list_topics failed");
+
+    let result = match SluiceClient::connect(server.client_config().without_retry()).await {
+        Ok(mut client) => client.list_topics().await.map(|_| ()),
+        Err(e) => Err(e),
+    };
+    assert!(result.is_err(), "client without a certificate was accepted");
+
+    server.shutdown().await;
+}
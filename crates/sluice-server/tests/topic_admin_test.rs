@@ -0,0 +1,492 @@
+//! Contract tests for the topic admin RPCs.
+//!
+//! Tests:
+//! - T058: DescribeTopic reports statistics, and PurgeTopic empties a topic
+//!   and moves its cursors
+//! - T059: DeleteTopic removes a topic and disconnects its subscribers
+//! - T063: CreateTopic and UpdateTopicConfig store per-topic configuration
+//!   and enforce the message size limit
+//! - T064: Publishing to an unknown topic fails when auto-creation is disabled
+//! - T082: A topic's auto_create setting overrides the server flag for
+//!   topics under it
+
+mod common;
+
+use futures::StreamExt;
+use sluice_server::config::Config;
+use sluice_server::proto::sluice::v1::{
+    sluice_client::SluiceClient, subscribe_downstream::Response as DownstreamResponse,
+    subscribe_upstream::Request as UpstreamRequest, Ack, BatchMessage, BatchPublishRequest,
+    CreateTopicRequest, CreditGrant, DeleteTopicRequest, DescribeTopicRequest,
+    DescribeTopicResponse, InitialPosition, ListTopicsRequest, PublishRequest, PurgeTopicRequest,
+    SubscribeUpstream, SubscriptionInit, TopicConfig, UpdateTopicConfigRequest,
+};
+use std::time::Duration;
+use tokio::time::timeout;
+use tonic::transport::Channel;
+
+fn make_publish(topic: &str, payload: &[u8]) -> PublishRequest {
+    PublishRequest {
+        topic: topic.to_string(),
+        payload: payload.to_vec(),
+        ..Default::default()
+    }
+}
+
+fn make_batch_message(payload: &[u8]) -> BatchMessage {
+    BatchMessage {
+        payload: payload.to_vec(),
+        ..Default::default()
+    }
+}
+
+fn make_init(topic: &str, consumer_group: &str) -> SubscribeUpstream {
+    SubscribeUpstream {
+        request: Some(UpstreamRequest::Init(SubscriptionInit {
+            topic: topic.to_string(),
+            consumer_group: consumer_group.to_string(),
+            initial_position: InitialPosition::Earliest as i32,
+            ..Default::default()
+        })),
+    }
+}
+
+fn make_credit(credits: u32) -> SubscribeUpstream {
+    SubscribeUpstream {
+        request: Some(UpstreamRequest::Credit(CreditGrant { credits })),
+    }
+}
+
+fn make_ack(message_id: &str) -> SubscribeUpstream {
+    SubscribeUpstream {
+        request: Some(UpstreamRequest::Ack(Ack {
+            message_id: message_id.to_string(),
+        })),
+    }
+}
+
+async fn describe(
+    client: &mut SluiceClient<Channel>,
+    name: &str,
+) -> Result<DescribeTopicResponse, tonic::Status> {
+    let request = DescribeTopicRequest {
+        name: name.to_string(),
+    };
+    Ok(client.describe_topic(request).await?.into_inner())
+}
+
+/// Describe a topic until `condition` holds, or panic after five seconds.
+async fn describe_until(
+    client: &mut SluiceClient<Channel>,
+    name: &str,
+    condition: impl Fn(&DescribeTopicResponse) -> bool,
+) -> DescribeTopicResponse {
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    loop {
+        let resp = describe(client, name).await.expect("describe failed");
+        if condition(&resp) {
+            return resp;
+        }
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "timed out waiting for topic state: {resp:?}"
+        );
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+/// T058: DescribeTopic reports statistics; PurgeTopic empties the topic.
+#[tokio::test]
+async fn test_describe_and_purge_topic() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    let mut sequences = Vec::new();
+    for payload in [b"one".as_slice(), b"two", b"three"] {
+        let resp = client
+            .publish(make_publish("orders", payload))
+            .await
+            .expect("publish failed")
+            .into_inner();
+        sequences.push(resp.sequence);
+    }
+
+    // Consume and ack the first message
+    let (tx, rx) = tokio::sync::mpsc::channel(10);
+    tx.send(make_init("orders", "workers")).await.unwrap();
+    tx.send(make_credit(1)).await.unwrap();
+    let mut stream = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect("subscribe failed")
+        .into_inner();
+    let delivery = loop {
+        let msg = timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("timeout waiting for delivery")
+            .expect("stream ended")
+            .expect("stream error");
+        if let Some(DownstreamResponse::Delivery(delivery)) = msg.response {
+            break delivery;
+        }
+    };
+    tx.send(make_ack(&delivery.message_id)).await.unwrap();
+
+    let mut admin = server.client().await;
+    let resp = describe_until(&mut admin, "orders", |resp| {
+        resp.consumer_groups
+            .first()
+            .is_some_and(|group| group.cursor_sequence == sequences[0])
+    })
+    .await;
+    assert_eq!(resp.topic.unwrap().name, "orders");
+    assert_eq!(resp.message_count, 3);
+    assert_eq!(resp.min_sequence, sequences[0]);
+    assert_eq!(resp.max_sequence, sequences[2]);
+    assert_eq!(resp.size_bytes, 11);
+    assert!(resp.oldest_timestamp > 0 && resp.oldest_timestamp <= resp.newest_timestamp);
+    assert_eq!(resp.consumer_groups.len(), 1);
+    assert_eq!(resp.consumer_groups[0].name, "workers");
+    assert_eq!(resp.consumer_groups[0].lag, 2);
+
+    // Purging ends the live subscription and moves the cursor past the purge
+    let purged = admin
+        .purge_topic(PurgeTopicRequest {
+            name: "orders".to_string(),
+        })
+        .await
+        .expect("purge failed")
+        .into_inner();
+    assert_eq!(purged.messages_deleted, 3);
+
+    let ended = loop {
+        match timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("timeout waiting for termination")
+        {
+            Some(Ok(_)) => continue,
+            Some(Err(status)) => break status,
+            None => panic!("stream ended without a status"),
+        }
+    };
+    assert_eq!(ended.code(), tonic::Code::Aborted);
+
+    let resp = describe(&mut admin, "orders").await.unwrap();
+    assert_eq!(resp.message_count, 0);
+    assert_eq!(resp.size_bytes, 0);
+    assert_eq!(resp.consumer_groups[0].cursor_sequence, sequences[2]);
+    assert_eq!(resp.consumer_groups[0].lag, 0);
+
+    let status = describe(&mut admin, "missing").await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+
+    drop(tx);
+    drop(stream);
+    server.shutdown().await;
+}
+
+/// T059: DeleteTopic removes a topic and disconnects its subscribers.
+#[tokio::test]
+async fn test_delete_topic() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    client
+        .publish(make_publish("orders", b"order"))
+        .await
+        .expect("publish failed");
+    client
+        .publish(make_publish("billing", b"invoice"))
+        .await
+        .expect("publish failed");
+
+    let (tx, rx) = tokio::sync::mpsc::channel(10);
+    tx.send(make_init("orders", "workers")).await.unwrap();
+    let mut stream = client
+        .subscribe(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .await
+        .expect("subscribe failed")
+        .into_inner();
+
+    let mut admin = server.client().await;
+    describe_until(&mut admin, "orders", |resp| {
+        !resp.consumer_groups.is_empty()
+    })
+    .await;
+
+    let resp = admin
+        .delete_topic(DeleteTopicRequest {
+            name: "orders".to_string(),
+        })
+        .await
+        .expect("delete failed")
+        .into_inner();
+    assert_eq!(resp.messages_deleted, 1);
+    assert_eq!(resp.consumer_groups_deleted, 1);
+
+    let ended = loop {
+        match timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("timeout waiting for termination")
+        {
+            Some(Ok(_)) => continue,
+            Some(Err(status)) => break status,
+            None => panic!("stream ended without a status"),
+        }
+    };
+    assert_eq!(ended.code(), tonic::Code::NotFound);
+
+    let topics = admin
+        .list_topics(ListTopicsRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .topics;
+    let names: Vec<&str> = topics.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["billing"]);
+
+    let status = admin
+        .delete_topic(DeleteTopicRequest {
+            name: "orders".to_string(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+
+    // Publishing again starts a fresh topic
+    client
+        .publish(make_publish("orders", b"again"))
+        .await
+        .expect("publish after delete failed");
+    let resp = describe(&mut admin, "orders").await.unwrap();
+    assert_eq!(resp.message_count, 1);
+    assert!(resp.consumer_groups.is_empty());
+
+    drop(tx);
+    drop(stream);
+    server.shutdown().await;
+}
+
+/// T063: CreateTopic and UpdateTopicConfig store per-topic configuration.
+#[tokio::test]
+async fn test_topic_config() {
+    let server = common::TestServer::start().await;
+    let mut client = server.client().await;
+
+    let config = TopicConfig {
+        retention_max_age_secs: 3600,
+        max_message_bytes: 8,
+        description: "Order events".to_string(),
+        ..Default::default()
+    };
+    let resp = client
+        .create_topic(CreateTopicRequest {
+            name: "orders".to_string(),
+            partitions: 1,
+            config: Some(config.clone()),
+        })
+        .await
+        .expect("create failed")
+        .into_inner();
+    assert_eq!(resp.config, Some(config.clone()));
+    assert_eq!(
+        describe(&mut client, "orders").await.unwrap().config,
+        Some(config)
+    );
+
+    // Payloads above the topic's limit are rejected, alone or in a batch
+    client
+        .publish(make_publish("orders", b"12345678"))
+        .await
+        .expect("publish failed");
+    let status = client
+        .publish(make_publish("orders", b"123456789"))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+    let status = client
+        .batch_publish(BatchPublishRequest {
+            topic: "orders".to_string(),
+            messages: vec![
+                make_batch_message(b"small"),
+                make_batch_message(b"far too large"),
+            ],
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+    assert_eq!(
+        describe(&mut client, "orders").await.unwrap().message_count,
+        1
+    );
+
+    // Updating replaces the whole configuration
+    let config = TopicConfig {
+        retention_max_messages: 100,
+        ..Default::default()
+    };
+    let resp = client
+        .update_topic_config(UpdateTopicConfigRequest {
+            name: "orders".to_string(),
+            config: Some(config.clone()),
+        })
+        .await
+        .expect("update failed")
+        .into_inner();
+    assert_eq!(resp.config, Some(config.clone()));
+    assert_eq!(
+        describe(&mut client, "orders").await.unwrap().config,
+        Some(config)
+    );
+    client
+        .publish(make_publish("orders", b"123456789"))
+        .await
+        .expect("publish failed");
+
+    let status = client
+        .update_topic_config(UpdateTopicConfigRequest {
+            name: "orders".to_string(),
+            config: Some(TopicConfig {
+                max_message_bytes: 64 * 1024 * 1024,
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+    let status = client
+        .update_topic_config(UpdateTopicConfigRequest {
+            name: "missing".to_string(),
+            config: None,
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+
+    server.shutdown().await;
+}
+
+/// T064: Publishing to an unknown topic fails when auto-creation is disabled.
+#[tokio::test]
+async fn test_auto_create_disabled() {
+    let server = common::TestServer::start_with_config(Config {
+        disable_auto_create: true,
+        ..Default::default()
+    })
+    .await;
+    let mut client = server.client().await;
+
+    let status = client
+        .publish(make_publish("ordres", b"typo"))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+    let status = client
+        .batch_publish(BatchPublishRequest {
+            topic: "ordres".to_string(),
+            messages: vec![make_batch_message(b"typo")],
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+    let topics = client
+        .list_topics(ListTopicsRequest {})
+        .await
+        .expect("list failed")
+        .into_inner()
+        .topics;
+    assert!(topics.is_empty());
+
+    client
+        .create_topic(CreateTopicRequest {
+            name: "orders".to_string(),
+            ..Default::default()
+        })
+        .await
+        .expect("create failed");
+    let resp = client
+        .publish(make_publish("orders", b"hello"))
+        .await
+        .expect("publish failed")
+        .into_inner();
+    assert_eq!(resp.sequence, 1);
+
+    server.shutdown().await;
+}
+
+/// T082: A topic's auto_create setting overrides the server flag for topics
+/// under it.
+#[tokio::test]
+async fn test_topic_auto_create_setting() {
+    let server = common::TestServer::start_with_config(Config {
+        disable_auto_create: true,
+        ..Default::default()
+    })
+    .await;
+    let mut client = server.client().await;
+
+    client
+        .create_topic(CreateTopicRequest {
+            name: "orders".to_string(),
+            config: Some(TopicConfig {
+                auto_create: Some(true),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await
+        .expect("create failed");
+    client
+        .create_topic(CreateTopicRequest {
+            name: "orders.eu".to_string(),
+            config: Some(TopicConfig {
+                auto_create: Some(false),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await
+        .expect("create failed");
+
+    // Allowed by `orders` although the server disables auto-creation
+    client
+        .publish(make_publish("orders.dlq", b"dead"))
+        .await
+        .expect("publish failed");
+    // `orders.eu` is the nearest ancestor and disallows it
+    let status = client
+        .publish(make_publish("orders.eu.creatd", b"typo"))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+    // Topics outside `orders` follow the server flag
+    let status = client
+        .publish(make_publish("ordres.dlq", b"typo"))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+
+    let mut names: Vec<_> = client
+        .list_topics(ListTopicsRequest {})
+        .await
+        .expect("list failed")
+        .into_inner()
+        .topics
+        .into_iter()
+        .map(|topic| topic.name)
+        .collect();
+    names.sort();
+    assert_eq!(names, ["orders", "orders.dlq", "orders.eu"]);
+
+    let config = describe(&mut client, "orders")
+        .await
+        .expect("describe failed")
+        .config
+        .unwrap();
+    assert_eq!(config.auto_create, Some(true));
+
+    server.shutdown().await;
+}
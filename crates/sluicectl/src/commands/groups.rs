@@ -0,0 +1,199 @@
+//! Consumer groups command implementation.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sluice_client::{ConnectConfig, ConsumerGroupInfo, ResetTarget, SluiceClient};
+
+use super::topics::chrono_format;
+use crate::OutputFormat;
+
+#[derive(Serialize)]
+pub(crate) struct ConsumerGroupOutput {
+    pub(crate) topic: String,
+    pub(crate) name: String,
+    pub(crate) cursor_sequence: u64,
+    pub(crate) max_sequence: u64,
+    pub(crate) lag: u64,
+    pub(crate) updated_at: i64,
+    pub(crate) connected: bool,
+}
+
+impl From<ConsumerGroupInfo> for ConsumerGroupOutput {
+    fn from(group: ConsumerGroupInfo) -> Self {
+        Self {
+            topic: group.topic,
+            name: group.name,
+            cursor_sequence: group.cursor_sequence,
+            max_sequence: group.max_sequence,
+            lag: group.lag,
+            updated_at: group.updated_at,
+            connected: group.connected,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct GroupsOutput {
+    consumer_groups: Vec<ConsumerGroupOutput>,
+    total: usize,
+}
+
+pub async fn list(config: ConnectConfig, topic: Option<&str>, format: OutputFormat) -> Result<()> {
+    let mut client = SluiceClient::connect(config)
+        .await
+        .context("failed to connect to server")?;
+
+    let groups = client
+        .list_consumer_groups(topic)
+        .await
+        .context("failed to list consumer groups")?;
+    let output = GroupsOutput {
+        total: groups.len(),
+        consumer_groups: groups.into_iter().map(ConsumerGroupOutput::from).collect(),
+    };
+
+    match format {
+        OutputFormat::Text => {
+            if output.consumer_groups.is_empty() {
+                println!("No consumer groups found.");
+            } else {
+                println!(
+                    "{:<30} {:<30} {:>12} {:>10} {:>10}",
+                    "TOPIC", "CONSUMER GROUP", "CURSOR", "LAG", "CONNECTED"
+                );
+                println!("{}", "-".repeat(96));
+                for group in &output.consumer_groups {
+                    println!(
+                        "{:<30} {:<30} {:>12} {:>10} {:>10}",
+                        group.topic,
+                        group.name,
+                        group.cursor_sequence,
+                        group.lag,
+                        if group.connected { "yes" } else { "no" }
+                    );
+                }
+                println!();
+                println!("Total: {} consumer group(s)", output.total);
+            }
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn describe(
+    config: ConnectConfig,
+    topic: &str,
+    group: &str,
+    format: OutputFormat,
+) -> Result<()> {
+    let mut client = SluiceClient::connect(config)
+        .await
+        .context("failed to connect to server")?;
+
+    let group = client
+        .describe_consumer_group(topic, group)
+        .await
+        .context("failed to describe consumer group")?;
+    print_group(&ConsumerGroupOutput::from(group), format)
+}
+
+pub async fn reset(
+    config: ConnectConfig,
+    topic: &str,
+    group: &str,
+    target: ResetTarget,
+    format: OutputFormat,
+) -> Result<()> {
+    let mut client = SluiceClient::connect(config)
+        .await
+        .context("failed to connect to server")?;
+
+    let group = client
+        .reset_consumer_group(topic, group, target)
+        .await
+        .context("failed to reset consumer group")?;
+    let output = ConsumerGroupOutput::from(group);
+
+    if format == OutputFormat::Text {
+        println!(
+            "Reset consumer group '{}' on topic '{}' to sequence {}",
+            output.name, output.topic, output.cursor_sequence
+        );
+        println!();
+    }
+    print_group(&output, format)
+}
+
+#[derive(Serialize)]
+struct DeleteOutput {
+    topic: String,
+    consumer_group: String,
+    disconnected: bool,
+}
+
+pub async fn delete(
+    config: ConnectConfig,
+    topic: &str,
+    group: &str,
+    format: OutputFormat,
+) -> Result<()> {
+    let mut client = SluiceClient::connect(config)
+        .await
+        .context("failed to connect to server")?;
+
+    let disconnected = client
+        .delete_consumer_group(topic, group)
+        .await
+        .context("failed to delete consumer group")?;
+    let output = DeleteOutput {
+        topic: topic.to_string(),
+        consumer_group: group.to_string(),
+        disconnected,
+    };
+
+    match format {
+        OutputFormat::Text => {
+            println!(
+                "Deleted consumer group '{}' from topic '{}'{}",
+                output.consumer_group,
+                output.topic,
+                if output.disconnected {
+                    " (disconnected its consumers)"
+                } else {
+                    ""
+                }
+            );
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+    }
+
+    Ok(())
+}
+
+fn print_group(group: &ConsumerGroupOutput, format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            println!("Topic:           {}", group.topic);
+            println!("Consumer group:  {}", group.name);
+            println!("Cursor:          {}", group.cursor_sequence);
+            println!("Max sequence:    {}", group.max_sequence);
+            println!("Lag:             {}", group.lag);
+            println!("Updated at:      {}", chrono_format(group.updated_at));
+            println!(
+                "Connected:       {}",
+                if group.connected { "yes" } else { "no" }
+            );
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(group)?);
+        }
+    }
+
+    Ok(())
+}
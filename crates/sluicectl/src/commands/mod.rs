@@ -1,5 +1,7 @@
 //! Command implementations for sluicectl.
 
+pub mod groups;
 pub mod publish;
+pub mod request;
 pub mod subscribe;
 pub mod topics;
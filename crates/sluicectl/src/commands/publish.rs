@@ -4,15 +4,23 @@ use std::fs;
 
 use anyhow::{anyhow, Context, Result};
 use serde::Serialize;
-use sluice_client::{ConnectConfig, SluiceClient};
+use sluice_client::{sequence_conflict, ConnectConfig, PublishOptions, SluiceClient};
 
+use super::topics::chrono_format;
 use crate::OutputFormat;
 
 #[derive(Serialize)]
 struct PublishOutput {
     message_id: String,
     topic: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key: Option<String>,
+    partition: u32,
+    partition_sequence: u64,
     payload_size: usize,
+    duplicate: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deliver_at: Option<i64>,
 }
 
 pub async fn run(
@@ -20,6 +28,7 @@ pub async fn run(
     topic: &str,
     payload: Option<String>,
     file: Option<String>,
+    options: PublishOptions,
     format: OutputFormat,
 ) -> Result<()> {
     // Determine payload source
@@ -46,21 +55,56 @@ pub async fn run(
         .await
         .context("failed to connect to server")?;
 
-    let result = client
-        .publish(topic, payload_bytes)
-        .await
-        .context("publish failed")?;
+    let key = options.key.clone();
+    let deliver_at = options.deliver_at;
+    let result = match client.publish_with(topic, payload_bytes, options).await {
+        Ok(result) => result,
+        Err(e) => match sequence_conflict(&e) {
+            Some(actual) => {
+                return Err(anyhow!(
+                    "publish rejected: last sequence of '{}' is {}",
+                    topic,
+                    actual
+                ))
+            }
+            None => return Err(e.context("publish failed")),
+        },
+    };
 
     let output = PublishOutput {
         message_id: result.message_id.clone(),
         topic: topic.to_string(),
+        key,
+        partition: result.partition,
+        partition_sequence: result.partition_sequence,
         payload_size,
+        duplicate: result.duplicate,
+        // A message due now is delivered and sequenced right away
+        deliver_at: deliver_at.filter(|_| result.sequence == 0),
     };
 
     match format {
         OutputFormat::Text => {
-            println!("Published message to '{}'", topic);
+            if output.duplicate {
+                println!("Already published to '{}' (idempotency key matched)", topic);
+            } else {
+                println!("Published message to '{}'", topic);
+            }
             println!("  Message ID: {}", output.message_id);
+            if let Some(key) = &output.key {
+                println!("  Key: {}", key);
+            }
+            match output.deliver_at {
+                Some(deliver_at) => println!(
+                    "  Partition: {} (scheduled for {})",
+                    output.partition,
+                    chrono_format(deliver_at)
+                ),
+                None => println!(
+                    "  Partition: {} (sequence {})",
+                    output.partition, output.partition_sequence
+                ),
+            }
             println!("  Payload size: {} bytes", output.payload_size);
         }
         OutputFormat::Json => {
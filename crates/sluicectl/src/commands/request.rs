@@ -0,0 +1,48 @@
+//! Request command implementation.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sluice_client::{ConnectConfig, SluiceClient};
+
+use crate::OutputFormat;
+
+#[derive(Serialize)]
+struct ReplyOutput {
+    message_id: String,
+    topic: String,
+    payload: String,
+    payload_bytes: usize,
+}
+
+pub async fn run(
+    config: ConnectConfig,
+    topic: &str,
+    payload: String,
+    timeout: Duration,
+    format: OutputFormat,
+) -> Result<()> {
+    let mut client = SluiceClient::connect(config)
+        .await
+        .context("failed to connect to server")?;
+
+    let reply = client
+        .request(topic, payload.into_bytes(), timeout)
+        .await
+        .context("request failed")?;
+
+    let output = ReplyOutput {
+        message_id: reply.message_id,
+        topic: topic.to_string(),
+        payload: String::from_utf8_lossy(&reply.payload).to_string(),
+        payload_bytes: reply.payload.len(),
+    };
+
+    match format {
+        OutputFormat::Text => println!("{}", output.payload),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&output)?),
+    }
+
+    Ok(())
+}
@@ -2,7 +2,10 @@
 
 use anyhow::{anyhow, Context, Result};
 use serde::Serialize;
-use sluice_client::{ConnectConfig, InitialPosition, SluiceClient};
+use sluice_client::{
+    parse_timestamp, AckMode, ConnectConfig, CreditConfig, InitialPosition, SluiceClient,
+    SubscribeOptions, SubscriptionMode,
+};
 use tokio::signal;
 
 use crate::OutputFormat;
@@ -12,32 +15,71 @@ struct MessageOutput {
     message_id: String,
     topic: String,
     sequence: u64,
+    partition: u32,
+    partition_sequence: u64,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    key: String,
     payload: String,
     payload_bytes: usize,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn run(
     config: ConnectConfig,
     topic: &str,
     group: &str,
     position: &str,
+    ack_mode: &str,
+    shared: bool,
+    partitions: Vec<u32>,
+    filter: Option<String>,
     credits: u32,
     count: u64,
     auto_ack: bool,
     format: OutputFormat,
 ) -> Result<()> {
-    let initial_position = match position.to_lowercase().as_str() {
-        "latest" => InitialPosition::Latest,
-        "earliest" => InitialPosition::Earliest,
-        _ => return Err(anyhow!("position must be 'latest' or 'earliest'")),
+    let (initial_position, start_timestamp) = match position.to_lowercase().as_str() {
+        "latest" => (InitialPosition::Latest, None),
+        "earliest" => (InitialPosition::Earliest, None),
+        _ => {
+            let timestamp = parse_timestamp(position)
+                .context("position must be 'latest', 'earliest' or a timestamp")?;
+            (InitialPosition::Timestamp, Some(timestamp))
+        }
+    };
+
+    let ack_mode = match ack_mode.to_lowercase().as_str() {
+        "cumulative" => AckMode::Cumulative,
+        "individual" => AckMode::Individual,
+        _ => return Err(anyhow!("ack mode must be 'cumulative' or 'individual'")),
+    };
+
+    let mode = if shared {
+        SubscriptionMode::Shared
+    } else {
+        SubscriptionMode::Exclusive
     };
 
     let mut client = SluiceClient::connect(config)
         .await
         .context("failed to connect to server")?;
 
+    let mut options = SubscribeOptions::default()
+        .consumer_group(group)
+        .initial_position(initial_position)
+        .credits(CreditConfig::with_window(credits))
+        .ack_mode(ack_mode)
+        .mode(mode)
+        .partitions(partitions);
+    if let Some(timestamp) = start_timestamp {
+        options = options.start_timestamp(timestamp);
+    }
+    if let Some(filter) = filter {
+        options = options.filter(filter);
+    }
+
     let mut subscription = client
-        .subscribe(topic, Some(group), None, initial_position, credits)
+        .subscribe_with(topic, options)
         .await
         .context("failed to subscribe")?;
 
@@ -67,19 +109,29 @@ pub async fn run(
                         let payload_str = String::from_utf8_lossy(&msg.payload).to_string();
                         let output = MessageOutput {
                             message_id: msg.message_id.clone(),
-                            topic: topic.to_string(),
+                            topic: msg.topic.clone(),
                             sequence: msg.sequence,
+                            partition: msg.partition,
+                            partition_sequence: msg.partition_sequence,
+                            key: msg.key.clone(),
                             payload: payload_str.clone(),
                             payload_bytes: msg.payload.len(),
                         };
 
                         match format {
                             OutputFormat::Text => {
+                                let key = if msg.key.is_empty() {
+                                    String::new()
+                                } else {
+                                    format!(" key={}", msg.key)
+                                };
                                 println!(
-                                    "[{}] seq={} id={}: {}",
-                                    topic,
+                                    "[{}] seq={} partition={} id={}{}: {}",
+                                    msg.topic,
                                     msg.sequence,
+                                    msg.partition,
                                     &msg.message_id[..8.min(msg.message_id.len())],
+                                    key,
                                     payload_str
                                 );
                             }
@@ -2,14 +2,118 @@
 
 use anyhow::{Context, Result};
 use serde::Serialize;
-use sluice_client::{ConnectConfig, SluiceClient};
+use sluice_client::{ConnectConfig, SluiceClient, TopicConfig};
 
+use super::groups::ConsumerGroupOutput;
 use crate::OutputFormat;
 
 #[derive(Serialize)]
 struct TopicInfo {
     name: String,
     created_at: i64,
+    partition_count: u32,
+    /// Highest sequence in each partition, in partition order.
+    partition_max_sequences: Vec<u64>,
+}
+
+impl From<sluice_client::Topic> for TopicInfo {
+    fn from(topic: sluice_client::Topic) -> Self {
+        Self {
+            name: topic.name,
+            created_at: topic.created_at,
+            partition_count: topic.partition_count,
+            partition_max_sequences: topic.partitions.iter().map(|p| p.max_sequence).collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct TopicConfigOutput {
+    retention_max_age_secs: u64,
+    retention_max_messages: u64,
+    retention_max_bytes: u64,
+    max_message_bytes: u64,
+    description: String,
+    dead_letter_expired: bool,
+    priority_delivery: bool,
+    auto_create: Option<bool>,
+}
+
+impl From<TopicConfig> for TopicConfigOutput {
+    fn from(config: TopicConfig) -> Self {
+        Self {
+            retention_max_age_secs: config.retention_max_age_secs,
+            retention_max_messages: config.retention_max_messages,
+            retention_max_bytes: config.retention_max_bytes,
+            max_message_bytes: config.max_message_bytes,
+            description: config.description,
+            dead_letter_expired: config.dead_letter_expired,
+            priority_delivery: config.priority_delivery,
+            auto_create: config.auto_create,
+        }
+    }
+}
+
+impl TopicConfigOutput {
+    fn print(&self) {
+        println!("Description: {}", or_dash(&self.description));
+        println!(
+            "Max age:     {}",
+            or_default(self.retention_max_age_secs, "s")
+        );
+        println!(
+            "Max count:   {}",
+            or_default(self.retention_max_messages, " messages")
+        );
+        println!(
+            "Max size:    {}",
+            or_default(self.retention_max_bytes, " bytes")
+        );
+        println!(
+            "Max message: {}",
+            or_default(self.max_message_bytes, " bytes")
+        );
+        println!(
+            "Expired:     {}",
+            if self.dead_letter_expired {
+                "dead-lettered"
+            } else {
+                "deleted"
+            }
+        );
+        println!(
+            "Delivery:    {}",
+            if self.priority_delivery {
+                "by priority"
+            } else {
+                "in order"
+            }
+        );
+        println!(
+            "Auto-create: {}",
+            match self.auto_create {
+                Some(true) => "allowed",
+                Some(false) => "disabled",
+                None => "server default",
+            }
+        );
+    }
+}
+
+fn or_dash(value: &str) -> &str {
+    if value.is_empty() {
+        "-"
+    } else {
+        value
+    }
+}
+
+fn or_default(value: u64, unit: &str) -> String {
+    if value == 0 {
+        "server default".to_string()
+    } else {
+        format!("{value}{unit}")
+    }
 }
 
 #[derive(Serialize)]
@@ -27,13 +131,7 @@ pub async fn list(config: ConnectConfig, format: OutputFormat) -> Result<()> {
 
     let output = TopicsOutput {
         total: topics.len(),
-        topics: topics
-            .into_iter()
-            .map(|t| TopicInfo {
-                name: t.name,
-                created_at: t.created_at,
-            })
-            .collect(),
+        topics: topics.into_iter().map(TopicInfo::from).collect(),
     };
 
     match format {
@@ -41,12 +139,15 @@ pub async fn list(config: ConnectConfig, format: OutputFormat) -> Result<()> {
             if output.topics.is_empty() {
                 println!("No topics found.");
             } else {
-                println!("{:<40} {:>20}", "TOPIC", "CREATED AT");
-                println!("{}", "-".repeat(62));
+                println!("{:<40} {:>10} {:>20}", "TOPIC", "PARTITIONS", "CREATED AT");
+                println!("{}", "-".repeat(73));
                 for topic in &output.topics {
                     // Format timestamp as human readable if possible
                     let ts = chrono_format(topic.created_at);
-                    println!("{:<40} {:>20}", topic.name, ts);
+                    println!(
+                        "{:<40} {:>10} {:>20}",
+                        topic.name, topic.partition_count, ts
+                    );
                 }
                 println!();
                 println!("Total: {} topic(s)", output.total);
@@ -60,7 +161,249 @@ pub async fn list(config: ConnectConfig, format: OutputFormat) -> Result<()> {
     Ok(())
 }
 
-fn chrono_format(millis: i64) -> String {
+pub async fn create(
+    config: ConnectConfig,
+    name: &str,
+    partitions: u32,
+    topic_config: TopicConfig,
+    format: OutputFormat,
+) -> Result<()> {
+    let mut client = SluiceClient::connect(config)
+        .await
+        .context("failed to connect to server")?;
+
+    let topic = client
+        .create_topic_with_config(name, partitions, topic_config)
+        .await
+        .context("failed to create topic")?;
+    let output = TopicInfo::from(topic);
+
+    match format {
+        OutputFormat::Text => {
+            println!(
+                "Created topic '{}' with {} partition(s)",
+                output.name, output.partition_count
+            );
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct ConfigureOutput {
+    topic: String,
+    config: TopicConfigOutput,
+}
+
+/// Update a topic's configuration, keeping the settings `change` leaves alone.
+pub async fn configure(
+    config: ConnectConfig,
+    name: &str,
+    change: impl FnOnce(&mut TopicConfig),
+    format: OutputFormat,
+) -> Result<()> {
+    let mut client = SluiceClient::connect(config)
+        .await
+        .context("failed to connect to server")?;
+
+    let mut topic_config = client
+        .describe_topic(name)
+        .await
+        .context("failed to describe topic")?
+        .config
+        .unwrap_or_default();
+    change(&mut topic_config);
+    let topic_config = client
+        .update_topic_config(name, topic_config)
+        .await
+        .context("failed to update topic config")?;
+    let output = ConfigureOutput {
+        topic: name.to_string(),
+        config: TopicConfigOutput::from(topic_config),
+    };
+
+    match format {
+        OutputFormat::Text => {
+            println!("Updated configuration of topic '{}'", output.topic);
+            println!();
+            output.config.print();
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct DescribeOutput {
+    #[serde(flatten)]
+    topic: TopicInfo,
+    message_count: u64,
+    min_sequence: u64,
+    max_sequence: u64,
+    size_bytes: u64,
+    oldest_timestamp: i64,
+    newest_timestamp: i64,
+    scheduled_count: u64,
+    config: TopicConfigOutput,
+    consumer_groups: Vec<ConsumerGroupOutput>,
+}
+
+pub async fn describe(config: ConnectConfig, name: &str, format: OutputFormat) -> Result<()> {
+    let mut client = SluiceClient::connect(config)
+        .await
+        .context("failed to connect to server")?;
+
+    let resp = client
+        .describe_topic(name)
+        .await
+        .context("failed to describe topic")?;
+    let topic = resp
+        .topic
+        .context("describe_topic response is missing the topic")?;
+
+    let output = DescribeOutput {
+        topic: TopicInfo::from(topic),
+        message_count: resp.message_count,
+        min_sequence: resp.min_sequence,
+        max_sequence: resp.max_sequence,
+        size_bytes: resp.size_bytes,
+        oldest_timestamp: resp.oldest_timestamp,
+        newest_timestamp: resp.newest_timestamp,
+        scheduled_count: resp.scheduled_count,
+        config: TopicConfigOutput::from(resp.config.unwrap_or_default()),
+        consumer_groups: resp
+            .consumer_groups
+            .into_iter()
+            .map(ConsumerGroupOutput::from)
+            .collect(),
+    };
+
+    match format {
+        OutputFormat::Text => {
+            println!("Topic:       {}", output.topic.name);
+            println!("Created at:  {}", chrono_format(output.topic.created_at));
+            println!("Partitions:  {}", output.topic.partition_count);
+            println!("Messages:    {}", output.message_count);
+            println!(
+                "Sequences:   {}..{}",
+                output.min_sequence, output.max_sequence
+            );
+            println!("Size:        {} bytes", output.size_bytes);
+            println!("Oldest:      {}", chrono_format(output.oldest_timestamp));
+            println!("Newest:      {}", chrono_format(output.newest_timestamp));
+            if output.scheduled_count > 0 {
+                println!("Scheduled:   {}", output.scheduled_count);
+            }
+            output.config.print();
+            println!();
+            if output.consumer_groups.is_empty() {
+                println!("No consumer groups.");
+            } else {
+                println!(
+                    "{:<40} {:>12} {:>10} {:>20} {:>10}",
+                    "CONSUMER GROUP", "CURSOR", "LAG", "UPDATED AT", "CONNECTED"
+                );
+                println!("{}", "-".repeat(96));
+                for group in &output.consumer_groups {
+                    println!(
+                        "{:<40} {:>12} {:>10} {:>20} {:>10}",
+                        group.name,
+                        group.cursor_sequence,
+                        group.lag,
+                        chrono_format(group.updated_at),
+                        if group.connected { "yes" } else { "no" }
+                    );
+                }
+            }
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct PurgeOutput {
+    topic: String,
+    messages_deleted: u64,
+}
+
+pub async fn purge(config: ConnectConfig, name: &str, format: OutputFormat) -> Result<()> {
+    let mut client = SluiceClient::connect(config)
+        .await
+        .context("failed to connect to server")?;
+
+    let messages_deleted = client
+        .purge_topic(name)
+        .await
+        .context("failed to purge topic")?;
+    let output = PurgeOutput {
+        topic: name.to_string(),
+        messages_deleted,
+    };
+
+    match format {
+        OutputFormat::Text => {
+            println!(
+                "Purged {} message(s) from topic '{}'",
+                output.messages_deleted, output.topic
+            );
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct DeleteOutput {
+    topic: String,
+    messages_deleted: u64,
+    consumer_groups_deleted: u32,
+}
+
+pub async fn delete(config: ConnectConfig, name: &str, format: OutputFormat) -> Result<()> {
+    let mut client = SluiceClient::connect(config)
+        .await
+        .context("failed to connect to server")?;
+
+    let resp = client
+        .delete_topic(name)
+        .await
+        .context("failed to delete topic")?;
+    let output = DeleteOutput {
+        topic: name.to_string(),
+        messages_deleted: resp.messages_deleted,
+        consumer_groups_deleted: resp.consumer_groups_deleted,
+    };
+
+    match format {
+        OutputFormat::Text => {
+            println!(
+                "Deleted topic '{}' ({} message(s), {} consumer group(s))",
+                output.topic, output.messages_deleted, output.consumer_groups_deleted
+            );
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn chrono_format(millis: i64) -> String {
     // Simple formatting: just show the unix timestamp if we don't have chrono
     if millis == 0 {
         return "-".to_string();
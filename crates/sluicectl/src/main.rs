@@ -31,6 +31,18 @@ struct Cli {
     #[arg(long, env = "SLUICE_TLS_DOMAIN")]
     tls_domain: Option<String>,
 
+    /// Path to TLS client certificate (for servers requiring mutual TLS)
+    #[arg(long, env = "SLUICE_TLS_CLIENT_CERT", requires = "tls_key")]
+    tls_cert: Option<String>,
+
+    /// Path to TLS client private key
+    #[arg(long, env = "SLUICE_TLS_CLIENT_KEY", requires = "tls_cert")]
+    tls_key: Option<String>,
+
+    /// Bearer token for servers that require authentication
+    #[arg(long, env = "SLUICE_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+
     /// Output format (text, json)
     #[arg(short, long, default_value = "text")]
     output: OutputFormat,
@@ -59,11 +71,16 @@ impl std::str::FromStr for OutputFormat {
 
 #[derive(Subcommand)]
 enum Commands {
-    /// List topics or show topic details
+    /// List, create, inspect and delete topics
     Topics {
         #[command(subcommand)]
         action: TopicsAction,
     },
+    /// List, inspect, reset and delete consumer groups
+    Groups {
+        #[command(subcommand)]
+        action: GroupsAction,
+    },
     /// Publish a message to a topic
     Publish {
         /// Topic name
@@ -73,17 +90,63 @@ enum Commands {
         /// Read payload from file
         #[arg(short, long)]
         file: Option<String>,
+        /// Routing key (messages with the same key go to the same shared consumer)
+        #[arg(short, long)]
+        key: Option<String>,
+        /// Idempotency key; retrying with the same key does not store a duplicate
+        #[arg(long)]
+        idempotency_key: Option<String>,
+        /// Only publish if the topic's last sequence is still this value
+        #[arg(long)]
+        expected_last_sequence: Option<u64>,
+        /// Deliver at this time (RFC 3339 such as 2024-05-01T14:05:00Z, or
+        /// Unix milliseconds) instead of now
+        #[arg(long, conflicts_with = "delay_secs")]
+        deliver_at: Option<String>,
+        /// Deliver after this many seconds instead of now
+        #[arg(long)]
+        delay_secs: Option<u64>,
+        /// Expire the message if it is not consumed within this many seconds
+        #[arg(long)]
+        ttl_secs: Option<u64>,
+        /// Priority from 0 to 9, for topics with priority delivery
+        #[arg(long, default_value_t = 0)]
+        priority: u32,
+    },
+    /// Publish a request to a topic and print the reply
+    Request {
+        /// Topic name
+        topic: String,
+        /// Request payload
+        payload: String,
+        /// Seconds to wait for the reply
+        #[arg(short, long, default_value = "30")]
+        timeout_secs: u64,
     },
     /// Subscribe to a topic and print messages
     Subscribe {
-        /// Topic name
+        /// Topic name, or a pattern such as orders.* or audit.>
         topic: String,
         /// Consumer group name
         #[arg(short, long, default_value = "sluicectl")]
         group: String,
-        /// Start position: latest or earliest
+        /// Start position: latest, earliest, or a timestamp (RFC 3339 such as
+        /// 2024-05-01T14:05:00Z, or Unix milliseconds)
         #[arg(short, long, default_value = "latest")]
         position: String,
+        /// Ack mode: cumulative or individual
+        #[arg(long, default_value = "cumulative")]
+        ack_mode: String,
+        /// Share the consumer group with other consumers instead of taking it over
+        #[arg(long)]
+        shared: bool,
+        /// Consume only this partition (repeatable; default: all partitions)
+        #[arg(long = "partition")]
+        partitions: Vec<u32>,
+        /// Only receive messages whose attributes match this expression,
+        /// e.g. "region IN ('eu', 'us') AND NOT EXISTS test"
+        #[arg(long)]
+        filter: Option<String>,
         /// Credits window size
         #[arg(long, default_value = "100")]
         credits: u32,
@@ -100,6 +163,157 @@ enum Commands {
 enum TopicsAction {
     /// List all topics
     List,
+    /// Create a topic
+    Create {
+        /// Topic name
+        name: String,
+        /// Number of partitions
+        #[arg(short, long, default_value = "1")]
+        partitions: u32,
+        #[command(flatten)]
+        config: TopicConfigArgs,
+    },
+    /// Change a topic's configuration; options not given keep their value
+    Config {
+        /// Topic name
+        name: String,
+        #[command(flatten)]
+        config: TopicConfigArgs,
+    },
+    /// Show message statistics and consumer groups of a topic
+    Describe {
+        /// Topic name
+        name: String,
+    },
+    /// Delete every message of a topic, keeping the topic
+    Purge {
+        /// Topic name
+        name: String,
+    },
+    /// Delete a topic with its messages and consumer groups
+    Delete {
+        /// Topic name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum GroupsAction {
+    /// List consumer groups with their lag
+    List {
+        /// Only list groups of this topic
+        #[arg(short, long)]
+        topic: Option<String>,
+    },
+    /// Show a consumer group's cursor, lag and connection state
+    Describe {
+        /// Topic name
+        topic: String,
+        /// Consumer group name
+        group: String,
+    },
+    /// Move a consumer group's cursor, disconnecting its consumers
+    Reset {
+        /// Topic name
+        topic: String,
+        /// Consumer group name
+        group: String,
+        #[command(flatten)]
+        target: ResetArgs,
+    },
+    /// Delete a consumer group, disconnecting its consumers
+    Delete {
+        /// Topic name
+        topic: String,
+        /// Consumer group name
+        group: String,
+    },
+}
+
+#[derive(clap::Args)]
+struct TopicConfigArgs {
+    /// Delete messages older than this many seconds (0 = server default)
+    #[arg(long, value_name = "SECS")]
+    retention_max_age_secs: Option<u64>,
+    /// Keep at most this many messages (0 = server default)
+    #[arg(long, value_name = "COUNT")]
+    retention_max_messages: Option<u64>,
+    /// Keep at most this many bytes of messages (0 = server default)
+    #[arg(long, value_name = "BYTES")]
+    retention_max_bytes: Option<u64>,
+    /// Reject payloads larger than this many bytes (0 = server limit)
+    #[arg(long, value_name = "BYTES")]
+    max_message_bytes: Option<u64>,
+    /// Description of the topic
+    #[arg(long)]
+    description: Option<String>,
+    /// Move expired messages to the dead-letter topic instead of deleting them
+    #[arg(long, value_name = "BOOL")]
+    dead_letter_expired: Option<bool>,
+    /// Deliver higher-priority messages first to individual-ack subscribers
+    #[arg(long, value_name = "BOOL")]
+    priority_delivery: Option<bool>,
+    /// Allow publishing to create unknown topics under this one
+    #[arg(long, value_name = "BOOL")]
+    auto_create: Option<bool>,
+}
+
+impl TopicConfigArgs {
+    /// Overwrite the settings given on the command line.
+    fn apply(self, config: &mut sluice_client::TopicConfig) {
+        if let Some(secs) = self.retention_max_age_secs {
+            config.retention_max_age_secs = secs;
+        }
+        if let Some(count) = self.retention_max_messages {
+            config.retention_max_messages = count;
+        }
+        if let Some(bytes) = self.retention_max_bytes {
+            config.retention_max_bytes = bytes;
+        }
+        if let Some(bytes) = self.max_message_bytes {
+            config.max_message_bytes = bytes;
+        }
+        if let Some(description) = self.description {
+            config.description = description;
+        }
+        if let Some(dead_letter_expired) = self.dead_letter_expired {
+            config.dead_letter_expired = dead_letter_expired;
+        }
+        if let Some(priority_delivery) = self.priority_delivery {
+            config.priority_delivery = priority_delivery;
+        }
+        if let Some(auto_create) = self.auto_create {
+            config.auto_create = Some(auto_create);
+        }
+    }
+}
+
+#[derive(clap::Args)]
+#[group(required = true, multiple = false)]
+struct ResetArgs {
+    /// Replay every stored message
+    #[arg(long)]
+    to_earliest: bool,
+    /// Skip every stored message
+    #[arg(long)]
+    to_latest: bool,
+    /// Resume after this sequence number
+    #[arg(long, value_name = "SEQUENCE")]
+    to_sequence: Option<u64>,
+    /// Resume from the first message published at or after this Unix time (ms)
+    #[arg(long, value_name = "MILLIS")]
+    to_timestamp: Option<i64>,
+}
+
+impl ResetArgs {
+    fn target(&self) -> sluice_client::ResetTarget {
+        match (self.to_sequence, self.to_timestamp) {
+            (Some(sequence), _) => sluice_client::ResetTarget::Sequence(sequence),
+            (_, Some(timestamp)) => sluice_client::ResetTarget::Timestamp(timestamp),
+            _ if self.to_earliest => sluice_client::ResetTarget::Earliest,
+            _ => sluice_client::ResetTarget::Latest,
+        }
+    }
 }
 
 #[tokio::main]
@@ -119,30 +333,110 @@ async fn main() -> Result<()> {
         endpoint: cli.endpoint,
         tls_ca: cli.tls_ca,
         tls_domain: cli.tls_domain,
+        tls_cert: cli.tls_cert,
+        tls_key: cli.tls_key,
+        auth_token: cli.token,
         retry: sluice_client::RetryConfig::default(),
     };
 
     match cli.command {
         Commands::Topics { action } => match action {
             TopicsAction::List => commands::topics::list(config, cli.output).await?,
+            TopicsAction::Create {
+                name,
+                partitions,
+                config: args,
+            } => {
+                let mut topic_config = sluice_client::TopicConfig::default();
+                args.apply(&mut topic_config);
+                commands::topics::create(config, &name, partitions, topic_config, cli.output)
+                    .await?
+            }
+            TopicsAction::Config { name, config: args } => {
+                commands::topics::configure(config, &name, |c| args.apply(c), cli.output).await?
+            }
+            TopicsAction::Describe { name } => {
+                commands::topics::describe(config, &name, cli.output).await?
+            }
+            TopicsAction::Purge { name } => {
+                commands::topics::purge(config, &name, cli.output).await?
+            }
+            TopicsAction::Delete { name } => {
+                commands::topics::delete(config, &name, cli.output).await?
+            }
+        },
+        Commands::Groups { action } => match action {
+            GroupsAction::List { topic } => {
+                commands::groups::list(config, topic.as_deref(), cli.output).await?
+            }
+            GroupsAction::Describe { topic, group } => {
+                commands::groups::describe(config, &topic, &group, cli.output).await?
+            }
+            GroupsAction::Reset {
+                topic,
+                group,
+                target,
+            } => {
+                commands::groups::reset(config, &topic, &group, target.target(), cli.output).await?
+            }
+            GroupsAction::Delete { topic, group } => {
+                commands::groups::delete(config, &topic, &group, cli.output).await?
+            }
         },
         Commands::Publish {
             topic,
             payload,
             file,
+            key,
+            idempotency_key,
+            expected_last_sequence,
+            deliver_at,
+            delay_secs,
+            ttl_secs,
+            priority,
+        } => {
+            let mut options = sluice_client::PublishOptions {
+                key,
+                idempotency_key,
+                expected_last_sequence,
+                deliver_at: deliver_at
+                    .as_deref()
+                    .map(sluice_client::parse_timestamp)
+                    .transpose()?,
+                priority,
+                ..Default::default()
+            };
+            if let Some(secs) = delay_secs {
+                options = options.delay(std::time::Duration::from_secs(secs));
+            }
+            if let Some(secs) = ttl_secs {
+                options = options.ttl(std::time::Duration::from_secs(secs));
+            }
+            commands::publish::run(config, &topic, payload, file, options, cli.output).await?;
+        }
+        Commands::Request {
+            topic,
+            payload,
+            timeout_secs,
         } => {
-            commands::publish::run(config, &topic, payload, file, cli.output).await?;
+            let timeout = std::time::Duration::from_secs(timeout_secs);
+            commands::request::run(config, &topic, payload, timeout, cli.output).await?;
         }
         Commands::Subscribe {
             topic,
             group,
             position,
+            ack_mode,
+            shared,
+            partitions,
+            filter,
             credits,
             count,
             auto_ack,
         } => {
             commands::subscribe::run(
-                config, &topic, &group, &position, credits, count, auto_ack, cli.output,
+                config, &topic, &group, &position, &ack_mode, shared, partitions, filter, credits,
+                count, auto_ack, cli.output,
             )
             .await?;
         }